@@ -0,0 +1,114 @@
+// Synthesis and IPA proving times for the Fibonacci and function circuits.
+// Run with `cargo bench --bench fibo`.
+//
+// Synthesis is timed through MockProver::run, which lays the circuit out and
+// fills in the witness without checking it. Throughput counts one gate per
+// laid out row as a constraint.
+
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp, plonk::Circuit};
+use halo2halo::{
+    fibo1::FiboCircuit,
+    fibo_online::OnlineFiboCircuit,
+    function::FunctionCircuit,
+    ipa::{keygen, prove, verify},
+};
+
+const SWEEP: [usize; 3] = [10, 100, 1000];
+
+// enough rows for `rows` rows of gates plus the blinding rows
+fn k_for(rows: usize) -> u32 {
+    (rows + 8).next_power_of_two().trailing_zeros()
+}
+
+fn bench(name: &str, iterations: u32, constraints: usize, mut f: impl FnMut()) {
+    // warm up
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let mean = start.elapsed() / iterations;
+    let throughput = constraints as f64 / mean.max(Duration::from_nanos(1)).as_secs_f64();
+    println!(
+        "{:<28} {:>12.3?} {:>12.0} constraints/s",
+        name, mean, throughput
+    );
+}
+
+fn fibo() -> FiboCircuit<Fp> {
+    FiboCircuit {
+        a: Value::known(Fp::one()),
+        b: Value::known(Fp::one()),
+    }
+}
+
+fn function() -> FunctionCircuit<Fp> {
+    FunctionCircuit {
+        x: Value::known(Fp::from(3)),
+    }
+}
+
+fn online_fibo(steps: usize) -> OnlineFiboCircuit<Fp> {
+    OnlineFiboCircuit {
+        a: Value::known(Fp::one()),
+        b: Value::known(Fp::one()),
+        steps,
+    }
+}
+
+// instance: | F(1) | F(2) | F(steps + 2) |
+fn online_fibo_public(steps: usize) -> Vec<Vec<Fp>> {
+    let last = (0..steps)
+        .fold((Fp::one(), Fp::one()), |(a, b), _| (b, a + b))
+        .1;
+    vec![vec![Fp::one(), Fp::one(), last]]
+}
+
+fn synthesize<C: Circuit<Fp>>(
+    name: &str,
+    iterations: u32,
+    rows: usize,
+    circuit: C,
+    public: Vec<Vec<Fp>>,
+) {
+    let k = k_for(rows);
+    bench(name, iterations, rows, || {
+        MockProver::run(k, &circuit, public.clone()).unwrap();
+    });
+}
+
+// circuits are consumed by the prover, `circuit` builds a fresh one per run
+fn prove_and_verify<C: Circuit<Fp>>(
+    name: &str,
+    iterations: u32,
+    rows: usize,
+    circuit: impl Fn() -> C,
+    public: Vec<Vec<Fp>>,
+) {
+    let k = k_for(rows);
+    let (params, pk) = keygen(&circuit(), k).unwrap();
+    bench(name, iterations, rows, || {
+        let proof = prove(&params, &pk, circuit(), &public).unwrap();
+        verify(&params, pk.get_vk(), &proof, &public).unwrap();
+    });
+}
+
+fn main() {
+    println!("synthesis");
+    synthesize("FiboCircuit", 100, 8, fibo(), vec![]);
+    synthesize("FunctionCircuit", 100, 6, function(), vec![]);
+    for n in SWEEP {
+        let name = format!("OnlineFiboCircuit n = {}", n);
+        synthesize(&name, 10, n, online_fibo(n), online_fibo_public(n));
+    }
+
+    println!("ipa prove + verify");
+    prove_and_verify("FiboCircuit", 10, 8, fibo, vec![]);
+    prove_and_verify("FunctionCircuit", 10, 6, function, vec![]);
+    for n in SWEEP {
+        let name = format!("OnlineFiboCircuit n = {}", n);
+        prove_and_verify(&name, 3, n, || online_fibo(n), online_fibo_public(n));
+    }
+}
@@ -0,0 +1,196 @@
+// Language neutral description of what a circuit takes and exposes:
+//
+// {
+//   "name": "FunctionCircuit",
+//   "private_inputs": [{ "name": "x", "bits": 255 }],
+//   "public_inputs": [],
+//   "outputs": []
+// }
+//
+// Public inputs and outputs list instance rows in order. Field elements are
+// described with the bit size of the field.
+
+use halo2_proofs::pasta::{group::ff::PrimeField, Fp};
+use serde_json::{json, Value};
+
+use crate::{fibo1::FiboCircuit, function::FunctionCircuit};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDescription {
+    pub name: String,
+    pub bits: usize,
+}
+
+impl FieldDescription {
+    pub fn new(name: &str, bits: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            bits,
+        }
+    }
+
+    fn field_element(name: &str) -> Self {
+        Self::new(name, Fp::NUM_BITS as usize)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitABI {
+    pub name: String,
+    pub private_inputs: Vec<FieldDescription>,
+    pub public_inputs: Vec<FieldDescription>,
+    pub outputs: Vec<FieldDescription>,
+}
+
+#[derive(Debug)]
+pub enum AbiError {
+    Json(serde_json::Error),
+    // path to the missing or mistyped field
+    Field(String),
+}
+
+impl From<serde_json::Error> for AbiError {
+    fn from(error: serde_json::Error) -> Self {
+        AbiError::Json(error)
+    }
+}
+
+fn fields_to_json(fields: &[FieldDescription]) -> Value {
+    fields
+        .iter()
+        .map(|field| json!({ "name": field.name, "bits": field.bits }))
+        .collect()
+}
+
+fn fields_from_json(abi: &Value, key: &str) -> Result<Vec<FieldDescription>, AbiError> {
+    abi[key]
+        .as_array()
+        .ok_or_else(|| AbiError::Field(key.to_string()))?
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let name = field["name"]
+                .as_str()
+                .ok_or_else(|| AbiError::Field(format!("{}[{}].name", key, i)))?;
+            let bits = field["bits"]
+                .as_u64()
+                .ok_or_else(|| AbiError::Field(format!("{}[{}].bits", key, i)))?;
+            Ok(FieldDescription::new(name, bits as usize))
+        })
+        .collect()
+}
+
+impl CircuitABI {
+    pub fn to_json(&self) -> String {
+        json!({
+            "name": self.name,
+            "private_inputs": fields_to_json(&self.private_inputs),
+            "public_inputs": fields_to_json(&self.public_inputs),
+            "outputs": fields_to_json(&self.outputs),
+        })
+        .to_string()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, AbiError> {
+        let abi: Value = serde_json::from_str(s)?;
+        let name = abi["name"]
+            .as_str()
+            .ok_or_else(|| AbiError::Field("name".to_string()))?;
+        Ok(Self {
+            name: name.to_string(),
+            private_inputs: fields_from_json(&abi, "private_inputs")?,
+            public_inputs: fields_from_json(&abi, "public_inputs")?,
+            outputs: fields_from_json(&abi, "outputs")?,
+        })
+    }
+}
+
+// F(1) = a and F(2) = b are witnessed, nothing is constrained to an instance
+impl From<FiboCircuit<Fp>> for CircuitABI {
+    fn from(_: FiboCircuit<Fp>) -> Self {
+        Self {
+            name: "FiboCircuit".to_string(),
+            private_inputs: vec![
+                FieldDescription::field_element("a"),
+                FieldDescription::field_element("b"),
+            ],
+            public_inputs: vec![],
+            outputs: vec![],
+        }
+    }
+}
+
+// x ^ 3 + x + 5 is checked against the constant 35, x is the only input
+impl From<FunctionCircuit<Fp>> for CircuitABI {
+    fn from(_: FunctionCircuit<Fp>) -> Self {
+        Self {
+            name: "FunctionCircuit".to_string(),
+            private_inputs: vec![FieldDescription::field_element("x")],
+            public_inputs: vec![],
+            outputs: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value as Witness;
+
+    use super::*;
+
+    #[test]
+    fn fibo_round_trip() {
+        let abi = CircuitABI::from(FiboCircuit::<Fp>::default());
+        assert_eq!(
+            abi.private_inputs,
+            [
+                FieldDescription::new("a", 255),
+                FieldDescription::new("b", 255)
+            ]
+        );
+        assert_eq!(CircuitABI::from_json(&abi.to_json()).unwrap(), abi);
+    }
+
+    #[test]
+    fn function_round_trip() {
+        let abi = CircuitABI::from(FunctionCircuit {
+            x: Witness::known(Fp::from(3)),
+        });
+        let json = abi.to_json();
+        let parsed: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["name"], "FunctionCircuit");
+        assert_eq!(
+            parsed["private_inputs"][0],
+            json!({ "name": "x", "bits": 255 })
+        );
+        assert_eq!(CircuitABI::from_json(&json).unwrap(), abi);
+    }
+
+    #[test]
+    fn public_inputs_and_outputs_round_trip() {
+        let abi = CircuitABI {
+            name: "FiboSegmentCircuit".to_string(),
+            private_inputs: vec![],
+            public_inputs: vec![
+                FieldDescription::new("F(start)", 64),
+                FieldDescription::new("F(start + 1)", 64),
+            ],
+            outputs: vec![FieldDescription::new("F(end)", 64)],
+        };
+        assert_eq!(CircuitABI::from_json(&abi.to_json()).unwrap(), abi);
+    }
+
+    #[test]
+    fn malformed_json_is_rejected() {
+        assert!(matches!(CircuitABI::from_json("{"), Err(AbiError::Json(_))));
+        let missing_bits = r#"{"name": "c", "private_inputs": [{"name": "x"}], "public_inputs": [], "outputs": []}"#;
+        match CircuitABI::from_json(missing_bits) {
+            Err(AbiError::Field(path)) => assert_eq!(path, "private_inputs[0].bits"),
+            other => panic!("expected a field error, got {:?}", other),
+        }
+        match CircuitABI::from_json(r#"{"name": "c", "private_inputs": []}"#) {
+            Err(AbiError::Field(path)) => assert_eq!(path, "public_inputs"),
+            other => panic!("expected a field error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// Subtraction and absolute value of small signed values.
+//
+// | a | b | out | s_sub |
+// gate sub: s_sub * (a - b - out) == 0
+//
+// | x | out | s_abs |
+// gate abs: s_abs * (out - x) * (out + x) == 0
+//
+// out is x or -x and is range checked to [0, 2^bits), so it is |x| whenever
+// |x| < 2^bits and the check fails otherwise.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::range_check::{RangeCheckChip, RangeCheckConfig};
+
+#[derive(Debug, Clone)]
+pub struct SubConfig {
+    pub advice: [Column<Advice>; 3],
+    pub s_sub: Selector,
+}
+
+pub struct SubChip<F: FieldExt> {
+    config: SubConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SubChip<F> {
+    pub fn construct(config: SubConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3]) -> SubConfig {
+        let [col_a, col_b, col_out] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let s_sub = meta.selector();
+
+        meta.create_gate("sub", |meta| {
+            let s = meta.query_selector(s_sub);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let out = meta.query_advice(col_out, Rotation::cur());
+            vec![s * (a - b - out)]
+        });
+
+        SubConfig { advice, s_sub }
+    }
+
+    pub fn sub(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "sub",
+            |mut region| {
+                config.s_sub.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let out = a.value().zip(b.value()).map(|(a, b)| *a - *b);
+                region.assign_advice(|| "a - b", config.advice[2], 0, || out)
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AbsoluteValueConfig {
+    pub advice: [Column<Advice>; 2],
+    pub s_abs: Selector,
+    pub range: RangeCheckConfig,
+}
+
+pub struct AbsoluteValueChip<F: FieldExt> {
+    config: AbsoluteValueConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> AbsoluteValueChip<F> {
+    pub fn construct(config: AbsoluteValueConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+        range: RangeCheckConfig,
+    ) -> AbsoluteValueConfig {
+        let [col_x, col_out] = advice;
+        meta.enable_equality(col_x);
+        meta.enable_equality(col_out);
+        let s_abs = meta.selector();
+
+        meta.create_gate("abs", |meta| {
+            let s = meta.query_selector(s_abs);
+            let x = meta.query_advice(col_x, Rotation::cur());
+            let out = meta.query_advice(col_out, Rotation::cur());
+            vec![s * (out.clone() - x.clone()) * (out + x)]
+        });
+
+        AbsoluteValueConfig {
+            advice,
+            s_abs,
+            range,
+        }
+    }
+
+    /// |x|, proven to be below 2^bits.
+    pub fn abs(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let out = layouter.assign_region(
+            || "abs",
+            |mut region| {
+                config.s_abs.enable(&mut region, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                // the negative side is the one up near the modulus
+                let out = x.value().map(|x| (*x).min(-*x));
+                region.assign_advice(|| "|x|", config.advice[1], 0, || out)
+            },
+        )?;
+
+        RangeCheckChip::<F, 64>::construct(config.range.clone()).check_bits(
+            layouter.namespace(|| "|x| range"),
+            &out,
+            bits,
+        )?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+    use crate::range_check::RangeCheckChip;
+
+    // |a - b| below 2^bits
+    //
+    // instance: | a | b | |a - b| |
+    struct DistanceCircuit {
+        bits: usize,
+    }
+
+    impl Circuit<Fp> for DistanceCircuit {
+        type Config = (SubConfig, AbsoluteValueConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { bits: self.bits }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [a, b, c, bit] = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let range = RangeCheckChip::<Fp, 64>::configure(meta, c, bit);
+            (
+                SubChip::configure(meta, [a, b, c]),
+                AbsoluteValueChip::configure(meta, [a, b], range),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (sub, abs, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let [a, b] = [0, 1].map(|row| {
+                layouter.assign_region(
+                    || "input",
+                    |mut region| {
+                        region.assign_advice_from_instance(
+                            || "input",
+                            instance,
+                            row,
+                            sub.advice[row],
+                            0,
+                        )
+                    },
+                )
+            });
+            let diff = SubChip::construct(sub).sub(layouter.namespace(|| "a - b"), &a?, &b?)?;
+            let out = AbsoluteValueChip::construct(abs).abs(
+                layouter.namespace(|| "|a - b|"),
+                &diff,
+                self.bits,
+            )?;
+            layouter.constrain_instance(out.cell(), instance, 2)
+        }
+    }
+
+    fn run(bits: usize, public: [Fp; 3]) -> MockProver<Fp> {
+        MockProver::run(6, &DistanceCircuit { bits }, vec![public.to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn distance_either_way() {
+        let [three, ten, seven] = [3, 10, 7].map(Fp::from);
+        run(8, [ten, three, seven]).assert_satisfied();
+        run(8, [three, ten, seven]).assert_satisfied();
+        run(8, [ten, ten, Fp::zero()]).assert_satisfied();
+    }
+
+    #[test]
+    fn negative_difference_is_not_its_own_absolute_value() {
+        let [three, ten, seven] = [3, 10, 7].map(Fp::from);
+        assert!(run(8, [three, ten, -seven]).verify().is_err());
+        assert!(run(8, [ten, three, seven + Fp::one()]).verify().is_err());
+    }
+
+    #[test]
+    fn absolute_value_must_fit_the_bits() {
+        let [zero, fifteen, sixteen] = [0, 15, 16].map(Fp::from);
+        run(4, [zero, fifteen, fifteen]).assert_satisfied();
+        assert!(run(4, [zero, sixteen, sixteen]).verify().is_err());
+        assert!(run(4, [sixteen, zero, sixteen]).verify().is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// Guards a chip against being synthesized twice. The first call to `synthesize`
+// runs, every later one fails before touching the layouter, so the same chip
+// can't lay out its regions again with a different witness.
+//
+// Error::Synthesis carries no message in halo2 0.2, a second call is reported
+// as the bare variant.
+
+use std::{
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter},
+    plonk::Error,
+};
+
+pub struct AccessControlledChip<F: FieldExt, C: Chip<F>> {
+    chip: C,
+    assigned: AtomicBool,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, C: Chip<F>> AccessControlledChip<F, C> {
+    pub fn new(chip: C) -> Self {
+        Self {
+            chip,
+            assigned: AtomicBool::new(false),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn is_assigned(&self) -> bool {
+        self.assigned.load(Ordering::SeqCst)
+    }
+
+    /// Runs `assign` with the wrapped chip, only the first time it is called.
+    pub fn synthesize<L: Layouter<F>, T>(
+        &self,
+        layouter: L,
+        assign: impl FnOnce(&C, L) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        if self.assigned.swap(true, Ordering::SeqCst) {
+            // double assignment
+            return Err(Error::Synthesis);
+        }
+        assign(&self.chip, layouter)
+    }
+}
+
+impl<F: FieldExt, C: Chip<F>> Chip<F> for AccessControlledChip<F, C> {
+    type Config = C::Config;
+    type Loaded = C::Loaded;
+
+    fn config(&self) -> &Self::Config {
+        self.chip.config()
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        self.chip.loaded()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    use super::*;
+    use crate::fibo1::{FiboChip, FiboConfig};
+
+    // lays out the first Fibonacci row once, or a second time from other
+    // witnesses when `twice` is set
+    struct GuardedCircuit {
+        twice: bool,
+    }
+
+    impl Circuit<Fp> for GuardedCircuit {
+        type Config = FiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { twice: self.twice }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            FiboChip::configure(meta, advice, false)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let guarded = AccessControlledChip::new(FiboChip::construct(config));
+            let known = |x: u64| Value::known(Fp::from(x));
+
+            assert!(!guarded.is_assigned());
+            guarded.synthesize(layouter.namespace(|| "first"), |chip, layouter| {
+                chip.assign_first_row(layouter, known(1), known(1))
+            })?;
+            assert!(guarded.is_assigned());
+            if self.twice {
+                guarded.synthesize(layouter.namespace(|| "second"), |chip, layouter| {
+                    chip.assign_first_row(layouter, known(2), known(3))
+                })?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn first_call_succeeds() {
+        MockProver::run(4, &GuardedCircuit { twice: false }, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn second_call_is_a_double_assignment() {
+        assert!(matches!(
+            MockProver::run(4, &GuardedCircuit { twice: true }, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
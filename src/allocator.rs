@@ -0,0 +1,128 @@
+// Hands out columns and selectors by purpose while a circuit configures. The
+// first request for a purpose allocates, every later one gets the same column
+// back, so chips that agree on a purpose share the column instead of each
+// allocating their own:
+//
+// alloc_advice("a") -> advice 0
+// alloc_advice("b") -> advice 1
+// alloc_advice("a") -> advice 0
+//
+// Purposes are separate per kind, "a" as advice and "a" as fixed are two
+// columns.
+
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Fixed, Selector},
+};
+
+pub struct ColumnAllocator<'a, F: FieldExt> {
+    meta: &'a mut ConstraintSystem<F>,
+    advice: HashMap<String, Column<Advice>>,
+    fixed: HashMap<String, Column<Fixed>>,
+    selectors: HashMap<String, Selector>,
+}
+
+impl<'a, F: FieldExt> ColumnAllocator<'a, F> {
+    pub fn new(meta: &'a mut ConstraintSystem<F>) -> Self {
+        Self {
+            meta,
+            advice: HashMap::new(),
+            fixed: HashMap::new(),
+            selectors: HashMap::new(),
+        }
+    }
+
+    /// The wrapped constraint system, for gates and anything else that isn't
+    /// an allocation.
+    pub fn meta(&mut self) -> &mut ConstraintSystem<F> {
+        self.meta
+    }
+
+    pub fn alloc_advice(&mut self, purpose: &str) -> Column<Advice> {
+        let meta = &mut *self.meta;
+        *self
+            .advice
+            .entry(purpose.to_string())
+            .or_insert_with(|| meta.advice_column())
+    }
+
+    pub fn alloc_fixed(&mut self, purpose: &str) -> Column<Fixed> {
+        let meta = &mut *self.meta;
+        *self
+            .fixed
+            .entry(purpose.to_string())
+            .or_insert_with(|| meta.fixed_column())
+    }
+
+    pub fn alloc_selector(&mut self, purpose: &str) -> Selector {
+        let meta = &mut *self.meta;
+        *self
+            .selectors
+            .entry(purpose.to_string())
+            .or_insert_with(|| meta.selector())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::{
+        fibo1::FiboChip,
+        function::SimpleFunctionChip,
+        recorder::{column_index, parse_index},
+    };
+
+    fn count(meta: &ConstraintSystem<Fp>, prefix: &str) -> usize {
+        parse_index(&format!("{:?}", meta.pinned()), prefix)
+    }
+
+    #[test]
+    fn same_purpose_same_column() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let mut alloc = ColumnAllocator::new(&mut meta);
+        let a = alloc.alloc_advice("a");
+        let b = alloc.alloc_advice("b");
+        assert_eq!(alloc.alloc_advice("a"), a);
+        assert_eq!(alloc.alloc_advice("b"), b);
+        assert_ne!(a, b);
+        assert_eq!([column_index(a), column_index(b)], [0, 1]);
+
+        let f = alloc.alloc_fixed("f");
+        assert_eq!(alloc.alloc_fixed("f"), f);
+        let s = alloc.alloc_selector("s");
+        assert_eq!(alloc.alloc_selector("s"), s);
+
+        assert_eq!(count(&meta, "num_advice_columns: "), 2);
+        assert_eq!(count(&meta, "num_fixed_columns: "), 1);
+        assert_eq!(count(&meta, "num_selectors: "), 1);
+    }
+
+    #[test]
+    fn purposes_are_per_kind() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let mut alloc = ColumnAllocator::new(&mut meta);
+        alloc.alloc_advice("a");
+        alloc.alloc_fixed("a");
+        alloc.alloc_selector("a");
+        alloc.alloc_selector("b");
+        assert_eq!(count(&meta, "num_advice_columns: "), 1);
+        assert_eq!(count(&meta, "num_fixed_columns: "), 1);
+        assert_eq!(count(&meta, "num_selectors: "), 2);
+    }
+
+    #[test]
+    fn chips_share_their_columns() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let mut alloc = ColumnAllocator::new(&mut meta);
+        let fibo = FiboChip::configure_with(&mut alloc, false);
+        let function = SimpleFunctionChip::configure_with(&mut alloc);
+        assert_eq!(fibo.advice, [function.x, function.y, function.z]);
+        // configuring again allocates nothing new
+        SimpleFunctionChip::configure_with(&mut alloc);
+        assert_eq!(count(&meta, "num_advice_columns: "), 3);
+    }
+}
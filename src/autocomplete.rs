@@ -0,0 +1,208 @@
+// Fills in the missing term of a gate. A gate is expected to constrain every
+// cell it is given, so the term that was left out is the product of the cells
+// in `available_cells` that the partial expression never queries, under the
+// selectors in front of it:
+//
+// s * (a + b - ?)   with [a, b, c]   becomes   s * (a + b - c)
+// s * (b - c + ?)   with [a, b, c]   becomes   s * (b - c + a)
+// s * (-c + ?)      with [a, b, c]   becomes   s * (-c + a * b)
+//
+// Solving p + t = 0 for t this way leaves the sign. A gate here reads
+// inputs - output, so a partial expression with no negative monomial lost its
+// output and the term comes in with -1, otherwise it lost an input and comes in
+// with +1.
+//
+// The queries of the new term get query_index 0, the completed gate is for
+// reading and comparing, not for `create_gate`.
+
+use std::cmp::Ordering;
+
+use halo2_proofs::{arithmetic::FieldExt, plonk::Expression, poly::Rotation};
+
+use crate::{
+    gate_flatten::{flatten_expression, ExprAtom},
+    linearize::{gated, split_selector},
+};
+
+fn to_expression<F: FieldExt>(atom: &ExprAtom<F>) -> Expression<F> {
+    let (column_index, rotation) = match *atom {
+        ExprAtom::Constant(c) => return Expression::Constant(c),
+        ExprAtom::Selector(selector) => return Expression::Selector(selector),
+        ExprAtom::Advice(index, rotation)
+        | ExprAtom::Fixed(index, rotation)
+        | ExprAtom::Instance(index, rotation) => (index, Rotation(rotation)),
+    };
+    match atom {
+        ExprAtom::Advice(..) => Expression::Advice {
+            query_index: 0,
+            column_index,
+            rotation,
+        },
+        ExprAtom::Fixed(..) => Expression::Fixed {
+            query_index: 0,
+            column_index,
+            rotation,
+        },
+        _ => Expression::Instance {
+            query_index: 0,
+            column_index,
+            rotation,
+        },
+    }
+}
+
+// past the field's midpoint, bigger than its own negation
+fn is_negative<F: FieldExt>(coeff: F) -> bool {
+    let (repr, negated) = (coeff.to_repr(), (-coeff).to_repr());
+    repr.as_ref()
+        .iter()
+        .rev()
+        .cmp(negated.as_ref().iter().rev())
+        == Ordering::Greater
+}
+
+/// `partial_expr` with its missing term added back, None when every available
+/// cell is already queried or there is nothing to complete.
+pub fn complete_gate<F: FieldExt>(
+    partial_expr: Expression<F>,
+    available_cells: &[ExprAtom<F>],
+) -> Option<Expression<F>> {
+    // the selectors in front gate the missing term too
+    let (selector, body) = split_selector(partial_expr);
+    let terms = flatten_expression(&body);
+    if terms.is_empty() {
+        return None;
+    }
+
+    let missing = available_cells
+        .iter()
+        .filter(|cell| !terms.iter().any(|(_, atoms)| atoms.contains(cell)))
+        .map(to_expression)
+        .reduce(|product, cell| product * cell)?;
+
+    let lost_output = terms.iter().all(|(coeff, atoms)| {
+        let coeff = atoms.iter().fold(*coeff, |coeff, atom| match atom {
+            ExprAtom::Constant(c) => coeff * c,
+            _ => coeff,
+        });
+        !is_negative(coeff)
+    });
+    let body = match lost_output {
+        true => body - missing,
+        false => body + missing,
+    };
+    Some(gated(&selector, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        pasta::Fp,
+        plonk::{ConstraintSystem, VirtualCells},
+    };
+
+    use super::*;
+
+    // the gates of SimpleFunctionChip over cells a, b and c: s * (a + b - c),
+    // s * (a * b - c) and the square s * (a * a - c), each in full and with the
+    // term at `removed` left out
+    #[derive(Clone, Copy)]
+    enum Gate {
+        Add,
+        Mul,
+        Square,
+    }
+
+    fn capture(
+        meta: &mut ConstraintSystem<Fp>,
+        gate: impl FnOnce(&mut VirtualCells<'_, Fp>) -> Expression<Fp>,
+    ) -> Expression<Fp> {
+        let mut captured = None;
+        meta.create_gate("captured", |meta| {
+            let expr = gate(meta);
+            captured = Some(expr.clone());
+            vec![expr]
+        });
+        captured.unwrap()
+    }
+
+    // (full gate, partial gate, cells of the gate)
+    fn gate(gate: Gate, removed: usize) -> (Expression<Fp>, Expression<Fp>, Vec<ExprAtom<Fp>>) {
+        let mut meta = ConstraintSystem::default();
+        let columns = [(); 3].map(|_| meta.advice_column());
+        let s = meta.selector();
+        let build = |meta: &mut VirtualCells<'_, Fp>, skip: Option<usize>| {
+            let s = meta.query_selector(s);
+            let [a, b, c] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+            let terms = match gate {
+                Gate::Add => vec![a, b, -c],
+                Gate::Mul => vec![a * b, -c],
+                Gate::Square => vec![a.clone() * a, -c],
+            };
+            let body = terms
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| Some(*i) != skip)
+                .map(|(_, term)| term)
+                .reduce(|sum, term| sum + term)
+                .unwrap();
+            s * body
+        };
+        let full = capture(&mut meta, |meta| build(meta, None));
+        let partial = capture(&mut meta, |meta| build(meta, Some(removed)));
+        let cells = match gate {
+            Gate::Square => vec![ExprAtom::Advice(0, 0), ExprAtom::Advice(2, 0)],
+            _ => (0..3).map(|i| ExprAtom::Advice(i, 0)).collect(),
+        };
+        (full, partial, cells)
+    }
+
+    // equal up to the order of the monomials
+    fn assert_same_terms(left: &Expression<Fp>, right: &Expression<Fp>) {
+        let (left, right) = (flatten_expression(left), flatten_expression(right));
+        assert_eq!(left.len(), right.len());
+        assert!(left.iter().all(|term| right.contains(term)));
+    }
+
+    #[test]
+    fn completes_the_add_gate() {
+        for removed in 0..3 {
+            let (full, partial, cells) = gate(Gate::Add, removed);
+            assert_same_terms(&complete_gate(partial, &cells).unwrap(), &full);
+        }
+    }
+
+    #[test]
+    fn completes_the_mul_gate() {
+        for removed in 0..2 {
+            let (full, partial, cells) = gate(Gate::Mul, removed);
+            assert_same_terms(&complete_gate(partial, &cells).unwrap(), &full);
+        }
+    }
+
+    #[test]
+    fn completes_the_square_gate() {
+        // s * (-c + ?) only knows a, which comes back on its own, not squared
+        let (_, partial, cells) = gate(Gate::Square, 0);
+        let completed = flatten_expression(&complete_gate(partial, &cells).unwrap());
+        assert_eq!(completed.len(), 2);
+        assert!(completed.iter().all(|(_, atoms)| atoms.len() == 2));
+
+        let (full, partial, cells) = gate(Gate::Square, 1);
+        assert_same_terms(&complete_gate(partial, &cells).unwrap(), &full);
+    }
+
+    #[test]
+    fn nothing_missing() {
+        let (full, _, cells) = gate(Gate::Add, 0);
+        assert!(complete_gate(full, &cells).is_none());
+    }
+
+    #[test]
+    fn negative_coefficients() {
+        assert!(is_negative(-Fp::one()));
+        assert!(is_negative(-Fp::from(5)));
+        assert!(!is_negative(Fp::one()));
+        assert!(!is_negative(Fp::zero()));
+    }
+}
@@ -0,0 +1,239 @@
+// K claimed evaluations p_i(z_i) = v_i of private polynomials, checked at once
+// through a random linear combination under a public challenge r:
+//
+// sum_i r^i * (p_i(z_i) - v_i) == 0
+//
+// p_i(z_i) on PolynomialEvalChip, d_i = p_i(z_i) - v_i on SubChip, r^i * d_i on
+// the mul gate of SimpleFunctionChip and the total on SumChip, which is pinned
+// to the constant 0. A wrong claim survives only if r is a root of the
+// combination, which r chosen after the claims makes unlikely.
+//
+// instance: | r | z_0 | v_0 | ... | z_{K-1} | v_{K-1} |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+};
+
+use crate::{
+    abs::{SubChip, SubConfig},
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+    poly_eval::{PolynomialEvalChip, PolynomialEvalConfig},
+    sum::{SumChip, SumConfig},
+};
+
+// out of circuit p(z), coefficients lowest degree first
+pub fn evaluate_polynomial<F: FieldExt>(coefficients: &[F], z: F) -> F {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, coeff| acc * z + *coeff)
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchVerifyConfig {
+    pub eval: PolynomialEvalConfig,
+    pub sub: SubConfig,
+    pub function: SimpleFunctionConfig,
+    pub sum: SumConfig,
+    pub constant: Column<Fixed>,
+    pub instance: Column<Instance>,
+}
+
+pub struct BatchVerifyCircuit<F, const K: usize> {
+    // coefficients of p_i, lowest degree first
+    pub polynomials: Vec<Vec<Value<F>>>,
+}
+
+impl<F: FieldExt, const K: usize> BatchVerifyCircuit<F, K> {
+    pub fn new(polynomials: Vec<Vec<F>>) -> Self {
+        Self {
+            polynomials: polynomials
+                .into_iter()
+                .map(|p| p.into_iter().map(Value::known).collect())
+                .collect(),
+        }
+    }
+
+    /// The instance for the claims (z_i, v_i) under challenge r.
+    pub fn public_inputs(r: F, claims: &[(F, F)]) -> Vec<F> {
+        let mut instance = vec![r];
+        for (z, v) in claims {
+            instance.extend([*z, *v]);
+        }
+        instance
+    }
+}
+
+impl<F: FieldExt, const K: usize> Circuit<F> for BatchVerifyCircuit<F, K> {
+    type Config = BatchVerifyConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            polynomials: self
+                .polynomials
+                .iter()
+                .map(|p| vec![Value::unknown(); p.len()])
+                .collect(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_constant(constant);
+        meta.enable_equality(instance);
+
+        BatchVerifyConfig {
+            eval: PolynomialEvalChip::configure(meta, a, b, c),
+            sub: SubChip::configure(meta, [a, b, c]),
+            function: SimpleFunctionChip::configure(meta, a, b, c),
+            sum: SumChip::configure(meta, a, b),
+            constant,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if K == 0 || self.polynomials.len() != K {
+            return Err(Error::Synthesis);
+        }
+        let eval = PolynomialEvalChip::construct(config.eval);
+        let sub = SubChip::construct(config.sub);
+        let function = SimpleFunctionChip::construct(config.function.clone());
+        let sum = SumChip::construct(config.sum);
+
+        let (r, claims) = layouter.assign_region(
+            || "load",
+            |mut region| {
+                let mut load = |row: usize| {
+                    region.assign_advice_from_instance(
+                        || "public",
+                        config.instance,
+                        row,
+                        config.function.x,
+                        row,
+                    )
+                };
+                let r = load(0)?;
+                let claims = (0..K)
+                    .map(|i| Ok((load(1 + 2 * i)?, load(2 + 2 * i)?)))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok((r, claims))
+            },
+        )?;
+
+        let mut terms: Vec<AssignedCell<F, F>> = vec![];
+        let mut power: Option<Number<F>> = None;
+        for (i, (polynomial, (z, v))) in self.polynomials.iter().zip(&claims).enumerate() {
+            let mut layouter = layouter.namespace(|| format!("claim {}", i));
+            let p = eval.evaluate(layouter.namespace(|| "p(z)"), polynomial, z)?;
+            let d = sub.sub(layouter.namespace(|| "p(z) - v"), &p, v)?;
+
+            // r^0 * d_0 needs no multiplication
+            let term = match &power {
+                None => {
+                    power = Some(Number(r.clone()));
+                    d
+                }
+                Some(r_i) => {
+                    let term =
+                        function.mul_cells(layouter.namespace(|| "r^i * d"), r_i, &Number(d))?;
+                    power = Some(function.mul_cells(
+                        layouter.namespace(|| "r^(i + 1)"),
+                        r_i,
+                        &Number(r.clone()),
+                    )?);
+                    term.0
+                }
+            };
+            terms.push(term);
+        }
+
+        let total = sum.sum(layouter.namespace(|| "combination"), &terms)?;
+        layouter.assign_region(
+            || "combination is zero",
+            |mut region| {
+                let total = total.copy_advice(|| "total", &mut region, config.function.x, 0)?;
+                region.constrain_constant(total.cell(), F::zero())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::group::ff::Field;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use rand_core::OsRng;
+
+    use super::*;
+
+    fn random_claims(count: usize, degree: usize) -> (Vec<Vec<Fp>>, Vec<(Fp, Fp)>) {
+        let polynomials: Vec<Vec<Fp>> = (0..count)
+            .map(|_| (0..=degree).map(|_| Fp::random(OsRng)).collect())
+            .collect();
+        let claims = polynomials
+            .iter()
+            .map(|p| {
+                let z = Fp::random(OsRng);
+                (z, evaluate_polynomial(p, z))
+            })
+            .collect();
+        (polynomials, claims)
+    }
+
+    fn run<const K: usize>(polynomials: Vec<Vec<Fp>>, instance: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = BatchVerifyCircuit::<Fp, K>::new(polynomials);
+        MockProver::run(8, &circuit, vec![instance]).unwrap()
+    }
+
+    #[test]
+    fn evaluate_polynomial_lowest_degree_first() {
+        // 3 + 2x + x^2 at 5
+        let p = [Fp::from(3), Fp::from(2), Fp::one()];
+        assert_eq!(evaluate_polynomial(&p, Fp::from(5)), Fp::from(38));
+    }
+
+    #[test]
+    fn three_random_claims() {
+        let (polynomials, claims) = random_claims(3, 3);
+        let instance = BatchVerifyCircuit::<Fp, 3>::public_inputs(Fp::random(OsRng), &claims);
+        run::<3>(polynomials, instance).assert_satisfied();
+    }
+
+    #[test]
+    fn five_random_claims() {
+        let (polynomials, claims) = random_claims(5, 4);
+        let instance = BatchVerifyCircuit::<Fp, 5>::public_inputs(Fp::random(OsRng), &claims);
+        run::<5>(polynomials, instance).assert_satisfied();
+    }
+
+    #[test]
+    fn one_wrong_claim_fails() {
+        let (polynomials, mut claims) = random_claims(5, 2);
+        claims[3].1 += Fp::one();
+        let instance = BatchVerifyCircuit::<Fp, 5>::public_inputs(Fp::random(OsRng), &claims);
+        assert!(run::<5>(polynomials, instance).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_polynomial_count() {
+        let (polynomials, claims) = random_claims(2, 2);
+        let instance = BatchVerifyCircuit::<Fp, 3>::public_inputs(Fp::random(OsRng), &claims);
+        let circuit = BatchVerifyCircuit::<Fp, 3>::new(polynomials);
+        assert!(matches!(
+            MockProver::run(8, &circuit, vec![instance]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+// z = x^T A y for a public N x N matrix A
+//
+// A is fixed through the constant column, then
+// w_i = <A_i, y> for every row A_i of A and z = <x, w>,
+// all on the inner product chip.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::{
+    inner_product::{InnerProductChip, InnerProductConfig},
+    matrix::load_constant_matrix,
+};
+
+#[derive(Debug, Clone)]
+pub struct BilinearFormConfig {
+    pub inner: InnerProductConfig,
+    pub constant: Column<Fixed>,
+}
+
+pub struct BilinearFormChip<F: FieldExt, const N: usize> {
+    config: BilinearFormConfig,
+    matrix: [[F; N]; N],
+}
+
+impl<F: FieldExt, const N: usize> BilinearFormChip<F, N> {
+    pub fn construct(config: BilinearFormConfig, matrix: [[F; N]; N]) -> Self {
+        Self {
+            config,
+            matrix,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constant: Column<Fixed>,
+    ) -> BilinearFormConfig {
+        meta.enable_constant(constant);
+
+        BilinearFormConfig {
+            inner: InnerProductChip::configure(meta, advice[0], advice[1], advice[2]),
+            constant,
+        }
+    }
+
+    fn load(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>; N],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let column = self.config.inner.a;
+        layouter.assign_region(
+            || "load vector",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| region.assign_advice(|| "v", column, row, || *value))
+                    .collect()
+            },
+        )
+    }
+
+    pub fn bilinear_form(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &[Value<F>; N],
+        y: &[Value<F>; N],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let inner = InnerProductChip::<F>::construct(self.config.inner.clone());
+
+        let x = self.load(layouter.namespace(|| "x"), x)?;
+        let y = self.load(layouter.namespace(|| "y"), y)?;
+        let matrix =
+            load_constant_matrix(layouter.namespace(|| "A"), self.config.inner.a, self.matrix)?;
+
+        let w = matrix
+            .iter()
+            .map(|row| inner.inner_product(layouter.namespace(|| "A_i y"), row, &y))
+            .collect::<Result<Vec<_>, _>>()?;
+        inner.inner_product(layouter.namespace(|| "x w"), &x, &w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    const A: [[u64; 3]; 3] = [[2, 1, 0], [1, 3, 1], [0, 1, 4]];
+
+    // x^T A y, instance: | z |
+    struct BilinearCircuit {
+        x: [Value<Fp>; 3],
+        y: [Value<Fp>; 3],
+    }
+
+    impl Circuit<Fp> for BilinearCircuit {
+        type Config = (BilinearFormConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                x: [Value::unknown(); 3],
+                y: [Value::unknown(); 3],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                BilinearFormChip::<Fp, 3>::configure(meta, advice, constant),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BilinearFormChip::construct(config, A.map(|row| row.map(Fp::from)));
+            let z = chip.bilinear_form(layouter.namespace(|| "x^T A y"), &self.x, &self.y)?;
+            layouter.constrain_instance(z.cell(), instance, 0)
+        }
+    }
+
+    fn prove(x: [u64; 3], y: [u64; 3], z: u64) -> MockProver<Fp> {
+        let circuit = BilinearCircuit {
+            x: x.map(|x| Value::known(Fp::from(x))),
+            y: y.map(|y| Value::known(Fp::from(y))),
+        };
+        MockProver::run(6, &circuit, vec![vec![Fp::from(z)]]).unwrap()
+    }
+
+    #[test]
+    fn symmetric_three_by_three() {
+        // A y = (13, 25, 29), x . A y = 13 + 50 + 87
+        prove([1, 2, 3], [4, 5, 6], 150).assert_satisfied();
+        // A is symmetric, so swapping x and y gives the same form
+        prove([4, 5, 6], [1, 2, 3], 150).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_form_fails() {
+        assert!(prove([1, 2, 3], [4, 5, 6], 151).verify().is_err());
+    }
+}
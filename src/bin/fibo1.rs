@@ -0,0 +1,30 @@
+use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+use halo2halo::fibo1::FiboCircuit;
+
+fn main() {
+    let k = 4;
+    let a = Fp::from(1);
+    let b = Fp::from(1);
+
+    let circuit = FiboCircuit {
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+
+    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+    prover.assert_satisfied();
+
+    use plotters::prelude::*;
+    let root = BitMapBackend::new("./target/fibo1circuit.png", (1024, 768)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let root = root
+        .titled("Fibo 1 Layout", ("sans-serif", 60))
+        .unwrap();
+
+    halo2_proofs::dev::CircuitLayout::default()
+        // .show_labels(false)
+        // Render the circuit onto your area!
+        // The first argument is the size parameter for the circuit.
+        .render(4, &circuit, &root)
+        .unwrap();
+}
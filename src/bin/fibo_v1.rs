@@ -0,0 +1,37 @@
+use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+use halo2halo::{
+    fibo1::FiboCircuit,
+    fibo_v1::{CircuitLayoutComparator, FiboCircuitV1},
+};
+
+fn main() {
+    let k = 4;
+    let a = Value::known(Fp::from(1));
+    let b = Value::known(Fp::from(1));
+
+    let simple = FiboCircuit { a, b };
+    let v1 = FiboCircuitV1 { a, b };
+
+    MockProver::run(k, &simple, vec![])
+        .unwrap()
+        .assert_satisfied();
+    MockProver::run(k, &v1, vec![]).unwrap().assert_satisfied();
+
+    let difference = CircuitLayoutComparator::cell_difference(&simple, &v1, k, vec![]).unwrap();
+    println!("V1 uses {} cells more than SimpleFloorPlanner", difference);
+
+    use plotters::prelude::*;
+    let root = BitMapBackend::new("./target/fibo1circuit_v1.png", (2048, 768)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let root = root
+        .titled("Fibo 1 Layout: SimpleFloorPlanner | V1", ("sans-serif", 60))
+        .unwrap();
+    let (left, right) = root.split_horizontally(1024);
+
+    halo2_proofs::dev::CircuitLayout::default()
+        .render(k, &simple, &left)
+        .unwrap();
+    halo2_proofs::dev::CircuitLayout::default()
+        .render(k, &v1, &right)
+        .unwrap();
+}
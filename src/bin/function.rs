@@ -0,0 +1,28 @@
+use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+use halo2halo::function::FunctionCircuit;
+
+fn main() {
+    let k = 4;
+    let x = Fp::from(3);
+
+    let circuit = FunctionCircuit {
+        x: Value::known(x),
+    };
+
+    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+    prover.assert_satisfied();
+
+    use plotters::prelude::*;
+    let root = BitMapBackend::new("./target/function.png", (1024, 768)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let root = root
+        .titled("Function", ("sans-serif", 60))
+        .unwrap();
+
+    halo2_proofs::dev::CircuitLayout::default()
+        // .show_labels(false)
+        // Render the circuit onto your area!
+        // The first argument is the size parameter for the circuit.
+        .render(4, &circuit, &root)
+        .unwrap();
+}
@@ -0,0 +1,168 @@
+// f(x, y) = a * x^2 * y + b * x * y^2 + c on the add and mul gates of
+// SimpleFunctionChip, evaluated as x * y * (a * x + b * y) + c:
+//
+// xy = x * y, ax = a * x, by = b * y, t = ax + by, p = xy * t, f = p + c
+//
+// x, y and the coefficients are copied into every row that uses them.
+//
+// instance: | a | b | c | f(x, y) |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::function::{Number, SimpleFunctionChip, SimpleFunctionConfig};
+
+pub struct BivariateFunctionChip<F: FieldExt> {
+    config: SimpleFunctionConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BivariateFunctionChip<F> {
+    pub fn construct(config: SimpleFunctionConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn evaluate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &Number<F>,
+        y: &Number<F>,
+        coefficients: [&Number<F>; 3],
+    ) -> Result<Number<F>, Error> {
+        let chip = SimpleFunctionChip::<F>::construct(self.config.clone());
+        let [a, b, c] = coefficients;
+
+        let xy = chip.mul_cells(layouter.namespace(|| "x * y"), x, y)?;
+        let ax = chip.mul_cells(layouter.namespace(|| "a * x"), a, x)?;
+        let by = chip.mul_cells(layouter.namespace(|| "b * y"), b, y)?;
+        let t = chip.add_cells(layouter.namespace(|| "a * x + b * y"), &ax, &by)?;
+        let p = chip.mul_cells(layouter.namespace(|| "x * y * t"), &xy, &t)?;
+        chip.add_cells(layouter.namespace(|| "+ c"), &p, c)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BivariateFunctionConfig {
+    pub function: SimpleFunctionConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct BivariateFunctionCircuit<F: FieldExt> {
+    pub x: Value<F>,
+    pub y: Value<F>,
+}
+
+impl<F: FieldExt> BivariateFunctionCircuit<F> {
+    pub fn new(x: F, y: F) -> Self {
+        Self {
+            x: Value::known(x),
+            y: Value::known(y),
+        }
+    }
+
+    pub fn public_inputs(coefficients: [F; 3], value: F) -> Vec<F> {
+        let [a, b, c] = coefficients;
+        vec![a, b, c, value]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for BivariateFunctionCircuit<F> {
+    type Config = BivariateFunctionConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        BivariateFunctionConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let function = &config.function;
+        let (x, y, [a, b, c]) = layouter.assign_region(
+            || "load",
+            |mut region| {
+                let x = region
+                    .assign_advice(|| "x", function.x, 0, || self.x)
+                    .map(Number)?;
+                let y = region
+                    .assign_advice(|| "y", function.y, 0, || self.y)
+                    .map(Number)?;
+                let mut coefficient = |row: usize| {
+                    region
+                        .assign_advice_from_instance(
+                            || "coefficient",
+                            config.instance,
+                            row,
+                            function.z,
+                            row,
+                        )
+                        .map(Number)
+                };
+                Ok((x, y, [coefficient(0)?, coefficient(1)?, coefficient(2)?]))
+            },
+        )?;
+
+        let chip = BivariateFunctionChip::construct(function.clone());
+        let f = chip.evaluate(layouter.namespace(|| "f(x, y)"), &x, &y, [&a, &b, &c])?;
+        layouter.constrain_instance(f.0.cell(), config.instance, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn run(x: u64, y: u64, coefficients: [Fp; 3], value: Fp) -> MockProver<Fp> {
+        let circuit = BivariateFunctionCircuit::new(Fp::from(x), Fp::from(y));
+        let public = BivariateFunctionCircuit::public_inputs(coefficients, value);
+        MockProver::run(4, &circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn x2y_plus_xy2_plus_1_at_2_3() {
+        // 4 * 3 + 2 * 9 + 1
+        run(2, 3, [Fp::one(); 3], Fp::from(31)).assert_satisfied();
+    }
+
+    #[test]
+    fn other_coefficients() {
+        // 2 * 4 * 3 - 2 * 9 + 5
+        let coefficients = [Fp::from(2), -Fp::one(), Fp::from(5)];
+        run(2, 3, coefficients, Fp::from(11)).assert_satisfied();
+        // only c at x = 0
+        run(0, 7, coefficients, Fp::from(5)).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        assert!(run(2, 3, [Fp::one(); 3], Fp::from(30)).verify().is_err());
+        // 2 * 4 * 3 + 2 * 9 + 1 = 43 with a = 2
+        let coefficients = [Fp::from(2), Fp::one(), Fp::one()];
+        assert!(run(2, 3, coefficients, Fp::from(31)).verify().is_err());
+    }
+}
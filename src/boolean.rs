@@ -0,0 +1,73 @@
+// | b | selector |
+// gate boolean: selector * b * (1 - b) == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct BooleanConfig {
+    pub advice: Column<Advice>,
+    pub selector: Selector,
+}
+
+pub struct BooleanChip<F: FieldExt> {
+    config: BooleanConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BooleanChip<F> {
+    pub fn construct(config: BooleanConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: Column<Advice>) -> BooleanConfig {
+        meta.enable_equality(advice);
+        let selector = meta.selector();
+
+        meta.create_gate("boolean", |meta| {
+            let s = meta.query_selector(selector);
+            let b = meta.query_advice(advice, Rotation::cur());
+            vec![s * b.clone() * (Expression::Constant(F::one()) - b)]
+        });
+
+        BooleanConfig { advice, selector }
+    }
+
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        b: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "boolean",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                region.assign_advice(|| "b", self.config.advice, 0, || b)
+            },
+        )
+    }
+
+    pub fn assert_boolean(
+        &self,
+        mut layouter: impl Layouter<F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "boolean",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                b.copy_advice(|| "b", &mut region, self.config.advice, 0)?;
+                Ok(())
+            },
+        )
+    }
+}
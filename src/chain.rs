@@ -0,0 +1,301 @@
+// Composes two chips into one circuit: A(x) = y, B(y) = z.
+//
+// x is copied from the instance into A's input, A's output is copied through the
+// io column into B's input, and B's output is constrained to the instance. The
+// copy between A and B is checked by a `CrossChipCopyManager`, and B depending on
+// A but not the other way round by a `ChipDependencyGraph`.
+//
+// instance: | x | z |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    chip_depends_on,
+    copy_manager::CrossChipCopyManager,
+    dependency::ChipDependencyGraph,
+    fibo1::{ACell, FiboChip, FiboConfig},
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+};
+
+/// A chip that maps one assigned cell to another.
+pub trait ChainStep<F: FieldExt>: Chip<F> {
+    fn configure_step(meta: &mut ConstraintSystem<F>) -> Self::Config;
+
+    fn construct_step(config: Self::Config) -> Self;
+
+    fn apply(
+        &self,
+        layouter: impl Layouter<F>,
+        input: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error>;
+}
+
+// x seeds both starting numbers, y is the last number of `FiboCircuit`'s run
+impl<F: FieldExt> ChainStep<F> for FiboChip<F> {
+    fn configure_step(meta: &mut ConstraintSystem<F>) -> FiboConfig {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        FiboChip::configure(meta, advices, false)
+    }
+
+    fn construct_step(config: FiboConfig) -> Self {
+        FiboChip::construct(config)
+    }
+
+    fn apply(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let x = ACell(input.clone());
+        let mut prev_b = x.clone();
+        let mut prev_c = self.assign_row(layouter.namespace(|| "first row"), &x, &x)?;
+
+        for _ in 3..10 {
+            let c_cell = self.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+
+            prev_b = prev_c;
+            prev_c = c_cell;
+        }
+        Ok(prev_c.0)
+    }
+}
+
+// y ^ 3 + y + 5 = z
+impl<F: FieldExt> ChainStep<F> for SimpleFunctionChip<F> {
+    fn configure_step(meta: &mut ConstraintSystem<F>) -> SimpleFunctionConfig {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+        SimpleFunctionChip::configure(meta, x, y, z)
+    }
+
+    fn construct_step(config: SimpleFunctionConfig) -> Self {
+        SimpleFunctionChip::construct(config)
+    }
+
+    fn apply(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let y = Number(input.clone());
+
+        let square = self.mul_cells(layouter.namespace(|| "square"), &y, &y)?;
+        let cube = self.mul_cells(layouter.namespace(|| "cube"), &square, &y)?;
+        let sum = self.add_cells(layouter.namespace(|| "add y"), &cube, &y)?;
+
+        let config = self.config();
+        let five = layouter.assign_region(
+            || "5",
+            |mut region| {
+                region
+                    .assign_advice_from_constant(|| "5", config.x, 0, F::from(5))
+                    .map(Number)
+            },
+        )?;
+        let z = self.add_cells(layouter.namespace(|| "add 5"), &sum, &five)?;
+
+        Ok(z.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainedConfig<A, B> {
+    pub io: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub a: A,
+    pub b: B,
+}
+
+pub struct ChainedCircuit<F: FieldExt, A, B> {
+    pub x: Value<F>,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<F: FieldExt, A, B> ChainedCircuit<F, A, B> {
+    pub fn new(x: F) -> Self {
+        Self {
+            x: Value::known(x),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, A, B> Default for ChainedCircuit<F, A, B> {
+    fn default() -> Self {
+        Self {
+            x: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, A: ChainStep<F>, B: ChainStep<F>> Circuit<F> for ChainedCircuit<F, A, B> {
+    type Config = ChainedConfig<A::Config, B::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let io = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(io);
+        meta.enable_equality(instance);
+
+        ChainedConfig {
+            io,
+            instance,
+            a: A::configure_step(meta),
+            b: B::configure_step(meta),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let mut dependencies = ChipDependencyGraph::new();
+        chip_depends_on!(dependencies, B, A);
+        if dependencies.detect_cycles().is_some() {
+            return Err(Error::Synthesis);
+        }
+
+        let a = A::construct_step(config.a);
+        let b = B::construct_step(config.b);
+
+        let x = layouter.assign_region(
+            || "x",
+            |mut region| {
+                region.assign_advice_from_instance(|| "x", config.instance, 0, config.io, 0)
+            },
+        )?;
+        let y = a.apply(layouter.namespace(|| "A"), &x)?;
+
+        let mut copies = CrossChipCopyManager::new();
+        let y_in = layouter.assign_region(
+            || "handoff",
+            |mut region| y.copy_advice(|| "y", &mut region, config.io, 0),
+        )?;
+        copies.register_copy("A", &y, "B", &y_in);
+
+        let z = b.apply(layouter.namespace(|| "B"), &y_in)?;
+        copies.validate().map_err(|_| Error::Synthesis)?;
+
+        layouter.constrain_instance(z.cell(), config.instance, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    use super::*;
+
+    type FiboThenPoly = ChainedCircuit<Fp, FiboChip<Fp>, SimpleFunctionChip<Fp>>;
+
+    // y = 55 x for F(1) = F(2) = x, z = y^3 + y + 5
+    fn z(y: u64) -> u64 {
+        y * y * y + y + 5
+    }
+
+    // `ChainedCircuit` with y + 1 handed to B, still copy constrained to y
+    struct TamperedChain;
+
+    impl Circuit<Fp> for TamperedChain {
+        type Config = <FiboThenPoly as Circuit<Fp>>::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FiboThenPoly::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let a = FiboChip::construct_step(config.a);
+            let b = SimpleFunctionChip::construct_step(config.b);
+
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| {
+                    region.assign_advice_from_instance(|| "x", config.instance, 0, config.io, 0)
+                },
+            )?;
+            let y = a.apply(layouter.namespace(|| "A"), &x)?;
+            let y_in = layouter.assign_region(
+                || "handoff",
+                |mut region| {
+                    let tampered = y.value().map(|y| *y + Fp::one());
+                    let y_in = region.assign_advice(|| "y", config.io, 0, || tampered)?;
+                    region.constrain_equal(y.cell(), y_in.cell())?;
+                    Ok(y_in)
+                },
+            )?;
+            let z = b.apply(layouter.namespace(|| "B"), &y_in)?;
+            layouter.constrain_instance(z.cell(), config.instance, 1)
+        }
+    }
+
+    #[test]
+    fn fibo_then_polynomial() {
+        let circuit = FiboThenPoly::new(Fp::one());
+        let public = vec![vec![Fp::one(), Fp::from(z(55))]];
+        MockProver::run(6, &circuit, public)
+            .unwrap()
+            .assert_satisfied();
+
+        let circuit = FiboThenPoly::new(Fp::from(2));
+        let public = vec![vec![Fp::from(2), Fp::from(z(110))]];
+        MockProver::run(6, &circuit, public)
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_output_fails() {
+        let circuit = FiboThenPoly::new(Fp::one());
+        let public = vec![vec![Fp::one(), Fp::from(z(55) + 1)]];
+        assert!(MockProver::run(6, &circuit, public)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn tampered_intermediate_breaks_the_copy() {
+        // z matches the tampered y = 56, only the handoff copy is off
+        let public = vec![vec![Fp::one(), Fp::from(z(56))]];
+        let failures = MockProver::run(6, &TamperedChain, public)
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert!(!failures.is_empty());
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+    }
+}
@@ -0,0 +1,118 @@
+// assign_advice that refuses unknown values. An unknown witness otherwise ends
+// up as a zero in the proof and only shows as a failed verification somewhere
+// else, this stops synthesis on the cell instead.
+//
+// The value closure is only called by backends that want witnesses: keygen and
+// the floor planner's measuring pass never call it, so circuits built from
+// `without_witnesses` still lay out as before. MockProver and create_proof do
+// call it, and there an unknown value is an error.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, Error},
+};
+
+use crate::recorder::value_of;
+
+/// Errors with `Error::Synthesis` when `val` is unknown while witnesses are
+/// being collected, `Error::Synthesis` carries no message in this halo2 so
+/// `name` only labels the cell.
+pub fn assign_advice_checked<F: FieldExt>(
+    region: &mut Region<'_, F>,
+    col: Column<Advice>,
+    row: usize,
+    val: Value<F>,
+    name: &str,
+) -> Result<AssignedCell<F, F>, Error> {
+    let mut unknown = false;
+    let cell = region.assign_advice(
+        || name,
+        col,
+        row,
+        || {
+            unknown = value_of(val).is_none();
+            val
+        },
+    )?;
+    match unknown {
+        true => Err(Error::Synthesis),
+        false => Ok(cell),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    use super::*;
+    use crate::ipa::keygen;
+
+    // one checked cell holding `value`
+    struct CheckedCircuit {
+        value: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for CheckedCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            column: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "checked",
+                |mut region| {
+                    assign_advice_checked(&mut region, column, 0, self.value, "value")?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn known_value_assigns() {
+        let circuit = CheckedCircuit {
+            value: Value::known(Fp::from(7)),
+        };
+        MockProver::run(4, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn unknown_value_fails_synthesis() {
+        let circuit = CheckedCircuit {
+            value: Value::known(Fp::from(7)),
+        };
+        assert!(matches!(
+            MockProver::run(4, &circuit.without_witnesses(), vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn keygen_never_asks_for_the_value() {
+        let circuit = CheckedCircuit {
+            value: Value::unknown(),
+        };
+        assert!(keygen(&circuit, 4).is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+// A Fibonacci proof the verifier only needs (n, a, b) for: the final value and
+// the bytes of an IPA proof. `FiboCircuit` keeps everything private, so the
+// proof is of `FiboSegmentCircuit` from F(1) = a, F(2) = b to F(n), which
+// exposes the final value.
+//
+// instance: | a | b | F(n) |
+
+use halo2_proofs::{pasta::Fp, plonk::Error};
+
+use crate::{
+    fibo_segment::FiboSegmentCircuit,
+    ipa::{create_ipa_proof, verify_ipa_proof},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactFiboProof {
+    pub final_value: Fp,
+    pub snark_bytes: Vec<u8>,
+}
+
+// F(n) out of circuit
+fn fib(n: usize, a: Fp, b: Fp) -> Fp {
+    let (mut a, mut b) = (a, b);
+    for _ in 1..n {
+        (a, b) = (b, a + b);
+    }
+    a
+}
+
+// n - 2 rows plus the blinding rows
+fn k_for(n: usize) -> u32 {
+    let mut k = 4;
+    while (1 << k) < n + 8 {
+        k += 1;
+    }
+    k
+}
+
+fn public_inputs(a: Fp, b: Fp, final_value: Fp) -> Vec<Vec<Fp>> {
+    vec![vec![a, b, final_value]]
+}
+
+/// Proves F(n) for F(1) = a, F(2) = b, n must be at least 3.
+pub fn generate_compact_proof(n: usize, a: Fp, b: Fp) -> Result<CompactFiboProof, Error> {
+    if n < 3 {
+        return Err(Error::Synthesis);
+    }
+    let final_value = fib(n, a, b);
+    let circuit = FiboSegmentCircuit::new(1, n, a, b);
+    let snark_bytes = create_ipa_proof(circuit, &public_inputs(a, b, final_value), k_for(n))?;
+    Ok(CompactFiboProof {
+        final_value,
+        snark_bytes,
+    })
+}
+
+pub fn verify_compact_proof(proof: &CompactFiboProof, n: usize, a: Fp, b: Fp) -> bool {
+    if n < 3 {
+        return false;
+    }
+    let circuit = FiboSegmentCircuit::<Fp> {
+        start: 1,
+        end: n,
+        ..Default::default()
+    };
+    verify_ipa_proof(
+        &circuit,
+        &proof.snark_bytes,
+        &public_inputs(a, b, proof.final_value),
+        k_for(n),
+    )
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let (one, two) = (Fp::one(), Fp::from(2));
+        let proof = generate_compact_proof(10, one, one).unwrap();
+        assert_eq!(proof.final_value, Fp::from(55));
+        assert!(verify_compact_proof(&proof, 10, one, one));
+
+        // 2, 3, 5, 8, 13, ..., F(20) = 17711
+        let proof = generate_compact_proof(20, two, Fp::from(3)).unwrap();
+        assert_eq!(proof.final_value, Fp::from(17711));
+        assert!(verify_compact_proof(&proof, 20, two, Fp::from(3)));
+    }
+
+    #[test]
+    fn wrong_statement_is_rejected() {
+        let one = Fp::one();
+        let proof = generate_compact_proof(10, one, one).unwrap();
+        assert!(!verify_compact_proof(&proof, 11, one, one));
+        assert!(!verify_compact_proof(&proof, 10, one, Fp::from(2)));
+
+        let wrong_value = CompactFiboProof {
+            final_value: Fp::from(56),
+            ..proof.clone()
+        };
+        assert!(!verify_compact_proof(&wrong_value, 10, one, one));
+    }
+
+    #[test]
+    fn tampered_bytes_are_rejected() {
+        let one = Fp::one();
+        let mut proof = generate_compact_proof(10, one, one).unwrap();
+        let middle = proof.snark_bytes.len() / 2;
+        proof.snark_bytes[middle] ^= 1;
+        assert!(!verify_compact_proof(&proof, 10, one, one));
+    }
+
+    #[test]
+    fn n_below_3_is_rejected() {
+        let one = Fp::one();
+        assert!(matches!(
+            generate_compact_proof(2, one, one),
+            Err(Error::Synthesis)
+        ));
+        let proof = generate_compact_proof(3, one, one).unwrap();
+        assert!(!verify_compact_proof(&proof, 2, one, one));
+    }
+}
@@ -0,0 +1,161 @@
+// | a | b | out | diff | s_min | s_max |
+// gate min: s_min * (out - a) * (out - b) == 0, s_min * (a + b - 2 * out - diff) == 0
+// gate max: s_max * (out - a) * (out - b) == 0, s_max * (2 * out - a - b - diff) == 0
+//
+// out is one of the inputs and diff = |a - b| is range checked to [0, 2^BITS),
+// so out is the smaller (larger) input as long as both inputs are below 2^BITS
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::range_check::{RangeCheckChip, RangeCheckConfig};
+
+#[derive(Debug, Clone)]
+pub struct CompareConfig {
+    pub advice: [Column<Advice>; 4],
+    pub selector: Selector,
+    pub range: RangeCheckConfig,
+}
+
+fn configure_compare<F: FieldExt>(
+    meta: &mut ConstraintSystem<F>,
+    name: &'static str,
+    advice: [Column<Advice>; 4],
+    range: RangeCheckConfig,
+    is_min: bool,
+) -> CompareConfig {
+    let [col_a, col_b, col_out, col_diff] = advice;
+    for column in advice {
+        meta.enable_equality(column);
+    }
+    let selector = meta.selector();
+
+    meta.create_gate(name, |meta| {
+        let s = meta.query_selector(selector);
+        let a = meta.query_advice(col_a, Rotation::cur());
+        let b = meta.query_advice(col_b, Rotation::cur());
+        let out = meta.query_advice(col_out, Rotation::cur());
+        let diff = meta.query_advice(col_diff, Rotation::cur());
+        let two = Expression::Constant(F::from(2));
+
+        let expected_diff = if is_min {
+            a.clone() + b.clone() - two * out.clone()
+        } else {
+            two * out.clone() - a.clone() - b.clone()
+        };
+        vec![
+            s.clone() * (out.clone() - a) * (out - b),
+            s * (expected_diff - diff),
+        ]
+    });
+
+    CompareConfig {
+        advice,
+        selector,
+        range,
+    }
+}
+
+fn assign_compare<F: FieldExt>(
+    config: &CompareConfig,
+    mut layouter: impl Layouter<F>,
+    a: &AssignedCell<F, F>,
+    b: &AssignedCell<F, F>,
+    is_min: bool,
+) -> Result<AssignedCell<F, F>, Error> {
+    let (out, diff) = layouter.assign_region(
+        || if is_min { "min" } else { "max" },
+        |mut region| {
+            config.selector.enable(&mut region, 0)?;
+            a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+            b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+
+            let out_val =
+                a.value()
+                    .zip(b.value())
+                    .map(|(a, b)| if is_min { *a.min(b) } else { *a.max(b) });
+            let diff_val =
+                a.value()
+                    .zip(b.value())
+                    .map(|(a, b)| if a > b { *a - *b } else { *b - *a });
+
+            let out = region.assign_advice(|| "out", config.advice[2], 0, || out_val)?;
+            let diff = region.assign_advice(|| "diff", config.advice[3], 0, || diff_val)?;
+            Ok((out, diff))
+        },
+    )?;
+
+    RangeCheckChip::<F, 64>::construct(config.range.clone())
+        .check(layouter.namespace(|| "diff range"), &diff)?;
+    Ok(out)
+}
+
+/// Outputs the smaller of two values below `2^64`.
+pub struct MinChip<F: FieldExt> {
+    config: CompareConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> MinChip<F> {
+    pub fn construct(config: CompareConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        range: RangeCheckConfig,
+    ) -> CompareConfig {
+        configure_compare(meta, "min", advice, range, true)
+    }
+
+    pub fn min(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assign_compare(&self.config, layouter, a, b, true)
+    }
+}
+
+/// Outputs the larger of two values below `2^64`.
+pub struct MaxChip<F: FieldExt> {
+    config: CompareConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> MaxChip<F> {
+    pub fn construct(config: CompareConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        range: RangeCheckConfig,
+    ) -> CompareConfig {
+        configure_compare(meta, "max", advice, range, false)
+    }
+
+    pub fn max(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assign_compare(&self.config, layouter, a, b, false)
+    }
+}
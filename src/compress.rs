@@ -0,0 +1,93 @@
+// Counts how many advice cells a circuit would keep if every value assigned in
+// more than two cells lived in one canonical cell, with the others copy
+// constrained to it. Copy constraints can only be added during synthesis, so
+// this reports the saving instead of rewriting the circuit.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, Error},
+};
+
+use crate::recorder::record;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionReport {
+    pub original_cells: usize,
+    pub cells_after: usize,
+}
+
+/// Only advice cells with a known value are counted.
+pub fn compress_witnesses<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    k: u32,
+) -> Result<CompressionReport, Error> {
+    let recorder = record(circuit, k, vec![])?;
+
+    let mut groups: BTreeMap<F, usize> = BTreeMap::new();
+    for value in recorder.advice.values().flatten() {
+        *groups.entry(*value).or_default() += 1;
+    }
+
+    let original_cells = groups.values().sum();
+    let merged: usize = groups
+        .values()
+        .filter(|&&size| size > 2)
+        .map(|size| size - 1)
+        .sum();
+    Ok(CompressionReport {
+        original_cells,
+        cells_after: original_cells - merged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use super::*;
+    use crate::fibo1::FiboCircuit;
+
+    fn fibo(a: u64, b: u64) -> FiboCircuit<Fp> {
+        FiboCircuit {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        }
+    }
+
+    #[test]
+    fn fibo_with_a_b_1() {
+        // 8 rows of 3 cells: 1 | 1 | 2, then 1 | 2 | 3 up to 21 | 34 | 55. 1 up
+        // to 21 each show up three times and keep one cell, 34 twice and 55 once.
+        let report = compress_witnesses(&fibo(1, 1), 5).unwrap();
+        assert_eq!(
+            report,
+            CompressionReport {
+                original_cells: 24,
+                cells_after: 7 + 2 + 1,
+            }
+        );
+    }
+
+    #[test]
+    fn all_zero_fibo_keeps_one_cell() {
+        let report = compress_witnesses(&fibo(0, 0), 5).unwrap();
+        assert_eq!(
+            report,
+            CompressionReport {
+                original_cells: 24,
+                cells_after: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn pairs_are_not_merged() {
+        // 2 | 1 | 3, 1 | 3 | 4, ...: 2 shows up once and 1 twice, 3 up to 29
+        // three times, 47 twice and 76 once. Only the six triples merge.
+        let report = compress_witnesses(&fibo(2, 1), 5).unwrap();
+        assert_eq!(report.original_cells, 24);
+        assert_eq!(report.cells_after, 1 + 2 + 6 + 2 + 1);
+    }
+}
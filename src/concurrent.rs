@@ -0,0 +1,137 @@
+// Lays out two independent circuits side by side in one constraint system, so a
+// single proof covers both. A's columns, gates and instance columns are
+// configured first, then B's.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Debug, Clone)]
+pub struct CombinedConfig<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+pub struct ConcurrentCircuit<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A, B> ConcurrentCircuit<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+
+    // instance columns of A followed by those of B
+    pub fn public_inputs<F: FieldExt>(a: Vec<Vec<F>>, b: Vec<Vec<F>>) -> Vec<Vec<F>> {
+        a.into_iter().chain(b).collect()
+    }
+}
+
+impl<F: FieldExt, A: Circuit<F>, B: Circuit<F>> Circuit<F> for ConcurrentCircuit<A, B> {
+    type Config = CombinedConfig<A::Config, B::Config>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.a.without_witnesses(), self.b.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        CombinedConfig {
+            a: A::configure(meta),
+            b: B::configure(meta),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.a.synthesize(config.a, layouter.namespace(|| "A"))?;
+        self.b.synthesize(config.b, layouter.namespace(|| "B"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{
+        fibo1::FiboCircuit,
+        fibo_segment::FiboSegmentCircuit,
+        function::{FunctionCircuit, ParametricFunctionCircuit},
+        ipa::{create_ipa_proof, verify_ipa_proof},
+    };
+
+    fn fibo_and_function() -> ConcurrentCircuit<FiboCircuit<Fp>, FunctionCircuit<Fp>> {
+        ConcurrentCircuit::new(
+            FiboCircuit {
+                a: Value::known(Fp::one()),
+                b: Value::known(Fp::one()),
+            },
+            FunctionCircuit {
+                x: Value::known(Fp::from(3)),
+            },
+        )
+    }
+
+    // F(0) = 0, F(1) = 1 up to F(10) = 55 and 2x^3 - 3x^2 + x + 7 = 13 at x = 2
+    fn segment_and_cubic() -> (
+        ConcurrentCircuit<FiboSegmentCircuit<Fp>, ParametricFunctionCircuit<Fp>>,
+        Vec<Vec<Fp>>,
+    ) {
+        let circuit = ConcurrentCircuit::new(
+            FiboSegmentCircuit::new(0, 10, Fp::zero(), Fp::one()),
+            ParametricFunctionCircuit {
+                x: Value::known(Fp::from(2)),
+            },
+        );
+        let public = ConcurrentCircuit::<(), ()>::public_inputs(
+            vec![[0, 1, 55].map(Fp::from).to_vec()],
+            vec![vec![
+                Fp::from(2),
+                -Fp::from(3),
+                Fp::one(),
+                Fp::from(7),
+                Fp::from(13),
+            ]],
+        );
+        (circuit, public)
+    }
+
+    #[test]
+    fn fibo_and_function_concurrently() {
+        MockProver::run(5, &fibo_and_function(), vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn both_instances_are_checked() {
+        let (circuit, public) = segment_and_cubic();
+        MockProver::run(5, &circuit, public.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        for (column, row) in [(0, 2), (1, 4)] {
+            let mut wrong = public.clone();
+            wrong[column][row] += Fp::one();
+            assert!(MockProver::run(5, &circuit, wrong)
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn one_proof_covers_both() {
+        let (circuit, public) = segment_and_cubic();
+        let proof = create_ipa_proof(circuit, &public, 5).unwrap();
+        let (circuit, _) = segment_and_cubic();
+        verify_ipa_proof(&circuit, &proof, &public, 5).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+//! A gate switched on by witness data instead of a fixed selector.
+//!
+//! | cond | input | expected | selector |
+//! gate conditional: selector * cond * (input - expected) == 0
+//! gate condition boolean: selector * cond * (1 - cond) == 0
+//!
+//! Security: `cond` is an advice cell, so the prover chooses it. A prover can
+//! always pick `cond = 0` and switch the check off, which makes the gate
+//! meaningless unless `cond` is tied to something the prover does not control
+//! (copied from a constrained cell, or exposed as a public input). This chip
+//! copies `cond` in from the caller so that binding stays the caller's job.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct ConditionalGateConfig {
+    pub cond: Column<Advice>,
+    pub input: Column<Advice>,
+    pub expected: Column<Advice>,
+    pub selector: Selector,
+}
+
+pub struct ConditionalGateChip<F: FieldExt> {
+    config: ConditionalGateConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> ConditionalGateChip<F> {
+    pub fn construct(config: ConditionalGateConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        cond: Column<Advice>,
+        input: Column<Advice>,
+        expected: Column<Advice>,
+    ) -> ConditionalGateConfig {
+        meta.enable_equality(cond);
+        let selector = meta.selector();
+
+        meta.create_gate("conditional", |meta| {
+            let s = meta.query_selector(selector);
+            let cond = meta.query_advice(cond, Rotation::cur());
+            let input = meta.query_advice(input, Rotation::cur());
+            let expected = meta.query_advice(expected, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            vec![
+                s.clone() * cond.clone() * (input - expected),
+                s * cond.clone() * (one - cond),
+            ]
+        });
+
+        ConditionalGateConfig {
+            cond,
+            input,
+            expected,
+            selector,
+        }
+    }
+
+    // enforces input == expected only when cond is 1
+    pub fn conditional(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: AssignedCell<F, F>,
+        input: Value<F>,
+        expected: Value<F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "conditional",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                cond.copy_advice(|| "cond", &mut region, config.cond, 0)?;
+                region.assign_advice(|| "input", config.input, 0, || input)?;
+                region.assign_advice(|| "expected", config.expected, 0, || expected)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // cond comes from the instance, so the prover can't switch the check off
+    struct ConditionalCircuit {
+        input: Value<Fp>,
+        expected: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for ConditionalCircuit {
+        type Config = (ConditionalGateConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                input: Value::unknown(),
+                expected: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [cond, input, expected] = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                ConditionalGateChip::configure(meta, cond, input, expected),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let cond = layouter.assign_region(
+                || "cond",
+                |mut region| {
+                    region.assign_advice_from_instance(|| "cond", instance, 0, config.cond, 0)
+                },
+            )?;
+            ConditionalGateChip::construct(config).conditional(
+                layouter.namespace(|| "check"),
+                cond,
+                self.input,
+                self.expected,
+            )
+        }
+    }
+
+    fn prove(cond: u64, input: u64, expected: u64) -> MockProver<Fp> {
+        let circuit = ConditionalCircuit {
+            input: Value::known(Fp::from(input)),
+            expected: Value::known(Fp::from(expected)),
+        };
+        MockProver::run(4, &circuit, vec![vec![Fp::from(cond)]]).unwrap()
+    }
+
+    #[test]
+    fn enabled_checks_equality() {
+        prove(1, 7, 7).assert_satisfied();
+        assert!(prove(1, 7, 8).verify().is_err());
+    }
+
+    #[test]
+    fn disabled_accepts_anything() {
+        prove(0, 7, 7).assert_satisfied();
+        prove(0, 7, 8).assert_satisfied();
+    }
+
+    #[test]
+    fn non_boolean_condition_fails() {
+        assert!(prove(2, 7, 7).verify().is_err());
+    }
+}
@@ -0,0 +1,249 @@
+// a * b mod N for a public modulus N < 2^32, on inputs already known to be
+// below 2^32:
+//
+// | a | b | q | r | diff | s_mul_mod | modulus |
+// | a | b | q | r | ...  | 1         | N       |
+// gate mul mod: s_mul_mod * (a * b - q * N - r) == 0
+//               s_mul_mod * (N - 1 - r - diff) == 0
+//
+// q, r and diff are range checked to [0, 2^32), so r < N and q * N + r stays
+// far below the field modulus: the product can't wrap on either side.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    fibo_modular::to_u64,
+    range_check::{RangeCheckChip, RangeCheckConfig},
+};
+
+pub const CONGRUENCE_BITS: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct CongruenceConfig {
+    pub advice: [Column<Advice>; 5],
+    pub modulus: Column<Fixed>,
+    pub s_mul_mod: Selector,
+    pub range: RangeCheckConfig,
+}
+
+pub struct CongruenceChip<F: FieldExt> {
+    config: CongruenceConfig,
+    modulus: u64,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> CongruenceChip<F> {
+    pub fn construct(config: CongruenceConfig, modulus: u64) -> Self {
+        Self {
+            config,
+            modulus,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        modulus: Column<Fixed>,
+    ) -> CongruenceConfig {
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let [col_a, col_b, col_q, col_r, col_diff] = advice;
+        let s_mul_mod = meta.selector();
+
+        meta.create_gate("mul mod", |meta| {
+            let s = meta.query_selector(s_mul_mod);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let q = meta.query_advice(col_q, Rotation::cur());
+            let r = meta.query_advice(col_r, Rotation::cur());
+            let diff = meta.query_advice(col_diff, Rotation::cur());
+            let n = meta.query_fixed(modulus, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            vec![
+                s.clone() * (a * b - q * n.clone() - r.clone()),
+                s * (n - one - r - diff),
+            ]
+        });
+
+        CongruenceConfig {
+            advice,
+            modulus,
+            s_mul_mod,
+            range: RangeCheckChip::<F, CONGRUENCE_BITS>::configure(meta, col_a, col_b),
+        }
+    }
+
+    /// Range checks `value` to [0, 2^32), what `mul_mod` expects of its inputs.
+    pub fn check_input(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        RangeCheckChip::<F, CONGRUENCE_BITS>::construct(self.config.range.clone())
+            .check(layouter, value)?;
+        Ok(())
+    }
+
+    /// The reduced a * b mod N, both inputs copied in.
+    pub fn mul_mod(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let n = self.modulus;
+        if !(2..1 << CONGRUENCE_BITS).contains(&n) {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+
+        let (q, r, diff) = layouter.assign_region(
+            || "mul mod",
+            |mut region| {
+                config.s_mul_mod.enable(&mut region, 0)?;
+                region.assign_fixed(|| "N", config.modulus, 0, || Value::known(F::from(n)))?;
+                let a = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+
+                let product = a
+                    .value()
+                    .zip(b.value())
+                    .map(|(a, b)| to_u64(*a) as u128 * to_u64(*b) as u128);
+                let q = product.map(|product| F::from_u128(product / n as u128));
+                let r = product.map(|product| (product % n as u128) as u64);
+
+                let q = region.assign_advice(|| "q", config.advice[2], 0, || q)?;
+                let r_cell =
+                    region.assign_advice(|| "r", config.advice[3], 0, || r.map(F::from))?;
+                let diff = region.assign_advice(
+                    || "diff",
+                    config.advice[4],
+                    0,
+                    || r.map(|r| F::from(n - 1 - r)),
+                )?;
+                Ok((q, r_cell, diff))
+            },
+        )?;
+
+        let range = RangeCheckChip::<F, CONGRUENCE_BITS>::construct(config.range.clone());
+        range.check(layouter.namespace(|| "q < 2^32"), &q)?;
+        range.check(layouter.namespace(|| "r < 2^32"), &r)?;
+        range.check(layouter.namespace(|| "N - 1 - r < 2^32"), &diff)?;
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // a * b mod N exposed at instance row 0, a and b range checked first
+    struct MulModCircuit {
+        modulus: u64,
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for MulModCircuit {
+        type Config = (CongruenceConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                modulus: self.modulus,
+                a: Value::unknown(),
+                b: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let modulus = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (CongruenceChip::configure(meta, advice, modulus), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let advice = config.advice;
+            let chip = CongruenceChip::construct(config, self.modulus);
+            let (a, b) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", advice[0], 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", advice[1], 0, || self.b)?;
+                    Ok((a, b))
+                },
+            )?;
+            chip.check_input(layouter.namespace(|| "a"), &a)?;
+            chip.check_input(layouter.namespace(|| "b"), &b)?;
+            let r = chip.mul_mod(layouter.namespace(|| "a * b mod N"), &a, &b)?;
+            layouter.constrain_instance(r.cell(), instance, 0)
+        }
+    }
+
+    fn prove(modulus: u64, a: u64, b: u64, r: u64) -> Result<MockProver<Fp>, Error> {
+        let circuit = MulModCircuit {
+            modulus,
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        };
+        MockProver::run(8, &circuit, vec![vec![Fp::from(r)]])
+    }
+
+    #[test]
+    fn small_product() {
+        prove(10, 7, 9, 3).unwrap().assert_satisfied();
+        // already reduced
+        prove(100, 7, 9, 63).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn product_past_64_bits() {
+        let n: u64 = (1 << 32) - 5;
+        let (a, b) = (n - 1, n - 2);
+        let r = (a as u128 * b as u128 % n as u128) as u64;
+        // (-1) * (-2) mod N
+        assert_eq!(r, 2);
+        prove(n, a, b, r).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_remainder_fails() {
+        assert!(prove(10, 7, 9, 13).unwrap().verify().is_err());
+        assert!(prove(10, 7, 9, 4).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn input_past_32_bits_fails() {
+        assert!(prove(10, 1 << 32, 1, 6).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn modulus_out_of_range() {
+        for modulus in [0, 1, 1 << 32] {
+            assert!(matches!(prove(modulus, 1, 1, 0), Err(Error::Synthesis)));
+        }
+    }
+}
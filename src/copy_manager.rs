@@ -0,0 +1,205 @@
+// Central record of the copies between chips. A copy points from the source cell
+// into the destination cell that takes its value, so a destination fed by two
+// sources or a chain of copies leading back to where it started means two chips
+// disagree about who owns a value.
+//
+// `Cell` has no Eq or Hash, cells are identified by their Debug form.
+
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::AssignedCell};
+
+#[derive(Debug, Clone)]
+pub struct CopyConstraint {
+    pub source_chip: String,
+    pub source_cell: String,
+    pub dest_chip: String,
+    pub dest_cell: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopyConflictError {
+    // the destination cell is copied into from more than one source
+    DoubleAssignment {
+        dest_chip: String,
+        dest_cell: String,
+    },
+    // chips along a chain of copies that returns to its first cell
+    Cycle {
+        chips: Vec<String>,
+    },
+}
+
+#[derive(Debug)]
+pub struct CrossChipCopyManager<F: FieldExt> {
+    pub copies: Vec<CopyConstraint>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Default for CrossChipCopyManager<F> {
+    fn default() -> Self {
+        Self {
+            copies: vec![],
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> CrossChipCopyManager<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_copy(
+        &mut self,
+        source_chip: &str,
+        source: &AssignedCell<F, F>,
+        dest_chip: &str,
+        dest: &AssignedCell<F, F>,
+    ) {
+        self.copies.push(CopyConstraint {
+            source_chip: source_chip.to_string(),
+            source_cell: format!("{:?}", source.cell()),
+            dest_chip: dest_chip.to_string(),
+            dest_cell: format!("{:?}", dest.cell()),
+        });
+    }
+
+    pub fn validate(&self) -> Result<(), CopyConflictError> {
+        let mut incoming: BTreeMap<&str, usize> = BTreeMap::new();
+        for (i, copy) in self.copies.iter().enumerate() {
+            if incoming.insert(&copy.dest_cell, i).is_some() {
+                return Err(CopyConflictError::DoubleAssignment {
+                    dest_chip: copy.dest_chip.clone(),
+                    dest_cell: copy.dest_cell.clone(),
+                });
+            }
+        }
+
+        // every cell has at most one incoming copy, so walking back along the
+        // sources from any cell either ends or loops
+        for start in &self.copies {
+            let mut chips = vec![start.dest_chip.clone()];
+            let mut cell = start.source_cell.as_str();
+            for _ in 0..self.copies.len() {
+                if cell == start.dest_cell {
+                    chips.reverse();
+                    return Err(CopyConflictError::Cycle { chips });
+                }
+                match incoming.get(cell) {
+                    Some(&i) => {
+                        chips.push(self.copies[i].dest_chip.clone());
+                        cell = &self.copies[i].source_cell;
+                    }
+                    None => break,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::*;
+    use crate::recorder::record;
+
+    // lays out cells c0..c3 and registers copies (source, dest) between them,
+    // cell i belongs to chip i
+    struct CopiesCircuit {
+        copies: Vec<(usize, usize)>,
+        result: RefCell<Option<Result<(), CopyConflictError>>>,
+    }
+
+    impl Circuit<Fp> for CopiesCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            copies_circuit(&self.copies)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            column: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let cells = layouter.assign_region(
+                || "cells",
+                |mut region| {
+                    (0..4)
+                        .map(|row| {
+                            region.assign_advice(|| "c", column, row, || Value::known(Fp::zero()))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            let chip = |i: usize| ["A", "B", "C", "D"][i];
+            let mut manager = CrossChipCopyManager::new();
+            for &(source, dest) in &self.copies {
+                manager.register_copy(chip(source), &cells[source], chip(dest), &cells[dest]);
+            }
+            *self.result.borrow_mut() = Some(manager.validate());
+            Ok(())
+        }
+    }
+
+    fn copies_circuit(copies: &[(usize, usize)]) -> CopiesCircuit {
+        CopiesCircuit {
+            copies: copies.to_vec(),
+            result: RefCell::new(None),
+        }
+    }
+
+    fn result(copies: &[(usize, usize)]) -> Result<(), CopyConflictError> {
+        let circuit = copies_circuit(copies);
+        record(&circuit, 4, vec![]).unwrap();
+        circuit.result.into_inner().unwrap()
+    }
+
+    #[test]
+    fn valid_copies() {
+        assert_eq!(result(&[]), Ok(()));
+        // A -> B -> C, A -> D
+        assert_eq!(result(&[(0, 1), (1, 2), (0, 3)]), Ok(()));
+    }
+
+    #[test]
+    fn double_assignment() {
+        match result(&[(0, 2), (1, 2)]) {
+            Err(CopyConflictError::DoubleAssignment { dest_chip, .. }) => {
+                assert_eq!(dest_chip, "C")
+            }
+            other => panic!("expected a double assignment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cycle() {
+        match result(&[(0, 1), (1, 2), (2, 0)]) {
+            Err(CopyConflictError::Cycle { mut chips }) => {
+                chips.sort();
+                assert_eq!(chips, ["A", "B", "C"]);
+            }
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        assert_eq!(
+            result(&[(3, 3)]),
+            Err(CopyConflictError::Cycle {
+                chips: vec!["D".to_string()]
+            })
+        );
+    }
+}
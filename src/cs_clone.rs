@@ -0,0 +1,209 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+// `pinned` holds everything that determines the constraint system (columns,
+// gates, queries, permutation, lookups) so two systems are isomorphic iff their
+// pinned forms print the same
+pub fn is_isomorphic<F: FieldExt>(a: &ConstraintSystem<F>, b: &ConstraintSystem<F>) -> bool {
+    format!("{:?}", a.pinned()) == format!("{:?}", b.pinned())
+}
+
+// rebuilds `cs` by running `configure`, the function that built it, on a fresh
+// system, so the copy owns nothing of the original
+pub fn clone_constraint_system<F: FieldExt>(
+    cs: &ConstraintSystem<F>,
+    configure: impl Fn(&mut ConstraintSystem<F>),
+) -> ConstraintSystem<F> {
+    let mut cloned = ConstraintSystem::default();
+    configure(&mut cloned);
+    assert!(
+        is_isomorphic(cs, &cloned),
+        "configure produced a different constraint system"
+    );
+    cloned
+}
+
+/// Captures a `configure` function so that fresh copies of the same constraint
+/// system can be produced on demand.
+pub struct CircuitTemplate<F: FieldExt> {
+    configure: Box<dyn Fn(&mut ConstraintSystem<F>)>,
+    reference: ConstraintSystem<F>,
+}
+
+impl<F: FieldExt> CircuitTemplate<F> {
+    pub fn new(configure: impl Fn(&mut ConstraintSystem<F>) + 'static) -> Self {
+        let mut reference = ConstraintSystem::default();
+        configure(&mut reference);
+        Self {
+            configure: Box::new(configure),
+            reference,
+        }
+    }
+
+    pub fn from_circuit<C: Circuit<F>>() -> Self {
+        Self::new(|meta| {
+            C::configure(meta);
+        })
+    }
+
+    pub fn instantiate(&self) -> ConstraintSystem<F> {
+        clone_constraint_system(&self.reference, &self.configure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::Error,
+    };
+
+    use super::*;
+    use crate::fibo1::{FiboChip, FiboCircuit, FiboConfig};
+
+    thread_local! {
+        // the constraint system the next `TemplateFibo::configure` runs on
+        static INSTANCE: RefCell<Option<ConstraintSystem<Fp>>> = RefCell::new(None);
+    }
+
+    // FiboCircuit proven on an instance of its template. The config is taken
+    // from a fresh FiboCircuit configure, isomorphic systems share column and
+    // selector indices. `tamper` breaks the second row.
+    struct TemplateFibo {
+        fibo: FiboCircuit<Fp>,
+        tamper: bool,
+    }
+
+    impl Circuit<Fp> for TemplateFibo {
+        type Config = FiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                fibo: self.fibo.without_witnesses(),
+                tamper: self.tamper,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let config = FiboCircuit::<Fp>::configure(&mut ConstraintSystem::default());
+            *meta = INSTANCE
+                .with(|instance| instance.borrow_mut().take())
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            if !self.tamper {
+                return self.fibo.synthesize(config, layouter);
+            }
+            let chip = FiboChip::construct(config.clone());
+            let (_, b, c) = chip.assign_first_row(
+                layouter.namespace(|| "first row"),
+                self.fibo.a,
+                self.fibo.b,
+            )?;
+            layouter.assign_region(
+                || "tampered row",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    b.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                    c.0.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                    let wrong = b.0.value().copied() + c.0.value() + Value::known(Fp::one());
+                    region.assign_advice(|| "c", config.advice[2], 0, || wrong)
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn prove_on(instance: ConstraintSystem<Fp>, tamper: bool) -> MockProver<Fp> {
+        INSTANCE.with(|cell| *cell.borrow_mut() = Some(instance));
+        let circuit = TemplateFibo {
+            fibo: FiboCircuit {
+                a: Value::known(Fp::one()),
+                b: Value::known(Fp::one()),
+            },
+            tamper,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap()
+    }
+
+    fn fibo(reverse: bool) -> impl Fn(&mut ConstraintSystem<Fp>) {
+        move |meta| {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            FiboChip::configure(meta, advice, reverse);
+        }
+    }
+
+    #[test]
+    fn instances_of_a_template_are_isomorphic() {
+        let template = CircuitTemplate::<Fp>::from_circuit::<FiboCircuit<Fp>>();
+        assert!(is_isomorphic(
+            &template.instantiate(),
+            &template.instantiate()
+        ));
+    }
+
+    #[test]
+    fn witness_satisfies_both_instances_of_a_template() {
+        let template = CircuitTemplate::<Fp>::from_circuit::<FiboCircuit<Fp>>();
+        let instances = [template.instantiate(), template.instantiate()];
+        for instance in instances.clone() {
+            prove_on(instance, false).assert_satisfied();
+        }
+        for instance in instances {
+            assert!(prove_on(instance, true).verify().is_err());
+        }
+    }
+
+    #[test]
+    fn clone_is_isomorphic_to_the_original() {
+        let mut cs = ConstraintSystem::default();
+        fibo(true)(&mut cs);
+        assert!(is_isomorphic(
+            &cs,
+            &clone_constraint_system(&cs, fibo(true))
+        ));
+    }
+
+    #[test]
+    fn different_configurations_are_not_isomorphic() {
+        // the reverse flag adds the "sub" gate
+        let forward = CircuitTemplate::new(fibo(false)).instantiate();
+        let reverse = CircuitTemplate::new(fibo(true)).instantiate();
+        assert!(!is_isomorphic(&forward, &reverse));
+    }
+
+    #[test]
+    #[should_panic(expected = "configure produced a different constraint system")]
+    fn clone_with_another_configure_panics() {
+        let mut cs = ConstraintSystem::default();
+        fibo(false)(&mut cs);
+        clone_constraint_system(&cs, fibo(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "configure produced a different constraint system")]
+    fn template_with_changing_configure_panics() {
+        // one more advice column every time it runs
+        let runs = Cell::new(0);
+        let template = CircuitTemplate::<Fp>::new(move |meta| {
+            runs.set(runs.get() + 1);
+            for _ in 0..runs.get() {
+                meta.advice_column();
+            }
+        });
+        template.instantiate();
+    }
+}
@@ -0,0 +1,129 @@
+// Every column holding a cell of a copy constraint has to be in the permutation
+// argument, which halo2 only reports once `MockProver` or the prover reaches the
+// copy. The permutation columns are read back from the pinned constraint system
+// and compared with the copies recorded during synthesis, by their Debug form.
+
+use std::collections::BTreeSet;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+use crate::recorder::{parse_index, record_layout};
+
+fn equality_columns<F: FieldExt>(cs: &ConstraintSystem<F>) -> BTreeSet<String> {
+    let pinned = format!("{:?}", cs.pinned());
+    let start = pinned.find("permutation: Argument { columns: [").unwrap();
+    let columns = &pinned[start..];
+    let columns = &columns[..columns.find(']').unwrap()];
+
+    columns
+        .match_indices("Column {")
+        .map(|(i, _)| {
+            let column = &columns[i..];
+            column[..column.find('}').unwrap() + 1].to_string()
+        })
+        .collect()
+}
+
+/// Reports every column that appears in a copy constraint of the synthesized
+/// circuit without having equality enabled.
+pub fn validate_equality_enabled<F: FieldExt, C: Circuit<F> + Default>(
+    k: u32,
+) -> Result<(), Vec<String>> {
+    let mut cs = ConstraintSystem::<F>::default();
+    C::configure(&mut cs);
+    let enabled = equality_columns(&cs);
+
+    let recorder = record_layout(&C::default(), k).map_err(|e| vec![format!("{:?}", e)])?;
+
+    let mut reported = BTreeSet::new();
+    let mut errors = vec![];
+    for (column, row) in recorder
+        .copies
+        .iter()
+        .flat_map(|(left, right)| [left, right])
+    {
+        let debug = format!("{:?}", column);
+        if enabled.contains(&debug) || !reported.insert(debug.clone()) {
+            continue;
+        }
+        errors.push(format!(
+            "{:?} column {} is copy constrained at row {} but equality is not enabled",
+            column.column_type(),
+            parse_index(&debug, "index: "),
+            row
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        pasta::Fp,
+        plonk::{Advice, Column, Error},
+    };
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, function::FunctionCircuit};
+
+    // copies a cell of `a` into `b`, equality is only enabled on `a`
+    #[derive(Default)]
+    struct MissingEqualityCircuit;
+
+    impl Circuit<Fp> for MissingEqualityCircuit {
+        type Config = [Column<Advice>; 2];
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [a, b] = [(); 2].map(|_| meta.advice_column());
+            meta.enable_equality(a);
+            [a, b]
+        }
+
+        fn synthesize(
+            &self,
+            [a, b]: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    let x = region.assign_advice(|| "x", a, 0, || Value::known(Fp::one()))?;
+                    x.copy_advice(|| "x", &mut region, b, 1)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn fibo_and_function_are_valid() {
+        assert_eq!(validate_equality_enabled::<Fp, FiboCircuit<Fp>>(4), Ok(()));
+        assert_eq!(
+            validate_equality_enabled::<Fp, FunctionCircuit<Fp>>(4),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn missing_equality_is_reported() {
+        let errors = validate_equality_enabled::<Fp, MissingEqualityCircuit>(4).unwrap_err();
+        assert_eq!(
+            errors,
+            ["Advice column 1 is copy constrained at row 1 but equality is not enabled"]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+// Which chips consume another chip's output, as a directed graph from a chip to
+// the chips it depends on. A cycle means no chip on it can be laid out first.
+//
+// petgraph isn't a dependency of this crate and there is no constructor crate
+// to run code at load time, so the graph is a plain adjacency map and
+// `chip_depends_on!` registers into a graph it is handed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Default)]
+pub struct ChipDependencyGraph {
+    pub edges: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// `chip_depends_on!(graph, A, B)` records that chip `A` depends on chip `B`.
+#[macro_export]
+macro_rules! chip_depends_on {
+    ($graph:expr, $from:ident, $to:ident) => {
+        $graph.add_dependency(stringify!($from), stringify!($to))
+    };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+impl ChipDependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_dependency(&mut self, from: &str, to: &str) {
+        self.edges
+            .entry(from.to_string())
+            .or_default()
+            .insert(to.to_string());
+        self.edges.entry(to.to_string()).or_default();
+    }
+
+    /// Chips along the first cycle found, starting and ending with the same chip.
+    pub fn detect_cycles(&self) -> Option<Vec<String>> {
+        let mut visits = BTreeMap::new();
+        let mut path = vec![];
+        self.edges
+            .keys()
+            .find_map(|chip| self.visit(chip, &mut visits, &mut path))
+    }
+
+    fn visit<'g>(
+        &'g self,
+        chip: &'g str,
+        visits: &mut BTreeMap<&'g str, Visit>,
+        path: &mut Vec<&'g str>,
+    ) -> Option<Vec<String>> {
+        match visits.get(chip) {
+            Some(Visit::Done) => return None,
+            Some(Visit::InProgress) => {
+                let start = path.iter().position(|c| *c == chip).unwrap();
+                let mut cycle: Vec<String> = path[start..].iter().map(|c| c.to_string()).collect();
+                cycle.push(chip.to_string());
+                return Some(cycle);
+            }
+            None => {}
+        }
+
+        visits.insert(chip, Visit::InProgress);
+        path.push(chip);
+        for next in &self.edges[chip] {
+            if let Some(cycle) = self.visit(next, visits, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        visits.insert(chip, Visit::Done);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_way_dependency_has_no_cycle() {
+        // the composed circuit in chain.rs: B consumes A's output
+        let mut graph = ChipDependencyGraph::new();
+        chip_depends_on!(graph, B, A);
+        assert_eq!(graph.detect_cycles(), None);
+        assert!(graph.edges["B"].contains("A"));
+        assert!(graph.edges["A"].is_empty());
+    }
+
+    #[test]
+    fn back_edge_introduces_a_cycle() {
+        let mut graph = ChipDependencyGraph::new();
+        chip_depends_on!(graph, B, A);
+        chip_depends_on!(graph, A, B);
+        assert_eq!(
+            graph.detect_cycles(),
+            Some(vec!["A".to_string(), "B".to_string(), "A".to_string()])
+        );
+    }
+
+    #[test]
+    fn longer_cycle_behind_an_acyclic_prefix() {
+        let mut graph = ChipDependencyGraph::new();
+        chip_depends_on!(graph, Root, C);
+        chip_depends_on!(graph, C, D);
+        chip_depends_on!(graph, D, E);
+        chip_depends_on!(graph, E, C);
+        assert_eq!(
+            graph.detect_cycles(),
+            Some(["C", "D", "E", "C"].map(String::from).to_vec())
+        );
+    }
+
+    #[test]
+    fn diamond_is_not_a_cycle() {
+        let mut graph = ChipDependencyGraph::new();
+        chip_depends_on!(graph, Top, Left);
+        chip_depends_on!(graph, Top, Right);
+        chip_depends_on!(graph, Left, Bottom);
+        chip_depends_on!(graph, Right, Bottom);
+        assert_eq!(graph.detect_cycles(), None);
+    }
+
+    #[test]
+    fn self_dependency_is_a_cycle() {
+        let mut graph = ChipDependencyGraph::new();
+        graph.add_dependency("A", "A");
+        assert_eq!(
+            graph.detect_cycles(),
+            Some(vec!["A".to_string(), "A".to_string()])
+        );
+    }
+}
@@ -0,0 +1,313 @@
+// Key-value table whose entries are witnessed at proving time.
+//
+// halo2 only looks up into fixed `TableColumn`s, so a query is instead checked
+// by selecting exactly one table row with a boolean b_i per row and
+// accumulating the selected key and value:
+//
+// | key   | value | b   | acc_key         | acc_value         | acc_b         |
+// | k_0   | v_0   | b_0 | b_0 * k_0       | b_0 * v_0         | b_0           | s_first
+// | k_1   | v_1   | b_1 | acc + b_1 * k_1 | acc + b_1 * v_1   | acc + b_1     | s_next
+// | ...   |       |     |                 |                   |               |
+// | query | out   |     |                 |                   |               | s_end
+// gate dynamic lookup first: s_first * (acc - b * entry) == 0, s_first * b * (1 - b) == 0
+// gate dynamic lookup next: s_next * (acc(prev) + b * entry - acc) == 0, s_next * b * (1 - b) == 0
+// gate dynamic lookup end: s_end * (acc_b(prev) - 1) == 0,
+//   s_end * (acc_key(prev) - query) == 0, s_end * (acc_value(prev) - out) == 0
+//
+// The table entries are copied into every query region, so each query costs one
+// row per table entry.
+
+use std::{cell::RefCell, marker::PhantomData};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct DynamicLookupConfig {
+    pub key: Column<Advice>,
+    pub value: Column<Advice>,
+    pub b: Column<Advice>,
+    pub acc: [Column<Advice>; 3],
+    pub s_first: Selector,
+    pub s_next: Selector,
+    pub s_end: Selector,
+}
+
+pub struct DynamicLookupChip<F: FieldExt> {
+    config: DynamicLookupConfig,
+    table: RefCell<Vec<(AssignedCell<F, F>, AssignedCell<F, F>)>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> DynamicLookupChip<F> {
+    pub fn construct(config: DynamicLookupConfig) -> Self {
+        Self {
+            config,
+            table: RefCell::new(vec![]),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        key: Column<Advice>,
+        value: Column<Advice>,
+        b: Column<Advice>,
+        acc: [Column<Advice>; 3],
+    ) -> DynamicLookupConfig {
+        meta.enable_equality(key);
+        meta.enable_equality(value);
+
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+        let s_end = meta.selector();
+        let [acc_key, acc_value, acc_b] = acc;
+
+        let row_constraints = |meta: &mut VirtualCells<'_, F>, s: Selector, first: bool| {
+            let s = meta.query_selector(s);
+            let b = meta.query_advice(b, Rotation::cur());
+            let entries = [
+                meta.query_advice(key, Rotation::cur()),
+                meta.query_advice(value, Rotation::cur()),
+                Expression::Constant(F::one()),
+            ];
+            let one = Expression::Constant(F::one());
+
+            let mut constraints: Vec<_> = acc
+                .iter()
+                .zip(entries)
+                .map(|(column, entry)| {
+                    let cur = meta.query_advice(*column, Rotation::cur());
+                    let prev = if first {
+                        Expression::Constant(F::zero())
+                    } else {
+                        meta.query_advice(*column, Rotation::prev())
+                    };
+                    s.clone() * (prev + b.clone() * entry - cur)
+                })
+                .collect();
+            constraints.push(s * b.clone() * (one - b));
+            constraints
+        };
+
+        meta.create_gate("dynamic lookup first", |meta| {
+            row_constraints(meta, s_first, true)
+        });
+        meta.create_gate("dynamic lookup next", |meta| {
+            row_constraints(meta, s_next, false)
+        });
+
+        meta.create_gate("dynamic lookup end", |meta| {
+            let s = meta.query_selector(s_end);
+            let acc_key = meta.query_advice(acc_key, Rotation::prev());
+            let acc_value = meta.query_advice(acc_value, Rotation::prev());
+            let acc_b = meta.query_advice(acc_b, Rotation::prev());
+            let query = meta.query_advice(key, Rotation::cur());
+            let out = meta.query_advice(value, Rotation::cur());
+            vec![
+                s.clone() * (acc_b - Expression::Constant(F::one())),
+                s.clone() * (acc_key - query),
+                s * (acc_value - out),
+            ]
+        });
+
+        DynamicLookupConfig {
+            key,
+            value,
+            b,
+            acc,
+            s_first,
+            s_next,
+            s_end,
+        }
+    }
+
+    pub fn load_table(
+        &self,
+        mut layouter: impl Layouter<F>,
+        entries: Vec<(Value<F>, Value<F>)>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        let table = layouter.assign_region(
+            || "dynamic table",
+            |mut region| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(row, (key, value))| {
+                        Ok((
+                            region.assign_advice(|| "key", config.key, row, || *key)?,
+                            region.assign_advice(|| "value", config.value, row, || *value)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+        *self.table.borrow_mut() = table;
+        Ok(())
+    }
+
+    // the value stored under `key`, the first matching entry when keys repeat
+    pub fn query(
+        &self,
+        mut layouter: impl Layouter<F>,
+        key: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let table = self.table.borrow();
+        if table.is_empty() {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "dynamic lookup",
+            |mut region| {
+                let mut found = Value::known(false);
+                let mut acc = [Value::known(F::zero()); 3];
+                for (row, (k, v)) in table.iter().enumerate() {
+                    let selector = if row == 0 {
+                        config.s_first
+                    } else {
+                        config.s_next
+                    };
+                    selector.enable(&mut region, row)?;
+                    k.copy_advice(|| "key", &mut region, config.key, row)?;
+                    v.copy_advice(|| "value", &mut region, config.value, row)?;
+
+                    let hit = found
+                        .zip(key)
+                        .zip(k.value())
+                        .map(|((found, key), k)| !found && key == *k);
+                    found = found.zip(hit).map(|(found, hit)| found || hit);
+                    let b = hit.map(F::from);
+                    region.assign_advice(|| "b", config.b, row, || b)?;
+
+                    let entries = [
+                        k.value().copied(),
+                        v.value().copied(),
+                        Value::known(F::one()),
+                    ];
+                    for ((acc, column), entry) in acc.iter_mut().zip(config.acc).zip(entries) {
+                        *acc = *acc + b * entry;
+                        region.assign_advice(|| "acc", column, row, || *acc)?;
+                    }
+                }
+
+                let row = table.len();
+                config.s_end.enable(&mut region, row)?;
+                region.assign_advice(|| "query", config.key, row, || key)?;
+                region.assign_advice(|| "out", config.value, row, || acc[1])
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // loads a witnessed table and exposes the value of every queried key
+    struct TableCircuit {
+        entries: Vec<(u64, u64)>,
+        queries: Vec<u64>,
+    }
+
+    impl Circuit<Fp> for TableCircuit {
+        type Config = (DynamicLookupConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                entries: self.entries.clone(),
+                queries: self.queries.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [key, value, b, acc_key, acc_value, acc_b] = [(); 6].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                DynamicLookupChip::configure(meta, key, value, b, [acc_key, acc_value, acc_b]),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = DynamicLookupChip::construct(config);
+            let entries = self
+                .entries
+                .iter()
+                .map(|&(key, value)| (Value::known(Fp::from(key)), Value::known(Fp::from(value))))
+                .collect();
+            chip.load_table(layouter.namespace(|| "table"), entries)?;
+            for (row, &key) in self.queries.iter().enumerate() {
+                let out =
+                    chip.query(layouter.namespace(|| "query"), Value::known(Fp::from(key)))?;
+                layouter.constrain_instance(out.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prove(entries: &[(u64, u64)], queries: &[u64], outs: &[u64]) -> MockProver<Fp> {
+        let circuit = TableCircuit {
+            entries: entries.to_vec(),
+            queries: queries.to_vec(),
+        };
+        let public = vec![outs.iter().map(|&out| Fp::from(out)).collect()];
+        MockProver::run(5, &circuit, public).unwrap()
+    }
+
+    #[test]
+    fn runtime_table() {
+        let table = [(1, 10), (2, 20), (5, 50)];
+        prove(&table, &[2, 5, 1], &[20, 50, 10]).assert_satisfied();
+        // same shape, other witnessed contents
+        prove(&[(7, 3), (8, 4), (9, 5)], &[9, 7], &[5, 3]).assert_satisfied();
+    }
+
+    #[test]
+    fn repeated_key_reads_the_first_entry() {
+        prove(&[(1, 10), (1, 11)], &[1], &[10]).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        assert!(prove(&[(1, 10), (2, 20)], &[2], &[10]).verify().is_err());
+    }
+
+    #[test]
+    fn missing_key_fails() {
+        // nothing is selected, acc_b stays 0
+        assert!(prove(&[(1, 10), (2, 20)], &[3], &[0]).verify().is_err());
+    }
+
+    #[test]
+    fn query_before_load_is_rejected() {
+        assert!(MockProver::run(
+            5,
+            &TableCircuit {
+                entries: vec![],
+                queries: vec![1]
+            },
+            vec![vec![Fp::one()]]
+        )
+        .is_err());
+    }
+}
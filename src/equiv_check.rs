@@ -0,0 +1,134 @@
+// Two circuits are treated as equivalent on a test vector when, given the vector
+// as their instance column, every instance row receives the same value from the
+// cell copy constrained into it. Rows nothing is copied into read as `None`.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Circuit, Error},
+};
+
+use crate::recorder::{column_index, record};
+
+#[derive(Debug)]
+pub enum EquivError<F: FieldExt> {
+    Synthesis(Error),
+    Mismatch {
+        test_vector: Vec<F>,
+        left: Vec<Option<F>>,
+        right: Vec<Option<F>>,
+    },
+}
+
+impl<F: FieldExt> From<Error> for EquivError<F> {
+    fn from(error: Error) -> Self {
+        EquivError::Synthesis(error)
+    }
+}
+
+/// Values wired into each row of the first instance column.
+pub fn public_outputs<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    k: u32,
+    instance: Vec<F>,
+) -> Result<Vec<Option<F>>, Error> {
+    let rows = instance.len();
+    let recorder = record(circuit, k, vec![instance])?;
+
+    let mut outputs = vec![None; rows];
+    for &(left, right) in &recorder.copies {
+        let ((instance, row), (cell, cell_row)) = match (left, right) {
+            ((column, row), other) if *column.column_type() == Any::Instance => {
+                ((column, row), other)
+            }
+            (other, (column, row)) if *column.column_type() == Any::Instance => {
+                ((column, row), other)
+            }
+            _ => continue,
+        };
+        if column_index(instance) != 0 || row >= rows {
+            continue;
+        }
+        let index = (column_index(cell), cell_row);
+        outputs[row] = match cell.column_type() {
+            Any::Advice => recorder.advice.get(&index).copied().flatten(),
+            Any::Fixed => recorder.fixed.get(&index).copied().flatten(),
+            Any::Instance => None,
+        };
+    }
+    Ok(outputs)
+}
+
+pub fn check_equivalent<F: FieldExt, C1: Circuit<F>, C2: Circuit<F>>(
+    c1: C1,
+    c2: C2,
+    k: u32,
+    test_vectors: &[Vec<F>],
+) -> Result<(), EquivError<F>> {
+    for test_vector in test_vectors {
+        let left = public_outputs(&c1, k, test_vector.clone())?;
+        let right = public_outputs(&c2, k, test_vector.clone())?;
+        if left != right {
+            return Err(EquivError::Mismatch {
+                test_vector: test_vector.clone(),
+                left,
+                right,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, fibo_segment::FiboSegmentCircuit};
+
+    fn fibo() -> FiboCircuit<Fp> {
+        FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        }
+    }
+
+    fn vectors() -> Vec<Vec<Fp>> {
+        vec![
+            [0, 1, 55].map(Fp::from).to_vec(),
+            [0, 0, 0].map(Fp::from).to_vec(),
+        ]
+    }
+
+    #[test]
+    fn fibo_circuit_is_equivalent_to_itself() {
+        check_equivalent(fibo(), fibo(), 4, &[vec![], vec![Fp::one()]]).unwrap();
+    }
+
+    #[test]
+    fn segment_outputs_are_read_back() {
+        let circuit = FiboSegmentCircuit::new(0, 10, Fp::zero(), Fp::one());
+        let outputs = public_outputs(&circuit, 4, vectors()[0].clone()).unwrap();
+        assert_eq!(outputs, [0, 1, 55].map(|v| Some(Fp::from(v))));
+        let equivalent = FiboSegmentCircuit::new(0, 10, Fp::zero(), Fp::one());
+        check_equivalent(circuit, equivalent, 4, &vectors()).unwrap();
+    }
+
+    #[test]
+    fn modified_circuit_is_not_equivalent() {
+        // one row short, exposes F(9) = 34 where F(10) = 55 is expected
+        let circuit = FiboSegmentCircuit::new(0, 10, Fp::zero(), Fp::one());
+        let modified = FiboSegmentCircuit::new(0, 9, Fp::zero(), Fp::one());
+        match check_equivalent(circuit, modified, 4, &vectors()) {
+            Err(EquivError::Mismatch {
+                test_vector,
+                left,
+                right,
+            }) => {
+                assert_eq!(test_vector, vectors()[0]);
+                assert_eq!(left[2], Some(Fp::from(55)));
+                assert_eq!(right[2], Some(Fp::from(34)));
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{FailureLocation, VerifyFailure},
+    plonk::Circuit,
+};
+
+use crate::recorder::{gate_selectors, record, Recorder};
+
+/// Gate names and selectors, indexed in `create_gate` order.
+#[derive(Debug, Clone)]
+pub struct GateRegistry {
+    pub gates: Vec<(String, Vec<usize>)>,
+}
+
+impl GateRegistry {
+    pub fn from_circuit<F: FieldExt, C: Circuit<F>>() -> Self {
+        Self {
+            gates: gate_selectors::<F, C>(),
+        }
+    }
+
+    pub fn name(&self, gate: usize) -> &str {
+        &self.gates[gate].0
+    }
+
+    pub fn selectors(&self, gate: usize) -> &[usize] {
+        &self.gates[gate].1
+    }
+}
+
+// metadata fields are private, read them back from the Debug output
+fn debug_field(debug: &str, field: &str) -> Option<usize> {
+    let start = debug.find(field)? + field.len();
+    debug[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+fn absolute_row<F: FieldExt>(recorder: &Recorder<F>, location: &FailureLocation) -> Option<usize> {
+    match location {
+        FailureLocation::InRegion { region, offset } => {
+            let index = debug_field(&format!("{:?}", region), "index: ")?;
+            let (start, _) = recorder.regions.get(index)?.rows?;
+            Some(start + offset)
+        }
+        FailureLocation::OutsideRegion { row } => Some(*row),
+    }
+}
+
+fn describe_location<F: FieldExt>(recorder: &Recorder<F>, location: &FailureLocation) -> String {
+    match absolute_row(recorder, location) {
+        Some(row) => format!("row {} ({})", row, location),
+        None => location.to_string(),
+    }
+}
+
+/// Rewrites `MockProver` failures in terms of gate names, absolute rows, the
+/// selectors enabled on that row and the values of the cells involved.
+pub fn explain_failures<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    failures: &[VerifyFailure],
+    k: u32,
+) -> Vec<String> {
+    let registry = GateRegistry::from_circuit::<F, C>();
+    let recorder = record(circuit, k, vec![]).expect("circuit should synthesize");
+
+    failures
+        .iter()
+        .map(|failure| match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                cell_values,
+            } => {
+                let gate = debug_field(&format!("{:?}", constraint), "Gate { index: ").unwrap();
+                let row = absolute_row(&recorder, location);
+                let enabled: Vec<String> = recorder
+                    .selectors
+                    .iter()
+                    .filter(|(selector, at)| {
+                        Some(*at) == row && registry.selectors(gate).contains(selector)
+                    })
+                    .map(|(selector, _)| format!("S{}", selector))
+                    .collect();
+                let cells: Vec<String> = cell_values
+                    .iter()
+                    .map(|(cell, value)| format!("{} = {}", cell, value))
+                    .collect();
+
+                format!(
+                    "gate '{}' is not satisfied at {}, enabled by [{}], cells [{}]",
+                    registry.name(gate),
+                    describe_location(&recorder, location),
+                    enabled.join(", "),
+                    cells.join(", ")
+                )
+            }
+            VerifyFailure::ConstraintPoisoned { constraint } => {
+                let gate = debug_field(&format!("{:?}", constraint), "Gate { index: ").unwrap();
+                format!(
+                    "gate '{}' is active on an unusable row, missing selector?",
+                    registry.name(gate)
+                )
+            }
+            VerifyFailure::Lookup {
+                lookup_index,
+                location,
+            } => format!(
+                "lookup {} is not satisfied at {}",
+                lookup_index,
+                describe_location(&recorder, location)
+            ),
+            VerifyFailure::Permutation { column, location } => format!(
+                "copy constraint on {} is not satisfied at {}",
+                column,
+                describe_location(&recorder, location)
+            ),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{ConstraintSystem, Error},
+    };
+
+    use super::*;
+    use crate::fibo1::{FiboChip, FiboCircuit, FiboConfig};
+
+    // FiboCircuit with F(4) off by one, in the region right after the first row
+    struct WrongFiboCircuit;
+
+    impl Circuit<Fp> for WrongFiboCircuit {
+        type Config = FiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FiboCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FiboChip::construct(config.clone());
+            let one = Value::known(Fp::one());
+            let (_, b, c) = chip.assign_first_row(layouter.namespace(|| "first row"), one, one)?;
+            layouter.assign_region(
+                || "wrong row",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    b.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                    c.0.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                    region.assign_advice(|| "c", config.advice[2], 0, || Value::known(Fp::from(4)))
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn wrong_fibo_row_names_the_add_gate_and_row() {
+        let failures = MockProver::run(4, &WrongFiboCircuit, vec![])
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        let explained = explain_failures(&WrongFiboCircuit, &failures, 4);
+
+        assert_eq!(explained.len(), 1);
+        assert!(explained[0].contains("gate 'add'"), "{}", explained[0]);
+        assert!(explained[0].contains("row 1"), "{}", explained[0]);
+    }
+}
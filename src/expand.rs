@@ -0,0 +1,315 @@
+// Rewrites a gate over several advice columns into a gate over a single one,
+// interleaving the columns down the rows. A cell (column i, rotation r) of a
+// gate over w columns becomes rotation r * w + i of the single column, and the
+// selector moves to the first row of each group of w:
+//
+// | a | b | c | s |            | x | s |
+// | 1 | 1 | 2 | 1 |    ->      | 1 | 1 |   a + b - c   becomes
+//                              | 1 | 0 |   x(0) + x(1) - x(2)
+//                              | 2 | 0 |
+//
+// `ExpandedFiboCircuit` lays FiboChip out this way, each row of a, b, c takes
+// three rows of the one column and the rows are chained by copy constraints as
+// FiboChip does. It takes the same instance as `AdaptiveFiboCircuit`.
+//
+// instance: | F(1) | F(2) | F(n) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+/// `coeff * cells[0] * cells[1] * ...` with a cell given as (column position,
+/// rotation), under one selector.
+#[derive(Debug, Clone)]
+pub struct MultiColGate<F> {
+    pub name: &'static str,
+    pub width: usize,
+    pub terms: Vec<(F, Vec<(usize, i32)>)>,
+}
+
+/// The same with every cell a rotation of one column, the selector is on for
+/// the first of every `stride` rows.
+#[derive(Debug, Clone)]
+pub struct SingleColGate<F> {
+    pub name: &'static str,
+    pub stride: usize,
+    pub terms: Vec<(F, Vec<i32>)>,
+}
+
+fn polynomial<F: FieldExt, C>(
+    terms: &[(F, Vec<C>)],
+    mut query: impl FnMut(&C) -> Expression<F>,
+) -> Expression<F> {
+    terms
+        .iter()
+        .map(|(coeff, cells)| {
+            cells
+                .iter()
+                .fold(Expression::Constant(*coeff), |product, cell| {
+                    product * query(cell)
+                })
+        })
+        .reduce(|sum, term| sum + term)
+        .unwrap_or(Expression::Constant(F::zero()))
+}
+
+impl<F: FieldExt> MultiColGate<F> {
+    pub fn configure(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        columns: &[Column<Advice>],
+        selector: Selector,
+    ) {
+        assert_eq!(columns.len(), self.width);
+        meta.create_gate(self.name, |meta| {
+            let s = meta.query_selector(selector);
+            let poly = polynomial(&self.terms, |(column, rotation)| {
+                meta.query_advice(columns[*column], Rotation(*rotation))
+            });
+            vec![s * poly]
+        });
+    }
+}
+
+impl<F: FieldExt> SingleColGate<F> {
+    pub fn configure(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        column: Column<Advice>,
+        selector: Selector,
+    ) {
+        meta.create_gate(self.name, |meta| {
+            let s = meta.query_selector(selector);
+            let poly = polynomial(&self.terms, |rotation| {
+                meta.query_advice(column, Rotation(*rotation))
+            });
+            vec![s * poly]
+        });
+    }
+
+    /// Turns the gate on for row `row` of the gate it was expanded from.
+    pub fn enable(
+        &self,
+        region: &mut Region<'_, F>,
+        selector: Selector,
+        row: usize,
+    ) -> Result<(), Error> {
+        selector.enable(region, row * self.stride)
+    }
+}
+
+pub fn expand_multi_column_gate<F: FieldExt>(gate: MultiColGate<F>) -> SingleColGate<F> {
+    let width = gate.width as i32;
+    SingleColGate {
+        name: gate.name,
+        stride: gate.width,
+        terms: gate
+            .terms
+            .into_iter()
+            .map(|(coeff, cells)| {
+                let rotations = cells
+                    .into_iter()
+                    .map(|(column, rotation)| rotation * width + column as i32)
+                    .collect();
+                (coeff, rotations)
+            })
+            .collect(),
+    }
+}
+
+// a + b - c over | a | b | c |, the gate of FiboChip
+pub fn fibo_add_gate<F: FieldExt>() -> MultiColGate<F> {
+    MultiColGate {
+        name: "add",
+        width: 3,
+        terms: vec![
+            (F::one(), vec![(0, 0)]),
+            (F::one(), vec![(1, 0)]),
+            (-F::one(), vec![(2, 0)]),
+        ],
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpandedFiboConfig<F> {
+    pub advice: Column<Advice>,
+    pub selector: Selector,
+    pub gate: SingleColGate<F>,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct ExpandedFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> ExpandedFiboCircuit<F> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for ExpandedFiboCircuit<F> {
+    type Config = ExpandedFiboConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = meta.advice_column();
+        let selector = meta.selector();
+        let instance = meta.instance_column();
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        let gate = expand_multi_column_gate(fibo_add_gate());
+        gate.configure(meta, advice, selector);
+
+        ExpandedFiboConfig {
+            advice,
+            selector,
+            gate,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.n < 3 {
+            return Err(Error::Synthesis);
+        }
+        let column = config.advice;
+        let stride = config.gate.stride;
+
+        let (a, b, last) = layouter.assign_region(
+            || "expanded fibo",
+            |mut region| {
+                config.gate.enable(&mut region, config.selector, 0)?;
+                let a = region.assign_advice(|| "a", column, 0, || self.a)?;
+                let b = region.assign_advice(|| "b", column, 1, || self.b)?;
+                let c = region.assign_advice(|| "c", column, 2, || self.a + self.b)?;
+
+                // row i of FiboChip at rows i * 3 .. i * 3 + 2
+                let (mut prev_b, mut prev_c) = (b.clone(), c);
+                for row in 1..self.n - 2 {
+                    config.gate.enable(&mut region, config.selector, row)?;
+                    let a = prev_b.copy_advice(|| "a", &mut region, column, row * stride)?;
+                    let b = prev_c.copy_advice(|| "b", &mut region, column, row * stride + 1)?;
+                    let next = a.value().copied() + b.value().copied();
+                    let c = region.assign_advice(|| "c", column, row * stride + 2, || next)?;
+                    (prev_b, prev_c) = (b, c);
+                }
+                Ok((a, b, prev_c))
+            },
+        )?;
+
+        layouter.constrain_instance(a.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.cell(), config.instance, 1)?;
+        layouter.constrain_instance(last.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{
+        fibo_adaptive::AdaptiveFiboCircuit,
+        ipa::{keygen, prove, verify},
+    };
+
+    #[test]
+    fn fibo_add_gate_rotations() {
+        let gate = expand_multi_column_gate(fibo_add_gate::<Fp>());
+        assert_eq!(gate.stride, 3);
+        assert_eq!(
+            gate.terms,
+            [
+                (Fp::one(), vec![0]),
+                (Fp::one(), vec![1]),
+                (-Fp::one(), vec![2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn previous_row_rotations() {
+        // c(prev) * 2 - a * b(next) over | a | b | c |
+        let gate = MultiColGate {
+            name: "prev",
+            width: 3,
+            terms: vec![
+                (Fp::from(2), vec![(2, -1)]),
+                (-Fp::one(), vec![(0, 0), (1, 1)]),
+            ],
+        };
+        assert_eq!(
+            expand_multi_column_gate(gate).terms,
+            [(Fp::from(2), vec![-1]), (-Fp::one(), vec![0, 4])]
+        );
+    }
+
+    // F(1) = F(2) = 1 and F(n) = last
+    fn public(last: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::one(), Fp::one(), Fp::from(last)]]
+    }
+
+    #[test]
+    fn same_instance_as_adaptive() {
+        for (n, last) in [(3, 2), (10, 55), (12, 144)] {
+            let expanded = ExpandedFiboCircuit::new(Fp::one(), Fp::one(), n);
+            let adaptive = AdaptiveFiboCircuit::new(Fp::one(), Fp::one(), n, 6);
+            MockProver::run(6, &expanded, public(last))
+                .unwrap()
+                .assert_satisfied();
+            MockProver::run(6, &adaptive, public(last))
+                .unwrap()
+                .assert_satisfied();
+
+            assert!(MockProver::run(6, &expanded, public(last + 1))
+                .unwrap()
+                .verify()
+                .is_err());
+            assert!(MockProver::run(6, &adaptive, public(last + 1))
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn n_below_3() {
+        let circuit = ExpandedFiboCircuit::new(Fp::one(), Fp::one(), 2);
+        assert!(matches!(
+            MockProver::run(6, &circuit, public(1)),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn ipa_round_trip() {
+        let circuit = ExpandedFiboCircuit::new(Fp::one(), Fp::one(), 10);
+        let (params, pk) = keygen(&circuit, 6).unwrap();
+        let proof = prove(&params, &pk, circuit, &public(55)).unwrap();
+        verify(&params, pk.get_vk(), &proof, &public(55)).unwrap();
+        assert!(verify(&params, pk.get_vk(), &proof, &public(56)).is_err());
+    }
+}
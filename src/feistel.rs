@@ -0,0 +1,160 @@
+// A Feistel permutation keyed by a private k, with the round function
+// f(x, k) = x^3 + k on the add and mul gates of SimpleFunctionChip:
+//
+// (L, R) -> (R, L + f(R, k))
+//
+// x2 = R * R, x3 = x2 * R, t = x3 + k, R' = L + t
+//
+// Every round is invertible whatever f is, so ROUNDS rounds make a permutation
+// of (L, R) for each key.
+//
+// instance: | L | R | L_out | R_out |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::function::{Number, SimpleFunctionChip, SimpleFunctionConfig};
+
+// out of circuit permutation
+pub fn feistel<F: FieldExt>(input: (F, F), key: F, rounds: usize) -> (F, F) {
+    (0..rounds).fold(input, |(l, r), _| (r, l + r * r * r + key))
+}
+
+#[derive(Debug, Clone)]
+pub struct FeistelConfig {
+    pub function: SimpleFunctionConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FeistelPRPCircuit<F, const ROUNDS: usize> {
+    pub key: Value<F>,
+}
+
+impl<F: FieldExt, const ROUNDS: usize> FeistelPRPCircuit<F, ROUNDS> {
+    pub fn new(key: F) -> Self {
+        Self {
+            key: Value::known(key),
+        }
+    }
+
+    pub fn public_inputs(input: (F, F), key: F) -> Vec<F> {
+        let (l, r) = feistel(input, key, ROUNDS);
+        vec![input.0, input.1, l, r]
+    }
+}
+
+impl<F: FieldExt, const ROUNDS: usize> Circuit<F> for FeistelPRPCircuit<F, ROUNDS> {
+    type Config = FeistelConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FeistelConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let function = &config.function;
+        let (mut l, mut r, key) = layouter.assign_region(
+            || "load",
+            |mut region| {
+                let l = region
+                    .assign_advice_from_instance(|| "L", config.instance, 0, function.x, 0)
+                    .map(Number)?;
+                let r = region
+                    .assign_advice_from_instance(|| "R", config.instance, 1, function.y, 0)
+                    .map(Number)?;
+                let key = region
+                    .assign_advice(|| "k", function.z, 0, || self.key)
+                    .map(Number)?;
+                Ok((l, r, key))
+            },
+        )?;
+
+        let chip = SimpleFunctionChip::<F>::construct(function.clone());
+        for round in 0..ROUNDS {
+            let mut layouter = layouter.namespace(|| format!("round {}", round));
+            let x2 = chip.mul_cells(layouter.namespace(|| "R * R"), &r, &r)?;
+            let x3 = chip.mul_cells(layouter.namespace(|| "R^2 * R"), &x2, &r)?;
+            let t = chip.add_cells(layouter.namespace(|| "R^3 + k"), &x3, &key)?;
+            let next = chip.add_cells(layouter.namespace(|| "L + f(R, k)"), &l, &t)?;
+            (l, r) = (r, next);
+        }
+
+        layouter.constrain_instance(l.0.cell(), config.instance, 2)?;
+        layouter.constrain_instance(r.0.cell(), config.instance, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn run<const ROUNDS: usize>(key: Fp, public: Vec<Fp>) -> MockProver<Fp> {
+        MockProver::run(6, &FeistelPRPCircuit::<Fp, ROUNDS>::new(key), vec![public]).unwrap()
+    }
+
+    // one round backwards: (R, L + f(R, k)) -> (L, R)
+    fn inverse(output: (Fp, Fp), key: Fp, rounds: usize) -> (Fp, Fp) {
+        (0..rounds).fold(output, |(l, r), _| (r - l * l * l - key, l))
+    }
+
+    #[test]
+    fn four_rounds() {
+        // (1, 2) -> (2, 12) -> (12, 1733) -> (1733, 5204699852) -> ...
+        let output = (
+            Fp::from(5204699852),
+            Fp::from_u128(140989596680350814051243159944),
+        );
+        let (input, key) = ((Fp::one(), Fp::from(2)), Fp::from(3));
+        assert_eq!(feistel(input, key, 4), output);
+
+        let public = FeistelPRPCircuit::<Fp, 4>::public_inputs(input, key);
+        assert_eq!(public[2..], [output.0, output.1]);
+        run::<4>(key, public).assert_satisfied();
+    }
+
+    #[test]
+    fn eight_rounds() {
+        let (input, key) = ((Fp::from(7), Fp::from(11)), Fp::from(0xfeed));
+        let output = feistel(input, key, 8);
+        assert_eq!(inverse(output, key, 8), input);
+        run::<8>(key, FeistelPRPCircuit::<Fp, 8>::public_inputs(input, key)).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_key_fails() {
+        let input = (Fp::one(), Fp::from(2));
+        let public = FeistelPRPCircuit::<Fp, 4>::public_inputs(input, Fp::from(3));
+        assert!(run::<4>(Fp::from(4), public).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_ciphertext_fails() {
+        let (input, key) = ((Fp::from(7), Fp::from(11)), Fp::from(0xfeed));
+        let mut public = FeistelPRPCircuit::<Fp, 8>::public_inputs(input, key);
+        public.swap(2, 3);
+        assert!(run::<8>(key, public).verify().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+// Schnorr style proof of knowledge of x with Y = G^x, made non interactive by
+// Fiat-Shamir. The group is the additive group of the field written
+// multiplicatively, G^x reads x * G, so discrete logs here are easy and the
+// circuit is about the shape of the protocol, not its hardness:
+//
+// R = G^r, c = H(H(G || Y) || R), s = r + c * x
+// verifier: G^s == R * Y^c, that is s * G == R + c * Y
+//
+// G^x and G^r on the mul gate of SimpleFunctionChip, c on AlgebraicHashChip
+// (there is no Poseidon chip in this crate), the response s is a private
+// witness checked by the verifier equation. With G != 0 that pins
+// s = r + c * x.
+//
+// instance: | G | Y |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+    hash::{hash, AlgebraicHashChip, AlgebraicHashConfig},
+};
+
+// out of circuit challenge for the commitment R
+pub fn challenge<F: FieldExt>(g: F, y: F, commitment: F) -> F {
+    hash(hash(g, y), commitment)
+}
+
+// (R, s) for the secret x and the nonce r
+pub fn respond<F: FieldExt>(g: F, x: F, r: F) -> (F, F) {
+    let commitment = r * g;
+    let c = challenge(g, x * g, commitment);
+    (commitment, r + c * x)
+}
+
+#[derive(Debug, Clone)]
+pub struct FiatShamirConfig {
+    pub function: SimpleFunctionConfig,
+    pub hash: AlgebraicHashConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiatShamirCircuit<F> {
+    pub x: Value<F>,
+    pub r: Value<F>,
+    // the response, r + c * x for an honest prover
+    pub s: Value<F>,
+}
+
+impl<F: FieldExt> FiatShamirCircuit<F> {
+    pub fn new(g: F, x: F, r: F) -> Self {
+        let (_, s) = respond(g, x, r);
+        Self {
+            x: Value::known(x),
+            r: Value::known(r),
+            s: Value::known(s),
+        }
+    }
+
+    pub fn public_inputs(g: F, x: F) -> Vec<F> {
+        vec![g, x * g]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiatShamirCircuit<F> {
+    type Config = FiatShamirConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let round_constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FiatShamirConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            hash: AlgebraicHashChip::configure(meta, x, y, round_constant),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let function = &config.function;
+        let (g, y, x, r, s) = layouter.assign_region(
+            || "load",
+            |mut region| {
+                let g = region
+                    .assign_advice_from_instance(|| "G", config.instance, 0, function.x, 0)
+                    .map(Number)?;
+                let y = region
+                    .assign_advice_from_instance(|| "Y", config.instance, 1, function.y, 0)
+                    .map(Number)?;
+                let x = region
+                    .assign_advice(|| "x", function.z, 0, || self.x)
+                    .map(Number)?;
+                let r = region
+                    .assign_advice(|| "r", function.x, 1, || self.r)
+                    .map(Number)?;
+                let s = region
+                    .assign_advice(|| "s", function.y, 1, || self.s)
+                    .map(Number)?;
+                Ok((g, y, x, r, s))
+            },
+        )?;
+
+        let chip = SimpleFunctionChip::<F>::construct(function.clone());
+        let hash = AlgebraicHashChip::construct(config.hash.clone());
+
+        // knowledge of x: Y = G^x
+        let public_key = chip.mul_cells(layouter.namespace(|| "G^x"), &x, &g)?;
+        layouter.constrain_instance(public_key.0.cell(), config.instance, 1)?;
+
+        let commitment = chip.mul_cells(layouter.namespace(|| "R = G^r"), &r, &g)?;
+        let c = hash.hash(layouter.namespace(|| "H(G || Y)"), &g.0, &y.0)?;
+        let c = hash
+            .hash(layouter.namespace(|| "c = H(.. || R)"), &c, &commitment.0)
+            .map(Number)?;
+
+        let lhs = chip.mul_cells(layouter.namespace(|| "G^s"), &s, &g)?;
+        let y_c = chip.mul_cells(layouter.namespace(|| "Y^c"), &c, &y)?;
+        let rhs = chip.add_cells(layouter.namespace(|| "R * Y^c"), &commitment, &y_c)?;
+        layouter.assign_region(
+            || "G^s == R * Y^c",
+            |mut region| {
+                let lhs = lhs.0.copy_advice(|| "G^s", &mut region, function.x, 0)?;
+                region.constrain_equal(lhs.cell(), rhs.0.cell())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::group::ff::Field, pasta::Fp};
+    use rand_core::OsRng;
+
+    use super::*;
+
+    const K: u32 = 7;
+
+    fn run(circuit: &FiatShamirCircuit<Fp>, instance: Vec<Fp>) -> MockProver<Fp> {
+        MockProver::run(K, circuit, vec![instance]).unwrap()
+    }
+
+    #[test]
+    fn respond_satisfies_the_verifier_equation() {
+        let (g, x, r) = (Fp::from(7), Fp::from(11), Fp::from(13));
+        let (commitment, s) = respond(g, x, r);
+        let c = challenge(g, x * g, commitment);
+        assert_eq!(s * g, commitment + c * (x * g));
+    }
+
+    #[test]
+    fn valid_response() {
+        let (g, x, r) = (Fp::random(OsRng), Fp::random(OsRng), Fp::random(OsRng));
+        let circuit = FiatShamirCircuit::new(g, x, r);
+        run(&circuit, FiatShamirCircuit::public_inputs(g, x)).assert_satisfied();
+    }
+
+    #[test]
+    fn invalid_response_fails() {
+        let (g, x, r) = (Fp::from(7), Fp::from(11), Fp::from(13));
+        let mut circuit = FiatShamirCircuit::new(g, x, r);
+        circuit.s = circuit.s + Value::known(Fp::one());
+        assert!(run(&circuit, FiatShamirCircuit::public_inputs(g, x))
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn response_for_another_nonce_fails() {
+        let (g, x) = (Fp::from(7), Fp::from(11));
+        let mut circuit = FiatShamirCircuit::new(g, x, Fp::from(13));
+        circuit.r = Value::known(Fp::from(14));
+        assert!(run(&circuit, FiatShamirCircuit::public_inputs(g, x))
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn wrong_public_key_fails() {
+        let (g, x, r) = (Fp::from(7), Fp::from(11), Fp::from(13));
+        let circuit = FiatShamirCircuit::new(g, x, r);
+        let instance = FiatShamirCircuit::public_inputs(g, x + Fp::one());
+        assert!(run(&circuit, instance).verify().is_err());
+    }
+}
@@ -2,13 +2,21 @@ use std::marker::PhantomData;
 
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
-    dev::MockProver,
-    pasta::Fp,
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
     poly::Rotation,
 };
 
+use crate::{
+    abs::{AbsoluteValueChip, AbsoluteValueConfig, SubChip, SubConfig},
+    allocator::ColumnAllocator,
+    checked_assign::assign_advice_checked,
+    inner_product::InnerProductChip,
+    matrix::{Matrix, MatrixMultiplyChip, MatrixMultiplyConfig},
+    range_check::RangeCheckChip,
+    static_assert::static_assert,
+    xor::{XorChip, XorConfig},
+};
 
 ///
 /// |a  |b  |c  | selector
@@ -16,22 +24,43 @@ use halo2_proofs::{
 /// 
 /// 
 /// constraints = selector * (a + b - c) == 0
+///
+/// In reverse mode an extra gate steps the sequence backwards:
+/// constraints = reverse_selector * (c - b - a) == 0
+///
+/// Wrapper chips own a `FiboConfig` next to gates of their own on the same
+/// advice columns: `FiboSkipChip`, `FiboChecksumChip`, `FiboRatioChip`,
+/// `fibo_modular::FiboPeriodChip` and `inline::FiboInlineChip`.
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub struct FiboConfig {
     pub advice: [Column<Advice>; 3],
     pub selector: Selector,
+    pub reverse_selector: Option<Selector>,
 }
 
 #[derive(Debug, Clone)]
-struct ACell<F: FieldExt>(AssignedCell<F, F>);
+pub struct ACell<F: FieldExt>(pub AssignedCell<F, F>);
 
-struct FiboChip<F: FieldExt> {
+pub struct FiboChip<F: FieldExt> {
     config: FiboConfig,
     _marker: PhantomData<F>,
 }
 
+impl<F: FieldExt> Chip<F> for FiboChip<F> {
+    type Config = FiboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
 impl<F: FieldExt> FiboChip<F> {
-    fn assign_first_row(
+    pub fn assign_first_row(
         &self,
         mut layouter: impl Layouter<F>,
         a: Value<F>,
@@ -42,26 +71,24 @@ impl<F: FieldExt> FiboChip<F> {
             |mut region| {
                 self.config.selector.enable(&mut region, 0)?;
 
-                let a_cell = region
-                    .assign_advice(|| "a", self.config.advice[0], 0, || a)
+                let a_cell = assign_advice_checked(&mut region, self.config.advice[0], 0, a, "a")
                     .map(ACell)?;
 
-                let b_cell = region
-                    .assign_advice(|| "b", self.config.advice[1], 0, || b)
+                let b_cell = assign_advice_checked(&mut region, self.config.advice[1], 0, b, "b")
                     .map(ACell)?;
 
                 let c_val = a.and_then(|a| b.map(|b| a + b));
 
-                let c_cell = region
-                    .assign_advice(|| "c", self.config.advice[2], 0, || c_val)
-                    .map(ACell)?;
+                let c_cell =
+                    assign_advice_checked(&mut region, self.config.advice[2], 0, c_val, "c")
+                        .map(ACell)?;
 
                 Ok((a_cell, b_cell, c_cell))
             },
         )
     }
 
-    fn assign_row(
+    pub fn assign_row(
         &self,
         mut layouter: impl Layouter<F>,
         prev_b: &ACell<F>,
@@ -84,18 +111,55 @@ impl<F: FieldExt> FiboChip<F> {
                     .value()
                     .and_then(|b| prev_c.0.value().map(|c| *b + *c));
 
-                let c_cell = region
-                    .assign_advice(|| "c", self.config.advice[2], 0, || c_val)
-                    .map(ACell)?;
+                let c_cell =
+                    assign_advice_checked(&mut region, self.config.advice[2], 0, c_val, "c")
+                        .map(ACell)?;
 
                 Ok(c_cell)
             },
         )
     }
 
+    // step back from (b, c) to the previous number a = c - b
+    pub fn assign_row_reverse(
+        &self,
+        mut layouter: impl Layouter<F>,
+        b: &ACell<F>,
+        c: &ACell<F>,
+    ) -> Result<ACell<F>, Error> {
+        let reverse_selector = self.config.reverse_selector.ok_or(Error::Synthesis)?;
+
+        layouter.assign_region(
+            || "prev row",
+            |mut region| -> Result<ACell<F>, Error> {
+                reverse_selector.enable(&mut region, 0)?;
+
+                b.0.copy_advice(|| "b", &mut region, self.config.advice[1], 0)?;
+                c.0.copy_advice(|| "c", &mut region, self.config.advice[2], 0)?;
+
+                let a_val = c.0.value().and_then(|c| b.0.value().map(|b| *c - *b));
+
+                let a_cell =
+                    assign_advice_checked(&mut region, self.config.advice[0], 0, a_val, "a")
+                        .map(ACell)?;
+
+                Ok(a_cell)
+            },
+        )
+    }
+
     // configure custome gates and define the constraints between cell
-    fn configure(meta: &mut ConstraintSystem<F>, advices: [Column<Advice>; 3]) -> FiboConfig {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 3],
+        reverse: bool,
+    ) -> FiboConfig {
         let [col_a, col_b, col_c] = advices;
+        static_assert(
+            meta,
+            col_a != col_b && col_b != col_c && col_a != col_c,
+            "FiboChip needs 3 distinct advice columns",
+        );
         let selector = meta.selector();
 
         // enable equality mean we can check copy constraint from this column to another column
@@ -114,22 +178,396 @@ impl<F: FieldExt> FiboChip<F> {
             vec![(s * (a + b - c))]
         });
 
+        // a | b | c | reverse_selector
+        // => constraint is s * (c - b - a) == 0
+        let reverse_selector = reverse.then(|| meta.selector());
+        if let Some(reverse_selector) = reverse_selector {
+            meta.create_gate("sub", |meta| {
+                let s = meta.query_selector(reverse_selector);
+                let a = meta.query_advice(col_a, Rotation::cur());
+                let b = meta.query_advice(col_b, Rotation::cur());
+                let c = meta.query_advice(col_c, Rotation::cur());
+                vec![(s * (c - b - a))]
+            });
+        }
+
         FiboConfig {
             advice: [col_a, col_b, col_c],
             selector,
+            reverse_selector,
+        }
+    }
+
+    // a, b and c as the lhs, rhs and out columns of `alloc`
+    pub fn configure_with(alloc: &mut ColumnAllocator<'_, F>, reverse: bool) -> FiboConfig {
+        let advices = ["lhs", "rhs", "out"].map(|purpose| alloc.alloc_advice(purpose));
+        Self::configure(alloc.meta(), advices, reverse)
+    }
+
+    pub fn construct(config: FiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// `FiboChip` that can also jump ahead by powers of [[1, 1], [1, 0]],
+/// multiplied on the same advice columns.
+#[derive(Debug, Clone)]
+pub struct FiboSkipConfig {
+    pub fibo: FiboConfig,
+    pub skip: MatrixMultiplyConfig,
+}
+
+pub struct FiboSkipChip<F: FieldExt> {
+    config: FiboSkipConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FiboSkipChip<F> {
+    pub fn construct(config: FiboSkipConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 3],
+        constant: Column<Fixed>,
+        reverse: bool,
+    ) -> FiboSkipConfig {
+        FiboSkipConfig {
+            fibo: FiboChip::configure(meta, advices, reverse),
+            skip: MatrixMultiplyChip::<F, 2>::configure(meta, advices, constant),
+        }
+    }
+
+    // (a, b) = (F(m), F(m + 1)) to (F(m + n), F(m + n + 1)) with
+    // [[F(n + 1), F(n)], [F(n), F(n - 1)]] = [[1, 1], [1, 0]]^n by square and multiply
+    pub fn skip_n_steps(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: u64,
+    ) -> Result<(ACell<F>, ACell<F>), Error> {
+        let (a, b) = layouter.assign_region(
+            || "skip start",
+            |mut region| {
+                let a = assign_advice_checked(&mut region, self.config.fibo.advice[0], 0, a, "a")?;
+                let b = assign_advice_checked(&mut region, self.config.fibo.advice[0], 1, b, "b")?;
+                Ok((ACell(a), ACell(b)))
+            },
+        )?;
+        self.skip_n_steps_from(layouter, &a, &b, n)
+    }
+
+    // same as `skip_n_steps`, starting from cells already laid out elsewhere
+    pub fn skip_n_steps_from(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &ACell<F>,
+        b: &ACell<F>,
+        n: u64,
+    ) -> Result<(ACell<F>, ACell<F>), Error> {
+        let matrix = MatrixMultiplyChip::<F, 2>::construct(self.config.skip.clone());
+        if n == 0 {
+            return Ok((a.clone(), b.clone()));
         }
+
+        let one = F::one();
+        let mut base = matrix.load_constant(
+            layouter.namespace(|| "fibo matrix"),
+            [[one, one], [one, F::zero()]],
+        )?;
+        let mut power: Option<Matrix<F>> = None;
+        let mut e = n;
+        loop {
+            if e & 1 == 1 {
+                power = Some(match power {
+                    None => base.clone(),
+                    Some(power) => {
+                        matrix.multiply(layouter.namespace(|| "multiply"), &power, &base)?
+                    }
+                });
+            }
+            e >>= 1;
+            if e == 0 {
+                break;
+            }
+            base = matrix.multiply(layouter.namespace(|| "square"), &base, &base)?;
+        }
+        let power = power.unwrap();
+
+        // [F(m + n + 1), F(m + n)] = power * [F(m + 1), F(m)]
+        let inner = InnerProductChip::<F>::construct(self.config.skip.inner.clone());
+        let start = vec![b.0.clone(), a.0.clone()];
+        let next = inner.inner_product(layouter.namespace(|| "F(m + n + 1)"), &power[0], &start)?;
+        let cur = inner.inner_product(layouter.namespace(|| "F(m + n)"), &power[1], &start)?;
+        Ok((ACell(cur), ACell(next)))
     }
+}
 
-    fn construct(config: FiboConfig) -> Self {
+#[derive(Debug, Clone)]
+pub struct ChecksumConfig {
+    pub xor: XorConfig,
+    pub carry: Column<Advice>,
+    pub s_wrap: Selector,
+}
+
+/// `FiboChip` over bytes, each step wraps the sum around 256 with a boolean
+/// carry q kept next to the row:
+/// constraints = s_wrap * (a + b - c - 256 * q) == 0, s_wrap * q * (1 - q) == 0
+#[derive(Debug, Clone)]
+pub struct FiboChecksumConfig {
+    pub fibo: FiboConfig,
+    pub checksum: ChecksumConfig,
+}
+
+pub struct FiboChecksumChip<F: FieldExt> {
+    config: FiboChecksumConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FiboChecksumChip<F> {
+    pub fn construct(config: FiboChecksumConfig) -> Self {
         Self {
             config,
             _marker: PhantomData,
         }
     }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 3],
+        bits: [Column<Advice>; 2],
+        reverse: bool,
+    ) -> FiboChecksumConfig {
+        let fibo = FiboChip::configure(meta, advices, reverse);
+        let [col_a, col_b, col_c] = advices;
+        let carry = bits[0];
+        let s_wrap = meta.selector();
+
+        meta.create_gate("add mod 256", |meta| {
+            let s = meta.query_selector(s_wrap);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            let q = meta.query_advice(carry, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let base = Expression::Constant(F::from(256));
+            vec![
+                s.clone() * (a + b - c - base * q.clone()),
+                s * q.clone() * (one - q),
+            ]
+        });
+
+        FiboChecksumConfig {
+            fibo,
+            checksum: ChecksumConfig {
+                xor: XorChip::configure(meta, advices, bits),
+                carry,
+                s_wrap,
+            },
+        }
+    }
+
+    // a = data[0], b = data[1], then for every later byte d:
+    // (a, b) = (b, ((a + b) mod 256) ^ d), the checksum is the last b
+    pub fn compute_checksum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        data: &[Value<F>],
+    ) -> Result<ACell<F>, Error> {
+        if data.len() < 2 {
+            return Err(Error::Synthesis);
+        }
+        let checksum = &self.config.checksum;
+        let xor = XorChip::<F>::construct(checksum.xor.clone());
+        let config = &self.config.fibo;
+
+        let bytes = layouter.assign_region(
+            || "load data",
+            |mut region| {
+                data.iter()
+                    .enumerate()
+                    .map(|(row, byte)| {
+                        assign_advice_checked(&mut region, config.advice[0], row, *byte, "byte")
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+        // only the decomposition matters, it range checks both starting bytes
+        xor.xor(
+            layouter.namespace(|| "data[0] ^ data[1]"),
+            &bytes[0],
+            &bytes[1],
+        )?;
+
+        let (mut a, mut b) = (bytes[0].clone(), bytes[1].clone());
+        for byte in &bytes[2..] {
+            let sum = layouter.assign_region(
+                || "add mod 256",
+                |mut region| {
+                    checksum.s_wrap.enable(&mut region, 0)?;
+                    let a = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                    let b = b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+
+                    let sum = a.value().zip(b.value()).map(|(a, b)| *a + *b);
+                    let q = sum.map(|sum| F::from(sum.to_repr().as_ref()[1] as u64));
+                    assign_advice_checked(&mut region, checksum.carry, 0, q, "q")?;
+                    let c = sum.zip(q).map(|(sum, q)| sum - q * F::from(256));
+                    assign_advice_checked(&mut region, config.advice[2], 0, c, "c")
+                },
+            )?;
+            let next = xor.xor(layouter.namespace(|| "c ^ d"), &sum, byte)?;
+            a = b;
+            b = next;
+        }
+        Ok(ACell(b))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RatioConfig {
+    pub sub: SubConfig,
+    pub abs: AbsoluteValueConfig,
+    pub factor: Column<Fixed>,
+    pub s_scale: Selector,
+}
+
+/// `FiboChip` that shows F(n + 1) / F(n) is close to phi, scaling both numbers
+/// by a fixed factor:
+/// constraints = s_scale * (out - factor * in) == 0
+#[derive(Debug, Clone)]
+pub struct FiboRatioConfig {
+    pub fibo: FiboConfig,
+    pub ratio: RatioConfig,
+}
+
+pub struct FiboRatioChip<F: FieldExt> {
+    config: FiboRatioConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FiboRatioChip<F> {
+    pub fn construct(config: FiboRatioConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 3],
+        bit: Column<Advice>,
+        factor: Column<Fixed>,
+        constant: Column<Fixed>,
+        reverse: bool,
+    ) -> FiboRatioConfig {
+        let fibo = FiboChip::configure(meta, advices, reverse);
+        meta.enable_constant(constant);
+        let [col_a, col_b, col_c] = advices;
+        let s_scale = meta.selector();
+
+        meta.create_gate("scale", |meta| {
+            let s = meta.query_selector(s_scale);
+            let input = meta.query_advice(col_a, Rotation::cur());
+            let out = meta.query_advice(col_b, Rotation::cur());
+            let factor = meta.query_fixed(factor, Rotation::cur());
+            vec![s * (out - factor * input)]
+        });
+
+        let range = RangeCheckChip::<F, 64>::configure(meta, col_c, bit);
+        FiboRatioConfig {
+            fibo,
+            ratio: RatioConfig {
+                sub: SubChip::configure(meta, advices),
+                abs: AbsoluteValueChip::configure(meta, [col_a, col_b], range),
+                factor,
+                s_scale,
+            },
+        }
+    }
+
+    // runs F(1) = F(2) = 1 up to F(n + 1) and shows
+    // |F(n + 1) * 1000 - F(n) * 1618| < 2^tolerance_bits, i.e. F(n) / F(n + 1)
+    // is close to 1 / phi. Returns (F(n), F(n + 1)).
+    pub fn prove_ratio(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: usize,
+        tolerance_bits: usize,
+    ) -> Result<(ACell<F>, ACell<F>), Error> {
+        if n == 0 {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config.ratio;
+        let fibo = FiboChip::<F>::construct(self.config.fibo.clone());
+
+        let (mut prev_b, mut prev_c) = layouter.assign_region(
+            || "F(1), F(2)",
+            |mut region| {
+                let one = F::one();
+                let a = region.assign_advice_from_constant(
+                    || "1",
+                    self.config.fibo.advice[0],
+                    0,
+                    one,
+                )?;
+                let b = region.assign_advice_from_constant(
+                    || "1",
+                    self.config.fibo.advice[1],
+                    0,
+                    one,
+                )?;
+                Ok((ACell(a), ACell(b)))
+            },
+        )?;
+        for _ in 1..n {
+            let c = fibo.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+
+        let mut scale = |name: &'static str, cell: &ACell<F>, factor: u64| {
+            layouter.assign_region(
+                || name,
+                |mut region| {
+                    config.s_scale.enable(&mut region, 0)?;
+                    let factor = F::from(factor);
+                    region.assign_fixed(|| "factor", config.factor, 0, || Value::known(factor))?;
+                    let input =
+                        cell.0
+                            .copy_advice(|| "in", &mut region, self.config.fibo.advice[0], 0)?;
+                    let out = input.value().map(|input| *input * factor);
+                    assign_advice_checked(&mut region, self.config.fibo.advice[1], 0, out, "out")
+                },
+            )
+        };
+        let next_scaled = scale("F(n + 1) * 1000", &prev_c, 1000)?;
+        let scaled = scale("F(n) * 1618", &prev_b, 1618)?;
+
+        let diff = SubChip::construct(config.sub.clone()).sub(
+            layouter.namespace(|| "difference"),
+            &next_scaled,
+            &scaled,
+        )?;
+        AbsoluteValueChip::construct(config.abs.clone()).abs(
+            layouter.namespace(|| "|difference|"),
+            &diff,
+            tolerance_bits,
+        )?;
+        Ok((prev_b, prev_c))
+    }
 }
 
 #[derive(Default)]
-struct FiboCircuit<F> {
+pub struct FiboCircuit<F> {
     pub a: Value<F>,
     pub b: Value<F>,
 }
@@ -142,12 +580,7 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advices = [
-            meta.advice_column(),
-            meta.advice_column(),
-            meta.advice_column(),
-        ];
-        FiboChip::configure(meta, advices)
+        FiboChip::configure_with(&mut ColumnAllocator::new(meta), false)
     }
 
     fn synthesize(
@@ -158,8 +591,7 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
         let chip = FiboChip::<F>::construct(config);
 
         let (_, mut prev_b, mut prev_c) = chip
-            .assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)
-            .unwrap();
+            .assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
 
         for _i in 3..10{
             let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
@@ -170,30 +602,399 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
         Ok(())
     }
 }
-fn main() {
-    let k = 4;
-    let a = Fp::from(1);
-    let b = Fp::from(1);
-
-    let circuit = FiboCircuit {
-        a: Value::known(a),
-        b: Value::known(b),
-    };
-
-    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-    prover.assert_satisfied();
-
-    use plotters::prelude::*;
-    let root = BitMapBackend::new("./target/fibo1circuit.png", (1024, 768)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let root = root
-        .titled("Fibo 1 Layout", ("sans-serif", 60))
-        .unwrap();
-
-    halo2_proofs::dev::CircuitLayout::default()
-        // .show_labels(false)
-        // Render the circuit onto your area!
-        // The first argument is the size parameter for the circuit.
-        .render(4, &circuit, &root)
-        .unwrap();
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp, plonk::Instance};
+
+    use super::*;
+
+    // F(1) = a, F(2) = b forwards to F(10), then backwards to F(1) and F(2)
+    // again, pinned to the starting cells. `tamper` breaks the last step back.
+    struct ReverseCircuit {
+        a: Value<Fp>,
+        b: Value<Fp>,
+        tamper: bool,
+    }
+
+    impl Circuit<Fp> for ReverseCircuit {
+        type Config = FiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                tamper: self.tamper,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            FiboChip::configure(meta, advice, true)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FiboChip::construct(config.clone());
+            let (a, b, c) =
+                chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+
+            let (mut prev_b, mut prev_c) = (b.clone(), c);
+            for _ in 4..=10 {
+                let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+                prev_b = prev_c;
+                prev_c = c;
+            }
+
+            // (F(9), F(10)) back to (F(1), F(2))
+            for step in (1..=8).rev() {
+                let prev_a = if self.tamper && step == 1 {
+                    layouter.assign_region(
+                        || "tampered prev row",
+                        |mut region| {
+                            config.reverse_selector.unwrap().enable(&mut region, 0)?;
+                            prev_b
+                                .0
+                                .copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                            prev_c
+                                .0
+                                .copy_advice(|| "c", &mut region, config.advice[2], 0)?;
+                            let a = prev_c.0.value().copied() - prev_b.0.value().copied()
+                                + Value::known(Fp::one());
+                            region
+                                .assign_advice(|| "a", config.advice[0], 0, || a)
+                                .map(ACell)
+                        },
+                    )?
+                } else {
+                    chip.assign_row_reverse(layouter.namespace(|| "prev row"), &prev_b, &prev_c)?
+                };
+                prev_c = prev_b;
+                prev_b = prev_a;
+            }
+
+            layouter.assign_region(
+                || "start again",
+                |mut region| {
+                    region.constrain_equal(prev_b.0.cell(), a.0.cell())?;
+                    region.constrain_equal(prev_c.0.cell(), b.0.cell())
+                },
+            )
+        }
+    }
+
+    fn reverse(tamper: bool) -> MockProver<Fp> {
+        let circuit = ReverseCircuit {
+            a: Value::known(Fp::from(1)),
+            b: Value::known(Fp::from(1)),
+            tamper,
+        };
+        MockProver::run(5, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn forward_to_f10_and_back_to_f1() {
+        reverse(false).assert_satisfied();
+    }
+
+    #[test]
+    fn tampered_reverse_row_fails() {
+        assert!(reverse(true).verify().is_err());
+    }
+
+    #[test]
+    fn reverse_selector_only_in_reverse_mode() {
+        for reverse in [false, true] {
+            let mut meta = ConstraintSystem::<Fp>::default();
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let config = FiboChip::configure(&mut meta, advice, reverse);
+            assert_eq!(config.reverse_selector.is_some(), reverse);
+            let selectors =
+                crate::recorder::parse_index(&format!("{:?}", meta.pinned()), "num_selectors: ");
+            assert_eq!(selectors, 1 + reverse as usize);
+        }
+    }
+
+    // (F(1), F(2)) moved `skip` steps by `skip_n_steps` and `naive` steps one
+    // row at a time, the two results pinned together
+    struct SkipCircuit {
+        skip: u64,
+        naive: usize,
+    }
+
+    impl Circuit<Fp> for SkipCircuit {
+        type Config = FiboSkipConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                skip: self.skip,
+                naive: self.naive,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            FiboSkipChip::configure(meta, advice, constant, false)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FiboChip::construct(config.fibo.clone());
+            let one = Value::known(Fp::one());
+            let (skip_a, skip_b) = FiboSkipChip::construct(config).skip_n_steps(
+                layouter.namespace(|| "skip"),
+                one,
+                one,
+                self.skip,
+            )?;
+
+            let (_, mut prev_b, mut prev_c) =
+                chip.assign_first_row(layouter.namespace(|| "first row"), one, one)?;
+            for _ in 1..self.naive {
+                let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+                prev_b = prev_c;
+                prev_c = c;
+            }
+
+            layouter.assign_region(
+                || "skip == naive",
+                |mut region| {
+                    region.constrain_equal(skip_a.0.cell(), prev_b.0.cell())?;
+                    region.constrain_equal(skip_b.0.cell(), prev_c.0.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn skip_100_steps_matches_naive() {
+        let circuit = SkipCircuit {
+            skip: 100,
+            naive: 100,
+        };
+        MockProver::run(9, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn skip_one_step_short_fails() {
+        let circuit = SkipCircuit {
+            skip: 99,
+            naive: 100,
+        };
+        assert!(MockProver::run(9, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    // a = data[0], b = data[1], (a, b) = (b, ((a + b) mod 256) ^ d)
+    fn checksum(data: &[u64]) -> u64 {
+        data[2..]
+            .iter()
+            .fold((data[0], data[1]), |(a, b), d| (b, ((a + b) % 256) ^ d))
+            .1
+    }
+
+    // instance: | checksum |
+    struct ChecksumCircuit(Vec<u64>);
+
+    impl Circuit<Fp> for ChecksumCircuit {
+        type Config = (FiboChecksumConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.clone())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let bits = [(); 2].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                FiboChecksumChip::configure(meta, advice, bits, false),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let data: Vec<_> = self
+                .0
+                .iter()
+                .map(|byte| Value::known(Fp::from(*byte)))
+                .collect();
+            let out = FiboChecksumChip::construct(config)
+                .compute_checksum(layouter.namespace(|| "checksum"), &data)?;
+            layouter.constrain_instance(out.0.cell(), instance, 0)
+        }
+    }
+
+    fn prove_checksum(data: &[u64], out: u64) -> MockProver<Fp> {
+        MockProver::run(
+            8,
+            &ChecksumCircuit(data.to_vec()),
+            vec![vec![Fp::from(out)]],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn checksum_of_known_bytes() {
+        for (data, out) in [
+            (&[1, 2, 3][..], 0),
+            (&[0x10, 0x20, 0x30, 0x40][..], 0x60),
+            (&[200, 100, 7][..], 43),
+            (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff][..], 0x01),
+        ] {
+            assert_eq!(checksum(data), out);
+            prove_checksum(data, out).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn checksum_of_two_bytes_is_the_second() {
+        prove_checksum(&[9, 42], 42).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_checksum_fails() {
+        assert!(prove_checksum(&[200, 100, 7], 44).verify().is_err());
+    }
+
+    #[test]
+    fn checksum_byte_out_of_range_fails() {
+        assert!(prove_checksum(&[256, 1, 2], checksum(&[256, 1, 2]))
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "FiboChip needs 3 distinct advice columns")]
+    fn fibo_chip_rejects_a_repeated_column() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let [a, b] = [(); 2].map(|_| meta.advice_column());
+        FiboChip::configure(&mut meta, [a, b, a], false);
+    }
+
+    // F(1) = F(2) = 1 up to F(n + 1), with the ratio shown to tolerance_bits
+    //
+    // instance: | F(n) | F(n + 1) |
+    struct RatioCircuit {
+        n: usize,
+        tolerance_bits: usize,
+    }
+
+    impl Circuit<Fp> for RatioCircuit {
+        type Config = (FiboRatioConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                n: self.n,
+                tolerance_bits: self.tolerance_bits,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let bit = meta.advice_column();
+            let (factor, constant) = (meta.fixed_column(), meta.fixed_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let config = FiboRatioChip::configure(meta, advice, bit, factor, constant, false);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FiboRatioChip::construct(config);
+            let (f_n, f_next) =
+                chip.prove_ratio(layouter.namespace(|| "ratio"), self.n, self.tolerance_bits)?;
+            layouter.constrain_instance(f_n.0.cell(), instance, 0)?;
+            layouter.constrain_instance(f_next.0.cell(), instance, 1)
+        }
+    }
+
+    fn ratio(n: usize, tolerance_bits: usize, f_n: u64, f_next: u64) -> MockProver<Fp> {
+        let circuit = RatioCircuit { n, tolerance_bits };
+        MockProver::run(7, &circuit, vec![vec![Fp::from(f_n), Fp::from(f_next)]]).unwrap()
+    }
+
+    // |F(n + 1) * 1000 - F(n) * 1618| is 10 for n = 10, 230 for n = 20 and 28280
+    // for n = 30, 1.618 is only phi to three places
+    #[test]
+    fn ratio_for_n_10_20_30() {
+        for (n, bits, f_n, f_next) in [
+            (10, 4, 55, 89),
+            (20, 8, 6765, 10946),
+            (30, 15, 832040, 1346269),
+        ] {
+            ratio(n, bits, f_n, f_next).assert_satisfied();
+            assert!(ratio(n, bits - 1, f_n, f_next).verify().is_err());
+        }
+    }
+
+    #[test]
+    fn ratio_below_phi() {
+        // 3 * 1000 - 2 * 1618 = -236
+        ratio(3, 8, 2, 3).assert_satisfied();
+        assert!(ratio(3, 7, 2, 3).verify().is_err());
+    }
+
+    #[test]
+    fn ratio_of_wrong_terms_fails() {
+        assert!(ratio(10, 4, 55, 90).verify().is_err());
+    }
+
+    #[test]
+    fn ratio_of_n_0_is_a_synthesis_error() {
+        assert!(matches!(
+            MockProver::run(
+                7,
+                &RatioCircuit {
+                    n: 0,
+                    tolerance_bits: 8
+                },
+                vec![vec![]]
+            ),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn fibo_circuit_without_witnesses() {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        MockProver::run(4, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        // keygen lays out the unknown cells without asking for them
+        assert!(crate::ipa::keygen(&circuit, 4).is_ok());
+        // witness generation does ask, and stops on the first one
+        assert!(matches!(
+            MockProver::run(4, &circuit.without_witnesses(), vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
 }
@@ -0,0 +1,161 @@
+// FiboCircuit that knows the k it will be proven at and refuses, before laying
+// anything out, to run more steps than 2^k rows can hold. F(1)..F(n) takes
+// n - 2 rows of FiboChip, and the last blinding_factors + 1 rows of the domain
+// are reserved for blinding, so
+//
+// max n = 2^k - (blinding_factors + 1) + 2
+//
+// with the blinding factors read off the circuit's own constraint system.
+//
+// instance: | F(1) | F(2) | F(n) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{FiboChip, FiboConfig};
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveFiboConfig {
+    pub fibo: FiboConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct AdaptiveFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+    pub k: u32,
+}
+
+impl<F: FieldExt> AdaptiveFiboCircuit<F> {
+    pub fn new(a: F, b: F, n: usize, k: u32) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+            k,
+        }
+    }
+
+    pub fn public_inputs(a: F, b: F, n: usize) -> Vec<F> {
+        let (mut prev, mut last) = (a, b);
+        for _ in 2..n {
+            (prev, last) = (last, prev + last);
+        }
+        vec![a, b, last]
+    }
+
+    // rows at the end of the domain taken by blinding
+    fn padding() -> usize {
+        let mut meta = ConstraintSystem::<F>::default();
+        Self::configure(&mut meta);
+        meta.blinding_factors() + 1
+    }
+
+    pub fn max_n_for_k(k: u32) -> usize {
+        (1usize << k).saturating_sub(Self::padding()) + 2
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for AdaptiveFiboCircuit<F> {
+    type Config = AdaptiveFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            k: self.k,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        AdaptiveFiboConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.n < 3 {
+            return Err(Error::Synthesis);
+        }
+        if self.n > Self::max_n_for_k(self.k) {
+            return Err(Error::NotEnoughRowsAvailable { current_k: self.k });
+        }
+        let chip = FiboChip::<F>::construct(config.fibo);
+
+        let (a, b, c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.0.cell(), config.instance, 1)?;
+
+        let (mut prev_b, mut prev_c) = (b, c);
+        for _ in 3..self.n {
+            let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+
+        layouter.constrain_instance(prev_c.0.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn run(n: usize, k: u32) -> Result<MockProver<Fp>, Error> {
+        let circuit = AdaptiveFiboCircuit::new(Fp::one(), Fp::one(), n, k);
+        let public = AdaptiveFiboCircuit::public_inputs(Fp::one(), Fp::one(), n);
+        MockProver::run(k, &circuit, vec![public])
+    }
+
+    #[test]
+    fn max_n_for_k_4() {
+        // 16 rows less 5 blinding factors and the row after them, plus F(1), F(2)
+        assert_eq!(AdaptiveFiboCircuit::<Fp>::max_n_for_k(4), 12);
+        assert_eq!(AdaptiveFiboCircuit::<Fp>::max_n_for_k(5), 28);
+    }
+
+    #[test]
+    fn max_n_fits() {
+        run(12, 4).unwrap().assert_satisfied();
+        assert_eq!(
+            AdaptiveFiboCircuit::public_inputs(Fp::one(), Fp::one(), 12)[2],
+            Fp::from(144)
+        );
+    }
+
+    #[test]
+    fn more_steps_is_an_error() {
+        assert!(matches!(
+            run(13, 4),
+            Err(Error::NotEnoughRowsAvailable { current_k: 4 })
+        ));
+        run(13, 5).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn fewer_than_3_terms_is_a_synthesis_error() {
+        assert!(matches!(run(2, 4), Err(Error::Synthesis)));
+    }
+}
@@ -0,0 +1,149 @@
+// Proves F(1)..F(n) on FiboChip and makes every term public, one instance row
+// per term, so the verifier checks the whole sequence and not only its end.
+//
+// instance: | F(1) | F(2) | ... | F(n) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{FiboChip, FiboConfig};
+
+#[derive(Debug, Clone)]
+pub struct FiboAllOutputsConfig {
+    pub fibo: FiboConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiboAllOutputsCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> FiboAllOutputsCircuit<F> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+
+    pub fn public_inputs(a: F, b: F, n: usize) -> Vec<F> {
+        let mut sequence = vec![a, b];
+        while sequence.len() < n {
+            let next = sequence[sequence.len() - 2] + sequence[sequence.len() - 1];
+            sequence.push(next);
+        }
+        sequence.truncate(n);
+        sequence
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboAllOutputsCircuit<F> {
+    type Config = FiboAllOutputsConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FiboAllOutputsConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        // the first row already holds F(3)
+        if self.n < 3 {
+            return Err(Error::Synthesis);
+        }
+        let chip = FiboChip::<F>::construct(config.fibo);
+
+        let (a, mut prev_b, mut prev_c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(prev_b.0.cell(), config.instance, 1)?;
+        layouter.constrain_instance(prev_c.0.cell(), config.instance, 2)?;
+
+        for row in 3..self.n {
+            let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            layouter.constrain_instance(c.0.cell(), config.instance, row)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Any,
+    };
+
+    use super::*;
+
+    const SEQUENCE: [u64; 10] = [1, 1, 2, 3, 5, 8, 13, 21, 34, 55];
+
+    fn run(public: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = FiboAllOutputsCircuit::new(Fp::one(), Fp::one(), 10);
+        MockProver::run(5, &circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn all_ten_terms_are_bound() {
+        let public = SEQUENCE.map(Fp::from).to_vec();
+        assert_eq!(
+            FiboAllOutputsCircuit::public_inputs(Fp::one(), Fp::one(), 10),
+            public
+        );
+        run(public).assert_satisfied();
+    }
+
+    #[test]
+    fn any_wrong_term_is_an_instance_mismatch() {
+        for i in 0..SEQUENCE.len() {
+            let mut public = SEQUENCE.map(Fp::from).to_vec();
+            public[i] += Fp::one();
+            let failures = run(public).verify().unwrap_err();
+            let mismatch = VerifyFailure::Permutation {
+                column: (Any::Instance, 0).into(),
+                location: FailureLocation::OutsideRegion { row: i },
+            };
+            assert!(failures.contains(&mismatch), "term {}: {:?}", i, failures);
+        }
+    }
+
+    #[test]
+    fn fewer_than_3_terms_is_a_synthesis_error() {
+        let circuit = FiboAllOutputsCircuit::new(Fp::one(), Fp::one(), 2);
+        assert!(matches!(
+            MockProver::run(5, &circuit, vec![vec![Fp::one(); 2]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
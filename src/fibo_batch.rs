@@ -0,0 +1,242 @@
+// M independent Fibonacci sequences side by side. Every sequence gets its own
+// a, b, c columns and all of them step under one selector, so M sequences up
+// to F(n) take the n - 2 rows of one instead of M times as many:
+//
+// | a_0 | b_0 | c_0 | ... | a_M-1 | b_M-1 | c_M-1 | selector |
+// | 1   | 1   | 2   |     | 2     | 3     | 5     | 1        |
+// | 1   | 2   | 3   |     | 3     | 5     | 8     | 1        |
+// gate add: selector * (a_i + b_i - c_i) == 0 for every i
+//
+// Rows are chained inside one region by copying b, c to the a, b of the next
+// row, as FiboChip does across regions. The seeds stay private.
+//
+// instance: | F_0(n) | ... | F_M-1(n) |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::fibo1::ACell;
+
+#[derive(Debug, Clone)]
+pub struct BatchFiboConfig<const M: usize> {
+    // a, b, c of every sequence
+    pub advice: [[Column<Advice>; 3]; M],
+    pub selector: Selector,
+}
+
+pub struct BatchFiboChip<F: FieldExt, const M: usize> {
+    config: BatchFiboConfig<M>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const M: usize> BatchFiboChip<F, M> {
+    pub fn construct(config: BatchFiboConfig<M>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> BatchFiboConfig<M> {
+        let advice = [(); M].map(|_| [(); 3].map(|_| meta.advice_column()));
+        for column in advice.iter().flatten() {
+            meta.enable_equality(*column);
+        }
+        let selector = meta.selector();
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(selector);
+            advice
+                .iter()
+                .map(|[col_a, col_b, col_c]| {
+                    let a = meta.query_advice(*col_a, Rotation::cur());
+                    let b = meta.query_advice(*col_b, Rotation::cur());
+                    let c = meta.query_advice(*col_c, Rotation::cur());
+                    s.clone() * (a + b - c)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        BatchFiboConfig { advice, selector }
+    }
+
+    /// Runs every sequence from F(1), F(2) = `seqs[i]` up to F(n), returns the
+    /// cells of F(n).
+    pub fn batch_assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        seqs: [(Value<F>, Value<F>); M],
+        n: usize,
+    ) -> Result<[ACell<F>; M], Error> {
+        if n < 3 {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        layouter.assign_region(
+            || "batch fibo",
+            |mut region| {
+                let mut rows = vec![];
+                config.selector.enable(&mut region, 0)?;
+                for ([col_a, col_b, col_c], (a, b)) in config.advice.iter().zip(seqs) {
+                    region.assign_advice(|| "a", *col_a, 0, || a)?;
+                    let b_cell = region.assign_advice(|| "b", *col_b, 0, || b)?;
+                    let c_cell = region.assign_advice(|| "c", *col_c, 0, || a + b)?;
+                    rows.push((b_cell, c_cell));
+                }
+
+                for row in 1..n - 2 {
+                    config.selector.enable(&mut region, row)?;
+                    for ([col_a, col_b, col_c], (prev_b, prev_c)) in
+                        config.advice.iter().zip(rows.iter_mut())
+                    {
+                        let a = prev_b.copy_advice(|| "a", &mut region, *col_a, row)?;
+                        let b = prev_c.copy_advice(|| "b", &mut region, *col_b, row)?;
+                        let next = a.value().copied() + b.value();
+                        let c = region.assign_advice(|| "c", *col_c, row, || next)?;
+                        (*prev_b, *prev_c) = (b, c);
+                    }
+                }
+
+                let last: Vec<_> = rows.into_iter().map(|(_, c)| ACell(c)).collect();
+                Ok(last.try_into().unwrap())
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchFiboCircuitConfig<const M: usize> {
+    pub fibo: BatchFiboConfig<M>,
+    pub instance: Column<Instance>,
+}
+
+pub struct BatchFiboCircuit<F, const M: usize> {
+    pub seqs: [(Value<F>, Value<F>); M],
+    pub n: usize,
+}
+
+impl<F: FieldExt, const M: usize> BatchFiboCircuit<F, M> {
+    pub fn new(seqs: [(F, F); M], n: usize) -> Self {
+        Self {
+            seqs: seqs.map(|(a, b)| (Value::known(a), Value::known(b))),
+            n,
+        }
+    }
+
+    pub fn public_inputs(seqs: [(F, F); M], n: usize) -> Vec<F> {
+        seqs.iter()
+            .map(|&(a, b)| (2..n).fold((a, b), |(a, b), _| (b, a + b)).1)
+            .collect()
+    }
+}
+
+impl<F: FieldExt, const M: usize> Circuit<F> for BatchFiboCircuit<F, M> {
+    type Config = BatchFiboCircuitConfig<M>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            seqs: [(Value::unknown(), Value::unknown()); M],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        BatchFiboCircuitConfig {
+            fibo: BatchFiboChip::configure(meta),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = BatchFiboChip::<F, M>::construct(config.fibo);
+        let last = chip.batch_assign(layouter.namespace(|| "batch fibo"), self.seqs, self.n)?;
+        for (i, cell) in last.iter().enumerate() {
+            layouter.constrain_instance(cell.0.cell(), config.instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::{parse_index, record};
+
+    fn seqs<const M: usize>(seeds: [(u64, u64); M]) -> [(Fp, Fp); M] {
+        seeds.map(|(a, b)| (Fp::from(a), Fp::from(b)))
+    }
+
+    fn prove<const M: usize>(seeds: [(u64, u64); M], n: usize, public: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = BatchFiboCircuit::new(seqs(seeds), n);
+        MockProver::run(5, &circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn two_sequences() {
+        let seeds = [(1, 1), (2, 3)];
+        let public = BatchFiboCircuit::public_inputs(seqs(seeds), 10);
+        assert_eq!(public, [55, 144].map(Fp::from));
+        prove(seeds, 10, public).assert_satisfied();
+    }
+
+    #[test]
+    fn four_sequences() {
+        let seeds = [(1, 1), (2, 3), (0, 1), (5, 5)];
+        let public = BatchFiboCircuit::public_inputs(seqs(seeds), 12);
+        assert_eq!(public, [144, 377, 89, 720].map(Fp::from));
+        prove(seeds, 12, public).assert_satisfied();
+    }
+
+    #[test]
+    fn one_wrong_output_fails() {
+        let seeds = [(1, 1), (2, 3), (0, 1), (5, 5)];
+        let mut public = BatchFiboCircuit::public_inputs(seqs(seeds), 12);
+        public[2] += Fp::one();
+        assert!(prove(seeds, 12, public).verify().is_err());
+
+        // the outputs of two sequences swapped
+        let mut public = BatchFiboCircuit::public_inputs(seqs([(1, 1), (2, 3)]), 10);
+        public.swap(0, 1);
+        assert!(prove([(1, 1), (2, 3)], 10, public).verify().is_err());
+    }
+
+    #[test]
+    fn rows_of_one_sequence() {
+        // n - 2 rows in one region whatever M is, 3 * M columns
+        let circuit = BatchFiboCircuit::new(seqs([(1, 1); 4]), 10);
+        let recorder = record(&circuit, 5, vec![vec![Fp::from(55); 4]]).unwrap();
+        assert_eq!(recorder.regions.len(), 1);
+        assert_eq!(recorder.regions[0].rows, Some((0, 7)));
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        BatchFiboCircuit::<Fp, 4>::configure(&mut meta);
+        let pinned = format!("{:?}", meta.pinned());
+        assert_eq!(parse_index(&pinned, "num_advice_columns: "), 12);
+        assert_eq!(parse_index(&pinned, "num_selectors: "), 1);
+    }
+
+    #[test]
+    fn n_below_3() {
+        let circuit = BatchFiboCircuit::new(seqs([(1, 1), (2, 3)]), 2);
+        assert!(matches!(
+            MockProver::run(5, &circuit, vec![vec![Fp::one(), Fp::from(3)]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
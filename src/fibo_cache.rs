@@ -0,0 +1,224 @@
+// Proves F(1)..F(n), or picks up from a cached checkpoint (F(m - 1), F(m))
+// left public by an earlier proof and only proves F(m + 1)..F(n). Every proof
+// ends with its last pair in the instance, so proving F(1)..F(100) and then
+// F(100)..F(200) from its checkpoint costs the same rows as F(1)..F(200).
+//
+// instance: | start | start + 1 | F(n - 1) | F(n) |
+//
+// with start = F(1), F(2) on a cache miss and F(m - 1), F(m) on a hit.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{FiboChip, FiboConfig};
+
+#[derive(Debug, Clone)]
+pub struct FiboCacheConfig {
+    pub fibo: FiboConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiboCacheCircuit<F> {
+    // F(1), F(2), unused when resuming from a checkpoint
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+    pub n_checkpoint: usize,
+    // (F(n_checkpoint - 1), F(n_checkpoint))
+    pub cache_checkpoint: Option<(Value<F>, Value<F>)>,
+}
+
+impl<F: FieldExt> FiboCacheCircuit<F> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+            n_checkpoint: 0,
+            cache_checkpoint: None,
+        }
+    }
+
+    pub fn resume(n_checkpoint: usize, checkpoint: (F, F), n: usize) -> Self {
+        Self {
+            n,
+            n_checkpoint,
+            cache_checkpoint: Some((Value::known(checkpoint.0), Value::known(checkpoint.1))),
+            ..Default::default()
+        }
+    }
+
+    pub fn public_inputs(start: (F, F), end: (F, F)) -> Vec<F> {
+        vec![start.0, start.1, end.0, end.1]
+    }
+
+    // index of the first number in the starting pair
+    fn start(&self) -> usize {
+        match self.cache_checkpoint {
+            Some(_) => self.n_checkpoint - 1,
+            None => 1,
+        }
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboCacheCircuit<F> {
+    type Config = FiboCacheConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            n_checkpoint: self.n_checkpoint,
+            cache_checkpoint: self
+                .cache_checkpoint
+                .map(|_| (Value::unknown(), Value::unknown())),
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FiboCacheConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.cache_checkpoint.is_some() && self.n_checkpoint < 2 {
+            return Err(Error::Synthesis);
+        }
+        let start = self.start();
+        // the first row already produces F(start + 2)
+        if self.n < start + 2 {
+            return Err(Error::Synthesis);
+        }
+
+        let chip = FiboChip::<F>::construct(config.fibo);
+        let (a, b) = self.cache_checkpoint.unwrap_or((self.a, self.b));
+
+        let (a, mut prev_b, mut prev_c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), a, b)?;
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(prev_b.0.cell(), config.instance, 1)?;
+
+        for _ in start + 2..self.n {
+            let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+
+        layouter.constrain_instance(prev_b.0.cell(), config.instance, 2)?;
+        layouter.constrain_instance(prev_c.0.cell(), config.instance, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::record;
+
+    // F(1) = F(2) = 1 up to F(n), index 0 unused
+    fn fib(n: usize) -> Vec<Fp> {
+        let mut f = vec![Fp::zero(), Fp::one(), Fp::one()];
+        for i in 3..=n {
+            f.push(f[i - 1] + f[i - 2]);
+        }
+        f
+    }
+
+    fn run(circuit: &FiboCacheCircuit<Fp>, public: Vec<Fp>) -> MockProver<Fp> {
+        MockProver::run(8, circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn cache_miss_proves_from_the_start() {
+        let f = fib(100);
+        let circuit = FiboCacheCircuit::new(Fp::one(), Fp::one(), 100);
+        run(
+            &circuit,
+            FiboCacheCircuit::public_inputs((f[1], f[2]), (f[99], f[100])),
+        )
+        .assert_satisfied();
+    }
+
+    #[test]
+    fn cache_hit_resumes_from_the_checkpoint() {
+        let f = fib(200);
+        let first = FiboCacheCircuit::new(Fp::one(), Fp::one(), 100);
+        let first_public = FiboCacheCircuit::public_inputs((f[1], f[2]), (f[99], f[100]));
+        run(&first, first_public.clone()).assert_satisfied();
+
+        // the checkpoint is the pair the first proof left public
+        let checkpoint = (first_public[2], first_public[3]);
+        let second = FiboCacheCircuit::resume(100, checkpoint, 200);
+        run(
+            &second,
+            FiboCacheCircuit::public_inputs(checkpoint, (f[199], f[200])),
+        )
+        .assert_satisfied();
+
+        // both halves together lay out as many regions as one proof of F(1)..F(200)
+        let regions = |circuit: &FiboCacheCircuit<Fp>, public| {
+            record(circuit, 8, vec![public]).unwrap().regions.len()
+        };
+        let whole = FiboCacheCircuit::new(Fp::one(), Fp::one(), 200);
+        assert_eq!(
+            regions(&first, first_public)
+                + regions(
+                    &second,
+                    FiboCacheCircuit::public_inputs(checkpoint, (f[199], f[200]))
+                ),
+            regions(
+                &whole,
+                FiboCacheCircuit::public_inputs((f[1], f[2]), (f[199], f[200]))
+            )
+        );
+    }
+
+    #[test]
+    fn wrong_checkpoint_fails() {
+        let f = fib(200);
+        let second = FiboCacheCircuit::resume(100, (f[99], f[100] + Fp::one()), 200);
+        let public = FiboCacheCircuit::public_inputs((f[99], f[100]), (f[199], f[200]));
+        assert!(run(&second, public).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_end_fails() {
+        let f = fib(100);
+        let circuit = FiboCacheCircuit::new(Fp::one(), Fp::one(), 100);
+        let public = FiboCacheCircuit::public_inputs((f[1], f[2]), (f[99], f[99]));
+        assert!(run(&circuit, public).verify().is_err());
+    }
+
+    #[test]
+    fn bad_checkpoint_or_length_is_a_synthesis_error() {
+        let public = vec![Fp::zero(); 4];
+        let too_early = FiboCacheCircuit::resume(1, (Fp::one(), Fp::one()), 10);
+        let too_short = FiboCacheCircuit::resume(10, (Fp::one(), Fp::one()), 10);
+        for circuit in [too_early, too_short] {
+            assert!(matches!(
+                MockProver::run(8, &circuit, vec![public.clone()]),
+                Err(Error::Synthesis)
+            ));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// Two Fibonacci segments F(s1)..F(e1) and F(s2)..F(e2) with a hole between them.
+// Each segment is laid out row by row as in `FiboSegmentCircuit`, the hole is
+// crossed with `FiboSkipChip::skip_n_steps_from` and the segments are chained to the
+// skip by copy constraints:
+//
+// (F(e1 - 1), F(e1)) --skip s2 - e1 + 1--> (F(s2), F(s2 + 1))
+//
+// instance: | F(s1) | F(s1 + 1) | F(e1) | F(s2) | F(e2) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{ACell, FiboChip, FiboSkipChip, FiboSkipConfig};
+
+#[derive(Debug, Clone)]
+pub struct FiboHolesConfig {
+    pub skip: FiboSkipConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiboHolesCircuit<F> {
+    // (start, end) of each segment, end >= start + 2 and s2 > e1
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+    pub fm: Value<F>,
+    pub fm1: Value<F>,
+}
+
+impl<F: FieldExt> FiboHolesCircuit<F> {
+    pub fn new(first: (usize, usize), second: (usize, usize), fm: F, fm1: F) -> Self {
+        Self {
+            first,
+            second,
+            fm: Value::known(fm),
+            fm1: Value::known(fm1),
+        }
+    }
+
+    // continues the first row (a, b, c) up to F(end), returns the last two terms
+    fn assign_segment(
+        chip: &FiboChip<F>,
+        mut layouter: impl Layouter<F>,
+        (start, end): (usize, usize),
+        (b, c): (ACell<F>, ACell<F>),
+    ) -> Result<(ACell<F>, ACell<F>), Error> {
+        let (mut prev_b, mut prev_c) = (b, c);
+        for _ in start + 2..end {
+            let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c_cell;
+        }
+        Ok((prev_b, prev_c))
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboHolesCircuit<F> {
+    type Config = FiboHolesConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            first: self.first,
+            second: self.second,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FiboHolesConfig {
+            skip: FiboSkipChip::configure(meta, advices, constant, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let ((s1, e1), (s2, e2)) = (self.first, self.second);
+        if e1 < s1 + 2 || e2 < s2 + 2 || s2 <= e1 {
+            return Err(Error::Synthesis);
+        }
+
+        let chip = FiboChip::<F>::construct(config.skip.fibo.clone());
+        let skip = FiboSkipChip::<F>::construct(config.skip);
+
+        let (fm, fm1, c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.fm, self.fm1)?;
+        let (before, end) = Self::assign_segment(
+            &chip,
+            layouter.namespace(|| "first segment"),
+            self.first,
+            (fm1.clone(), c),
+        )?;
+
+        let (start, start1) = skip.skip_n_steps_from(
+            layouter.namespace(|| "hole"),
+            &before,
+            &end,
+            (s2 - e1 + 1) as u64,
+        )?;
+
+        // the first row of the second segment copies the skip output
+        let c = chip.assign_row(layouter.namespace(|| "second row"), &start, &start1)?;
+        let (_, last) = Self::assign_segment(
+            &chip,
+            layouter.namespace(|| "second segment"),
+            self.second,
+            (start1, c),
+        )?;
+
+        for (row, cell) in [fm, fm1, end, start, last].iter().enumerate() {
+            layouter.constrain_instance(cell.0.cell(), config.instance, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    const K: u32 = 7;
+
+    // F(0) = 0, F(1) = 1
+    fn fib(n: usize) -> u64 {
+        (0..n).fold((0, 1), |(a, b), _| (b, a + b)).0
+    }
+
+    fn public(first: (usize, usize), second: (usize, usize)) -> Vec<Vec<Fp>> {
+        let rows = [
+            fib(first.0),
+            fib(first.0 + 1),
+            fib(first.1),
+            fib(second.0),
+            fib(second.1),
+        ];
+        vec![rows.map(Fp::from).to_vec()]
+    }
+
+    fn circuit(first: (usize, usize), second: (usize, usize)) -> FiboHolesCircuit<Fp> {
+        FiboHolesCircuit::new(
+            first,
+            second,
+            Fp::from(fib(first.0)),
+            Fp::from(fib(first.0 + 1)),
+        )
+    }
+
+    #[test]
+    fn gap_of_five() {
+        let (first, second) = ((0, 5), (10, 15));
+        assert_eq!(public(first, second)[0], [0, 1, 5, 55, 610].map(Fp::from));
+        MockProver::run(K, &circuit(first, second), public(first, second))
+            .unwrap()
+            .assert_satisfied();
+
+        let (first, second) = ((3, 8), (13, 20));
+        MockProver::run(K, &circuit(first, second), public(first, second))
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn every_boundary_is_constrained() {
+        let (first, second) = ((0, 5), (10, 15));
+        for row in 0..5 {
+            let mut wrong = public(first, second);
+            wrong[0][row] += Fp::one();
+            let prover = MockProver::run(K, &circuit(first, second), wrong).unwrap();
+            assert!(prover.verify().is_err(), "row {} is not constrained", row);
+        }
+    }
+
+    #[test]
+    fn overlapping_segments_are_rejected() {
+        let (first, second) = ((0, 5), (5, 10));
+        assert!(MockProver::run(K, &circuit(first, second), public(first, second)).is_err());
+    }
+}
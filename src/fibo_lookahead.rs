@@ -0,0 +1,187 @@
+// K-step Fibonacci on a single column, each term is the sum of the previous K
+// | x    | selector |
+// | x_0  | 1        |
+// | x_1  | 1        |
+// | ...  |
+// gate: selector * (x(cur) + x(cur + 1) + ... + x(cur + K - 1) - x(cur + K)) == 0
+//
+// K = 2 is Fibonacci, K = 3 is Tribonacci, K = 4 is Tetranacci
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct LookaheadFiboConfig {
+    pub advice: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct LookaheadFiboChip<F: FieldExt, const K: usize> {
+    config: LookaheadFiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const K: usize> LookaheadFiboChip<F, K> {
+    pub fn construct(config: LookaheadFiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: Column<Advice>,
+        instance: Column<Instance>,
+    ) -> LookaheadFiboConfig {
+        let selector = meta.selector();
+
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        meta.create_gate("lookahead fibo", |meta| {
+            let s = meta.query_selector(selector);
+            let next = meta.query_advice(advice, Rotation(K as i32));
+            let sum = (0..K)
+                .map(|i| meta.query_advice(advice, Rotation(i as i32)))
+                .reduce(|acc, term| acc + term)
+                .expect("lookahead needs at least one term");
+            vec![s * (sum - next)]
+        });
+
+        LookaheadFiboConfig {
+            advice,
+            selector,
+            instance,
+        }
+    }
+
+    // assign the K initial terms followed by `steps` new terms, return the last one
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        initial: [Value<F>; K],
+        steps: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "lookahead fibo",
+            |mut region| {
+                let mut terms = Vec::with_capacity(K + steps);
+                for (row, x) in initial.iter().enumerate() {
+                    terms.push(region.assign_advice(|| "initial", config.advice, row, || *x)?);
+                }
+
+                for row in 0..steps {
+                    config.selector.enable(&mut region, row)?;
+
+                    let next = terms[row..row + K]
+                        .iter()
+                        .fold(Value::known(F::zero()), |acc, term| {
+                            acc + term.value().copied()
+                        });
+                    terms.push(region.assign_advice(|| "next", config.advice, row + K, || next)?);
+                }
+
+                Ok(terms.pop().unwrap())
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+/// Proves that the public output is the term reached after `steps` K-step
+/// Fibonacci steps from the private initial terms.
+pub struct LookaheadFiboCircuit<F: FieldExt, const K: usize> {
+    pub initial: [Value<F>; K],
+    pub steps: usize,
+}
+
+impl<F: FieldExt, const K: usize> LookaheadFiboCircuit<F, K> {
+    pub fn new(initial: [Value<F>; K], steps: usize) -> Self {
+        Self { initial, steps }
+    }
+}
+
+impl<F: FieldExt, const K: usize> Circuit<F> for LookaheadFiboCircuit<F, K> {
+    type Config = LookaheadFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new([Value::unknown(); K], self.steps)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = meta.advice_column();
+        let instance = meta.instance_column();
+        LookaheadFiboChip::<F, K>::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = LookaheadFiboChip::<F, K>::construct(config);
+
+        let out = chip.assign(
+            layouter.namespace(|| "lookahead fibo"),
+            self.initial,
+            self.steps,
+        )?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn prove<const K: usize>(initial: [u64; K], steps: usize, out: u64) -> MockProver<Fp> {
+        let circuit =
+            LookaheadFiboCircuit::<Fp, K>::new(initial.map(|x| Value::known(Fp::from(x))), steps);
+        MockProver::run(5, &circuit, vec![vec![Fp::from(out)]]).unwrap()
+    }
+
+    #[test]
+    fn fibonacci() {
+        // 1, 1, 2, 3, 5, 8, 13, 21, 34, 55
+        prove([1, 1], 8, 55).assert_satisfied();
+    }
+
+    #[test]
+    fn tribonacci() {
+        // 0, 0, 1, 1, 2, 4, 7, 13, 24, 44, 81
+        prove([0, 0, 1], 8, 81).assert_satisfied();
+    }
+
+    #[test]
+    fn tetranacci() {
+        // 0, 0, 0, 1, 1, 2, 4, 8, 15, 29, 56, 108, 208
+        prove([0, 0, 0, 1], 9, 208).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_term_fails() {
+        assert!(prove([1, 1], 8, 89).verify().is_err());
+        assert!(prove([0, 0, 1], 8, 44).verify().is_err());
+        assert!(prove([0, 0, 0, 1], 9, 207).verify().is_err());
+    }
+}
@@ -0,0 +1,553 @@
+// Fibonacci modulo a public 32-bit prime p, every step is reduced so the terms
+// stay below p.
+//
+// | a   | b   | q | r | diff | s_mod | s_below | modulus |
+// | F0  | F1  | q | F2| ...  | 1     | 0       | p       |
+// | F1  | F2  | q | F3| ...  | 1     | 0       | p       |
+// gate mod: s_mod * (a + b - q * p - r) == 0
+//           s_mod * q * (1 - q) == 0
+//           s_mod * (p - 1 - r - diff) == 0
+// r and diff are range checked to [0, 2^32), so r < p. With a, b < p the sum is
+// below 2p and a boolean quotient is enough.
+//
+// Later rows take a and b from earlier remainders, the first row checks F0 and
+// F1 against p on its own:
+// | x  |   |   |   | diff | 0     | 1       | p       |
+// gate below: s_below * (p - 1 - x - diff) == 0, x and diff range checked
+//
+// instance: | F(0) | F(1) | F(n) mod p |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
+};
+
+use crate::{
+    fibo1::{ACell, FiboChip, FiboConfig},
+    range_check::{RangeCheckChip, RangeCheckConfig},
+};
+
+pub const MODULUS_BITS: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct ModularFiboConfig {
+    pub advice: [Column<Advice>; 5],
+    pub modulus: Column<Fixed>,
+    pub s_mod: Selector,
+    pub s_below: Selector,
+    pub range: RangeCheckConfig,
+}
+
+pub struct ModularFiboChip<F: FieldExt> {
+    config: ModularFiboConfig,
+    p: u32,
+    _marker: PhantomData<F>,
+}
+
+pub(crate) fn to_u64<F: FieldExt>(value: F) -> u64 {
+    u64::from_le_bytes(value.to_repr().as_ref()[..8].try_into().unwrap())
+}
+
+impl<F: FieldExt> ModularFiboChip<F> {
+    pub fn construct(config: ModularFiboConfig, p: u32) -> Self {
+        Self {
+            config,
+            p,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        modulus: Column<Fixed>,
+    ) -> ModularFiboConfig {
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let [col_a, col_b, col_q, col_r, col_diff] = advice;
+        let s_mod = meta.selector();
+        let s_below = meta.selector();
+
+        meta.create_gate("mod", |meta| {
+            let s = meta.query_selector(s_mod);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let q = meta.query_advice(col_q, Rotation::cur());
+            let r = meta.query_advice(col_r, Rotation::cur());
+            let diff = meta.query_advice(col_diff, Rotation::cur());
+            let p = meta.query_fixed(modulus, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            vec![
+                s.clone() * (a + b - q.clone() * p.clone() - r.clone()),
+                s.clone() * q.clone() * (one.clone() - q),
+                s * (p - one - r - diff),
+            ]
+        });
+
+        meta.create_gate("below", |meta| {
+            let s = meta.query_selector(s_below);
+            let x = meta.query_advice(col_a, Rotation::cur());
+            let diff = meta.query_advice(col_diff, Rotation::cur());
+            let p = meta.query_fixed(modulus, Rotation::cur());
+            vec![s * (p - Expression::Constant(F::one()) - x - diff)]
+        });
+
+        ModularFiboConfig {
+            advice,
+            modulus,
+            s_mod,
+            s_below,
+            range: RangeCheckChip::<F, MODULUS_BITS>::configure(meta, col_a, col_b),
+        }
+    }
+
+    // one reduced step (a, b) -> (a + b) mod p, `a` and `b` are copied when given
+    fn assign_step(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Result<&AssignedCell<F, F>, Value<F>>,
+        b: Result<&AssignedCell<F, F>, Value<F>>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let config = &self.config;
+        let p = self.p as u64;
+
+        let (a, b, r, diff) = layouter.assign_region(
+            || "mod step",
+            |mut region| {
+                config.s_mod.enable(&mut region, 0)?;
+                region.assign_fixed(|| "p", config.modulus, 0, || Value::known(F::from(p)))?;
+
+                let mut load = |input: &Result<&AssignedCell<F, F>, Value<F>>, column| match input {
+                    Ok(cell) => cell.copy_advice(|| "term", &mut region, column, 0),
+                    Err(value) => region.assign_advice(|| "term", column, 0, || *value),
+                };
+                let a = load(&a, config.advice[0])?;
+                let b = load(&b, config.advice[1])?;
+
+                let sum = a
+                    .value()
+                    .zip(b.value())
+                    .map(|(a, b)| to_u64(*a) + to_u64(*b));
+                let q = sum.map(|sum| F::from(sum / p));
+                let r = sum.map(|sum| sum % p);
+
+                region.assign_advice(|| "q", config.advice[2], 0, || q)?;
+                let r_cell =
+                    region.assign_advice(|| "r", config.advice[3], 0, || r.map(F::from))?;
+                let diff = region.assign_advice(
+                    || "diff",
+                    config.advice[4],
+                    0,
+                    || r.map(|r| F::from(p - 1 - r)),
+                )?;
+                Ok((a, b, r_cell, diff))
+            },
+        )?;
+
+        let range = RangeCheckChip::<F, MODULUS_BITS>::construct(config.range.clone());
+        range.check(layouter.namespace(|| "r < 2^32"), &r)?;
+        range.check(layouter.namespace(|| "p - 1 - r < 2^32"), &diff)?;
+        Ok((a, b, r))
+    }
+
+    // x < p, for terms that don't come out of a reduction
+    fn check_below_p(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        let p = self.p as u64;
+
+        let diff = layouter.assign_region(
+            || "below p",
+            |mut region| {
+                config.s_below.enable(&mut region, 0)?;
+                region.assign_fixed(|| "p", config.modulus, 0, || Value::known(F::from(p)))?;
+                x.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                let diff = x.value().map(|x| F::from(p) - F::one() - x);
+                region.assign_advice(|| "diff", config.advice[4], 0, || diff)
+            },
+        )?;
+
+        let range = RangeCheckChip::<F, MODULUS_BITS>::construct(config.range.clone());
+        range.check(layouter.namespace(|| "x < 2^32"), x)?;
+        range.check(layouter.namespace(|| "p - 1 - x < 2^32"), &diff)?;
+        Ok(())
+    }
+
+    // a and b are checked against p, the boolean quotient relies on a + b < 2p
+    pub fn assign_first_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let (a, b, c) = self.assign_step(layouter.namespace(|| "step"), Err(a), Err(b))?;
+        self.check_below_p(layouter.namespace(|| "a < p"), &a)?;
+        self.check_below_p(layouter.namespace(|| "b < p"), &b)?;
+        Ok((a, b, c))
+    }
+
+    pub fn assign_row(
+        &self,
+        layouter: impl Layouter<F>,
+        prev_b: &AssignedCell<F, F>,
+        prev_c: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.assign_step(layouter, Ok(prev_b), Ok(prev_c))
+            .map(|(_, _, c)| c)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModularFiboCircuitConfig {
+    pub fibo: ModularFiboConfig,
+    pub instance: Column<Instance>,
+}
+
+/// Proves F(n) mod p from the public F(0) and F(1), for n >= 2.
+#[derive(Default)]
+pub struct ModularFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+    pub p: u32,
+}
+
+impl<F: FieldExt> ModularFiboCircuit<F> {
+    pub fn new(a: u32, b: u32, n: usize, p: u32) -> Self {
+        Self {
+            a: Value::known(F::from(a as u64)),
+            b: Value::known(F::from(b as u64)),
+            n,
+            p,
+        }
+    }
+
+    // instance column for F(0) = a, F(1) = b, both below p
+    pub fn public_inputs(a: u32, b: u32, n: usize, p: u32) -> Vec<Vec<F>> {
+        let p = p as u64;
+        let (out, _) = (0..n).fold((a as u64, b as u64), |(a, b), _| (b, (a + b) % p));
+        vec![vec![F::from(a as u64), F::from(b as u64), F::from(out)]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for ModularFiboCircuit<F> {
+    type Config = ModularFiboCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            p: self.p,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let modulus = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        ModularFiboCircuitConfig {
+            fibo: ModularFiboChip::configure(meta, advice, modulus),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.n < 2 {
+            return Err(Error::Synthesis);
+        }
+
+        let chip = ModularFiboChip::<F>::construct(config.fibo, self.p);
+
+        let (a, mut prev_b, mut prev_c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+        layouter.constrain_instance(a.cell(), config.instance, 0)?;
+        layouter.constrain_instance(prev_b.cell(), config.instance, 1)?;
+
+        for _ in 2..self.n {
+            let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+
+        layouter.constrain_instance(prev_c.cell(), config.instance, 2)
+    }
+}
+
+/// `FiboChip` run modulo a prime on `ModularFiboChip`, sharing the three advice
+/// columns, to prove periods of the sequence.
+#[derive(Debug, Clone)]
+pub struct FiboPeriodConfig {
+    pub fibo: FiboConfig,
+    pub modular: ModularFiboConfig,
+}
+
+pub struct FiboPeriodChip<F: FieldExt> {
+    config: FiboPeriodConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FiboPeriodChip<F> {
+    pub fn construct(config: FiboPeriodConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 5],
+        modulus: Column<Fixed>,
+        constant: Column<Fixed>,
+        reverse: bool,
+    ) -> FiboPeriodConfig {
+        let fibo = FiboChip::configure(meta, [advices[0], advices[1], advices[2]], reverse);
+        meta.enable_constant(constant);
+        FiboPeriodConfig {
+            fibo,
+            modular: ModularFiboChip::configure(meta, advices, modulus),
+        }
+    }
+
+    // runs (1, 1) for `period` steps modulo p and pins the pair it ends on back
+    // to (1, 1). Returns the cell holding `period`, fixed by the circuit itself.
+    // This proves `period` is a period of the sequence modulo p, so a multiple of
+    // the Pisano period. It does not prove it is the smallest one.
+    pub fn prove_period(
+        &self,
+        mut layouter: impl Layouter<F>,
+        p: F,
+        period: u64,
+    ) -> Result<ACell<F>, Error> {
+        let repr = p.to_repr();
+        let (low, high) = repr.as_ref().split_at(4);
+        if period == 0 || high.iter().any(|byte| *byte != 0) {
+            return Err(Error::Synthesis);
+        }
+        let (config, p) = (
+            &self.config.modular,
+            u32::from_le_bytes(low.try_into().unwrap()),
+        );
+        let chip = ModularFiboChip::<F>::construct(config.clone(), p);
+
+        let (a, b, period_cell) = layouter.assign_region(
+            || "period start",
+            |mut region| {
+                let one = F::one();
+                let a = region.assign_advice_from_constant(|| "1", config.advice[0], 0, one)?;
+                let b = region.assign_advice_from_constant(|| "1", config.advice[1], 0, one)?;
+                let period_cell = region.assign_advice_from_constant(
+                    || "period",
+                    config.advice[2],
+                    0,
+                    F::from(period),
+                )?;
+                Ok((a, b, period_cell))
+            },
+        )?;
+
+        let (mut prev_b, mut prev_c) = (a, b);
+        for _ in 0..period {
+            let c = chip.assign_row(layouter.namespace(|| "mod row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+
+        layouter.assign_region(
+            || "period end",
+            |mut region| {
+                let a = prev_b.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let b = prev_c.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                region.constrain_constant(a.cell(), F::one())?;
+                region.constrain_constant(b.cell(), F::one())
+            },
+        )?;
+        Ok(ACell(period_cell))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PisanoPeriodConfig {
+    pub period: FiboPeriodConfig,
+    pub instance: Column<Instance>,
+}
+
+/// Proves the Fibonacci sequence modulo p is back at (1, 1) after `period` steps,
+/// i.e. that `period` is a period. Any multiple of the Pisano period passes, the
+/// circuit does not show that no shorter period exists.
+///
+/// instance: | period |
+#[derive(Default)]
+pub struct PisanoPeriodCircuit<F> {
+    pub p: u32,
+    pub period: u64,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> PisanoPeriodCircuit<F> {
+    pub fn new(p: u32, period: u64) -> Self {
+        Self {
+            p,
+            period,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn public_inputs(period: u64) -> Vec<Vec<F>> {
+        vec![vec![F::from(period)]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for PisanoPeriodCircuit<F> {
+    type Config = PisanoPeriodConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // p and the period shape the circuit, there is no witness to drop
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.p, self.period)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let modulus = meta.fixed_column();
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        PisanoPeriodConfig {
+            period: FiboPeriodChip::configure(meta, advice, modulus, constant, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboPeriodChip::<F>::construct(config.period);
+        let period = chip.prove_period(
+            layouter.namespace(|| "pisano period"),
+            F::from(self.p as u64),
+            self.period,
+        )?;
+        layouter.constrain_instance(period.0.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    const P: u32 = 1_000_000_007;
+
+    fn prove(a: u32, b: u32, n: usize, instance: Vec<Vec<Fp>>) -> MockProver<Fp> {
+        // 201 rows for the first step, 67 for every later one
+        let k = if n > 50 { 13 } else { 12 };
+        MockProver::run(k, &ModularFiboCircuit::new(a, b, n, P), instance).unwrap()
+    }
+
+    #[test]
+    fn fibonacci_mod_p() {
+        // F(0) = 0, F(1) = 1
+        for (n, expected) in [(10, 55), (30, 832040), (50, 586268941), (100, 687995182)] {
+            let public = ModularFiboCircuit::public_inputs(0, 1, n, P);
+            assert_eq!(public[0][2], Fp::from(expected));
+            prove(0, 1, n, public).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn wrong_output_fails() {
+        let mut public = ModularFiboCircuit::public_inputs(0, 1, 50, P);
+        // F(50) without the reduction
+        public[0][2] = Fp::from(12586269025);
+        assert!(prove(0, 1, 50, public).verify().is_err());
+    }
+
+    #[test]
+    fn first_terms_at_or_above_p_fail() {
+        // p + 1 + 1 = 1 * p + 2 passes the mod gate, F(0) = p + 1 is not reduced
+        for (a, b) in [(P + 1, 1), (1, P), (P, P)] {
+            let public = ModularFiboCircuit::public_inputs(a, b, 10, P);
+            assert!(prove(a, b, 10, public).verify().is_err());
+        }
+    }
+
+    #[test]
+    fn first_terms_just_below_p() {
+        let public = ModularFiboCircuit::public_inputs(P - 1, P - 1, 10, P);
+        prove(P - 1, P - 1, 10, public).assert_satisfied();
+    }
+
+    #[test]
+    fn fewer_than_two_steps_is_rejected() {
+        let circuit = ModularFiboCircuit::<Fp>::new(0, 1, 1, P);
+        let public = ModularFiboCircuit::public_inputs(0, 1, 1, P);
+        assert!(MockProver::run(12, &circuit, public).is_err());
+    }
+
+    fn prove_period(p: u32, period: u64, claimed: u64) -> MockProver<Fp> {
+        let circuit = PisanoPeriodCircuit::<Fp>::new(p, period);
+        MockProver::run(12, &circuit, PisanoPeriodCircuit::public_inputs(claimed)).unwrap()
+    }
+
+    #[test]
+    fn pisano_period_of_5_is_20() {
+        prove_period(5, 20, 20).assert_satisfied();
+    }
+
+    #[test]
+    fn pisano_period_of_7_is_16() {
+        prove_period(7, 16, 16).assert_satisfied();
+    }
+
+    #[test]
+    fn multiples_of_the_period_pass() {
+        // a period, not necessarily the smallest
+        prove_period(5, 40, 40).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_period_fails() {
+        for (p, period) in [(5, 19), (5, 10), (7, 8), (7, 20)] {
+            assert!(prove_period(p, period, period).verify().is_err());
+        }
+        // the exposed period is fixed by the circuit
+        assert!(prove_period(7, 16, 15).verify().is_err());
+    }
+
+    #[test]
+    fn zero_period_is_rejected() {
+        let circuit = PisanoPeriodCircuit::<Fp>::new(5, 0);
+        assert!(MockProver::run(12, &circuit, PisanoPeriodCircuit::public_inputs(0)).is_err());
+    }
+
+    #[test]
+    fn to_u64_reads_the_low_limb() {
+        assert_eq!(to_u64(Fp::from(u64::MAX)), u64::MAX);
+        assert_eq!(to_u64(Fp::from(P as u64)), P as u64);
+        // 2^64 + 5 keeps only the 5
+        assert_eq!(to_u64(Fp::from_u128((1 << 64) + 5)), 5);
+    }
+}
@@ -0,0 +1,327 @@
+// Fibonacci sequence with a second, blinded copy of every row.
+//
+// halo2_proofs 0.2.0 has no multi-phase API: all advice is committed at once
+// and no challenge can be squeezed mid-synthesis. The phases are emulated: the
+// challenge `r` is derived outside the circuit by hashing the phase 1 witness
+// (`FiboMultiphaseCircuit::challenge`). F(1) and F(2) are public and fix the
+// whole sequence, so the verifier recomputes `r` from them rather than taking
+// the prover's word for it.
+//
+// phase 1, the sequence itself:
+//
+// | a | b | c | s_add |
+//
+// phase 2, one row per phase 1 row, folding the blinded rows into
+// acc = sum blinded_i * r^(n - 1 - i) by Horner's rule:
+//
+// | a | b | acc_in | r | blinded | acc | s_blind |
+//
+// constraints = s_blind * (a * r + b - blinded) == 0
+//               s_blind * (acc_in * r + blinded - acc) == 0
+//
+// acc_in of the first row is the constant 0, the last acc is public, so every
+// blinded cell is pinned by a random linear combination.
+//
+// instance: | r | F(1) | F(2) | acc |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::fibo1::{ACell, FiboChip, FiboConfig};
+
+/// Rows of the sequence, matching `FiboCircuit`.
+pub const FIBO_ROWS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct BlindConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub acc_in: Column<Advice>,
+    pub r: Column<Advice>,
+    pub blinded: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub constant: Column<Fixed>,
+    pub instance: Column<Instance>,
+    pub selector: Selector,
+}
+
+pub struct BlindChip<F: FieldExt> {
+    config: BlindConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BlindChip<F> {
+    pub fn construct(config: BlindConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    // a, b, acc_in, r, blinded, acc
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+        constant: Column<Fixed>,
+        instance: Column<Instance>,
+    ) -> BlindConfig {
+        let [a, b, acc_in, r, blinded, acc] = advice;
+        let selector = meta.selector();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+
+        meta.create_gate("blind", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let acc_in = meta.query_advice(acc_in, Rotation::cur());
+            let r = meta.query_advice(r, Rotation::cur());
+            let blinded = meta.query_advice(blinded, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![
+                s.clone() * (a * r.clone() + b - blinded.clone()),
+                s * (acc_in * r + blinded - acc),
+            ]
+        });
+
+        BlindConfig {
+            a,
+            b,
+            acc_in,
+            r,
+            blinded,
+            acc,
+            constant,
+            instance,
+            selector,
+        }
+    }
+
+    // the accumulator before the first row
+    pub fn load_zero(&self, mut layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "acc 0",
+            |mut region| region.assign_advice_from_constant(|| "0", self.config.acc, 0, F::zero()),
+        )
+    }
+
+    pub fn load_challenge(
+        &self,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "challenge",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "r",
+                    self.config.instance,
+                    0,
+                    self.config.r,
+                    0,
+                )
+            },
+        )
+    }
+
+    // blinds (a, b) and folds it into `acc`, returns the new accumulator
+    pub fn blind(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &ACell<F>,
+        b: &ACell<F>,
+        r: &AssignedCell<F, F>,
+        acc: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "blind",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+
+                let a = a.0.copy_advice(|| "a", &mut region, config.a, 0)?;
+                let b = b.0.copy_advice(|| "b", &mut region, config.b, 0)?;
+                let acc_in = acc.copy_advice(|| "acc_in", &mut region, config.acc_in, 0)?;
+                let r = r.copy_advice(|| "r", &mut region, config.r, 0)?;
+
+                let blinded = a.value().copied() * r.value().copied() + b.value().copied();
+                region.assign_advice(|| "blinded", config.blinded, 0, || blinded)?;
+                let acc = acc_in.value().copied() * r.value().copied() + blinded;
+                region.assign_advice(|| "acc", config.acc, 0, || acc)
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FiboMultiphaseConfig {
+    pub fibo: FiboConfig,
+    pub blind: BlindConfig,
+}
+
+#[derive(Default)]
+pub struct FiboMultiphaseCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: FieldExt> FiboMultiphaseCircuit<F> {
+    pub fn new(a: F, b: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+        }
+    }
+
+    /// Phase 1 witness, the `(a, b)` pair of every row.
+    pub fn rows(a: F, b: F) -> Vec<(F, F)> {
+        (0..FIBO_ROWS)
+            .scan((a, b), |(a, b), _| {
+                let row = (*a, *b);
+                (*a, *b) = (*b, *a + *b);
+                Some(row)
+            })
+            .collect()
+    }
+
+    /// Fiat-Shamir stand-in: blake2b over the phase 1 witness.
+    pub fn challenge(a: F, b: F) -> F {
+        let mut state = blake2b_simd::Params::new()
+            .hash_length(64)
+            .personal(b"hola2halo2_phase")
+            .to_state();
+        for (a, b) in Self::rows(a, b) {
+            state.update(a.to_repr().as_ref());
+            state.update(b.to_repr().as_ref());
+        }
+
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(state.finalize().as_bytes());
+        F::from_bytes_wide(&bytes)
+    }
+
+    // instance column for a sequence starting F(1) = a, F(2) = b
+    pub fn public_inputs(a: F, b: F) -> Vec<Vec<F>> {
+        let r = Self::challenge(a, b);
+        let acc = Self::rows(a, b)
+            .into_iter()
+            .fold(F::zero(), |acc, (a, b)| acc * r + a * r + b);
+        vec![vec![r, a, b, acc]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboMultiphaseCircuit<F> {
+    type Config = FiboMultiphaseConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let [r, blinded, acc] = [(); 3].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        let [a, b, c] = advices;
+
+        FiboMultiphaseConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            blind: BlindChip::configure(meta, [a, b, c, r, blinded, acc], constant, instance),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let fibo = FiboChip::<F>::construct(config.fibo);
+        let blind = BlindChip::<F>::construct(config.blind);
+
+        // phase 1
+        let (a, mut prev_b, mut prev_c) =
+            fibo.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+        blind.expose_public(layouter.namespace(|| "F(1)"), &a.0, 1)?;
+        blind.expose_public(layouter.namespace(|| "F(2)"), &prev_b.0, 2)?;
+        let mut rows = vec![(a, prev_b.clone())];
+        for _ in 1..FIBO_ROWS {
+            let c_cell = fibo.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            rows.push((prev_b, prev_c.clone()));
+
+            prev_b = prev_c;
+            prev_c = c_cell;
+        }
+
+        // phase 2
+        let r = blind.load_challenge(layouter.namespace(|| "challenge"))?;
+        let mut acc = blind.load_zero(layouter.namespace(|| "acc"))?;
+        for (a, b) in rows.iter() {
+            acc = blind.blind(layouter.namespace(|| "blind"), a, b, &r, &acc)?;
+        }
+        blind.expose_public(layouter.namespace(|| "acc"), &acc, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn prove(a: u64, b: u64, public: Vec<Vec<Fp>>) -> MockProver<Fp> {
+        let circuit = FiboMultiphaseCircuit::new(Fp::from(a), Fp::from(b));
+        MockProver::run(5, &circuit, public).unwrap()
+    }
+
+    #[test]
+    fn both_phases_are_satisfied() {
+        let public = FiboMultiphaseCircuit::public_inputs(Fp::one(), Fp::one());
+        prove(1, 1, public).assert_satisfied();
+        let public = FiboMultiphaseCircuit::public_inputs(Fp::from(2), Fp::from(5));
+        prove(2, 5, public).assert_satisfied();
+    }
+
+    #[test]
+    fn phase_one_is_bound_to_the_public_start() {
+        // the verifier's r and acc for (1, 1), the witness starts at (1, 2)
+        let public = FiboMultiphaseCircuit::public_inputs(Fp::one(), Fp::one());
+        assert!(prove(1, 2, public).verify().is_err());
+    }
+
+    #[test]
+    fn phase_two_is_bound_to_the_accumulator() {
+        let mut public = FiboMultiphaseCircuit::public_inputs(Fp::one(), Fp::one());
+        public[0][3] += Fp::one();
+        assert!(prove(1, 1, public).verify().is_err());
+    }
+
+    #[test]
+    fn phase_two_is_bound_to_the_challenge() {
+        // acc under r + 1 is not acc under r
+        let mut public = FiboMultiphaseCircuit::public_inputs(Fp::one(), Fp::one());
+        public[0][0] += Fp::one();
+        assert!(prove(1, 1, public).verify().is_err());
+    }
+}
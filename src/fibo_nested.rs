@@ -0,0 +1,203 @@
+// Fibonacci seeded with Fibonacci numbers: a = F(m1) and b = F(m2) come out of
+// the fixed table of FiboTableChip, then FiboChip runs G(1) = a, G(2) = b up
+// to G(n). With m1 = 1, m2 = 2 this is F(n) itself, so F(F(5)) = F(5) = 5 is
+// n = 5 on the seeds (1, 1).
+//
+// F counts from F(1) = F(2) = 1, entry m - 1 of the table. The indices stay
+// private, the seeds are tied to the first row of FiboChip by binding both to
+// the same instance rows.
+//
+// instance: | F(m1) | F(m2) | G(n) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    fibo1::{FiboChip, FiboConfig},
+    fibo_table::{fibo_table, FiboTableChip, FiboTableConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct NestedFiboConfig {
+    pub fibo: FiboConfig,
+    pub table: FiboTableConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct NestedFiboCircuit<F> {
+    pub n: usize,
+    pub m1: Value<F>,
+    pub m2: Value<F>,
+}
+
+impl<F: FieldExt> NestedFiboCircuit<F> {
+    pub fn new(n: usize, m1: u64, m2: u64) -> Self {
+        Self {
+            n,
+            m1: Value::known(F::from(m1)),
+            m2: Value::known(F::from(m2)),
+        }
+    }
+
+    /// None when m1 or m2 is outside the table, F(1)..F(FIBO_TABLE_SIZE).
+    pub fn public_inputs(n: usize, m1: u64, m2: u64) -> Option<Vec<F>> {
+        let fib = |m: u64| {
+            let index = (m as usize).checked_sub(1)?;
+            fibo_table().get(index).copied()
+        };
+        let (a, b) = (F::from(fib(m1)?), F::from(fib(m2)?));
+        let (mut prev, mut last) = (a, b);
+        for _ in 2..n {
+            (prev, last) = (last, prev + last);
+        }
+        Some(vec![a, b, last])
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for NestedFiboCircuit<F> {
+    type Config = NestedFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        NestedFiboConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            table: FiboTableChip::configure(meta, advices[0], advices[1]),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.n < 3 {
+            return Err(Error::Synthesis);
+        }
+        let table = FiboTableChip::<F>::construct(config.table);
+        table.load_table(layouter.namespace(|| "fibo table"))?;
+
+        // F(m) sits at table index m - 1
+        let one = Value::known(F::one());
+        let a = table.lookup_fib(layouter.namespace(|| "F(m1)"), self.m1 - one)?;
+        let b = table.lookup_fib(layouter.namespace(|| "F(m2)"), self.m2 - one)?;
+        layouter.constrain_instance(a.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.cell(), config.instance, 1)?;
+
+        let chip = FiboChip::<F>::construct(config.fibo);
+        let (first_a, first_b, c) = chip.assign_first_row(
+            layouter.namespace(|| "first row"),
+            a.value().copied(),
+            b.value().copied(),
+        )?;
+        layouter.constrain_instance(first_a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(first_b.0.cell(), config.instance, 1)?;
+
+        let (mut prev_b, mut prev_c) = (first_b, c);
+        for _ in 3..self.n {
+            let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+            prev_b = prev_c;
+            prev_c = c;
+        }
+        layouter.constrain_instance(prev_c.0.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::fibo_table::FIBO_TABLE_SIZE;
+
+    fn prove(n: usize, m1: u64, m2: u64, public: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = NestedFiboCircuit::new(n, m1, m2);
+        MockProver::run(8, &circuit, vec![public]).unwrap()
+    }
+
+    fn public(n: usize, m1: u64, m2: u64) -> Vec<Fp> {
+        NestedFiboCircuit::public_inputs(n, m1, m2).unwrap()
+    }
+
+    #[test]
+    fn f_of_f_5() {
+        // F(5) = 5 from the seeds F(1), F(2), and F(F(5)) = F(5)
+        let instance = public(5, 1, 2);
+        assert_eq!(instance, [1, 1, 5].map(Fp::from));
+        prove(5, 1, 2, instance).assert_satisfied();
+    }
+
+    #[test]
+    fn seeded_further_along() {
+        // G(1) = F(5), G(2) = F(6), so G(n) = F(n + 4) and G(10) = F(14)
+        let instance = public(10, 5, 6);
+        assert_eq!(instance, [5, 8, 377].map(Fp::from));
+        prove(10, 5, 6, instance).assert_satisfied();
+
+        // F(F(6)) = F(8) = 21 on the plain seeds or three steps from F(6), F(7)
+        assert_eq!(public(8, 1, 2)[2], Fp::from(21));
+        assert_eq!(public(3, 6, 7), [8, 13, 21].map(Fp::from));
+        prove(3, 6, 7, public(3, 6, 7)).assert_satisfied();
+    }
+
+    #[test]
+    fn seeds_need_not_be_neighbours() {
+        // G(1) = F(3) = 2, G(2) = F(10) = 55
+        let instance = public(4, 3, 10);
+        assert_eq!(instance, [2, 55, 112].map(Fp::from));
+        prove(4, 3, 10, instance).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_output_fails() {
+        let mut instance = public(5, 1, 2);
+        instance[2] = Fp::from(8);
+        assert!(prove(5, 1, 2, instance).verify().is_err());
+    }
+
+    #[test]
+    fn seeds_of_other_indices_fail() {
+        // the instance of m1 = 2, m2 = 3 with the witness m1 = 1, m2 = 2
+        assert!(prove(5, 1, 2, public(5, 2, 3)).verify().is_err());
+    }
+
+    #[test]
+    fn indices_outside_the_table() {
+        assert!(NestedFiboCircuit::<Fp>::public_inputs(5, 0, 1).is_none());
+        let past = FIBO_TABLE_SIZE as u64 + 1;
+        assert!(NestedFiboCircuit::<Fp>::public_inputs(5, 1, past).is_none());
+
+        // no table row holds index 64, the lookup fails
+        let mut instance = public(5, 1, 2);
+        instance[1] = Fp::zero();
+        assert!(prove(5, 1, past, instance).verify().is_err());
+    }
+
+    #[test]
+    fn n_below_3() {
+        let circuit = NestedFiboCircuit::new(2, 1, 2);
+        assert!(matches!(
+            MockProver::run(8, &circuit, vec![public(3, 1, 2)]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
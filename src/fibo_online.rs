@@ -0,0 +1,227 @@
+// FiboChip driven one row at a time: the iterator owns a mutable borrow of the
+// layouter and every `next()` lays out one more row, yielding the new term.
+// The first call lays out the first row from (a, b) and yields a + b.
+//
+// instance: | F(1) | F(2) | F(steps + 2) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{ACell, FiboChip, FiboConfig};
+
+pub struct OnlineFiboChip<'l, F: FieldExt, L: Layouter<F>> {
+    chip: FiboChip<F>,
+    layouter: &'l mut L,
+    start: (Value<F>, Value<F>),
+    // (a, b) of the first row once it is laid out
+    first: Option<(ACell<F>, ACell<F>)>,
+    // the last two terms
+    prev: Option<(ACell<F>, ACell<F>)>,
+    failed: bool,
+}
+
+impl<'l, F: FieldExt, L: Layouter<F>> OnlineFiboChip<'l, F, L> {
+    pub fn new(config: FiboConfig, layouter: &'l mut L, a: Value<F>, b: Value<F>) -> Self {
+        Self {
+            chip: FiboChip::construct(config),
+            layouter,
+            start: (a, b),
+            first: None,
+            prev: None,
+            failed: false,
+        }
+    }
+
+    pub fn first(&self) -> Option<&(ACell<F>, ACell<F>)> {
+        self.first.as_ref()
+    }
+
+    fn step(&mut self) -> Result<ACell<F>, Error> {
+        match self.prev.take() {
+            None => {
+                let (a, b, c) = self.chip.assign_first_row(
+                    self.layouter.namespace(|| "first row"),
+                    self.start.0,
+                    self.start.1,
+                )?;
+                self.first = Some((a, b.clone()));
+                self.prev = Some((b, c.clone()));
+                Ok(c)
+            }
+            Some((prev_b, prev_c)) => {
+                let c = self.chip.assign_row(
+                    self.layouter.namespace(|| "next row"),
+                    &prev_b,
+                    &prev_c,
+                )?;
+                self.prev = Some((prev_c, c.clone()));
+                Ok(c)
+            }
+        }
+    }
+}
+
+impl<'l, F: FieldExt, L: Layouter<F>> Iterator for OnlineFiboChip<'l, F, L> {
+    type Item = Result<ACell<F>, Error>;
+
+    // stops after the first error
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let next = self.step();
+        self.failed = next.is_err();
+        Some(next)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OnlineFiboConfig {
+    pub fibo: FiboConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct OnlineFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub steps: usize,
+}
+
+impl<F: FieldExt> Circuit<F> for OnlineFiboCircuit<F> {
+    type Config = OnlineFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            steps: self.steps,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        OnlineFiboConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.steps == 0 {
+            return Err(Error::Synthesis);
+        }
+
+        let mut online = OnlineFiboChip::new(config.fibo, &mut layouter, self.a, self.b);
+        let last = online.by_ref().take(self.steps).last().unwrap()?;
+        let (a, b) = online.first().cloned().unwrap();
+
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.0.cell(), config.instance, 1)?;
+        layouter.constrain_instance(last.0.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::value_of;
+
+    // F(1) = F(2) = 1
+    fn fib(n: usize) -> u64 {
+        (1..n).fold((1, 1), |(a, b), _| (b, a + b)).0
+    }
+
+    fn prove(steps: usize, out: u64) -> MockProver<Fp> {
+        let circuit = OnlineFiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+            steps,
+        };
+        MockProver::run(5, &circuit, vec![[1, 1, out].map(Fp::from).to_vec()]).unwrap()
+    }
+
+    // consumes the iterator and keeps every yielded term
+    struct CollectCircuit(RefCell<Vec<Fp>>);
+
+    impl Circuit<Fp> for CollectCircuit {
+        type Config = OnlineFiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(RefCell::new(vec![]))
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            OnlineFiboCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let online = OnlineFiboChip::new(
+                config.fibo,
+                &mut layouter,
+                Value::known(Fp::one()),
+                Value::known(Fp::one()),
+            );
+            let terms = online.take(20).collect::<Result<Vec<_>, Error>>()?;
+            *self.0.borrow_mut() = terms
+                .iter()
+                .filter_map(|term| value_of(term.0.value().copied()))
+                .collect();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn twenty_steps() {
+        prove(20, fib(22)).assert_satisfied();
+        assert_eq!(fib(22), 17711);
+    }
+
+    #[test]
+    fn iterator_yields_each_term() {
+        let circuit = CollectCircuit(RefCell::new(vec![]));
+        MockProver::run(5, &circuit, vec![vec![]])
+            .unwrap()
+            .assert_satisfied();
+        let expected: Vec<_> = (3..23).map(|n| Fp::from(fib(n))).collect();
+        assert_eq!(*circuit.0.borrow(), expected);
+    }
+
+    #[test]
+    fn wrong_last_term_fails() {
+        assert!(prove(20, fib(21)).verify().is_err());
+    }
+
+    #[test]
+    fn zero_steps_is_rejected() {
+        let circuit = OnlineFiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+            steps: 0,
+        };
+        assert!(MockProver::run(5, &circuit, vec![vec![Fp::one(); 3]]).is_err());
+    }
+}
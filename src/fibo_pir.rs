@@ -0,0 +1,166 @@
+// Private retrieval from a public Fibonacci table. The table F(0)..F(N-1) of
+// `fibo_table`, F(0) = F(1) = 1, is baked into a fixed column, the query index
+// i is a private advice cell and MuxChip picks F(i) out of the table cells, so
+// the proof shows the response is in the table at some index the verifier
+// never learns.
+//
+// | table    |
+// | F(0) = 1 |
+// | F(1) = 1 |
+// | ...      |
+// | F(N - 1) |
+//
+// instance: | F(i) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+};
+
+use crate::{
+    fibo_table::{fibo_table, FIBO_TABLE_SIZE},
+    mux::{MuxChip, MuxConfig},
+};
+
+fn table<F: FieldExt>(n: usize) -> Vec<F> {
+    fibo_table()[..n]
+        .iter()
+        .map(|value| F::from(*value))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct FiboPIRConfig {
+    pub table: Column<Fixed>,
+    pub mux: MuxConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiboPIRCircuit<F, const N: usize> {
+    pub index: Value<F>,
+}
+
+impl<F: FieldExt, const N: usize> FiboPIRCircuit<F, N> {
+    pub fn new(index: usize) -> Self {
+        Self {
+            index: Value::known(F::from(index as u64)),
+        }
+    }
+
+    pub fn public_inputs(index: usize) -> Vec<F> {
+        vec![table::<F>(N)[index]]
+    }
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for FiboPIRCircuit<F, N> {
+    type Config = FiboPIRConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let position = meta.fixed_column();
+        let table = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(table);
+        meta.enable_equality(instance);
+
+        FiboPIRConfig {
+            table,
+            mux: MuxChip::configure(meta, advice, position),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if N == 0 || N > FIBO_TABLE_SIZE {
+            return Err(Error::Synthesis);
+        }
+        let index_column = config.mux.advice[1];
+        let (table, index) = layouter.assign_region(
+            || "table",
+            |mut region| {
+                let table = table::<F>(N)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        region.assign_fixed(|| "F(j)", config.table, row, || Value::known(value))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let index = region.assign_advice(|| "i", index_column, 0, || self.index)?;
+                Ok((table, index))
+            },
+        )?;
+
+        let mux = MuxChip::construct(config.mux);
+        let response = mux.select(layouter.namespace(|| "retrieve"), &table, &index)?;
+        layouter.constrain_instance(response.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn run(index: usize, response: Fp) -> MockProver<Fp> {
+        let circuit = FiboPIRCircuit::<Fp, 16>::new(index);
+        MockProver::run(6, &circuit, vec![vec![response]]).unwrap()
+    }
+
+    #[test]
+    fn every_index_of_16() {
+        for index in 0..16 {
+            run(index, FiboPIRCircuit::<Fp, 16>::public_inputs(index)[0]).assert_satisfied();
+        }
+        assert_eq!(
+            FiboPIRCircuit::<Fp, 16>::public_inputs(15),
+            vec![Fp::from(987)]
+        );
+    }
+
+    #[test]
+    fn response_from_another_index_fails() {
+        // F(6) = 13 is in the table, but not at 5
+        assert!(run(5, Fp::from(13)).verify().is_err());
+        assert!(run(5, Fp::from(9)).verify().is_err());
+    }
+
+    #[test]
+    fn index_past_the_table_fails() {
+        assert!(run(16, Fp::from(1597)).verify().is_err());
+    }
+
+    #[test]
+    fn table_size_out_of_range_is_a_synthesis_error() {
+        let public = vec![vec![Fp::one()]];
+        assert!(matches!(
+            MockProver::run(6, &FiboPIRCircuit::<Fp, 0>::new(0), public.clone()),
+            Err(Error::Synthesis)
+        ));
+        assert!(matches!(
+            MockProver::run(
+                8,
+                &FiboPIRCircuit::<Fp, { FIBO_TABLE_SIZE + 1 }>::new(0),
+                public
+            ),
+            Err(Error::Synthesis)
+        ));
+    }
+}
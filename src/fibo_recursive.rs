@@ -0,0 +1,214 @@
+// F(n) in O(log n) rows by doubling. Walking the bits of n from the top, the
+// pair (F(k - 1), F(k)) doubles to (F(2k - 1), F(2k)) and, on a set bit, steps
+// once more to (F(2k), F(2k + 1)):
+//
+// F(2k - 1) = F(k - 1)^2 + F(k)^2
+// F(2k)     = F(k) * (2 * F(k - 1) + F(k))
+// F(2k + 1) = F(2k) + F(2k - 1)
+//
+// F(2k) is F(k) * (2 * F(k + 1) - F(k)) with F(k + 1) = F(k) + F(k - 1), so
+// every step is an add or mul gate on copied cells.
+//
+// The pair (F(0), F(1)) comes from the hint and is fixed as constants.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::{
+    fibo1::ACell,
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct RecursiveFiboConfig {
+    pub function: SimpleFunctionConfig,
+    pub constant: Column<Fixed>,
+}
+
+pub struct RecursiveFiboChip<F: FieldExt> {
+    config: RecursiveFiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> RecursiveFiboChip<F> {
+    pub fn construct(config: RecursiveFiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constant: Column<Fixed>,
+    ) -> RecursiveFiboConfig {
+        meta.enable_constant(constant);
+        let [x, y, z] = advice;
+
+        RecursiveFiboConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            constant,
+        }
+    }
+
+    // `hint_fn(k)` is (F(k), F(k + 1)), only k = 0 is asked for
+    pub fn prove_fib(
+        &self,
+        mut layouter: impl Layouter<F>,
+        n: u64,
+        hint_fn: impl Fn(u64) -> (F, F),
+    ) -> Result<ACell<F>, Error> {
+        let chip = SimpleFunctionChip::<F>::construct(self.config.function.clone());
+        let (f0, f1) = hint_fn(0);
+
+        let (mut prev, mut cur) = layouter.assign_region(
+            || "F(0), F(1)",
+            |mut region| {
+                let config = &self.config.function;
+                let f0 = region.assign_advice_from_constant(|| "F(0)", config.x, 0, f0)?;
+                let f1 = region.assign_advice_from_constant(|| "F(1)", config.y, 0, f1)?;
+                Ok((Number(f0), Number(f1)))
+            },
+        )?;
+        if n == 0 {
+            return Ok(ACell(prev.0));
+        }
+
+        // (prev, cur) = (F(k - 1), F(k)) with k = 1 for the leading bit
+        for bit in (0..63 - n.leading_zeros()).rev().map(|i| n >> i & 1 == 1) {
+            let mut layouter = layouter.namespace(|| "double");
+            let prev_square = chip.mul_cells(layouter.namespace(|| "F(k - 1)^2"), &prev, &prev)?;
+            let cur_square = chip.mul_cells(layouter.namespace(|| "F(k)^2"), &cur, &cur)?;
+            let odd = chip.add_cells(
+                layouter.namespace(|| "F(2k - 1)"),
+                &prev_square,
+                &cur_square,
+            )?;
+
+            let twice = chip.add_cells(layouter.namespace(|| "2 * F(k - 1)"), &prev, &prev)?;
+            let factor =
+                chip.add_cells(layouter.namespace(|| "2 * F(k - 1) + F(k)"), &twice, &cur)?;
+            let even = chip.mul_cells(layouter.namespace(|| "F(2k)"), &cur, &factor)?;
+
+            (prev, cur) = if bit {
+                let next = chip.add_cells(layouter.namespace(|| "F(2k + 1)"), &even, &odd)?;
+                (even, next)
+            } else {
+                (odd, even)
+            };
+        }
+        Ok(ACell(cur.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+    use crate::{fibo_segment::FiboSegmentCircuit, recorder::record};
+
+    // instance: | F(n) |
+    struct RecursiveCircuit(u64);
+
+    impl Circuit<Fp> for RecursiveCircuit {
+        type Config = (RecursiveFiboConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                RecursiveFiboChip::configure(meta, advice, constant),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let hint = |k| {
+                assert_eq!(k, 0);
+                (Fp::zero(), Fp::one())
+            };
+            let out = RecursiveFiboChip::construct(config).prove_fib(
+                layouter.namespace(|| "fib"),
+                self.0,
+                hint,
+            )?;
+            layouter.constrain_instance(out.0.cell(), instance, 0)
+        }
+    }
+
+    // F(0) = 0, F(1) = 1, in the field
+    fn sequential(n: u64) -> Fp {
+        (0..n)
+            .fold((Fp::zero(), Fp::one()), |(a, b), _| (b, a + b))
+            .0
+    }
+
+    #[test]
+    fn small_n_match_sequential() {
+        for n in 0..20 {
+            MockProver::run(6, &RecursiveCircuit(n), vec![vec![sequential(n)]])
+                .unwrap()
+                .assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn n_100_matches_sequential() {
+        let f100 = sequential(100);
+        MockProver::run(7, &RecursiveCircuit(100), vec![vec![f100]])
+            .unwrap()
+            .assert_satisfied();
+
+        // the row by row circuit agrees on F(100)
+        let segment = FiboSegmentCircuit::new(0, 100, Fp::zero(), Fp::one());
+        let public = vec![vec![Fp::zero(), Fp::one(), f100]];
+        MockProver::run(7, &segment, public)
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        let prover =
+            MockProver::run(7, &RecursiveCircuit(100), vec![vec![sequential(99)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn regions_grow_with_log_n() {
+        let regions = |n| {
+            record(&RecursiveCircuit(n), 12, vec![vec![sequential(n)]])
+                .unwrap()
+                .regions
+                .len()
+        };
+        // the start region, six gates per bit below the leading one and
+        // one more per set bit
+        assert_eq!(regions(1), 1);
+        assert_eq!(regions(100), 1 + 6 * 6 + 2);
+        assert_eq!(regions(1 << 20), 1 + 6 * 20);
+    }
+}
@@ -0,0 +1,148 @@
+// Proves F(start)..F(end) given F(start) and F(start + 1).
+//
+// instance: | F(start) | F(start + 1) | F(end) |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{FiboChip, FiboConfig};
+
+#[derive(Debug, Clone)]
+pub struct FiboSegmentConfig {
+    pub fibo: FiboConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiboSegmentCircuit<F> {
+    pub start: usize,
+    pub end: usize,
+    pub fm: Value<F>,
+    pub fm1: Value<F>,
+}
+
+impl<F: FieldExt> FiboSegmentCircuit<F> {
+    pub fn new(start: usize, end: usize, fm: F, fm1: F) -> Self {
+        Self {
+            start,
+            end,
+            fm: Value::known(fm),
+            fm1: Value::known(fm1),
+        }
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboSegmentCircuit<F> {
+    type Config = FiboSegmentConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            start: self.start,
+            end: self.end,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FiboSegmentConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        // the first row already produces F(start + 2)
+        if self.end < self.start + 2 {
+            return Err(Error::Synthesis);
+        }
+
+        let chip = FiboChip::<F>::construct(config.fibo);
+
+        let (fm, mut prev_b, mut prev_c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.fm, self.fm1)?;
+        layouter.constrain_instance(fm.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(prev_b.0.cell(), config.instance, 1)?;
+
+        for _ in self.start + 2..self.end {
+            let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+
+            prev_b = prev_c;
+            prev_c = c_cell;
+        }
+
+        layouter.constrain_instance(prev_c.0.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    // F(0) = 0, F(1) = 1
+    fn fib(n: usize) -> u64 {
+        (0..n).fold((0, 1), |(a, b), _| (b, a + b)).0
+    }
+
+    // proves F(start)..F(end) and returns its public inputs
+    fn prove_segment(start: usize, end: usize) -> Vec<Fp> {
+        let public = [fib(start), fib(start + 1), fib(end)]
+            .map(Fp::from)
+            .to_vec();
+        let circuit = FiboSegmentCircuit::new(start, end, public[0], public[1]);
+        MockProver::run(4, &circuit, vec![public.clone()])
+            .unwrap()
+            .assert_satisfied();
+        public
+    }
+
+    #[test]
+    fn segment_from_five() {
+        assert_eq!(prove_segment(5, 10), [5, 8, 55].map(Fp::from));
+    }
+
+    #[test]
+    fn segments_compose() {
+        let head = prove_segment(0, 5);
+        let next = prove_segment(0, 6);
+        let tail = prove_segment(5, 10);
+        // the tail starts where the head and its one step longer twin end
+        assert_eq!(tail[0], head[2]);
+        assert_eq!(tail[1], next[2]);
+        assert_eq!(tail[2], prove_segment(0, 10)[2]);
+    }
+
+    #[test]
+    fn wrong_start_fails() {
+        let circuit = FiboSegmentCircuit::new(5, 10, Fp::from(5), Fp::from(9));
+        let public = vec![[5, 8, 55].map(Fp::from).to_vec()];
+        assert!(MockProver::run(4, &circuit, public)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn too_short_segment_is_rejected() {
+        let circuit = FiboSegmentCircuit::new(5, 6, Fp::from(5), Fp::from(8));
+        let public = vec![[5, 8, 8].map(Fp::from).to_vec()];
+        assert!(MockProver::run(4, &circuit, public).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+// Fibonacci in one region. Each row holds two consecutive numbers and the gate
+// steps to the next row through rotations, so no copy constraints are needed
+// between rows:
+//
+// | a    | b      | selector |
+// | F(1) | F(2)   | 1        |
+// | F(2) | F(3)   | 1        |
+// | ...  |        |          |
+// | F(n - 1) | F(n) | 0      |
+// gate step: selector * (a(next) - b) == 0
+//            selector * (b(next) - a - b) == 0
+//
+// Up to F(10) this takes 26 cells over 9 rows, against 32 cells over 8 rows for
+// the region per row of `FiboChip` (see `CircuitLayoutComparator`).
+//
+// instance: | F(1) | F(2) | F(n) |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::fibo1::ACell;
+
+#[derive(Debug, Clone)]
+pub struct SingleRegionFiboConfig {
+    pub advice: [Column<Advice>; 2],
+    pub selector: Selector,
+}
+
+pub struct SingleRegionFiboChip<F: FieldExt> {
+    config: SingleRegionFiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SingleRegionFiboChip<F> {
+    pub fn construct(config: SingleRegionFiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> SingleRegionFiboConfig {
+        let [col_a, col_b] = advice;
+        meta.enable_equality(col_a);
+        meta.enable_equality(col_b);
+        let selector = meta.selector();
+
+        meta.create_gate("step", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let a_next = meta.query_advice(col_a, Rotation::next());
+            let b_next = meta.query_advice(col_b, Rotation::next());
+            vec![s.clone() * (a_next - b.clone()), s * (b_next - a - b)]
+        });
+
+        SingleRegionFiboConfig { advice, selector }
+    }
+
+    /// Lays out F(1) = a up to F(n) in one region, returns the cells of F(1),
+    /// F(2) and F(n).
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+    ) -> Result<(ACell<F>, ACell<F>, ACell<F>), Error> {
+        if n < 2 {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        layouter.assign_region(
+            || "fibo",
+            |mut region| {
+                let first = region
+                    .assign_advice(|| "F(1)", config.advice[0], 0, || a)
+                    .map(ACell)?;
+                let mut last = region
+                    .assign_advice(|| "F(2)", config.advice[1], 0, || b)
+                    .map(ACell)?;
+                let second = last.clone();
+
+                let (mut prev_a, mut prev_b) = (a, b);
+                for row in 1..n - 1 {
+                    config.selector.enable(&mut region, row - 1)?;
+                    let next = prev_a + prev_b;
+                    region.assign_advice(|| "a", config.advice[0], row, || prev_b)?;
+                    last = region
+                        .assign_advice(|| "b", config.advice[1], row, || next)
+                        .map(ACell)?;
+                    (prev_a, prev_b) = (prev_b, next);
+                }
+                Ok((first, second, last))
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SingleRegionFiboCircuitConfig {
+    pub fibo: SingleRegionFiboConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct SingleRegionFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> Circuit<F> for SingleRegionFiboCircuit<F> {
+    type Config = SingleRegionFiboCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        SingleRegionFiboCircuitConfig {
+            fibo: SingleRegionFiboChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = SingleRegionFiboChip::construct(config.fibo);
+        let (a, b, last) = chip.assign(layouter.namespace(|| "fibo"), self.a, self.b, self.n)?;
+
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.0.cell(), config.instance, 1)?;
+        layouter.constrain_instance(last.0.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{
+        fibo1::FiboCircuit,
+        fibo_segment::FiboSegmentCircuit,
+        fibo_v1::{CircuitLayoutComparator, LayoutSummary},
+        recorder::record,
+    };
+
+    fn single(a: u64, b: u64, n: usize) -> SingleRegionFiboCircuit<Fp> {
+        SingleRegionFiboCircuit {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+            n,
+        }
+    }
+
+    fn public(a: u64, b: u64, last: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::from(a), Fp::from(b), Fp::from(last)]]
+    }
+
+    #[test]
+    fn same_final_value_as_multi_region() {
+        for (a, b, n, last) in [(1, 1, 10, 55), (2, 3, 10, 144), (1, 1, 30, 832040)] {
+            MockProver::run(6, &single(a, b, n), public(a, b, last))
+                .unwrap()
+                .assert_satisfied();
+            // one region per row, same instance layout
+            let multi = FiboSegmentCircuit::new(1, n, Fp::from(a), Fp::from(b));
+            MockProver::run(6, &multi, public(a, b, last))
+                .unwrap()
+                .assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn n_2_is_the_second_number() {
+        MockProver::run(4, &single(1, 1, 2), public(1, 1, 1))
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_final_value_fails() {
+        let prover = MockProver::run(6, &single(1, 1, 10), public(1, 1, 56)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn n_below_2_is_a_synthesis_error() {
+        assert!(matches!(
+            MockProver::run(4, &single(1, 1, 1), public(1, 1, 1)),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn layout_against_region_per_row() {
+        let circuit = single(1, 1, 10);
+        let recorder = record(&circuit, 5, public(1, 1, 55)).unwrap();
+        assert_eq!(recorder.regions.len(), 1);
+
+        let single = CircuitLayoutComparator::summarize(&circuit, 5, public(1, 1, 55)).unwrap();
+        assert_eq!(single, LayoutSummary { cells: 26, rows: 9 });
+
+        let multi = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        let multi = CircuitLayoutComparator::summarize(&multi, 5, vec![]).unwrap();
+        assert_eq!(multi, LayoutSummary { cells: 32, rows: 8 });
+    }
+}
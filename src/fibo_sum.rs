@@ -0,0 +1,286 @@
+// Fibonacci with the running sum S(n) = F(1) + ... + F(n) kept in a fourth
+// column, in one region:
+//
+// | a        | b        | c        | sum  | s_fib | s_first | s_step |
+// | F(1)     | F(2)     | F(3)     | S(1) | 1     | 1       | 0      |
+// | F(2)     | F(3)     | F(4)     | S(2) | 1     | 0       | 1      |
+// | ...      |          |          |      |       |         |        |
+// | F(n)     | F(n + 1) | F(n + 2) | S(n) | 1     | 0       | 1      |
+// gate fibo: s_fib * (a + b - c) == 0
+// gate sum first: s_first * (sum - a) == 0
+// gate step: s_step * (a - b(prev)) == 0, s_step * (b - c(prev)) == 0
+//            s_step * (sum - sum(prev) - a) == 0
+//
+// The sum is then tied to the sequence with `SubChip`, S(n) = F(n + 2) - F(2),
+// which is S(n) = F(n + 2) - 1 for the usual F(1) = F(2) = 1.
+//
+// instance: | F(1) | F(2) | F(n) | S(n) |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    abs::{SubChip, SubConfig},
+    fibo1::ACell,
+};
+
+#[derive(Debug, Clone)]
+pub struct FiboSumConfig {
+    pub advice: [Column<Advice>; 4],
+    pub s_fib: Selector,
+    pub s_first: Selector,
+    pub s_step: Selector,
+    pub sub: SubConfig,
+}
+
+pub struct FiboSumChip<F: FieldExt> {
+    config: FiboSumConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FiboSumChip<F> {
+    pub fn construct(config: FiboSumConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4]) -> FiboSumConfig {
+        let [col_a, col_b, col_c, col_sum] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let s_fib = meta.selector();
+        let s_first = meta.selector();
+        let s_step = meta.selector();
+
+        meta.create_gate("fibo", |meta| {
+            let s = meta.query_selector(s_fib);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            vec![s * (a + b - c)]
+        });
+
+        meta.create_gate("sum first", |meta| {
+            let s = meta.query_selector(s_first);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let sum = meta.query_advice(col_sum, Rotation::cur());
+            vec![s * (sum - a)]
+        });
+
+        meta.create_gate("step", |meta| {
+            let s = meta.query_selector(s_step);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let sum = meta.query_advice(col_sum, Rotation::cur());
+            let prev_b = meta.query_advice(col_b, Rotation::prev());
+            let prev_c = meta.query_advice(col_c, Rotation::prev());
+            let prev_sum = meta.query_advice(col_sum, Rotation::prev());
+            vec![
+                s.clone() * (a.clone() - prev_b),
+                s.clone() * (b - prev_c),
+                s * (sum - prev_sum - a),
+            ]
+        });
+
+        FiboSumConfig {
+            advice,
+            s_fib,
+            s_first,
+            s_step,
+            sub: SubChip::configure(meta, [col_a, col_b, col_c]),
+        }
+    }
+
+    /// Lays out F(1) = a up to F(n + 2) with the running sum and proves
+    /// S(n) = F(n + 2) - F(2). Returns the cells of F(1), F(2), F(n) and S(n).
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+    ) -> Result<(ACell<F>, ACell<F>, ACell<F>, ACell<F>), Error> {
+        if n < 1 {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        let (first, second, last, sum, shifted) = layouter.assign_region(
+            || "fibo sum",
+            |mut region| {
+                let (mut prev_a, mut prev_b) = (a, b);
+                let mut prev_sum = Value::known(F::zero());
+                let mut cells = None;
+                let mut first = None;
+                for row in 0..n {
+                    config.s_fib.enable(&mut region, row)?;
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_step.enable(&mut region, row)?;
+                    }
+                    let c = prev_a + prev_b;
+                    let sum = prev_sum + prev_a;
+                    let a_cell = region.assign_advice(|| "a", config.advice[0], row, || prev_a)?;
+                    let b_cell = region.assign_advice(|| "b", config.advice[1], row, || prev_b)?;
+                    let c_cell = region.assign_advice(|| "c", config.advice[2], row, || c)?;
+                    let sum_cell = region.assign_advice(|| "sum", config.advice[3], row, || sum)?;
+                    if row == 0 {
+                        first = Some((a_cell.clone(), b_cell));
+                    }
+                    cells = Some((a_cell, sum_cell, c_cell));
+                    (prev_a, prev_b, prev_sum) = (prev_b, c, sum);
+                }
+                let (first, second) = first.unwrap();
+                let (last, sum, shifted) = cells.unwrap();
+                Ok((first, second, last, sum, shifted))
+            },
+        )?;
+
+        let sub = SubChip::construct(config.sub.clone());
+        let diff = sub.sub(layouter.namespace(|| "F(n + 2) - F(2)"), &shifted, &second)?;
+        layouter.assign_region(
+            || "sum identity",
+            |mut region| {
+                let diff = diff.copy_advice(|| "S(n)", &mut region, config.advice[3], 0)?;
+                region.constrain_equal(diff.cell(), sum.cell())
+            },
+        )?;
+
+        Ok((ACell(first), ACell(second), ACell(last), ACell(sum)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FiboSumCircuitConfig {
+    pub fibo: FiboSumConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct FiboSumCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> FiboSumCircuit<F> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+
+    pub fn public_inputs(a: F, b: F, n: usize) -> Vec<F> {
+        let (mut prev, mut last, mut sum) = (a, b, a);
+        for _ in 1..n {
+            sum += last;
+            (prev, last) = (last, prev + last);
+        }
+        vec![a, b, prev, sum]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboSumCircuit<F> {
+    type Config = FiboSumCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        FiboSumCircuitConfig {
+            fibo: FiboSumChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboSumChip::construct(config.fibo);
+        let (a, b, last, sum) =
+            chip.assign(layouter.namespace(|| "fibo sum"), self.a, self.b, self.n)?;
+
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.0.cell(), config.instance, 1)?;
+        layouter.constrain_instance(last.0.cell(), config.instance, 2)?;
+        layouter.constrain_instance(sum.0.cell(), config.instance, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn run(a: u64, b: u64, n: usize, public: [u64; 4]) -> MockProver<Fp> {
+        let circuit = FiboSumCircuit::new(Fp::from(a), Fp::from(b), n);
+        MockProver::run(5, &circuit, vec![public.map(Fp::from).to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn n_5_and_10() {
+        // S(5) = 1 + 1 + 2 + 3 + 5 = F(7) - 1
+        run(1, 1, 5, [1, 1, 5, 12]).assert_satisfied();
+        // S(10) = F(12) - 1
+        run(1, 1, 10, [1, 1, 55, 143]).assert_satisfied();
+    }
+
+    #[test]
+    fn public_inputs_match() {
+        let expected = [1, 1, 55, 143].map(Fp::from).to_vec();
+        assert_eq!(
+            FiboSumCircuit::public_inputs(Fp::one(), Fp::one(), 10),
+            expected
+        );
+    }
+
+    #[test]
+    fn other_start_subtracts_f2() {
+        // 2 + 3 + 5 + 8 + 13 = F(7) - F(2) = 34 - 3
+        run(2, 3, 5, [2, 3, 13, 31]).assert_satisfied();
+        run(1, 1, 1, [1, 1, 1, 1]).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_outputs_fail() {
+        assert!(run(1, 1, 10, [1, 1, 55, 144]).verify().is_err());
+        assert!(run(1, 1, 10, [1, 1, 89, 143]).verify().is_err());
+    }
+
+    #[test]
+    fn n_0_is_a_synthesis_error() {
+        let circuit = FiboSumCircuit::new(Fp::one(), Fp::one(), 0);
+        assert!(matches!(
+            MockProver::run(5, &circuit, vec![vec![Fp::zero(); 4]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+// Fibonacci numbers F(0) = F(1) = 1 up to F(63) as a fixed table, a queried
+// (index, value) pair is proven with a single lookup.
+// | index | value | q_lookup |
+// lookup fib: (q_lookup * (index + 1), q_lookup * value) in (table_index, table_value)
+//
+// The table is keyed by index + 1 so the (0, 0) row, which every disabled row
+// looks up, can not be mistaken for an entry.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+pub const FIBO_TABLE_SIZE: usize = 64;
+
+pub fn fibo_table() -> [u64; FIBO_TABLE_SIZE] {
+    let mut table = [1u64; FIBO_TABLE_SIZE];
+    for i in 2..FIBO_TABLE_SIZE {
+        table[i] = table[i - 1] + table[i - 2];
+    }
+    table
+}
+
+#[derive(Debug, Clone)]
+pub struct FiboTableConfig {
+    pub index: Column<Advice>,
+    pub value: Column<Advice>,
+    pub table_index: TableColumn,
+    pub table_value: TableColumn,
+    pub q_lookup: Selector,
+}
+
+pub struct FiboTableChip<F: FieldExt> {
+    config: FiboTableConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FiboTableChip<F> {
+    pub fn construct(config: FiboTableConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        index: Column<Advice>,
+        value: Column<Advice>,
+    ) -> FiboTableConfig {
+        meta.enable_equality(index);
+        meta.enable_equality(value);
+
+        let table_index = meta.lookup_table_column();
+        let table_value = meta.lookup_table_column();
+        let q_lookup = meta.complex_selector();
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let index = meta.query_advice(index, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![
+                (q.clone() * (index + one), table_index),
+                (q * value, table_value),
+            ]
+        });
+
+        FiboTableConfig {
+            index,
+            value,
+            table_index,
+            table_value,
+            q_lookup,
+        }
+    }
+
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_table(
+            || "fibo table",
+            |mut table| {
+                table.assign_cell(|| "pad", config.table_index, 0, || Value::known(F::zero()))?;
+                table.assign_cell(|| "pad", config.table_value, 0, || Value::known(F::zero()))?;
+                for (i, fib) in fibo_table().iter().enumerate() {
+                    let key = F::from(i as u64 + 1);
+                    table.assign_cell(
+                        || "index",
+                        config.table_index,
+                        i + 1,
+                        || Value::known(key),
+                    )?;
+                    table.assign_cell(
+                        || "F(index)",
+                        config.table_value,
+                        i + 1,
+                        || Value::known(F::from(*fib)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // indices outside the table get a zero value and fail the lookup
+    pub fn lookup_fib(
+        &self,
+        mut layouter: impl Layouter<F>,
+        index: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let table = fibo_table();
+        let value = index.map(|index| {
+            (0..FIBO_TABLE_SIZE)
+                .find(|i| F::from(*i as u64) == index)
+                .map_or(F::zero(), |i| F::from(table[i]))
+        });
+
+        layouter.assign_region(
+            || "lookup fib",
+            |mut region| {
+                config.q_lookup.enable(&mut region, 0)?;
+                region.assign_advice(|| "index", config.index, 0, || index)?;
+                region.assign_advice(|| "F(index)", config.value, 0, || value)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // looks up every index and exposes the values in order
+    struct LookupCircuit(Vec<u64>);
+
+    impl Circuit<Fp> for LookupCircuit {
+        type Config = (FiboTableConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.clone())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [index, value] = [(); 2].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (FiboTableChip::configure(meta, index, value), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FiboTableChip::construct(config);
+            chip.load_table(layouter.namespace(|| "table"))?;
+            for (row, index) in self.0.iter().enumerate() {
+                let value = chip.lookup_fib(
+                    layouter.namespace(|| "lookup"),
+                    Value::known(Fp::from(*index)),
+                )?;
+                layouter.constrain_instance(value.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prove(indices: Vec<u64>, values: Vec<u64>) -> MockProver<Fp> {
+        let public = vec![values.into_iter().map(Fp::from).collect()];
+        MockProver::run(8, &LookupCircuit(indices), public).unwrap()
+    }
+
+    #[test]
+    fn all_64_entries() {
+        let table = fibo_table();
+        assert_eq!(table[..6], [1, 1, 2, 3, 5, 8]);
+        assert_eq!(table[63], 10610209857723);
+        prove((0..64).collect(), table.to_vec()).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        assert!(prove(vec![10], vec![55]).verify().is_err());
+    }
+
+    #[test]
+    fn out_of_table_index_fails() {
+        let failures = prove(vec![64], vec![0]).verify().unwrap_err();
+        assert!(failures
+            .iter()
+            .any(|failure| matches!(failure, VerifyFailure::Lookup { .. })));
+    }
+}
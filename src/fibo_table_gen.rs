@@ -0,0 +1,226 @@
+// A Fibonacci table generated at synthesis: F(1) = a, F(2) = b up to F(len)
+// are computed in the field and loaded into table columns, then any claimed
+// (index, value) pair is proven to be in the sequence with one lookup.
+//
+// | index | value | q_lookup |        | tag | table_index | table_value |
+// | i     | v     | 1        |        | 0   | 0           | 0           |
+//                                     | 1   | 1           | a           |
+//                                     | 1   | 2           | b           |
+//                                     | ... |             |             |
+// lookup fib: (q_lookup, q_lookup * index, q_lookup * value)
+//             in (tag, table_index, table_value)
+//
+// Disabled rows look up the (0, 0, 0) padding row. The tag keeps an enabled
+// row off it, so (0, 0) is never a member.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct FiboTableGenConfig {
+    pub index: Column<Advice>,
+    pub value: Column<Advice>,
+    pub tag: TableColumn,
+    pub table_index: TableColumn,
+    pub table_value: TableColumn,
+    pub q_lookup: Selector,
+}
+
+pub struct FiboTableGenChip<F: FieldExt> {
+    config: FiboTableGenConfig,
+    // F(1) up to F(len)
+    sequence: Vec<F>,
+}
+
+impl<F: FieldExt> FiboTableGenChip<F> {
+    pub fn construct(config: FiboTableGenConfig, a: F, b: F, len: usize) -> Self {
+        let mut sequence = vec![a, b];
+        while sequence.len() < len {
+            let next = sequence[sequence.len() - 2] + sequence[sequence.len() - 1];
+            sequence.push(next);
+        }
+        sequence.truncate(len);
+        Self { config, sequence }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        index: Column<Advice>,
+        value: Column<Advice>,
+    ) -> FiboTableGenConfig {
+        let tag = meta.lookup_table_column();
+        let table_index = meta.lookup_table_column();
+        let table_value = meta.lookup_table_column();
+        let q_lookup = meta.complex_selector();
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let index = meta.query_advice(index, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![
+                (q.clone(), tag),
+                (q.clone() * index, table_index),
+                (q * value, table_value),
+            ]
+        });
+
+        FiboTableGenConfig {
+            index,
+            value,
+            tag,
+            table_index,
+            table_value,
+            q_lookup,
+        }
+    }
+
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let config = &self.config;
+        let columns = [config.tag, config.table_index, config.table_value];
+        layouter.assign_table(
+            || "fibo table",
+            |mut table| {
+                for column in columns {
+                    table.assign_cell(|| "pad", column, 0, || Value::known(F::zero()))?;
+                }
+                for (i, fib) in self.sequence.iter().enumerate() {
+                    let row = [F::one(), F::from(i as u64 + 1), *fib];
+                    for (column, cell) in columns.into_iter().zip(row) {
+                        table.assign_cell(|| "F(i)", column, i + 1, || Value::known(cell))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // F(index) == value, with F(1) the first entry
+    pub fn prove_membership(
+        &self,
+        mut layouter: impl Layouter<F>,
+        index: Value<F>,
+        value: Value<F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "membership",
+            |mut region| {
+                config.q_lookup.enable(&mut region, 0)?;
+                region.assign_advice(|| "index", config.index, 0, || index)?;
+                region.assign_advice(|| "value", config.value, 0, || value)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    use super::*;
+
+    // a table of F(1) = a, F(2) = b up to F(20) and one membership per pair
+    struct MembershipCircuit {
+        seeds: (u64, u64),
+        pairs: Vec<(u64, u64)>,
+    }
+
+    impl Circuit<Fp> for MembershipCircuit {
+        type Config = FiboTableGenConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                seeds: self.seeds,
+                pairs: self.pairs.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let index = meta.advice_column();
+            let value = meta.advice_column();
+            FiboTableGenChip::configure(meta, index, value)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (a, b) = self.seeds;
+            let chip = FiboTableGenChip::construct(config, Fp::from(a), Fp::from(b), 20);
+            chip.load_table(layouter.namespace(|| "table"))?;
+            for (index, value) in &self.pairs {
+                chip.prove_membership(
+                    layouter.namespace(|| "member"),
+                    Value::known(Fp::from(*index)),
+                    Value::known(Fp::from(*value)),
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    fn verify(seeds: (u64, u64), pairs: &[(u64, u64)]) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = MembershipCircuit {
+            seeds,
+            pairs: pairs.to_vec(),
+        };
+        MockProver::run(6, &circuit, vec![]).unwrap().verify()
+    }
+
+    #[test]
+    fn sequence_is_generated() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let [index, value] = [(); 2].map(|_| meta.advice_column());
+        let config = FiboTableGenChip::configure(&mut meta, index, value);
+
+        let chip = FiboTableGenChip::construct(config.clone(), Fp::one(), Fp::one(), 10);
+        let expected = [1, 1, 2, 3, 5, 8, 13, 21, 34, 55].map(Fp::from);
+        assert_eq!(chip.sequence, expected);
+        // shorter than the two seeds
+        let chip = FiboTableGenChip::construct(config, Fp::from(2), Fp::from(3), 1);
+        assert_eq!(chip.sequence, [Fp::from(2)]);
+    }
+
+    #[test]
+    fn valid_pairs() {
+        verify((1, 1), &[(1, 1), (2, 1), (3, 2), (10, 55), (20, 6765)]).unwrap();
+        verify((2, 3), &[(1, 2), (2, 3), (5, 13)]).unwrap();
+    }
+
+    #[test]
+    fn invalid_pairs_fail_the_lookup() {
+        // wrong value, off by one index, past the table, the padding row
+        for pair in [(10, 54), (11, 55), (21, 10946), (0, 0)] {
+            let failures = verify((1, 1), &[(1, 1), pair]).unwrap_err();
+            // only the second membership, region 2 after the table and the first
+            let second = FailureLocation::InRegion {
+                region: (2, "membership").into(),
+                offset: 0,
+            };
+            assert!(
+                matches!(&failures[..], [VerifyFailure::Lookup { location, .. }] if *location == second),
+                "{:?}: {:?}",
+                pair,
+                failures
+            );
+        }
+    }
+
+    #[test]
+    fn pairs_of_another_sequence_fail() {
+        // F(5) = 5 from (1, 1) but 13 from (2, 3)
+        assert!(verify((2, 3), &[(5, 5)]).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// `FiboCircuit` laid out by the V1 floor planner instead of SimpleFloorPlanner.
+// Every row region of FiboChip uses all three advice columns, so V1 has nothing
+// to pack side by side and both planners end up with the same cells.
+//
+// `CircuitLayoutComparator` compares what two circuits assign, counted from a
+// recorded synthesis.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, Layouter, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+    fibo1::{FiboChip, FiboConfig},
+    recorder::record,
+};
+
+#[derive(Default)]
+pub struct FiboCircuitV1<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for FiboCircuitV1<F> {
+    type Config = FiboConfig;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        FiboChip::configure(meta, advices, false)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboChip::<F>::construct(config);
+
+        let (_, mut prev_b, mut prev_c) =
+            chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+
+        for _ in 3..10 {
+            let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+
+            prev_b = prev_c;
+            prev_c = c_cell;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutSummary {
+    // advice and fixed cells plus enabled selectors
+    pub cells: usize,
+    // one past the last row anything is assigned in
+    pub rows: usize,
+}
+
+pub struct CircuitLayoutComparator;
+
+impl CircuitLayoutComparator {
+    pub fn summarize<F: FieldExt, C: Circuit<F>>(
+        circuit: &C,
+        k: u32,
+        instance: Vec<Vec<F>>,
+    ) -> Result<LayoutSummary, Error> {
+        let recorder = record(circuit, k, instance)?;
+        let rows = recorder
+            .advice
+            .keys()
+            .chain(recorder.fixed.keys())
+            .map(|(_, row)| row)
+            .chain(recorder.selectors.iter().map(|(_, row)| row))
+            .max()
+            .map_or(0, |row| row + 1);
+        Ok(LayoutSummary {
+            cells: recorder.advice.len() + recorder.fixed.len() + recorder.selectors.len(),
+            rows,
+        })
+    }
+
+    /// Cells used by `b` minus cells used by `a`.
+    pub fn cell_difference<F: FieldExt, A: Circuit<F>, B: Circuit<F>>(
+        a: &A,
+        b: &B,
+        k: u32,
+        instance: Vec<Vec<F>>,
+    ) -> Result<isize, Error> {
+        let a = Self::summarize(a, k, instance.clone())?;
+        let b = Self::summarize(b, k, instance)?;
+        Ok(b.cells as isize - a.cells as isize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{
+        fibo1::FiboCircuit,
+        function::FunctionCircuit,
+        ipa::{create_ipa_proof, verify_ipa_proof},
+    };
+
+    fn known(a: u64, b: u64) -> (Value<Fp>, Value<Fp>) {
+        (Value::known(Fp::from(a)), Value::known(Fp::from(b)))
+    }
+
+    #[test]
+    fn both_planners_are_satisfied() {
+        let (a, b) = known(1, 1);
+        MockProver::run(4, &FiboCircuit { a, b }, vec![])
+            .unwrap()
+            .assert_satisfied();
+        MockProver::run(4, &FiboCircuitV1 { a, b }, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn both_planners_produce_verifying_proofs() {
+        let (a, b) = known(1, 1);
+        let simple = create_ipa_proof(FiboCircuit { a, b }, &[], 4).unwrap();
+        assert!(verify_ipa_proof(&FiboCircuit::<Fp>::default(), &simple, &[], 4).is_ok());
+        let v1 = create_ipa_proof(FiboCircuitV1 { a, b }, &[], 4).unwrap();
+        assert!(verify_ipa_proof(&FiboCircuitV1::<Fp>::default(), &v1, &[], 4).is_ok());
+    }
+
+    #[test]
+    fn v1_uses_the_same_cells() {
+        let (a, b) = known(1, 1);
+        let simple = FiboCircuit { a, b };
+        let v1 = FiboCircuitV1 { a, b };
+
+        // 8 rows of 3 advice cells and a selector
+        let expected = LayoutSummary { cells: 32, rows: 8 };
+        assert_eq!(
+            CircuitLayoutComparator::summarize(&simple, 4, vec![]).unwrap(),
+            expected
+        );
+        assert_eq!(
+            CircuitLayoutComparator::summarize(&v1, 4, vec![]).unwrap(),
+            expected
+        );
+        assert_eq!(
+            CircuitLayoutComparator::cell_difference(&simple, &v1, 4, vec![]).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn difference_is_signed() {
+        let (a, b) = known(1, 1);
+        let fibo = FiboCircuitV1 { a, b };
+        // six rows of 3 advice cells and a selector
+        let function = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        assert_eq!(
+            CircuitLayoutComparator::cell_difference(&fibo, &function, 4, vec![]).unwrap(),
+            -8
+        );
+        assert_eq!(
+            CircuitLayoutComparator::cell_difference(&function, &fibo, 4, vec![]).unwrap(),
+            8
+        );
+    }
+}
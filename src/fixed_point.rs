@@ -0,0 +1,439 @@
+// Fixed point numbers stored as integers scaled by 2^PRECISION, negative
+// numbers as their field negation.
+//
+// | a      | b      | c      | rem | s_add | s_sub | s_mul |
+// | a      | b      | c      | rem | ...   |       | 1     |
+// | a + Ki | b + Ki | c + Ko |     |       |       | 0     |   fp mul only
+// gate fp add: s_add * (a + b - c) == 0
+// gate fp sub: s_sub * (a - b - c) == 0
+// gate fp mul: s_mul * (a * b - c * 2^PRECISION - rem) == 0
+//              s_mul * (a(next) - a - Ki) == 0, same for b
+//              s_mul * (c(next) - c - Ko) == 0
+//
+// fp mul takes operands in [-2^(B - 1), 2^(B - 1)) with B = 2 * PRECISION
+// bits, checked by range checking a + Ki and b + Ki to B bits with the bias
+// Ki = 2^(B - 1). The product then lies in [-2^(2B - 2), 2^(2B - 2)], c + Ko is
+// range checked to 2B - PRECISION bits with Ko = 2^(2B - 2 - PRECISION) and rem
+// to PRECISION bits. With both parts bounded, a * b = c * 2^PRECISION + rem is
+// far from wrapping the field, so the split is unique: c is the product
+// rescaled and rounded towards negative infinity.
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::range_check::{RangeCheckChip, RangeCheckConfig};
+
+#[derive(Debug, Clone)]
+pub struct FixedPointConfig {
+    pub advice: [Column<Advice>; 4],
+    pub s_add: Selector,
+    pub s_sub: Selector,
+    pub s_mul: Selector,
+    pub range: RangeCheckConfig,
+}
+
+/// Add, sub and rescaling mul on fixed point numbers, `PRECISION` below 64.
+pub struct FixedPointChip<F: FieldExt, const PRECISION: usize> {
+    config: FixedPointConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const PRECISION: usize> FixedPointChip<F, PRECISION> {
+    pub fn construct(config: FixedPointConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        range: RangeCheckConfig,
+    ) -> FixedPointConfig {
+        let [col_a, col_b, col_c, col_rem] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let s_add = meta.selector();
+        let s_sub = meta.selector();
+        let s_mul = meta.selector();
+
+        meta.create_gate("fp add", |meta| {
+            let s = meta.query_selector(s_add);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            vec![s * (a + b - c)]
+        });
+
+        meta.create_gate("fp sub", |meta| {
+            let s = meta.query_selector(s_sub);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            vec![s * (a - b - c)]
+        });
+
+        meta.create_gate("fp mul", |meta| {
+            let s = meta.query_selector(s_mul);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            let rem = meta.query_advice(col_rem, Rotation::cur());
+            let a_biased = meta.query_advice(col_a, Rotation::next());
+            let b_biased = meta.query_advice(col_b, Rotation::next());
+            let c_biased = meta.query_advice(col_c, Rotation::next());
+            let scale = Expression::Constant(Self::scale());
+            let operand_bias = Expression::Constant(Self::operand_bias());
+            let product_bias = Expression::Constant(Self::product_bias());
+            vec![
+                s.clone() * (a.clone() * b.clone() - c.clone() * scale - rem),
+                s.clone() * (a_biased - a - operand_bias.clone()),
+                s.clone() * (b_biased - b - operand_bias),
+                s * (c_biased - c - product_bias),
+            ]
+        });
+
+        FixedPointConfig {
+            advice,
+            s_add,
+            s_sub,
+            s_mul,
+            range,
+        }
+    }
+
+    /// Bits of the operands of `fp_mul`, sign included.
+    pub const OPERAND_BITS: usize = 2 * PRECISION;
+    // bits of c + Ko
+    const PRODUCT_BITS: usize = 2 * Self::OPERAND_BITS - PRECISION;
+
+    fn pow2(exp: usize) -> F {
+        F::from(2).pow(&[exp as u64, 0, 0, 0])
+    }
+
+    fn scale() -> F {
+        Self::pow2(PRECISION)
+    }
+
+    // Ki
+    fn operand_bias() -> F {
+        Self::pow2(Self::OPERAND_BITS - 1)
+    }
+
+    // Ko
+    fn product_bias() -> F {
+        Self::pow2(2 * Self::OPERAND_BITS - 2 - PRECISION)
+    }
+
+    pub fn encode(value: f64) -> F {
+        let scaled = (value * (1u128 << PRECISION) as f64).round();
+        let magnitude = F::from_u128(scaled.abs() as u128);
+        if scaled < 0.0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn decode(value: F) -> f64 {
+        let to_u128 = |value: F| {
+            let repr = value.to_repr();
+            let bytes = repr.as_ref();
+            bytes[16..]
+                .iter()
+                .all(|byte| *byte == 0)
+                .then(|| u128::from_le_bytes(bytes[..16].try_into().unwrap()))
+        };
+        let scale = (1u128 << PRECISION) as f64;
+        match to_u128(value) {
+            Some(magnitude) => magnitude as f64 / scale,
+            None => -(to_u128(-value).expect("value out of range") as f64) / scale,
+        }
+    }
+
+    pub fn load(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "load",
+            |mut region| region.assign_advice(|| "value", self.config.advice[0], 0, || value),
+        )
+    }
+
+    fn assign_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        c: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "fixed point op",
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                region.assign_advice(|| "c", config.advice[2], 0, || c)
+            },
+        )
+    }
+
+    pub fn fp_add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let c = a.value().zip(b.value()).map(|(a, b)| *a + *b);
+        self.assign_op(layouter, self.config.s_add, a, b, c)
+    }
+
+    pub fn fp_sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let c = a.value().zip(b.value()).map(|(a, b)| *a - *b);
+        self.assign_op(layouter, self.config.s_sub, a, b, c)
+    }
+
+    /// a * b rescaled, rounded towards negative infinity. Both operands have
+    /// to lie in [-2^(OPERAND_BITS - 1), 2^(OPERAND_BITS - 1)), the range
+    /// checks fail otherwise.
+    pub fn fp_mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let product = a.value().zip(b.value()).map(|(a, b)| *a * *b);
+        // the product shifted to non-negative has the same low bits, the bias
+        // being a multiple of 2^PRECISION
+        let rem = product.map(|product| {
+            let shifted = product + Self::pow2(2 * Self::OPERAND_BITS - 2);
+            let low = u64::from_le_bytes(shifted.to_repr().as_ref()[..8].try_into().unwrap());
+            F::from(low & ((1u64 << PRECISION) - 1))
+        });
+        let scale_inv = Self::scale().invert().unwrap();
+        let c = product
+            .zip(rem)
+            .map(|(product, rem)| (product - rem) * scale_inv);
+        self.assign_mul(layouter, a, b, c, rem)
+    }
+
+    // the fp mul rows and range checks for the claimed split c, rem
+    fn assign_mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        c: Value<F>,
+        rem: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let (c, rem, biased) = layouter.assign_region(
+            || "fixed point mul",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let c = region.assign_advice(|| "c", config.advice[2], 0, || c)?;
+                let rem = region.assign_advice(|| "rem", config.advice[3], 0, || rem)?;
+
+                let biased = [
+                    (&a, Self::operand_bias()),
+                    (&b, Self::operand_bias()),
+                    (&c, Self::product_bias()),
+                ]
+                .into_iter()
+                .enumerate()
+                .map(|(i, (cell, bias))| {
+                    region.assign_advice(
+                        || "biased",
+                        config.advice[i],
+                        1,
+                        || cell.value().map(|v| *v + bias),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+                Ok((c, rem, biased))
+            },
+        )?;
+
+        let range = RangeCheckChip::<F, PRECISION>::construct(config.range.clone());
+        range.check(layouter.namespace(|| "rem range"), &rem)?;
+        let bits = [Self::OPERAND_BITS, Self::OPERAND_BITS, Self::PRODUCT_BITS];
+        for (cell, bits) in biased.iter().zip(bits) {
+            range.check_bits(layouter.namespace(|| "biased range"), cell, bits)?;
+        }
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    type Chip16 = FixedPointChip<Fp, 16>;
+
+    #[derive(Clone, Copy)]
+    enum Op {
+        Add,
+        Sub,
+        Mul,
+        // mul claiming the split c, rem
+        ForgedMul(Fp, Fp),
+    }
+
+    // a op b, instance: | a op b |
+    struct OpCircuit {
+        a: Value<Fp>,
+        b: Value<Fp>,
+        op: Op,
+    }
+
+    impl Circuit<Fp> for OpCircuit {
+        type Config = (FixedPointConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                op: self.op,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let bit = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let range = RangeCheckChip::<Fp, 16>::configure(meta, advice[3], bit);
+            (Chip16::configure(meta, advice, range), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Chip16::construct(config);
+            let a = chip.load(layouter.namespace(|| "a"), self.a)?;
+            let b = chip.load(layouter.namespace(|| "b"), self.b)?;
+            let c = match self.op {
+                Op::Add => chip.fp_add(layouter.namespace(|| "add"), &a, &b)?,
+                Op::Sub => chip.fp_sub(layouter.namespace(|| "sub"), &a, &b)?,
+                Op::Mul => chip.fp_mul(layouter.namespace(|| "mul"), &a, &b)?,
+                Op::ForgedMul(c, rem) => chip.assign_mul(
+                    layouter.namespace(|| "forged mul"),
+                    &a,
+                    &b,
+                    Value::known(c),
+                    Value::known(rem),
+                )?,
+            };
+            layouter.constrain_instance(c.cell(), instance, 0)
+        }
+    }
+
+    fn prove(a: f64, b: f64, op: Op, c: f64) -> MockProver<Fp> {
+        run(a, b, op, Chip16::encode(c))
+    }
+
+    fn run(a: f64, b: f64, op: Op, c: Fp) -> MockProver<Fp> {
+        let circuit = OpCircuit {
+            a: Value::known(Chip16::encode(a)),
+            b: Value::known(Chip16::encode(b)),
+            op,
+        };
+        MockProver::run(8, &circuit, vec![vec![c]]).unwrap()
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        for value in [0.0, 0.25, 1.5, -1.5, 1234.0625] {
+            assert_eq!(Chip16::decode(Chip16::encode(value)), value);
+        }
+    }
+
+    #[test]
+    fn half_times_half() {
+        prove(0.5, 0.5, Op::Mul, 0.25).assert_satisfied();
+        assert!(prove(0.5, 0.5, Op::Mul, 0.5).verify().is_err());
+    }
+
+    #[test]
+    fn mul_truncates_below_precision() {
+        // 2^-16 * 0.5 = 2^-17 has no bit left at 16 bits of precision
+        prove(1.0 / 65536.0, 0.5, Op::Mul, 0.0).assert_satisfied();
+    }
+
+    #[test]
+    fn forged_rem_fails_the_quotient_range() {
+        // 0.5 * 0.5 = 2^30 = c * 2^16 + 1 with c = (2^30 - 1) / 2^16 in the field
+        let rem = Fp::one();
+        let c = (Fp::from(1 << 30) - rem) * Chip16::scale().invert().unwrap();
+        // the mul gate holds, only the range check of the biased c fails
+        let failures = run(0.5, 0.5, Op::ForgedMul(c, rem), c)
+            .verify()
+            .unwrap_err();
+        assert!(failures.iter().all(|failure| matches!(
+            failure,
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                if format!("{}", constraint).contains("decompose end")
+        )));
+        let honest = Op::ForgedMul(Chip16::encode(0.25), Fp::zero());
+        prove(0.5, 0.5, honest, 0.25).assert_satisfied();
+    }
+
+    #[test]
+    fn negative_product() {
+        prove(-1.5, 2.0, Op::Mul, -3.0).assert_satisfied();
+        prove(-1.5, -2.0, Op::Mul, 3.0).assert_satisfied();
+        assert!(prove(-1.5, 2.0, Op::Mul, 3.0).verify().is_err());
+    }
+
+    #[test]
+    fn negative_mul_rounds_down() {
+        // -2^-17 rounds towards negative infinity to -2^-16
+        prove(-1.0 / 65536.0, 0.5, Op::Mul, -1.0 / 65536.0).assert_satisfied();
+    }
+
+    #[test]
+    fn mul_operand_out_of_range() {
+        prove(-32768.0, 1.0, Op::Mul, -32768.0).assert_satisfied();
+        assert!(prove(32768.0, 0.0, Op::Mul, 0.0).verify().is_err());
+    }
+
+    #[test]
+    fn one_and_a_half_plus_two_and_a_half() {
+        prove(1.5, 2.5, Op::Add, 4.0).assert_satisfied();
+        assert!(prove(1.5, 2.5, Op::Add, 4.5).verify().is_err());
+    }
+
+    #[test]
+    fn sub_goes_negative() {
+        prove(1.0, 2.5, Op::Sub, -1.5).assert_satisfied();
+    }
+}
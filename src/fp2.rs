@@ -0,0 +1,334 @@
+// Arithmetic in Fp2 = Fp[i] / (i^2 + 1), an element re + im * i being a pair of
+// cells. Everything runs on the add and mul gates of SimpleFunctionChip and the
+// sub gate of SubChip over the same three columns:
+//
+// (a + bi) + (c + di) = (a + c) + (b + d)i               2 adds
+// (a + bi) * (c + di) = (ac - bd) + (ad + bc)i           4 muls, a sub, an add
+// conj(a + bi)        = a + (0 - b)i                     a sub from a constant 0
+//
+// i^2 + 1 is only irreducible when p = 3 mod 4, which holds for neither Pasta
+// field, so there Fp2 has zero divisors. The formulas don't mind.
+//
+// Fp2MulCircuit proves a product.
+//
+// instance: | re | im |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+};
+
+use crate::{
+    abs::{SubChip, SubConfig},
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct Fp2Cell<F: FieldExt> {
+    pub re: AssignedCell<F, F>,
+    pub im: AssignedCell<F, F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fp2Config {
+    pub function: SimpleFunctionConfig,
+    pub sub: SubConfig,
+    pub constant: Column<Fixed>,
+}
+
+pub struct Fp2Chip<F: FieldExt> {
+    function: SimpleFunctionChip<F>,
+    sub: SubChip<F>,
+    config: Fp2Config,
+}
+
+impl<F: FieldExt> Fp2Chip<F> {
+    pub fn construct(config: Fp2Config) -> Self {
+        Self {
+            function: SimpleFunctionChip::construct(config.function.clone()),
+            sub: SubChip::construct(config.sub.clone()),
+            config,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constant: Column<Fixed>,
+    ) -> Fp2Config {
+        meta.enable_constant(constant);
+        let [x, y, z] = advice;
+
+        Fp2Config {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            sub: SubChip::configure(meta, advice),
+            constant,
+        }
+    }
+
+    pub fn load(
+        &self,
+        mut layouter: impl Layouter<F>,
+        re: Value<F>,
+        im: Value<F>,
+    ) -> Result<Fp2Cell<F>, Error> {
+        let [x, y, _] = self.config.sub.advice;
+        layouter.assign_region(
+            || "load fp2",
+            |mut region| {
+                Ok(Fp2Cell {
+                    re: region.assign_advice(|| "re", x, 0, || re)?,
+                    im: region.assign_advice(|| "im", y, 0, || im)?,
+                })
+            },
+        )
+    }
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (a, b) = (Number(a.clone()), Number(b.clone()));
+        self.function.add_cells(layouter, &a, &b).map(|sum| sum.0)
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (a, b) = (Number(a.clone()), Number(b.clone()));
+        self.function
+            .mul_cells(layouter, &a, &b)
+            .map(|product| product.0)
+    }
+
+    pub fn fp2_add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Fp2Cell<F>,
+        b: &Fp2Cell<F>,
+    ) -> Result<Fp2Cell<F>, Error> {
+        Ok(Fp2Cell {
+            re: self.add(layouter.namespace(|| "re"), &a.re, &b.re)?,
+            im: self.add(layouter.namespace(|| "im"), &a.im, &b.im)?,
+        })
+    }
+
+    pub fn fp2_mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Fp2Cell<F>,
+        b: &Fp2Cell<F>,
+    ) -> Result<Fp2Cell<F>, Error> {
+        let ac = self.mul(layouter.namespace(|| "ac"), &a.re, &b.re)?;
+        let bd = self.mul(layouter.namespace(|| "bd"), &a.im, &b.im)?;
+        let ad = self.mul(layouter.namespace(|| "ad"), &a.re, &b.im)?;
+        let bc = self.mul(layouter.namespace(|| "bc"), &a.im, &b.re)?;
+        Ok(Fp2Cell {
+            re: self.sub.sub(layouter.namespace(|| "ac - bd"), &ac, &bd)?,
+            im: self.add(layouter.namespace(|| "ad + bc"), &ad, &bc)?,
+        })
+    }
+
+    pub fn fp2_conjugate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Fp2Cell<F>,
+    ) -> Result<Fp2Cell<F>, Error> {
+        let column = self.config.sub.advice[0];
+        let zero = layouter.assign_region(
+            || "zero",
+            |mut region| region.assign_advice_from_constant(|| "0", column, 0, F::zero()),
+        )?;
+        Ok(Fp2Cell {
+            re: a.re.clone(),
+            im: self.sub.sub(layouter.namespace(|| "-im"), &zero, &a.im)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fp2MulConfig {
+    pub fp2: Fp2Config,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct Fp2MulCircuit<F> {
+    pub a: (Value<F>, Value<F>),
+    pub b: (Value<F>, Value<F>),
+}
+
+impl<F: FieldExt> Fp2MulCircuit<F> {
+    pub fn new(a: (F, F), b: (F, F)) -> Self {
+        Self {
+            a: (Value::known(a.0), Value::known(a.1)),
+            b: (Value::known(b.0), Value::known(b.1)),
+        }
+    }
+
+    // instance column for (a.0 + a.1 i) * (b.0 + b.1 i)
+    pub fn public_inputs(a: (F, F), b: (F, F)) -> Vec<Vec<F>> {
+        vec![vec![a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for Fp2MulCircuit<F> {
+    type Config = Fp2MulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        Fp2MulConfig {
+            fp2: Fp2Chip::configure(meta, advice, constant),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = Fp2Chip::construct(config.fp2);
+        let a = chip.load(layouter.namespace(|| "a"), self.a.0, self.a.1)?;
+        let b = chip.load(layouter.namespace(|| "b"), self.b.0, self.b.1)?;
+        let product = chip.fp2_mul(layouter.namespace(|| "a * b"), &a, &b)?;
+
+        layouter.constrain_instance(product.re.cell(), config.instance, 0)?;
+        layouter.constrain_instance(product.im.cell(), config.instance, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum Op {
+        Add,
+        Conjugate,
+        // a * conj(a)
+        Norm,
+    }
+
+    // `op` on a (and b), the result exposed as | re | im |
+    struct OpCircuit {
+        op: Op,
+        a: (u64, u64),
+        b: (u64, u64),
+    }
+
+    impl Circuit<Fp> for OpCircuit {
+        type Config = Fp2MulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { ..*self }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            Fp2MulCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Fp2Chip::construct(config.fp2);
+            let known = |v: u64| Value::known(Fp::from(v));
+            let a = chip.load(layouter.namespace(|| "a"), known(self.a.0), known(self.a.1))?;
+            let b = chip.load(layouter.namespace(|| "b"), known(self.b.0), known(self.b.1))?;
+            let result = match self.op {
+                Op::Add => chip.fp2_add(layouter.namespace(|| "a + b"), &a, &b)?,
+                Op::Conjugate => chip.fp2_conjugate(layouter.namespace(|| "conj a"), &a)?,
+                Op::Norm => {
+                    let conj = chip.fp2_conjugate(layouter.namespace(|| "conj a"), &a)?;
+                    chip.fp2_mul(layouter.namespace(|| "a * conj a"), &a, &conj)?
+                }
+            };
+            layouter.constrain_instance(result.re.cell(), config.instance, 0)?;
+            layouter.constrain_instance(result.im.cell(), config.instance, 1)
+        }
+    }
+
+    fn prove_mul(a: (u64, u64), b: (u64, u64), public: [Fp; 2]) -> MockProver<Fp> {
+        let (a, b) = (
+            (Fp::from(a.0), Fp::from(a.1)),
+            (Fp::from(b.0), Fp::from(b.1)),
+        );
+        MockProver::run(5, &Fp2MulCircuit::new(a, b), vec![public.to_vec()]).unwrap()
+    }
+
+    fn prove_op(op: Op, a: (u64, u64), b: (u64, u64), public: [Fp; 2]) -> MockProver<Fp> {
+        let circuit = OpCircuit { op, a, b };
+        MockProver::run(5, &circuit, vec![public.to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn one_plus_i_squared_is_2i() {
+        let one = (Fp::one(), Fp::one());
+        assert_eq!(
+            Fp2MulCircuit::public_inputs(one, one),
+            vec![vec![Fp::zero(), Fp::from(2)]]
+        );
+        prove_mul((1, 1), (1, 1), [Fp::zero(), Fp::from(2)]).assert_satisfied();
+    }
+
+    #[test]
+    fn negative_real_part() {
+        // (2 + 3i)(4 + 5i) = -7 + 22i
+        prove_mul((2, 3), (4, 5), [-Fp::from(7), Fp::from(22)]).assert_satisfied();
+        // i * i = -1
+        prove_mul((0, 1), (0, 1), [-Fp::one(), Fp::zero()]).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_product_fails() {
+        // (1 + i)^2 computed without i^2 = -1
+        assert!(prove_mul((1, 1), (1, 1), [Fp::from(2), Fp::from(2)])
+            .verify()
+            .is_err());
+        assert!(prove_mul((1, 1), (1, 1), [Fp::from(2), Fp::zero()])
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn add() {
+        prove_op(Op::Add, (1, 2), (3, 4), [Fp::from(4), Fp::from(6)]).assert_satisfied();
+        assert!(
+            prove_op(Op::Add, (1, 2), (3, 4), [Fp::from(4), Fp::from(8)])
+                .verify()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn conjugate() {
+        prove_op(Op::Conjugate, (3, 4), (0, 0), [Fp::from(3), -Fp::from(4)]).assert_satisfied();
+        assert!(
+            prove_op(Op::Conjugate, (3, 4), (0, 0), [Fp::from(3), Fp::from(4)])
+                .verify()
+                .is_err()
+        );
+        // (3 + 4i)(3 - 4i) = 25
+        prove_op(Op::Norm, (3, 4), (0, 0), [Fp::from(25), Fp::zero()]).assert_satisfied();
+    }
+}
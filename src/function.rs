@@ -4,17 +4,24 @@
 // gate power: (x * x -c) * selector_power
 // gate plus: (a + b - c) * add_selector
 
-use std::marker::PhantomData;
+use std::{cell::Cell, marker::PhantomData};
 
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value},
-    pasta::Fp,
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
-    poly::Rotation, dev::MockProver,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
 };
 
-trait SimpleFunctionInstructions<F: FieldExt>: Chip<F> {
+use crate::{
+    allocator::ColumnAllocator,
+    checked_assign::assign_advice_checked,
+    linearize::create_linearized_gate,
+    state_machine::{CircuitStateMachine, START},
+    static_assert::static_assert,
+};
+
+pub trait SimpleFunctionInstructions<F: FieldExt>: Chip<F> {
     type Num;
 
     fn load_add(
@@ -30,6 +37,12 @@ trait SimpleFunctionInstructions<F: FieldExt>: Chip<F> {
         y: Value<F>,
     ) -> Result<(Self::Num, Self::Num, Self::Num), Error>;
 
+    fn load_square(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Value<F>,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+
     fn load_assign(
         &self,
         layouter: impl Layouter<F>,
@@ -39,15 +52,15 @@ trait SimpleFunctionInstructions<F: FieldExt>: Chip<F> {
 }
 
 #[derive(Clone, Debug)]
-struct SimpleFunctionConfig {
-    x: Column<Advice>,
-    y: Column<Advice>,
-    z: Column<Advice>,
-    s_add: Selector,
-    s_mul: Selector,
+pub struct SimpleFunctionConfig {
+    pub x: Column<Advice>,
+    pub y: Column<Advice>,
+    pub z: Column<Advice>,
+    pub s_add: Selector,
+    pub s_mul: Selector,
 }
 
-struct SimpleFunctionChip<F: FieldExt> {
+pub struct SimpleFunctionChip<F: FieldExt> {
     config: SimpleFunctionConfig,
     _market: PhantomData<F>,
 }
@@ -65,14 +78,14 @@ impl<F: FieldExt> Chip<F> for SimpleFunctionChip<F> {
 }
 
 impl<F: FieldExt> SimpleFunctionChip<F> {
-    fn construct(config: <Self as Chip<F>>::Config) -> Self {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
         Self {
             config,
             _market: PhantomData,
         }
     }
 
-    fn configure(
+    pub fn configure(
         meta: &mut ConstraintSystem<F>,
         x: Column<Advice>,
         y: Column<Advice>,
@@ -84,7 +97,7 @@ impl<F: FieldExt> SimpleFunctionChip<F> {
 
         let s_add = meta.selector();
 
-        meta.create_gate("add", |meta| {
+        let add_aux = create_linearized_gate(meta, "add", |meta| {
             let left = meta.query_advice(x, Rotation::cur());
             let right = meta.query_advice(y, Rotation::cur());
             let out = meta.query_advice(z, Rotation::cur());
@@ -95,7 +108,7 @@ impl<F: FieldExt> SimpleFunctionChip<F> {
         });
 
         let s_mul = meta.selector();
-        meta.create_gate("mul", |meta| {
+        let mul_aux = create_linearized_gate(meta, "mul", |meta| {
             let left = meta.query_advice(x, Rotation::cur());
             let right = meta.query_advice(y, Rotation::cur());
             let out = meta.query_advice(z, Rotation::cur());
@@ -104,6 +117,12 @@ impl<F: FieldExt> SimpleFunctionChip<F> {
 
             vec![s * (left * right - out)]
         });
+        static_assert(meta, s_add != s_mul, "add and mul gates need distinct selectors");
+        static_assert(
+            meta,
+            add_aux.is_empty() && mul_aux.is_empty(),
+            "SimpleFunctionChip does not assign auxiliary cells",
+        );
         SimpleFunctionConfig {
             x,
             y,
@@ -112,10 +131,57 @@ impl<F: FieldExt> SimpleFunctionChip<F> {
             s_mul,
         }
     }
+
+    // x, y and z as the lhs, rhs and out columns of `alloc`
+    pub fn configure_with(alloc: &mut ColumnAllocator<'_, F>) -> SimpleFunctionConfig {
+        let [x, y, z] = ["lhs", "rhs", "out"].map(|purpose| alloc.alloc_advice(purpose));
+        Self::configure(alloc.meta(), x, y, z)
+    }
+
+    // add and mul on already assigned cells, both inputs are copy constrained
+    pub fn add_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Number<F>,
+        b: &Number<F>,
+    ) -> Result<Number<F>, Error> {
+        self.op_cells(layouter, self.config.s_add, a, b, |a, b| a + b)
+    }
+
+    pub fn mul_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Number<F>,
+        b: &Number<F>,
+    ) -> Result<Number<F>, Error> {
+        self.op_cells(layouter, self.config.s_mul, a, b, |a, b| a * b)
+    }
+
+    fn op_cells(
+        &self,
+        mut layouter: impl Layouter<F>,
+        selector: Selector,
+        a: &Number<F>,
+        b: &Number<F>,
+        op: impl Fn(F, F) -> F,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "op",
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                let a = a.0.copy_advice(|| "a", &mut region, config.x, 0)?;
+                let b = b.0.copy_advice(|| "b", &mut region, config.y, 0)?;
+                let c = a.value().zip(b.value()).map(|(a, b)| op(*a, *b));
+                assign_advice_checked(&mut region, config.z, 0, c, "c").map(Number)
+            },
+        )
+    }
 }
 
 #[derive(Clone)]
-struct Number<F: FieldExt>(AssignedCell<F, F>);
+pub struct Number<F: FieldExt>(pub AssignedCell<F, F>);
 
 impl<F: FieldExt> SimpleFunctionInstructions<F> for SimpleFunctionChip<F> {
     type Num = Number<F>;
@@ -132,10 +198,10 @@ impl<F: FieldExt> SimpleFunctionInstructions<F> for SimpleFunctionChip<F> {
             || "add",
             |mut region| {
                 self.config().s_add.enable(&mut region, 0)?;
-                let x_cell = region.assign_advice(|| "a", config.x, 0, || x).map(Number)?;
-                let y_cell = region.assign_advice(|| "b", config.y, 0, || y).map(Number)?;
+                let x_cell = assign_advice_checked(&mut region, config.x, 0, x, "a").map(Number)?;
+                let y_cell = assign_advice_checked(&mut region, config.y, 0, y, "b").map(Number)?;
                 let z = x.and_then(|x_val| y.map(|y_val| x_val + y_val));
-                let z_cell = region.assign_advice(|| "c", config.z, 0, || z).map(Number)?;
+                let z_cell = assign_advice_checked(&mut region, config.z, 0, z, "c").map(Number)?;
                 Ok((x_cell, y_cell, z_cell))
             },
         )
@@ -153,25 +219,46 @@ impl<F: FieldExt> SimpleFunctionInstructions<F> for SimpleFunctionChip<F> {
             || "mul",
             |mut region| {
                 self.config().s_mul.enable(&mut region, 0)?;
-                let x_cell = region.assign_advice(|| "", config.x, 0, || x).map(Number)?;
-                let y_cell = region.assign_advice(|| "", config.y, 0, || y).map(Number)?;
+                let x_cell = assign_advice_checked(&mut region, config.x, 0, x, "").map(Number)?;
+                let y_cell = assign_advice_checked(&mut region, config.y, 0, y, "").map(Number)?;
                 let z = x.and_then(|x_val| y.map(|y_val| x_val * y_val));
 
-                let z_cell = region.assign_advice(|| "", config.z, 0, || z).map(Number)?;
+                let z_cell = assign_advice_checked(&mut region, config.z, 0, z, "").map(Number)?;
                 Ok((x_cell, y_cell, z_cell))
             },
         )
     }
 
+    // x * x = y on the mul gate, the right input is a copy of the left one
+    fn load_square(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "square",
+            |mut region| {
+                self.config().s_mul.enable(&mut region, 0)?;
+                let x_cell = assign_advice_checked(&mut region, config.x, 0, x, "x")?;
+                x_cell.copy_advice(|| "x", &mut region, config.y, 0)?;
+                let y = x.map(|x_val| x_val.square());
+                let y_cell = assign_advice_checked(&mut region, config.z, 0, y, "y").map(Number)?;
+                Ok((Number(x_cell), y_cell))
+            },
+        )
+    }
+
     fn load_assign(&self, mut layouter: impl Layouter<F>, x: Value<F>, y: Value<F>) -> Result<Self::Num, Error>{
         let config = self.config();
         layouter.assign_region(
             || "equal",
             |mut region| {
                 self.config().s_add.enable(&mut region, 0)?;
-                let x_cell = region.assign_advice(|| "", config.x, 0, || x).map(Number)?;
-                region.assign_advice(|| "", config.y, 0, || Value::known(FieldExt::from_u128(0))).map(Number)?;
-                region.assign_advice(|| "", config.z, 0, || y).map(Number)?;
+                let x_cell = assign_advice_checked(&mut region, config.x, 0, x, "").map(Number)?;
+                assign_advice_checked(&mut region, config.y, 0, Value::known(FieldExt::from_u128(0)), "").map(Number)?;
+                assign_advice_checked(&mut region, config.z, 0, y, "").map(Number)?;
                 Ok(x_cell)
             },
         )
@@ -179,8 +266,8 @@ impl<F: FieldExt> SimpleFunctionInstructions<F> for SimpleFunctionChip<F> {
 }
 
 #[derive(Default)]
-struct FunctionCircuit<F: FieldExt> {
-    x: Value<F>,
+pub struct FunctionCircuit<F: FieldExt> {
+    pub x: Value<F>,
 }
 
 impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
@@ -191,10 +278,7 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let x = meta.advice_column();
-        let y = meta.advice_column();
-        let z = meta.advice_column();
-        SimpleFunctionChip::configure(meta, x, y, z)
+        SimpleFunctionChip::configure_with(&mut ColumnAllocator::new(meta))
     }
 
     fn synthesize(
@@ -203,64 +287,290 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let chip = SimpleFunctionChip::<F>::construct(config);
+        let x = self.x;
 
         // | x^3 + x + 5 = 35 |
+        let x_cube = Cell::new(Value::unknown());
+        let sum = Cell::new(Value::unknown());
+        let mut machine = CircuitStateMachine::new(&mut layouter);
 
         // mul gate
-        let (_, _, x) = chip.load_mul(
-            layouter.namespace(|| "mul"),
-            self.x,
-            Value::known(FieldExt::from_u128(1)),
-        )?;
-        let (_, _, x_square) = chip.load_mul(
-            layouter.namespace(|| "mul"),
-            x.0.value().map(|x| *x),
-            self.x,
-        )?;
-        let (_, _, x_cube) = chip.load_mul(
-            layouter.namespace(|| "mul"),
-            x_square.0.value().map(|x_val| *x_val),
-            self.x,
-        )?;
+        machine.register("multiply", &[START], |layouter| {
+            let (_, _, x1) = chip.load_mul(
+                layouter.namespace(|| "mul"),
+                x,
+                Value::known(FieldExt::from_u128(1)),
+            )?;
+            let (_, _, x_square) =
+                chip.load_mul(layouter.namespace(|| "mul"), x1.0.value().copied(), x)?;
+            let (_, _, cube) =
+                chip.load_mul(layouter.namespace(|| "mul"), x_square.0.value().copied(), x)?;
+            x_cube.set(cube.0.value().copied());
+            Ok(())
+        });
 
         // add gate
-        let (_, _, tmp1) = chip.load_add(
-            layouter.namespace(|| "add"),
-            x_cube.0.value().map(|x_val| *x_val),
-            self.x,
-        )?;
-        let (_, _, tmp2) = chip.load_add(
-            layouter.namespace(|| "add"),
-            tmp1.0.value().map(|x_val| *x_val),
-            Value::known(FieldExt::from_u128(5)),
-        )?;
+        machine.register("add", &["multiply"], |layouter| {
+            let (_, _, tmp1) = chip.load_add(layouter.namespace(|| "add"), x_cube.get(), x)?;
+            let (_, _, tmp2) = chip.load_add(
+                layouter.namespace(|| "add"),
+                tmp1.0.value().copied(),
+                Value::known(FieldExt::from_u128(5)),
+            )?;
+            sum.set(tmp2.0.value().copied());
+            Ok(())
+        });
 
-        chip.load_assign(layouter, tmp2.0.value().map(|x| *x), Value::known(FieldExt::from_u128(35)))?;
+        machine.register("constrain", &["add"], |layouter| {
+            chip.load_assign(
+                layouter.namespace(|| "equal"),
+                sum.get(),
+                Value::known(FieldExt::from_u128(35)),
+            )?;
+            Ok(())
+        });
+
+        for state in ["multiply", "add", "constrain"] {
+            machine.transition(state)?;
+        }
         Ok(())
     }
 }
-fn main() {
-    let k = 4;
-    let x = Fp::from(3);
 
-    let circuit = FunctionCircuit {
-        x: Value::known(x),
+// a * x^3 + b * x^2 + c * x + d = y evaluated as ((a * x + b) * x + c) * x + d
+// instance: | a | b | c | d | y |
+#[derive(Clone, Debug)]
+pub struct ParametricFunctionConfig {
+    pub function: SimpleFunctionConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct ParametricFunctionCircuit<F: FieldExt> {
+    pub x: Value<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for ParametricFunctionCircuit<F> {
+    type Config = ParametricFunctionConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        ParametricFunctionConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = SimpleFunctionChip::<F>::construct(config.function.clone());
+
+        let (x, [a, b, c, d]) = layouter.assign_region(
+            || "load",
+            |mut region| {
+                let x = region
+                    .assign_advice(|| "x", config.function.x, 0, || self.x)
+                    .map(Number)?;
+                let mut coefficient = |row: usize| {
+                    region
+                        .assign_advice_from_instance(
+                            || "coefficient",
+                            config.instance,
+                            row,
+                            config.function.y,
+                            row,
+                        )
+                        .map(Number)
+                };
+                Ok((x, [coefficient(0)?, coefficient(1)?, coefficient(2)?, coefficient(3)?]))
+            },
+        )?;
+
+        let mut acc = a;
+        for (i, coefficient) in [b, c, d].iter().enumerate() {
+            let product = chip.mul_cells(layouter.namespace(|| format!("mul {}", i)), &acc, &x)?;
+            acc = chip.add_cells(layouter.namespace(|| format!("add {}", i)), &product, coefficient)?;
+        }
+
+        layouter.constrain_instance(acc.0.cell(), config.instance, 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::{MockProver, VerifyFailure},
+        pasta::Fp,
     };
 
-    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
-    prover.assert_satisfied();
-
-    use plotters::prelude::*;
-    let root = BitMapBackend::new("./target/function.png", (1024, 768)).into_drawing_area();
-    root.fill(&WHITE).unwrap();
-    let root = root
-        .titled("Function", ("sans-serif", 60))
-        .unwrap();
-
-    halo2_proofs::dev::CircuitLayout::default()
-        // .show_labels(false)
-        // Render the circuit onto your area!
-        // The first argument is the size parameter for the circuit.
-        .render(4, &circuit, &root)
-        .unwrap();
+    use super::*;
+
+    // x^2 = y with y public. `split` lays the square out by hand with the right
+    // input in a cell of its own, copy constrained to x.
+    struct SquareCircuit {
+        x: Value<Fp>,
+        rhs: Value<Fp>,
+        split: bool,
+    }
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = (SimpleFunctionConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                x: Value::unknown(),
+                rhs: Value::unknown(),
+                split: self.split,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [x, y, z] = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (SimpleFunctionChip::configure(meta, x, y, z), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let y = if self.split {
+                layouter.assign_region(
+                    || "split square",
+                    |mut region| {
+                        config.s_mul.enable(&mut region, 0)?;
+                        let x = region.assign_advice(|| "x", config.x, 0, || self.x)?;
+                        let rhs = region.assign_advice(|| "x", config.y, 0, || self.rhs)?;
+                        region.constrain_equal(x.cell(), rhs.cell())?;
+                        region.assign_advice(|| "y", config.z, 0, || self.x * self.rhs)
+                    },
+                )?
+            } else {
+                let chip = SimpleFunctionChip::construct(config);
+                chip.load_square(layouter.namespace(|| "square"), self.x)?
+                    .1
+                     .0
+            };
+            layouter.constrain_instance(y.cell(), instance, 0)
+        }
+    }
+
+    fn square(x: u64, rhs: u64, split: bool, y: u64) -> MockProver<Fp> {
+        let circuit = SquareCircuit {
+            x: Value::known(Fp::from(x)),
+            rhs: Value::known(Fp::from(rhs)),
+            split,
+        };
+        MockProver::run(4, &circuit, vec![vec![Fp::from(y)]]).unwrap()
+    }
+
+    #[test]
+    fn three_squared_is_nine() {
+        square(3, 3, false, 9).assert_satisfied();
+        assert!(square(3, 3, false, 12).verify().is_err());
+    }
+
+    #[test]
+    fn split_inputs_with_the_same_value_pass() {
+        square(3, 3, true, 9).assert_satisfied();
+    }
+
+    #[test]
+    fn split_inputs_with_other_values_break_the_copy() {
+        // 3 * 4 = 12 holds on the mul gate, the copy of x into the right input doesn't
+        let failures = square(3, 4, true, 12).verify().unwrap_err();
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+        assert!(!failures.is_empty());
+    }
+
+    fn cubic(coefficients: [i64; 4], x: i64, y: i64) -> MockProver<Fp> {
+        let field = |v: i64| {
+            if v < 0 {
+                -Fp::from(v.unsigned_abs())
+            } else {
+                Fp::from(v as u64)
+            }
+        };
+        let circuit = ParametricFunctionCircuit {
+            x: Value::known(field(x)),
+        };
+        let mut instance: Vec<_> = coefficients.iter().map(|&c| field(c)).collect();
+        instance.push(field(y));
+        MockProver::run(4, &circuit, vec![instance]).unwrap()
+    }
+
+    #[test]
+    fn parametric_matches_the_original_function() {
+        // x^3 + x + 5
+        cubic([1, 0, 1, 5], 3, 35).assert_satisfied();
+        cubic([1, 0, 1, 5], 0, 5).assert_satisfied();
+    }
+
+    #[test]
+    fn parametric_with_negative_coefficients() {
+        // 2x^3 - 3x^2 + x + 7
+        cubic([2, -3, 1, 7], 2, 13).assert_satisfied();
+        cubic([2, -3, 1, 7], -1, 1).assert_satisfied();
+        cubic([0, 0, 0, 4], 9, 4).assert_satisfied();
+    }
+
+    #[test]
+    fn parametric_wrong_output_fails() {
+        assert!(cubic([2, -3, 1, 7], 2, 14).verify().is_err());
+        assert!(cubic([1, 0, 1, 5], 3, 13).verify().is_err());
+    }
+
+    #[test]
+    fn configure_passes_its_static_assertions() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let [x, y, z] = [(); 3].map(|_| meta.advice_column());
+        let config = SimpleFunctionChip::configure(&mut meta, x, y, z);
+        assert_ne!(config.s_add, config.s_mul);
+    }
+
+    #[test]
+    fn function_circuit_without_witnesses() {
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        MockProver::run(4, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        // keygen lays out the unknown cells without asking for them
+        assert!(crate::ipa::keygen(&circuit, 4).is_ok());
+        // witness generation does ask, and stops on the first one
+        assert!(matches!(
+            MockProver::run(4, &circuit.without_witnesses(), vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn function_circuit_pins_the_output() {
+        // x^3 + x + 5 = 35 only for x = 3, the constrain state pins the 35
+        for x in [2, 4] {
+            let circuit = FunctionCircuit {
+                x: Value::known(Fp::from(x)),
+            };
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
 }
@@ -0,0 +1,369 @@
+// SimpleFunctionChip with the add and mul gates merged into one, a fixed mode
+// cell picks the operation on each row:
+//
+// | x | y | z | mode | s_op |
+// gate op: s_op * (mode * (x * y - z) + (1 - mode) * (x + y - z)) == 0
+//
+// mode = 1 multiplies, mode = 0 adds. One selector fewer, but the fixed mode
+// column takes its place and the gate goes from degree 3 to degree 4, which
+// doubles the extended evaluation domain the prover works on. It only pays off
+// where selectors, not prover time, are the bottleneck.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::function::{Number, SimpleFunctionInstructions};
+
+#[derive(Clone, Debug)]
+pub struct FunctionConfigV2 {
+    pub x: Column<Advice>,
+    pub y: Column<Advice>,
+    pub z: Column<Advice>,
+    pub mode: Column<Fixed>,
+    pub s_op: Selector,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionMode {
+    Add,
+    Mul,
+}
+
+impl FunctionMode {
+    fn value<F: FieldExt>(self) -> F {
+        match self {
+            FunctionMode::Add => F::zero(),
+            FunctionMode::Mul => F::one(),
+        }
+    }
+
+    fn apply<F: FieldExt>(self, a: F, b: F) -> F {
+        match self {
+            FunctionMode::Add => a + b,
+            FunctionMode::Mul => a * b,
+        }
+    }
+}
+
+pub struct FunctionChipV2<F: FieldExt> {
+    config: FunctionConfigV2,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for FunctionChipV2<F> {
+    type Config = FunctionConfigV2;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FunctionChipV2<F> {
+    pub fn construct(config: FunctionConfigV2) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        x: Column<Advice>,
+        y: Column<Advice>,
+        z: Column<Advice>,
+        mode: Column<Fixed>,
+    ) -> FunctionConfigV2 {
+        meta.enable_equality(x);
+        meta.enable_equality(y);
+        meta.enable_equality(z);
+
+        let s_op = meta.selector();
+        meta.create_gate("op", |meta| {
+            let left = meta.query_advice(x, Rotation::cur());
+            let right = meta.query_advice(y, Rotation::cur());
+            let out = meta.query_advice(z, Rotation::cur());
+            let mode = meta.query_fixed(mode, Rotation::cur());
+            let s = meta.query_selector(s_op);
+
+            let mul = left.clone() * right.clone() - out.clone();
+            let add = left + right - out;
+            vec![s * (mode.clone() * mul + (Expression::Constant(F::one()) - mode) * add)]
+        });
+
+        FunctionConfigV2 {
+            x,
+            y,
+            z,
+            mode,
+            s_op,
+        }
+    }
+
+    pub fn add_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Number<F>,
+        b: &Number<F>,
+    ) -> Result<Number<F>, Error> {
+        self.op_cells(layouter, FunctionMode::Add, a, b)
+    }
+
+    pub fn mul_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Number<F>,
+        b: &Number<F>,
+    ) -> Result<Number<F>, Error> {
+        self.op_cells(layouter, FunctionMode::Mul, a, b)
+    }
+
+    fn op_cells(
+        &self,
+        mut layouter: impl Layouter<F>,
+        mode: FunctionMode,
+        a: &Number<F>,
+        b: &Number<F>,
+    ) -> Result<Number<F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "op",
+            |mut region| {
+                config.s_op.enable(&mut region, 0)?;
+                region.assign_fixed(
+                    || "mode",
+                    config.mode,
+                    0,
+                    || Value::known(mode.value::<F>()),
+                )?;
+                let a = a.0.copy_advice(|| "a", &mut region, config.x, 0)?;
+                let b = b.0.copy_advice(|| "b", &mut region, config.y, 0)?;
+                let c = a.value().zip(b.value()).map(|(a, b)| mode.apply(*a, *b));
+                region.assign_advice(|| "c", config.z, 0, || c).map(Number)
+            },
+        )
+    }
+
+    fn load_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        mode: FunctionMode,
+        x: Value<F>,
+        y: Value<F>,
+    ) -> Result<(Number<F>, Number<F>, Number<F>), Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load op",
+            |mut region| {
+                config.s_op.enable(&mut region, 0)?;
+                region.assign_fixed(
+                    || "mode",
+                    config.mode,
+                    0,
+                    || Value::known(mode.value::<F>()),
+                )?;
+                let x_cell = region
+                    .assign_advice(|| "a", config.x, 0, || x)
+                    .map(Number)?;
+                let y_cell = region
+                    .assign_advice(|| "b", config.y, 0, || y)
+                    .map(Number)?;
+                let z = x.zip(y).map(|(x, y)| mode.apply(x, y));
+                let z_cell = region
+                    .assign_advice(|| "c", config.z, 0, || z)
+                    .map(Number)?;
+                Ok((x_cell, y_cell, z_cell))
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> SimpleFunctionInstructions<F> for FunctionChipV2<F> {
+    type Num = Number<F>;
+
+    fn load_add(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Value<F>,
+        y: Value<F>,
+    ) -> Result<(Self::Num, Self::Num, Self::Num), Error> {
+        self.load_op(layouter, FunctionMode::Add, x, y)
+    }
+
+    fn load_mul(
+        &self,
+        layouter: impl Layouter<F>,
+        x: Value<F>,
+        y: Value<F>,
+    ) -> Result<(Self::Num, Self::Num, Self::Num), Error> {
+        self.load_op(layouter, FunctionMode::Mul, x, y)
+    }
+
+    // x * x = y, the right input is a copy of the left one
+    fn load_square(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "square",
+            |mut region| {
+                config.s_op.enable(&mut region, 0)?;
+                region.assign_fixed(
+                    || "mode",
+                    config.mode,
+                    0,
+                    || Value::known(FunctionMode::Mul.value::<F>()),
+                )?;
+                let x_cell = region.assign_advice(|| "x", config.x, 0, || x)?;
+                x_cell.copy_advice(|| "x", &mut region, config.y, 0)?;
+                let y = x.map(|x| x.square());
+                let y_cell = region
+                    .assign_advice(|| "y", config.z, 0, || y)
+                    .map(Number)?;
+                Ok((Number(x_cell), y_cell))
+            },
+        )
+    }
+
+    // x + 0 = y on an add row
+    fn load_assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+        y: Value<F>,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "equal",
+            |mut region| {
+                config.s_op.enable(&mut region, 0)?;
+                region.assign_fixed(
+                    || "mode",
+                    config.mode,
+                    0,
+                    || Value::known(FunctionMode::Add.value::<F>()),
+                )?;
+                let x_cell = region
+                    .assign_advice(|| "x", config.x, 0, || x)
+                    .map(Number)?;
+                region.assign_advice(|| "0", config.y, 0, || Value::known(F::zero()))?;
+                region.assign_advice(|| "y", config.z, 0, || y)?;
+                Ok(x_cell)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+    use crate::{function::SimpleFunctionChip, recorder::parse_index};
+
+    // x + y, x * y and x * y + y on one chip
+    //
+    // instance: | x + y | x * y | x * y + y |
+    struct BothModesCircuit {
+        x: Value<Fp>,
+        y: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for BothModesCircuit {
+        type Config = (FunctionConfigV2, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                x: Value::unknown(),
+                y: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [x, y, z] = [(); 3].map(|_| meta.advice_column());
+            let mode = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (FunctionChipV2::configure(meta, x, y, z, mode), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FunctionChipV2::construct(config);
+            let (_, _, sum) = chip.load_add(layouter.namespace(|| "x + y"), self.x, self.y)?;
+            let (_, y, product) = chip.load_mul(layouter.namespace(|| "x * y"), self.x, self.y)?;
+            let out = chip.add_cells(layouter.namespace(|| "x * y + y"), &product, &y)?;
+            for (row, cell) in [sum, product, out].iter().enumerate() {
+                layouter.constrain_instance(cell.0.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run(x: u64, y: u64, public: [u64; 3]) -> MockProver<Fp> {
+        let circuit = BothModesCircuit {
+            x: Value::known(Fp::from(x)),
+            y: Value::known(Fp::from(y)),
+        };
+        MockProver::run(4, &circuit, vec![public.map(Fp::from).to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn both_modes_work() {
+        run(3, 4, [7, 12, 16]).assert_satisfied();
+        run(0, 9, [9, 0, 9]).assert_satisfied();
+    }
+
+    #[test]
+    fn add_row_does_not_accept_a_product() {
+        assert!(run(3, 4, [12, 12, 16]).verify().is_err());
+    }
+
+    #[test]
+    fn mul_row_does_not_accept_a_sum() {
+        assert!(run(3, 4, [7, 7, 11]).verify().is_err());
+    }
+
+    // one selector traded for a fixed column and one degree
+    #[test]
+    fn merged_gate_trades_a_selector_for_a_degree() {
+        let mut v1 = ConstraintSystem::<Fp>::default();
+        let [x, y, z] = [(); 3].map(|_| v1.advice_column());
+        SimpleFunctionChip::configure(&mut v1, x, y, z);
+
+        let mut v2 = ConstraintSystem::<Fp>::default();
+        let [x, y, z] = [(); 3].map(|_| v2.advice_column());
+        let mode = v2.fixed_column();
+        FunctionChipV2::configure(&mut v2, x, y, z, mode);
+
+        let count =
+            |cs: &ConstraintSystem<Fp>, prefix| parse_index(&format!("{:?}", cs.pinned()), prefix);
+        assert_eq!(count(&v1, "num_selectors: "), 2);
+        assert_eq!(count(&v2, "num_selectors: "), 1);
+        assert_eq!(count(&v1, "num_fixed_columns: "), 0);
+        assert_eq!(count(&v2, "num_fixed_columns: "), 1);
+        assert_eq!(v1.degree(), 3);
+        assert_eq!(v2.degree(), 4);
+    }
+}
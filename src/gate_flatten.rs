@@ -0,0 +1,230 @@
+// Multiplies a gate expression out into a flat sum of monomials, each a
+// coefficient and the atoms it multiplies:
+//
+// S0 * (A0 - 2 * A1)   becomes   [(1, [S0, A0]), (-2, [S0, A1])]
+//
+// Negations and scalings end up in the coefficient, constants stay atoms.
+// Like terms are not combined, so every monomial is one path through the tree.
+
+use std::hash::{Hash, Hasher};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Expression, Selector},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprAtom<F> {
+    // (column index, rotation)
+    Advice(usize, i32),
+    Fixed(usize, i32),
+    Instance(usize, i32),
+    Selector(Selector),
+    Constant(F),
+}
+
+// field elements don't implement Hash, constants hash by their representation
+impl<F: FieldExt> Hash for ExprAtom<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            ExprAtom::Advice(index, rotation)
+            | ExprAtom::Fixed(index, rotation)
+            | ExprAtom::Instance(index, rotation) => (index, rotation).hash(state),
+            ExprAtom::Selector(selector) => selector.hash(state),
+            ExprAtom::Constant(c) => c.to_repr().as_ref().hash(state),
+        }
+    }
+}
+
+pub fn flatten_expression<F: FieldExt>(expr: &Expression<F>) -> Vec<(F, Vec<ExprAtom<F>>)> {
+    let atom = |atom| vec![(F::one(), vec![atom])];
+    match expr {
+        Expression::Constant(c) => atom(ExprAtom::Constant(*c)),
+        Expression::Selector(selector) => atom(ExprAtom::Selector(*selector)),
+        Expression::Fixed {
+            column_index,
+            rotation,
+            ..
+        } => atom(ExprAtom::Fixed(*column_index, rotation.0)),
+        Expression::Advice {
+            column_index,
+            rotation,
+            ..
+        } => atom(ExprAtom::Advice(*column_index, rotation.0)),
+        Expression::Instance {
+            column_index,
+            rotation,
+            ..
+        } => atom(ExprAtom::Instance(*column_index, rotation.0)),
+        Expression::Negated(a) => scale(flatten_expression(a), -F::one()),
+        Expression::Scaled(a, c) => scale(flatten_expression(a), *c),
+        Expression::Sum(a, b) => {
+            let mut terms = flatten_expression(a);
+            terms.extend(flatten_expression(b));
+            terms
+        }
+        Expression::Product(a, b) => {
+            let b = flatten_expression(b);
+            flatten_expression(a)
+                .into_iter()
+                .flat_map(|(ca, xa)| {
+                    b.iter().map(move |(cb, xb)| {
+                        let atoms = xa.iter().chain(xb.iter()).copied().collect();
+                        (ca * cb, atoms)
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+fn scale<F: FieldExt>(terms: Vec<(F, Vec<ExprAtom<F>>)>, factor: F) -> Vec<(F, Vec<ExprAtom<F>>)> {
+    terms
+        .into_iter()
+        .map(|(coeff, atoms)| (coeff * factor, atoms))
+        .collect()
+}
+
+/// Highest number of column and selector atoms in one monomial, constants don't
+/// count towards the degree.
+pub fn max_degree<F: FieldExt>(flattened: &[(F, Vec<ExprAtom<F>>)]) -> usize {
+    flattened
+        .iter()
+        .map(|(_, atoms)| {
+            atoms
+                .iter()
+                .filter(|atom| !matches!(atom, ExprAtom::Constant(_)))
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use halo2_proofs::{
+        pasta::Fp,
+        plonk::{ConstraintSystem, VirtualCells},
+        poly::Rotation,
+    };
+
+    use super::*;
+
+    // the one constraint `gate` builds, as create_gate hands it over
+    fn capture(
+        meta: &mut ConstraintSystem<Fp>,
+        gate: impl FnOnce(&mut VirtualCells<'_, Fp>) -> Expression<Fp>,
+    ) -> Expression<Fp> {
+        let mut captured = None;
+        meta.create_gate("captured", |meta| {
+            let expr = gate(meta);
+            captured = Some(expr.clone());
+            vec![expr]
+        });
+        captured.unwrap()
+    }
+
+    // s * (a + b - c) for the Fibonacci add gate, s * (a * b - c) for the
+    // function mul gate
+    fn gate(mul: bool) -> (Expression<Fp>, Selector) {
+        let mut meta = ConstraintSystem::default();
+        let [a, b, c] = [(); 3].map(|_| meta.advice_column());
+        let s = meta.selector();
+        let expr = capture(&mut meta, |meta| {
+            let s = meta.query_selector(s);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            if mul {
+                s * (a * b - c)
+            } else {
+                s * (a + b - c)
+            }
+        });
+        (expr, s)
+    }
+
+    #[test]
+    fn fibonacci_add_gate_has_degree_2() {
+        let (expr, s) = gate(false);
+        let flattened = flatten_expression(&expr);
+        let s = ExprAtom::Selector(s);
+        assert_eq!(
+            flattened,
+            vec![
+                (Fp::one(), vec![s, ExprAtom::Advice(0, 0)]),
+                (Fp::one(), vec![s, ExprAtom::Advice(1, 0)]),
+                (-Fp::one(), vec![s, ExprAtom::Advice(2, 0)]),
+            ]
+        );
+        assert_eq!(max_degree(&flattened), 2);
+    }
+
+    #[test]
+    fn function_mul_gate_has_degree_3() {
+        let (expr, s) = gate(true);
+        let flattened = flatten_expression(&expr);
+        let s = ExprAtom::Selector(s);
+        assert_eq!(
+            flattened,
+            vec![
+                (
+                    Fp::one(),
+                    vec![s, ExprAtom::Advice(0, 0), ExprAtom::Advice(1, 0)]
+                ),
+                (-Fp::one(), vec![s, ExprAtom::Advice(2, 0)]),
+            ]
+        );
+        assert_eq!(max_degree(&flattened), 3);
+    }
+
+    #[test]
+    fn constants_and_rotations() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let (a, f) = (meta.advice_column(), meta.fixed_column());
+        let expr = capture(&mut meta, |meta| {
+            let next = meta.query_advice(a, Rotation::next());
+            let f = meta.query_fixed(f, Rotation::prev());
+            (next - Expression::Constant(Fp::from(3))) * f * Fp::from(2)
+        });
+        let flattened = flatten_expression(&expr);
+        assert_eq!(
+            flattened,
+            vec![
+                (
+                    Fp::from(2),
+                    vec![ExprAtom::Advice(0, 1), ExprAtom::Fixed(0, -1)]
+                ),
+                (
+                    -Fp::from(2),
+                    vec![ExprAtom::Constant(Fp::from(3)), ExprAtom::Fixed(0, -1)]
+                ),
+            ]
+        );
+        // the constant doesn't count
+        assert_eq!(max_degree(&flattened), 2);
+        assert_eq!(max_degree::<Fp>(&[]), 0);
+    }
+
+    #[test]
+    fn atoms_hash_like_they_compare() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let s = meta.selector();
+        let atoms: HashSet<ExprAtom<Fp>> = [
+            ExprAtom::Advice(0, 0),
+            ExprAtom::Advice(0, 0),
+            ExprAtom::Advice(0, 1),
+            ExprAtom::Fixed(0, 0),
+            ExprAtom::Selector(s),
+            ExprAtom::Constant(Fp::from(2)),
+            ExprAtom::Constant(Fp::one() + Fp::one()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(atoms.len(), 5);
+        assert!(atoms.contains(&ExprAtom::Constant(Fp::from(2))));
+    }
+}
@@ -0,0 +1,212 @@
+// The gates of a circuit are only reachable through the text rendering of
+// `CircuitGates`, e.g. `S0 * (A0@0 * A1@0 - A2@0)`. This parses that rendering
+// back into a sum of monomials.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{arithmetic::FieldExt, dev::CircuitGates, plonk::Circuit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GateAtom {
+    Selector(usize),
+    // (column index, rotation)
+    Fixed(usize, i32),
+    Advice(usize, i32),
+    Instance(usize, i32),
+}
+
+/// `coeff * atoms[0] * atoms[1] * ...`, atoms kept sorted.
+pub type Monomial<F> = (F, Vec<GateAtom>);
+
+#[derive(Debug, Clone)]
+pub struct ParsedGate<F> {
+    pub name: String,
+    pub polys: Vec<Vec<Monomial<F>>>,
+}
+
+pub fn degree<F>(poly: &[Monomial<F>]) -> usize {
+    poly.iter().map(|(_, atoms)| atoms.len()).max().unwrap_or(0)
+}
+
+pub fn parse_gates<F: FieldExt, C: Circuit<F>>() -> Vec<ParsedGate<F>> {
+    let mut gates: Vec<ParsedGate<F>> = vec![];
+    for line in CircuitGates::collect::<F, C>().to_string().lines() {
+        if line.starts_with("Total gates") {
+            break;
+        } else if line.starts_with("- ") && line.ends_with(':') {
+            continue;
+        } else if let Some(expr) = line.strip_prefix("- ").or_else(|| line.strip_prefix("  ")) {
+            gates.last_mut().unwrap().polys.push(parse_poly(expr));
+        } else {
+            gates.push(ParsedGate {
+                name: line.trim_end_matches(':').to_string(),
+                polys: vec![],
+            });
+        }
+    }
+    gates
+}
+
+pub fn parse_poly<F: FieldExt>(expr: &str) -> Vec<Monomial<F>> {
+    let tokens = tokenize(expr);
+    let mut pos = 0;
+    let poly = parse_sum::<F>(&tokens, &mut pos);
+    assert_eq!(pos, tokens.len(), "trailing tokens in {}", expr);
+    poly
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Plus,
+    Minus,
+    Star,
+    Open,
+    Close,
+    Word(String),
+}
+
+fn tokenize(expr: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = expr.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {}
+            '+' => tokens.push(Token::Plus),
+            '-' => tokens.push(Token::Minus),
+            '*' => tokens.push(Token::Star),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            _ => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    // rotations may be negative, e.g. A0@-1
+                    if next.is_ascii_alphanumeric()
+                        || next == '@'
+                        || (next == '-' && word.ends_with('@'))
+                    {
+                        word.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_sum<F: FieldExt>(tokens: &[Token], pos: &mut usize) -> Vec<Monomial<F>> {
+    let mut acc = parse_product::<F>(tokens, pos);
+    while let Some(op) = tokens.get(*pos) {
+        let negate = match op {
+            Token::Plus => false,
+            Token::Minus => true,
+            _ => break,
+        };
+        *pos += 1;
+        let rhs = parse_product::<F>(tokens, pos);
+        acc = add(acc, if negate { scale(rhs, -F::one()) } else { rhs });
+    }
+    acc
+}
+
+fn parse_product<F: FieldExt>(tokens: &[Token], pos: &mut usize) -> Vec<Monomial<F>> {
+    let mut acc = parse_unary::<F>(tokens, pos);
+    while tokens.get(*pos) == Some(&Token::Star) {
+        *pos += 1;
+        let rhs = parse_unary::<F>(tokens, pos);
+        acc = mul(&acc, &rhs);
+    }
+    acc
+}
+
+fn parse_unary<F: FieldExt>(tokens: &[Token], pos: &mut usize) -> Vec<Monomial<F>> {
+    match &tokens[*pos] {
+        Token::Minus => {
+            *pos += 1;
+            scale(parse_unary::<F>(tokens, pos), -F::one())
+        }
+        Token::Open => {
+            *pos += 1;
+            let inner = parse_sum::<F>(tokens, pos);
+            assert_eq!(tokens[*pos], Token::Close);
+            *pos += 1;
+            inner
+        }
+        Token::Word(word) => {
+            *pos += 1;
+            parse_word(word)
+        }
+        token => panic!("unexpected token {:?}", token),
+    }
+}
+
+fn parse_word<F: FieldExt>(word: &str) -> Vec<Monomial<F>> {
+    if let Some(hex) = word.strip_prefix("0x") {
+        return vec![(parse_hex(hex), vec![])];
+    }
+    if let Ok(value) = word.parse::<u64>() {
+        return vec![(F::from(value), vec![])];
+    }
+    if let Some(index) = word.strip_prefix('S') {
+        return vec![(F::one(), vec![GateAtom::Selector(index.parse().unwrap())])];
+    }
+
+    let (column, rotation) = word.split_once('@').expect("column query");
+    let (kind, index) = column.split_at(1);
+    let (index, rotation) = (index.parse().unwrap(), rotation.parse().unwrap());
+    let atom = match kind {
+        "F" => GateAtom::Fixed(index, rotation),
+        "A" => GateAtom::Advice(index, rotation),
+        "I" => GateAtom::Instance(index, rotation),
+        _ => panic!("unknown column kind in {}", word),
+    };
+    vec![(F::one(), vec![atom])]
+}
+
+// big endian hex, as printed by the field's Debug impl
+pub fn parse_hex<F: FieldExt>(hex: &str) -> F {
+    let mut repr = F::Repr::default();
+    let bytes = repr.as_mut();
+    let digits: Vec<u8> = hex
+        .chars()
+        .rev()
+        .map(|c| c.to_digit(16).expect("hex digit") as u8)
+        .collect();
+    for (i, pair) in digits.chunks(2).enumerate() {
+        bytes[i] = pair[0] | pair.get(1).map_or(0, |high| high << 4);
+    }
+    F::from_repr(repr).unwrap()
+}
+
+fn normalize<F: FieldExt>(terms: impl IntoIterator<Item = Monomial<F>>) -> Vec<Monomial<F>> {
+    let mut combined: BTreeMap<Vec<GateAtom>, F> = BTreeMap::new();
+    for (coeff, mut atoms) in terms {
+        atoms.sort();
+        *combined.entry(atoms).or_insert_with(F::zero) += coeff;
+    }
+    combined
+        .into_iter()
+        .filter(|(_, coeff)| !bool::from(coeff.is_zero()))
+        .map(|(atoms, coeff)| (coeff, atoms))
+        .collect()
+}
+
+fn add<F: FieldExt>(a: Vec<Monomial<F>>, b: Vec<Monomial<F>>) -> Vec<Monomial<F>> {
+    normalize(a.into_iter().chain(b))
+}
+
+fn scale<F: FieldExt>(a: Vec<Monomial<F>>, factor: F) -> Vec<Monomial<F>> {
+    normalize(a.into_iter().map(|(coeff, atoms)| (coeff * factor, atoms)))
+}
+
+fn mul<F: FieldExt>(a: &[Monomial<F>], b: &[Monomial<F>]) -> Vec<Monomial<F>> {
+    normalize(a.iter().flat_map(|(ca, xa)| {
+        b.iter().map(move |(cb, xb)| {
+            let atoms = xa.iter().chain(xb.iter()).copied().collect();
+            (*ca * *cb, atoms)
+        })
+    }))
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+use crate::recorder::{gate_selectors, record};
+
+/// Counts, per gate name, the rows on which the gate is switched on by one of
+/// its selectors. Gates without a selector are active on every usable row.
+pub fn profile_gates<F: FieldExt, C: Circuit<F>>(circuit: &C, k: u32) -> HashMap<String, usize> {
+    let recorder = record(circuit, k, vec![]).expect("circuit should synthesize");
+
+    let mut cs = ConstraintSystem::<F>::default();
+    C::configure(&mut cs);
+    let usable_rows = (1 << k) - (cs.blinding_factors() + 1);
+
+    let mut profile = HashMap::new();
+    for (name, selectors) in gate_selectors::<F, C>() {
+        let count = if selectors.is_empty() {
+            usable_rows
+        } else {
+            let mut rows: Vec<_> = recorder
+                .selectors
+                .iter()
+                .filter(|(selector, _)| selectors.contains(selector))
+                .map(|(_, row)| *row)
+                .collect();
+            rows.sort_unstable();
+            rows.dedup();
+            rows.len()
+        };
+        *profile.entry(name).or_insert(0) += count;
+    }
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, function::FunctionCircuit};
+
+    #[test]
+    fn fibo_add_gate_runs_once_per_new_term() {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        // one row per term after F(1) and F(2), F(3) up to F(10)
+        assert_eq!(profile_gates(&circuit, 4)["add"], 8);
+    }
+
+    #[test]
+    fn function_mul_gate_runs_three_times() {
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        let profile = profile_gates(&circuit, 4);
+        assert_eq!(profile["mul"], 3);
+        // two adds and the final equality, which runs on the add gate
+        assert_eq!(profile["add"], 3);
+    }
+}
@@ -0,0 +1,220 @@
+// one gradient descent step for a linear model: w_new = w - lr * grad
+//
+// | w | grad | prod | w_new | lr (fixed) | s_mul | s_sub |
+// gate mul: s_mul * (lr * grad - prod) == 0
+// gate sub: s_sub * (w - prod - w_new) == 0
+//
+// instance: | w_initial | w_final |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct GDStepConfig {
+    pub advice: [Column<Advice>; 4],
+    pub lr: Column<Fixed>,
+    pub instance: Column<Instance>,
+    pub s_mul: Selector,
+    pub s_sub: Selector,
+}
+
+pub struct GDStepChip<F: FieldExt> {
+    config: GDStepConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> GDStepChip<F> {
+    pub fn construct(config: GDStepConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        lr: Column<Fixed>,
+        instance: Column<Instance>,
+    ) -> GDStepConfig {
+        let [col_w, col_grad, col_prod, col_new] = advice;
+        meta.enable_equality(col_w);
+        meta.enable_equality(col_new);
+        meta.enable_equality(instance);
+
+        let s_mul = meta.selector();
+        let s_sub = meta.selector();
+
+        meta.create_gate("mul", |meta| {
+            let s = meta.query_selector(s_mul);
+            let lr = meta.query_fixed(lr, Rotation::cur());
+            let grad = meta.query_advice(col_grad, Rotation::cur());
+            let prod = meta.query_advice(col_prod, Rotation::cur());
+            vec![s * (lr * grad - prod)]
+        });
+
+        meta.create_gate("sub", |meta| {
+            let s = meta.query_selector(s_sub);
+            let w = meta.query_advice(col_w, Rotation::cur());
+            let prod = meta.query_advice(col_prod, Rotation::cur());
+            let new = meta.query_advice(col_new, Rotation::cur());
+            vec![s * (w - prod - new)]
+        });
+
+        GDStepConfig {
+            advice,
+            lr,
+            instance,
+            s_mul,
+            s_sub,
+        }
+    }
+
+    pub fn load_weight(&self, mut layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "initial weight",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "w",
+                    self.config.instance,
+                    0,
+                    self.config.advice[0],
+                    0,
+                )
+            },
+        )
+    }
+
+    // returns the updated weight
+    pub fn step(
+        &self,
+        mut layouter: impl Layouter<F>,
+        w: &AssignedCell<F, F>,
+        grad: Value<F>,
+        lr: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "gd step",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                config.s_sub.enable(&mut region, 0)?;
+
+                region.assign_fixed(|| "lr", config.lr, 0, || Value::known(lr))?;
+                w.copy_advice(|| "w", &mut region, config.advice[0], 0)?;
+                region.assign_advice(|| "grad", config.advice[1], 0, || grad)?;
+
+                let prod = grad.map(|grad| lr * grad);
+                region.assign_advice(|| "prod", config.advice[2], 0, || prod)?;
+                region.assign_advice(
+                    || "w_new",
+                    config.advice[3],
+                    0,
+                    || w.value().copied() - prod,
+                )
+            },
+        )
+    }
+
+    pub fn expose_final(
+        &self,
+        mut layouter: impl Layouter<F>,
+        w: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(w.cell(), self.config.instance, 1)
+    }
+}
+
+/// Applies one gradient descent step per entry of `grads`, starting from the
+/// public initial weight and ending at the public final weight.
+#[derive(Default)]
+pub struct GDStepCircuit<F: FieldExt> {
+    pub grads: Vec<Value<F>>,
+    pub lr: F,
+}
+
+impl<F: FieldExt> Circuit<F> for GDStepCircuit<F> {
+    type Config = GDStepConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            grads: vec![Value::unknown(); self.grads.len()],
+            lr: self.lr,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let lr = meta.fixed_column();
+        let instance = meta.instance_column();
+        GDStepChip::configure(meta, advice, lr, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = GDStepChip::construct(config);
+
+        let mut w = chip.load_weight(layouter.namespace(|| "load"))?;
+        for grad in self.grads.iter() {
+            w = chip.step(layouter.namespace(|| "step"), &w, *grad, self.lr)?;
+        }
+        chip.expose_final(layouter.namespace(|| "final"), &w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    // f(w) = (w - 3)^2, grad = 2 * (w - 3), so with lr = 1/4 a step halves the
+    // distance to the minimum: w_new = w - (w - 3) / 2
+    fn descend(w: u64, steps: usize) -> (GDStepCircuit<Fp>, u64) {
+        let mut grads = vec![];
+        let mut w = w;
+        for _ in 0..steps {
+            let grad = 2 * (w - 3);
+            grads.push(Value::known(Fp::from(grad)));
+            w -= grad / 4;
+        }
+        let lr = Fp::from(4).invert().unwrap();
+        (GDStepCircuit { grads, lr }, w)
+    }
+
+    fn prove(circuit: &GDStepCircuit<Fp>, w: u64, w_final: u64) -> MockProver<Fp> {
+        let public = vec![vec![Fp::from(w), Fp::from(w_final)]];
+        MockProver::run(4, circuit, public).unwrap()
+    }
+
+    #[test]
+    fn single_step() {
+        let (circuit, w) = descend(11, 1);
+        assert_eq!(w, 7);
+        prove(&circuit, 11, 7).assert_satisfied();
+    }
+
+    #[test]
+    fn three_steps_converge() {
+        // 11, 7, 5, 4 towards the minimum at 3
+        let (circuit, w) = descend(11, 3);
+        assert_eq!(w, 4);
+        prove(&circuit, 11, 4).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_final_weight_fails() {
+        let (circuit, _) = descend(11, 3);
+        assert!(prove(&circuit, 11, 3).verify().is_err());
+    }
+}
@@ -0,0 +1,136 @@
+// Proves the private x has a public number of set bits. RangeCheckChip splits x
+// into BITS bits with its running sum, which also shows x < 2^BITS, and SumChip
+// adds the bits up:
+//
+// | z | bit | x   | acc | selector | s_end | s_first | s_next |
+// range check on z, bit; the bits are copied into x and summed in acc
+//
+// instance: | weight |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    range_check::{RangeCheckChip, RangeCheckConfig},
+    sum::{SumChip, SumConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct HammingWeightConfig {
+    pub advice: [Column<Advice>; 4],
+    pub instance: Column<Instance>,
+    pub range: RangeCheckConfig,
+    pub sum: SumConfig,
+}
+
+#[derive(Default)]
+pub struct HammingWeightCircuit<F, const BITS: usize> {
+    pub x: Value<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> HammingWeightCircuit<F, BITS> {
+    pub fn new(x: u64) -> Self {
+        Self {
+            x: Value::known(F::from(x)),
+        }
+    }
+
+    // instance column for x, whose weight only counts when x < 2^BITS
+    pub fn public_inputs(x: u64) -> Vec<Vec<F>> {
+        vec![vec![F::from(x.count_ones() as u64)]]
+    }
+}
+
+impl<F: FieldExt, const BITS: usize> Circuit<F> for HammingWeightCircuit<F, BITS> {
+    type Config = HammingWeightConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let range = RangeCheckChip::<F, BITS>::configure(meta, advice[0], advice[1]);
+        let sum = SumChip::configure(meta, advice[2], advice[3]);
+        // the bits are copied out to be summed
+        meta.enable_equality(advice[1]);
+
+        HammingWeightConfig {
+            advice,
+            instance,
+            range,
+            sum,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let x = layouter.assign_region(
+            || "x",
+            |mut region| region.assign_advice(|| "x", config.advice[2], 0, || self.x),
+        )?;
+
+        let range = RangeCheckChip::<F, BITS>::construct(config.range);
+        let bits = range.check(layouter.namespace(|| "bits"), &x)?;
+
+        let sum = SumChip::construct(config.sum);
+        let weight = sum.sum(layouter.namespace(|| "weight"), &bits)?;
+        layouter.constrain_instance(weight.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn prove<const BITS: usize>(x: u64, weight: u64) -> MockProver<Fp> {
+        let circuit = HammingWeightCircuit::<Fp, BITS>::new(x);
+        MockProver::run(6, &circuit, vec![vec![Fp::from(weight)]]).unwrap()
+    }
+
+    #[test]
+    fn four_of_eight_bits() {
+        assert_eq!(
+            HammingWeightCircuit::<Fp, 8>::public_inputs(0b10110100),
+            vec![vec![Fp::from(4)]]
+        );
+        prove::<8>(0b10110100, 4).assert_satisfied();
+    }
+
+    #[test]
+    fn zero_input() {
+        prove::<8>(0, 0).assert_satisfied();
+        prove::<16>(0, 0).assert_satisfied();
+    }
+
+    #[test]
+    fn all_ones() {
+        prove::<8>((1 << 8) - 1, 8).assert_satisfied();
+        prove::<16>((1 << 16) - 1, 16).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_weight_fails() {
+        assert!(prove::<8>(0b10110100, 3).verify().is_err());
+        assert!(prove::<8>(0b10110100, 5).verify().is_err());
+    }
+
+    #[test]
+    fn input_past_bits_fails() {
+        // bit 8 of 0x1ff has nowhere to go, whichever weight is claimed
+        assert!(prove::<8>(0x1ff, 9).verify().is_err());
+        assert!(prove::<8>(0x1ff, 8).verify().is_err());
+    }
+}
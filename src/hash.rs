@@ -0,0 +1,132 @@
+// MiMC style keyed permutation with x^5 rounds, H(value || nonce) takes the value
+// as the initial state and the nonce as the key. Good enough for commitments in
+// the examples here, not a vetted hash.
+//
+// | state | key | round_constant | s_round |
+// | x_0   | k   | c_0            | 1       |
+// | x_1   | k   | c_1            | 1       |
+// | ...   |     |                |         |
+// | x_R   | k   |                | 0       |
+// gate round: s_round * ((state + key + c)^5 - state(next)) == 0
+// gate round key: s_round * (key(next) - key) == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+pub const HASH_ROUNDS: usize = 8;
+
+pub fn round_constants<F: FieldExt>() -> [F; HASH_ROUNDS] {
+    let mut constants = [F::zero(); HASH_ROUNDS];
+    for (i, c) in constants.iter_mut().enumerate() {
+        *c = F::from((i as u64 + 1).pow(3) + 7);
+    }
+    constants
+}
+
+fn round<F: FieldExt>(state: F, key: F, c: F) -> F {
+    (state + key + c).pow_vartime([5])
+}
+
+// out of circuit evaluation, used to compute public commitments
+pub fn hash<F: FieldExt>(value: F, nonce: F) -> F {
+    round_constants()
+        .iter()
+        .fold(value, |state, c| round(state, nonce, *c))
+}
+
+#[derive(Debug, Clone)]
+pub struct AlgebraicHashConfig {
+    pub state: Column<Advice>,
+    pub key: Column<Advice>,
+    pub round_constant: Column<Fixed>,
+    pub s_round: Selector,
+}
+
+pub struct AlgebraicHashChip<F: FieldExt> {
+    config: AlgebraicHashConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> AlgebraicHashChip<F> {
+    pub fn construct(config: AlgebraicHashConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: Column<Advice>,
+        key: Column<Advice>,
+        round_constant: Column<Fixed>,
+    ) -> AlgebraicHashConfig {
+        meta.enable_equality(state);
+        meta.enable_equality(key);
+
+        let s_round = meta.selector();
+
+        meta.create_gate("round", |meta| {
+            let s = meta.query_selector(s_round);
+            let state_cur = meta.query_advice(state, Rotation::cur());
+            let state_next = meta.query_advice(state, Rotation::next());
+            let key_cur = meta.query_advice(key, Rotation::cur());
+            let key_next = meta.query_advice(key, Rotation::next());
+            let c = meta.query_fixed(round_constant, Rotation::cur());
+
+            let x = state_cur + key_cur.clone() + c;
+            let x2 = x.clone() * x.clone();
+            let x5: Expression<F> = x2.clone() * x2 * x;
+            vec![
+                s.clone() * (x5 - state_next),
+                s * (key_next - key_cur),
+            ]
+        });
+
+        AlgebraicHashConfig {
+            state,
+            key,
+            round_constant,
+            s_round,
+        }
+    }
+
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        nonce: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "hash",
+            |mut region| {
+                let mut state = value.copy_advice(|| "x_0", &mut region, config.state, 0)?;
+                let mut key = nonce.copy_advice(|| "k", &mut region, config.key, 0)?;
+                for (row, c) in round_constants::<F>().iter().enumerate() {
+                    config.s_round.enable(&mut region, row)?;
+                    region.assign_fixed(|| "c", config.round_constant, row, || Value::known(*c))?;
+
+                    let next = state
+                        .value()
+                        .zip(key.value())
+                        .map(|(state, key)| round(*state, *key, *c));
+                    state = region.assign_advice(|| "x", config.state, row + 1, || next)?;
+                    key = region.assign_advice(
+                        || "k",
+                        config.key,
+                        row + 1,
+                        || key.value().copied(),
+                    )?;
+                }
+                Ok(state)
+            },
+        )
+    }
+}
@@ -0,0 +1,184 @@
+// Homomorphic addition of Paillier ciphertexts without decrypting them. With
+// g = n + 1 a plaintext m encrypts as
+//
+// enc(m, r) = (1 + m * n) * r^n mod n^2
+//
+// and the product of two ciphertexts is a ciphertext of the sum:
+//
+// enc(m1, r1) * enc(m2, r2) = enc(m1 + m2, r1 * r2) mod n^2
+//
+// The private ciphertexts c1, c2 are range checked to 32 bits and multiplied
+// modulo the fixed N = n^2 on CongruenceChip, the reduced product is bound to
+// the public result. n is a circuit parameter below 2^16, so N fits the chip.
+//
+// instance: | c1 * c2 mod n^2 |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::congruence::{CongruenceChip, CongruenceConfig};
+
+fn pow_mod(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let (mut base, mut acc) = (base as u128 % modulus as u128, 1u128);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = acc * base % modulus as u128;
+        }
+        base = base * base % modulus as u128;
+        exp >>= 1;
+    }
+    acc as u64
+}
+
+// out of circuit enc(m, r) under the public key n, g = n + 1
+pub fn paillier_encrypt(n: u64, m: u64, r: u64) -> u64 {
+    let n2 = n * n;
+    let gm = (1 + (m % n) as u128 * n as u128) % n2 as u128;
+    (gm * pow_mod(r, n, n2) as u128 % n2 as u128) as u64
+}
+
+#[derive(Debug, Clone)]
+pub struct HomomorphicAddConfig {
+    pub congruence: CongruenceConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct HomomorphicAddCircuit<F> {
+    pub n: u64,
+    pub c1: Value<F>,
+    pub c2: Value<F>,
+}
+
+impl<F: FieldExt> HomomorphicAddCircuit<F> {
+    pub fn new(n: u64, c1: u64, c2: u64) -> Self {
+        Self {
+            n,
+            c1: Value::known(F::from(c1)),
+            c2: Value::known(F::from(c2)),
+        }
+    }
+
+    pub fn public_inputs(n: u64, c1: u64, c2: u64) -> Vec<F> {
+        let n2 = (n * n) as u128;
+        vec![F::from((c1 as u128 * c2 as u128 % n2) as u64)]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for HomomorphicAddCircuit<F> {
+    type Config = HomomorphicAddConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let modulus = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        HomomorphicAddConfig {
+            congruence: CongruenceChip::configure(meta, advice, modulus),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if !(2..1 << 16).contains(&self.n) {
+            return Err(Error::Synthesis);
+        }
+        let advice = config.congruence.advice;
+        let chip = CongruenceChip::construct(config.congruence, self.n * self.n);
+
+        let (c1, c2) = layouter.assign_region(
+            || "ciphertexts",
+            |mut region| {
+                let c1 = region.assign_advice(|| "c1", advice[0], 0, || self.c1)?;
+                let c2 = region.assign_advice(|| "c2", advice[1], 0, || self.c2)?;
+                Ok((c1, c2))
+            },
+        )?;
+        chip.check_input(layouter.namespace(|| "c1 < 2^32"), &c1)?;
+        chip.check_input(layouter.namespace(|| "c2 < 2^32"), &c2)?;
+
+        let sum = chip.mul_mod(layouter.namespace(|| "c1 * c2 mod n^2"), &c1, &c2)?;
+        layouter.constrain_instance(sum.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    const N: u64 = 251;
+
+    fn prove(n: u64, c1: u64, c2: u64, public: u64) -> Result<MockProver<Fp>, Error> {
+        let circuit = HomomorphicAddCircuit::new(n, c1, c2);
+        MockProver::run(8, &circuit, vec![vec![Fp::from(public)]])
+    }
+
+    // out of circuit decryption with the factors p and q of n
+    fn decrypt(p: u64, q: u64, c: u64) -> u64 {
+        let n = p * q;
+        let lambda = (p - 1) * (q - 1);
+        let l = (pow_mod(c, lambda, n * n) - 1) / n;
+        // lambda^-1 mod n
+        let mu = (1..n).find(|mu| lambda % n * mu % n == 1).unwrap();
+        l * mu % n
+    }
+
+    #[test]
+    fn product_of_ciphertexts_encrypts_the_sum() {
+        // n = 17 * 19
+        let n = 323;
+        let (c1, c2) = (paillier_encrypt(n, 40, 7), paillier_encrypt(n, 100, 13));
+        assert_eq!(decrypt(17, 19, c1), 40);
+        assert_eq!(decrypt(17, 19, c2 * c1 % (n * n)), 140);
+
+        let public = HomomorphicAddCircuit::<Fp>::public_inputs(n, c1, c2);
+        assert_eq!(public, vec![Fp::from(paillier_encrypt(n, 140, 91))]);
+        prove(n, c1, c2, paillier_encrypt(n, 140, 91))
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn largest_modulus() {
+        let n = (1 << 16) - 1;
+        let (c1, c2) = (paillier_encrypt(n, 12345, 2), paillier_encrypt(n, 54321, 3));
+        let public = c1 as u128 * c2 as u128 % (n * n) as u128;
+        prove(n, c1, c2, public as u64).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_result_fails() {
+        let (c1, c2) = (paillier_encrypt(N, 4, 5), paillier_encrypt(N, 5, 7));
+        // the sum under a different r, and the plain product
+        assert!(prove(N, c1, c2, paillier_encrypt(N, 9, 7))
+            .unwrap()
+            .verify()
+            .is_err());
+        assert!(prove(N, c1, c2, c1 * c2).unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn modulus_out_of_range() {
+        for n in [0, 1, 1 << 16] {
+            assert!(matches!(prove(n, 1, 1, 1), Err(Error::Synthesis)));
+        }
+    }
+}
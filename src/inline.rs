@@ -0,0 +1,419 @@
+// Lets a parent chip run the gates of a sub-chip inside its own regions. The
+// gates stay as the sub-chip configured them, on columns the parent shares;
+// inlining hands their selectors to the parent config, which then enables them
+// on its own rows instead of calling into the sub-chip and its regions:
+//
+// FiboInlineChip + SimpleFunctionChip on the same | lhs | rhs | out |
+//
+// | a | b | c | s_add |
+// | 1 | 1 | 2 |   1   |    one region, row i + 1 copies b, c of row i,
+// | 1 | 2 | 3 |   1   |    each row checked by SimpleFunctionChip's
+// | 2 | 3 | 5 |   1   |    s_add * (x + y - z)
+//
+// A sub-chip gate on a column the parent doesn't have can't be inlined.
+// `InlinedFiboCircuit` lays the Fibonacci rows out this way and takes the same instance
+// as `ExpandedFiboCircuit`.
+//
+// instance: | F(1) | F(2) | F(n) |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+};
+
+use crate::{
+    allocator::ColumnAllocator,
+    checked_assign::assign_advice_checked,
+    fibo1::{ACell, FiboChip, FiboConfig},
+    function::SimpleFunctionChip,
+};
+
+/// A gate of another chip: its selector and the advice columns it queries, in
+/// the order the chip gives them meaning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlinedGate {
+    pub name: &'static str,
+    pub selector: Selector,
+    pub columns: Vec<Column<Advice>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InlineError {
+    // the gate queries a column the parent doesn't lay out
+    ForeignColumn(&'static str),
+    // the parent already has a gate of that name
+    Duplicate(&'static str),
+}
+
+/// A chip whose gates can be enabled from another chip's regions.
+pub trait InlineSource<F: FieldExt>: Chip<F> {
+    fn gates(&self) -> Vec<InlinedGate>;
+}
+
+/// A chip config that can take the gates of other chips.
+pub trait InlineTarget {
+    fn advice_columns(&self) -> Vec<Column<Advice>>;
+
+    fn inlined_gates(&mut self) -> &mut Vec<InlinedGate>;
+}
+
+/// Moves every gate of `sub_chip` into `parent_config`. Nothing is inlined
+/// when one of the gates is refused.
+pub fn inline_chip<F: FieldExt, Parent: Chip<F>, Sub: InlineSource<F>>(
+    parent_config: &mut Parent::Config,
+    sub_chip: Sub,
+) -> Result<(), InlineError>
+where
+    Parent::Config: InlineTarget,
+{
+    let columns = parent_config.advice_columns();
+    let gates = sub_chip.gates();
+    for (i, gate) in gates.iter().enumerate() {
+        if gate.columns.iter().any(|column| !columns.contains(column)) {
+            return Err(InlineError::ForeignColumn(gate.name));
+        }
+        let inlined = parent_config.inlined_gates();
+        if inlined
+            .iter()
+            .chain(&gates[..i])
+            .any(|g| g.name == gate.name)
+        {
+            return Err(InlineError::Duplicate(gate.name));
+        }
+    }
+    parent_config.inlined_gates().extend(gates);
+    Ok(())
+}
+
+/// `FiboChip` columns plus the gates inlined into them.
+#[derive(Debug, Clone)]
+pub struct FiboInlineConfig {
+    pub fibo: FiboConfig,
+    pub inlined: Vec<InlinedGate>,
+}
+
+pub struct FiboInlineChip<F: FieldExt> {
+    config: FiboInlineConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for FiboInlineChip<F> {
+    type Config = FiboInlineConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FiboInlineChip<F> {
+    pub fn construct(config: FiboInlineConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    // nothing inlined yet, see `inline_chip`
+    pub fn configure_with(alloc: &mut ColumnAllocator<'_, F>, reverse: bool) -> FiboInlineConfig {
+        FiboInlineConfig {
+            fibo: FiboChip::configure_with(alloc, reverse),
+            inlined: vec![],
+        }
+    }
+
+    // F(1) = a, F(2) = b up to F(n) in a single region, every row under the
+    // inlined gate `gate` with a, b and c on its columns, so the rows are
+    // chained by copies inside the region instead of across regions
+    pub fn assign_inlined(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+        gate: &str,
+    ) -> Result<(ACell<F>, ACell<F>, ACell<F>), Error> {
+        let gate = self
+            .config
+            .inlined
+            .iter()
+            .find(|inlined| inlined.name == gate)
+            .ok_or(Error::Synthesis)?;
+        let (col_a, col_b, col_c) = match gate.columns[..] {
+            [a, b, c] => (a, b, c),
+            _ => return Err(Error::Synthesis),
+        };
+        if n < 3 {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "inlined rows",
+            |mut region| {
+                gate.selector.enable(&mut region, 0)?;
+                let a_cell = assign_advice_checked(&mut region, col_a, 0, a, "a").map(ACell)?;
+                let b_cell = assign_advice_checked(&mut region, col_b, 0, b, "b").map(ACell)?;
+                let c_cell = assign_advice_checked(&mut region, col_c, 0, a + b, "c").map(ACell)?;
+
+                let (mut prev_b, mut prev_c) = (b_cell.clone(), c_cell);
+                for row in 1..n - 2 {
+                    gate.selector.enable(&mut region, row)?;
+                    let a = prev_b.0.copy_advice(|| "a", &mut region, col_a, row)?;
+                    let b = prev_c.0.copy_advice(|| "b", &mut region, col_b, row)?;
+                    let c_val = a.value().copied() + b.value().copied();
+                    let c =
+                        assign_advice_checked(&mut region, col_c, row, c_val, "c").map(ACell)?;
+                    (prev_b, prev_c) = (ACell(b), c);
+                }
+                Ok((a_cell, b_cell, prev_c))
+            },
+        )
+    }
+}
+
+impl InlineTarget for FiboInlineConfig {
+    fn advice_columns(&self) -> Vec<Column<Advice>> {
+        self.fibo.advice.to_vec()
+    }
+
+    fn inlined_gates(&mut self) -> &mut Vec<InlinedGate> {
+        &mut self.inlined
+    }
+}
+
+impl<F: FieldExt> InlineSource<F> for SimpleFunctionChip<F> {
+    fn gates(&self) -> Vec<InlinedGate> {
+        let config = self.config();
+        let columns = vec![config.x, config.y, config.z];
+        vec![
+            InlinedGate {
+                name: "add",
+                selector: config.s_add,
+                columns: columns.clone(),
+            },
+            InlinedGate {
+                name: "mul",
+                selector: config.s_mul,
+                columns,
+            },
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InlinedFiboConfig {
+    pub inline: FiboInlineConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct InlinedFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> InlinedFiboCircuit<F> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for InlinedFiboCircuit<F> {
+    type Config = InlinedFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut alloc = ColumnAllocator::new(meta);
+        let mut inline = FiboInlineChip::configure_with(&mut alloc, false);
+        let function = SimpleFunctionChip::configure_with(&mut alloc);
+        inline_chip::<F, FiboInlineChip<F>, _>(
+            &mut inline,
+            SimpleFunctionChip::construct(function),
+        )
+        .expect("SimpleFunctionChip shares the columns of FiboChip");
+
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        InlinedFiboConfig { inline, instance }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboInlineChip::<F>::construct(config.inline);
+        let (a, b, last) = chip.assign_inlined(
+            layouter.namespace(|| "inlined fibo"),
+            self.a,
+            self.b,
+            self.n,
+            "add",
+        )?;
+
+        layouter.constrain_instance(a.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.0.cell(), config.instance, 1)?;
+        layouter.constrain_instance(last.0.cell(), config.instance, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{expand::ExpandedFiboCircuit, recorder::record};
+
+    // F(1) = F(2) = 1 and F(n) = last
+    fn instance(last: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::one(), Fp::one(), Fp::from(last)]]
+    }
+
+    #[test]
+    fn same_instance_as_expanded() {
+        for (n, last) in [(3, 2), (10, 55), (20, 6765)] {
+            let inlined = InlinedFiboCircuit::new(Fp::one(), Fp::one(), n);
+            MockProver::run(6, &inlined, instance(last))
+                .unwrap()
+                .assert_satisfied();
+            let expanded = ExpandedFiboCircuit::new(Fp::one(), Fp::one(), n);
+            MockProver::run(6, &expanded, instance(last))
+                .unwrap()
+                .assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn one_region() {
+        let circuit = InlinedFiboCircuit::new(Fp::one(), Fp::one(), 10);
+        let recorder = record(&circuit, 6, instance(55)).unwrap();
+        let regions: Vec<_> = recorder.regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(regions, ["inlined rows"]);
+        // rows 0..8 under the add gate, F(3) to F(10) in the out column
+        assert_eq!(recorder.selectors.len(), 8);
+        assert_eq!(recorder.advice[&(2, 7)], Some(Fp::from(55)));
+    }
+
+    #[test]
+    fn wrong_output_fails() {
+        let circuit = InlinedFiboCircuit::new(Fp::one(), Fp::one(), 10);
+        let prover = MockProver::run(6, &circuit, instance(54)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn n_below_3() {
+        let circuit = InlinedFiboCircuit::new(Fp::one(), Fp::one(), 2);
+        assert!(matches!(
+            MockProver::run(6, &circuit, instance(1)),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn inlines_both_gates() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let config = InlinedFiboCircuit::<Fp>::configure(&mut meta);
+        let names: Vec<_> = config.inline.inlined.iter().map(|g| g.name).collect();
+        assert_eq!(names, ["add", "mul"]);
+        assert!(config
+            .inline
+            .inlined
+            .iter()
+            .all(|gate| gate.columns == config.inline.fibo.advice));
+    }
+
+    #[test]
+    fn refused_gates() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let mut alloc = ColumnAllocator::new(&mut meta);
+        let mut fibo = FiboInlineChip::configure_with(&mut alloc, false);
+        let shared = SimpleFunctionChip::configure_with(&mut alloc);
+        inline_chip::<Fp, FiboInlineChip<Fp>, _>(
+            &mut fibo,
+            SimpleFunctionChip::construct(shared.clone()),
+        )
+        .unwrap();
+
+        // the same gates again
+        assert_eq!(
+            inline_chip::<Fp, FiboInlineChip<Fp>, _>(
+                &mut fibo,
+                SimpleFunctionChip::construct(shared)
+            ),
+            Err(InlineError::Duplicate("add"))
+        );
+
+        // columns of its own, nothing of it is inlined
+        let meta = alloc.meta();
+        let [x, y, z] = [(); 3].map(|_| meta.advice_column());
+        let foreign = SimpleFunctionChip::configure(meta, x, y, z);
+        fibo.inlined.clear();
+        assert_eq!(
+            inline_chip::<Fp, FiboInlineChip<Fp>, _>(
+                &mut fibo,
+                SimpleFunctionChip::construct(foreign)
+            ),
+            Err(InlineError::ForeignColumn("add"))
+        );
+        assert!(fibo.inlined.is_empty());
+    }
+
+    // F(1) = F(2) = 1 to F(5) under the inlined gate named `gate`
+    struct InlinedGateCircuit {
+        gate: &'static str,
+    }
+
+    impl Circuit<Fp> for InlinedGateCircuit {
+        type Config = InlinedFiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { gate: self.gate }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            InlinedFiboCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FiboInlineChip::construct(config.inline);
+            let one = Value::known(Fp::one());
+            chip.assign_inlined(layouter.namespace(|| "inlined"), one, one, 5, self.gate)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn assign_inlined_runs_the_named_gate() {
+        let run = |gate| MockProver::run(4, &InlinedGateCircuit { gate }, vec![vec![]]);
+        run("add").unwrap().assert_satisfied();
+        // 1 * 1 != 2
+        assert!(run("mul").unwrap().verify().is_err());
+        assert!(matches!(run("sub"), Err(Error::Synthesis)));
+    }
+}
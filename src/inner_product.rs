@@ -0,0 +1,112 @@
+// | a   | b   | acc                 | s_first | s_next |
+// | a_0 | b_0 | a_0 * b_0           | 1       | 0      |
+// | a_1 | b_1 | acc_0 + a_1 * b_1   | 0       | 1      |
+// | ... |
+// gate inner product first: s_first * (a * b - acc) == 0
+// gate inner product next: s_next * (acc(prev) + a * b - acc) == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct InnerProductConfig {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub s_first: Selector,
+    pub s_next: Selector,
+}
+
+pub struct InnerProductChip<F: FieldExt> {
+    config: InnerProductConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> InnerProductChip<F> {
+    pub fn construct(config: InnerProductConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        acc: Column<Advice>,
+    ) -> InnerProductConfig {
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(acc);
+
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+
+        meta.create_gate("inner product first", |meta| {
+            let s = meta.query_selector(s_first);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (a * b - acc)]
+        });
+
+        meta.create_gate("inner product next", |meta| {
+            let s = meta.query_selector(s_next);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (prev + a * b - acc)]
+        });
+
+        InnerProductConfig {
+            a,
+            b,
+            acc,
+            s_first,
+            s_next,
+        }
+    }
+
+    pub fn inner_product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[AssignedCell<F, F>],
+        b: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!a.is_empty(), "empty vectors");
+        assert_eq!(a.len(), b.len(), "vector lengths differ");
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "inner product",
+            |mut region| {
+                let mut acc: Option<AssignedCell<F, F>> = None;
+                for (row, (a, b)) in a.iter().zip(b).enumerate() {
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_next.enable(&mut region, row)?;
+                    }
+                    a.copy_advice(|| "a", &mut region, config.a, row)?;
+                    b.copy_advice(|| "b", &mut region, config.b, row)?;
+
+                    let product = a.value().copied() * b.value().copied();
+                    let acc_val = match &acc {
+                        None => product,
+                        Some(prev) => prev.value().copied() + product,
+                    };
+                    acc = Some(region.assign_advice(|| "acc", config.acc, row, || acc_val)?);
+                }
+                Ok(acc.unwrap())
+            },
+        )
+    }
+}
@@ -0,0 +1,202 @@
+// | a | a_inv | selector |
+// gate inverse: (a * a_inv - 1) * selector
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::oracle::HintOracle;
+
+#[derive(Clone, Debug)]
+pub struct FieldInverseConfig {
+    pub a: Column<Advice>,
+    pub a_inv: Column<Advice>,
+    pub selector: Selector,
+}
+
+pub struct FieldInverseChip<F: FieldExt> {
+    config: FieldInverseConfig,
+    oracle: Option<Box<dyn HintOracle<F>>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for FieldInverseChip<F> {
+    type Config = FieldInverseConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FieldInverseChip<F> {
+    pub fn construct(config: FieldInverseConfig) -> Self {
+        Self {
+            config,
+            oracle: None,
+            _marker: PhantomData,
+        }
+    }
+
+    // take a_inv from the oracle instead of inverting in the prover
+    pub fn with_oracle(config: FieldInverseConfig, oracle: impl HintOracle<F> + 'static) -> Self {
+        Self {
+            config,
+            oracle: Some(Box::new(oracle)),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        a_inv: Column<Advice>,
+    ) -> FieldInverseConfig {
+        meta.enable_equality(a);
+        meta.enable_equality(a_inv);
+
+        let selector = meta.selector();
+
+        meta.create_gate("inverse", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(a, Rotation::cur());
+            let a_inv = meta.query_advice(a_inv, Rotation::cur());
+
+            vec![s * (a * a_inv - Expression::Constant(F::one()))]
+        });
+
+        FieldInverseConfig { a, a_inv, selector }
+    }
+
+    // returns the (a, a_inv) cells
+    pub fn invert(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let a_inv = match &self.oracle {
+            Some(oracle) => a.map(|a| oracle.hint(a)),
+            None => {
+                // zero has no inverse and there is no oracle to ask
+                a.error_if_known_and(|a| bool::from(a.is_zero()))?;
+                a.map(|a| a.invert().unwrap())
+            }
+        };
+
+        let config = self.config();
+        layouter.assign_region(
+            || "inverse",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                let a_cell = region.assign_advice(|| "a", config.a, 0, || a)?;
+                let a_inv_cell = region.assign_advice(|| "a_inv", config.a_inv, 0, || a_inv)?;
+                Ok((a_cell, a_inv_cell))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::{EqAffine, Fp},
+        plonk::{keygen_vk, Circuit},
+        poly::commitment::Params,
+    };
+
+    use super::*;
+    use crate::oracle::{LookupOracle, PanicOracle};
+
+    // inverts `a` with a LookupOracle over `hints`, or a PanicOracle without
+    struct InverseCircuit {
+        a: Value<Fp>,
+        hints: Option<Vec<(Fp, Fp)>>,
+    }
+
+    impl Circuit<Fp> for InverseCircuit {
+        type Config = FieldInverseConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: Value::unknown(),
+                hints: self.hints.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let a_inv = meta.advice_column();
+            FieldInverseChip::configure(meta, a, a_inv)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = match &self.hints {
+                Some(hints) => {
+                    FieldInverseChip::with_oracle(config, LookupOracle::new(hints.clone()))
+                }
+                None => FieldInverseChip::with_oracle(config, PanicOracle),
+            };
+            chip.invert(layouter, self.a).map(|_| ())
+        }
+    }
+
+    fn prove(a: u64, hints: Option<Vec<(Fp, Fp)>>) -> MockProver<Fp> {
+        let circuit = InverseCircuit {
+            a: Value::known(Fp::from(a)),
+            hints,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn lookup_oracle_hints_the_inverse() {
+        let five = Fp::from(5);
+        prove(5, Some(vec![(five, five.invert().unwrap())])).assert_satisfied();
+    }
+
+    #[test]
+    fn lookup_oracle_wrong_hint_fails() {
+        assert!(prove(5, Some(vec![(Fp::from(5), Fp::from(3))]))
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "LookupOracle has no hint")]
+    fn lookup_oracle_without_entry_panics() {
+        prove(5, Some(vec![]));
+    }
+
+    #[test]
+    #[should_panic(expected = "PanicOracle asked for a hint")]
+    fn panic_oracle_panics_when_asked() {
+        prove(5, None);
+    }
+
+    #[test]
+    fn panic_oracle_is_not_asked_at_keygen() {
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = InverseCircuit {
+            a: Value::unknown(),
+            hints: None,
+        };
+        keygen_vk(&params, &circuit).unwrap();
+    }
+}
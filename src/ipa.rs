@@ -0,0 +1,65 @@
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey,
+        SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+pub fn keygen<C: Circuit<Fp>>(
+    circuit: &C,
+    k: u32,
+) -> Result<(Params<EqAffine>, ProvingKey<EqAffine>), Error> {
+    let params = Params::new(k);
+    let empty = circuit.without_witnesses();
+    let vk = keygen_vk(&params, &empty)?;
+    let pk = keygen_pk(&params, vk, &empty)?;
+    Ok((params, pk))
+}
+
+pub fn prove<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    public: &[Vec<Fp>],
+) -> Result<Vec<u8>, Error> {
+    let instance: Vec<&[Fp]> = public.iter().map(|column| column.as_slice()).collect();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(params, pk, &[circuit], &[&instance], OsRng, &mut transcript)?;
+    Ok(transcript.finalize())
+}
+
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    public: &[Vec<Fp>],
+) -> Result<(), Error> {
+    let instance: Vec<&[Fp]> = public.iter().map(|column| column.as_slice()).collect();
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, &[&instance], &mut transcript)
+}
+
+// keygen + prove in one go, for callers that don't keep the keys around
+pub fn create_ipa_proof<C: Circuit<Fp>>(
+    circuit: C,
+    public: &[Vec<Fp>],
+    k: u32,
+) -> Result<Vec<u8>, Error> {
+    let (params, pk) = keygen(&circuit, k)?;
+    prove(&params, &pk, circuit, public)
+}
+
+pub fn verify_ipa_proof<C: Circuit<Fp>>(
+    circuit: &C,
+    proof: &[u8],
+    public: &[Vec<Fp>],
+    k: u32,
+) -> Result<(), Error> {
+    let (params, pk) = keygen(circuit, k)?;
+    verify(&params, pk.get_vk(), proof, public)
+}
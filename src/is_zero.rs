@@ -0,0 +1,154 @@
+// | value | inv | out | selector |
+// gate is zero: selector * (value * inv + out - 1) == 0, selector * value * out == 0
+// out is 1 when value is 0, otherwise inv is forced to value^-1 and out to 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct IsZeroConfig {
+    pub value: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub out: Column<Advice>,
+    pub selector: Selector,
+}
+
+pub struct IsZeroChip<F: FieldExt> {
+    config: IsZeroConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> IsZeroChip<F> {
+    pub fn construct(config: IsZeroConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        inv: Column<Advice>,
+        out: Column<Advice>,
+    ) -> IsZeroConfig {
+        meta.enable_equality(value);
+        meta.enable_equality(out);
+        let selector = meta.selector();
+
+        meta.create_gate("is zero", |meta| {
+            let s = meta.query_selector(selector);
+            let value = meta.query_advice(value, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![
+                s.clone() * (value.clone() * inv + out.clone() - one),
+                s * value * out,
+            ]
+        });
+
+        IsZeroConfig {
+            value,
+            inv,
+            out,
+            selector,
+        }
+    }
+
+    // returns the boolean `value == 0`
+    pub fn is_zero(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "is zero",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                value.copy_advice(|| "value", &mut region, config.value, 0)?;
+
+                let inv = value.value().map(|v| v.invert().unwrap_or(F::zero()));
+                region.assign_advice(|| "inv", config.inv, 0, || inv)?;
+
+                let out = value.value().map(|v| F::from(bool::from(v.is_zero())));
+                region.assign_advice(|| "out", config.out, 0, || out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // exposes is_zero(value)
+    struct IsZeroCircuit(Value<Fp>);
+
+    impl Circuit<Fp> for IsZeroCircuit {
+        type Config = (IsZeroConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(Value::unknown())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [value, inv, out] = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (IsZeroChip::configure(meta, value, inv, out), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let value = layouter.assign_region(
+                || "value",
+                |mut region| region.assign_advice(|| "value", config.value, 0, || self.0),
+            )?;
+            let out =
+                IsZeroChip::construct(config).is_zero(layouter.namespace(|| "is zero"), &value)?;
+            layouter.constrain_instance(out.cell(), instance, 0)
+        }
+    }
+
+    fn prove(value: Fp, out: u64) -> MockProver<Fp> {
+        MockProver::run(
+            4,
+            &IsZeroCircuit(Value::known(value)),
+            vec![vec![Fp::from(out)]],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn zero_is_zero() {
+        prove(Fp::zero(), 1).assert_satisfied();
+        assert!(prove(Fp::zero(), 0).verify().is_err());
+    }
+
+    #[test]
+    fn non_zero_is_not_zero() {
+        for value in [Fp::one(), Fp::from(5), -Fp::one()] {
+            prove(value, 0).assert_satisfied();
+            assert!(prove(value, 1).verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,319 @@
+// Synthesis split into numbered layers that only meet through the instance
+// column: a layer constrains the cells it hands on to instance rows and the next
+// layer copies them back in from the same rows. A single layer is then a circuit
+// of its own that can be proven on another machine against the shared public
+// inputs, and all layers together lay out the monolithic circuit.
+
+use std::{marker::PhantomData, rc::Rc};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::{MockProver, VerifyFailure},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::fibo1::{ACell, FiboChip, FiboConfig};
+
+pub trait Layer<F: FieldExt>: Circuit<F> {
+    fn num_layers(&self) -> usize;
+
+    fn synthesize_layer(
+        &self,
+        config: Self::Config,
+        layer: usize,
+        layouter: impl Layouter<F>,
+    ) -> Result<(), Error>;
+}
+
+/// Lays out one layer of `circuit`, or all of them in order when `layer` is None.
+pub struct LayeredCircuit<F: FieldExt, C: Layer<F>> {
+    pub circuit: Rc<C>,
+    pub layer: Option<usize>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, C: Layer<F>> LayeredCircuit<F, C> {
+    pub fn new(circuit: Rc<C>, layer: Option<usize>) -> Self {
+        Self {
+            circuit,
+            layer,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, C: Layer<F>> Circuit<F> for LayeredCircuit<F, C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(Rc::new(self.circuit.without_witnesses()), self.layer)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let layers = match self.layer {
+            Some(layer) => layer..layer + 1,
+            None => 0..self.circuit.num_layers(),
+        };
+        for layer in layers {
+            self.circuit.synthesize_layer(
+                config.clone(),
+                layer,
+                layouter.namespace(|| format!("layer {}", layer)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum LayerFailure {
+    Synthesis {
+        layer: usize,
+        error: Error,
+    },
+    Unsatisfied {
+        layer: usize,
+        failures: Vec<VerifyFailure>,
+    },
+}
+
+/// Runs the layers one after another on this machine, each checked on its own
+/// against the full instance.
+pub struct LayerCoordinator;
+
+impl LayerCoordinator {
+    pub fn run<F: FieldExt, C: Layer<F>>(
+        circuit: C,
+        k: u32,
+        instance: Vec<Vec<F>>,
+    ) -> Result<(), Vec<LayerFailure>> {
+        let circuit = Rc::new(circuit);
+        let mut failures = vec![];
+        for layer in 0..circuit.num_layers() {
+            let layered = LayeredCircuit::new(circuit.clone(), Some(layer));
+            match MockProver::run(k, &layered, instance.clone()) {
+                Ok(prover) => {
+                    if let Err(layer_failures) = prover.verify() {
+                        failures.push(LayerFailure::Unsatisfied {
+                            layer,
+                            failures: layer_failures,
+                        });
+                    }
+                }
+                Err(error) => failures.push(LayerFailure::Synthesis { layer, error }),
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LayeredFiboConfig {
+    pub fibo: FiboConfig,
+    pub instance: Column<Instance>,
+}
+
+/// `FiboCircuit` split at F(6): the first half runs F(1), F(2) up to F(6), the
+/// second half picks up from (F(5), F(6)) and runs to F(10).
+///
+/// instance: | F(5) | F(6) | F(10) |
+#[derive(Default)]
+pub struct LayeredFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for LayeredFiboCircuit<F> {
+    type Config = LayeredFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        LayeredFiboConfig {
+            fibo: FiboChip::configure(meta, advices, false),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        for layer in 0..self.num_layers() {
+            self.synthesize_layer(config.clone(), layer, layouter.namespace(|| "layer"))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> Layer<F> for LayeredFiboCircuit<F> {
+    fn num_layers(&self) -> usize {
+        2
+    }
+
+    fn synthesize_layer(
+        &self,
+        config: Self::Config,
+        layer: usize,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FiboChip::construct(config.fibo.clone());
+        match layer {
+            0 => {
+                let (_, mut prev_b, mut prev_c) =
+                    chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
+                for _ in 4..7 {
+                    let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+                    prev_b = prev_c;
+                    prev_c = c;
+                }
+                layouter.constrain_instance(prev_b.0.cell(), config.instance, 0)?;
+                layouter.constrain_instance(prev_c.0.cell(), config.instance, 1)
+            }
+            1 => {
+                let (mut prev_b, mut prev_c) = layouter.assign_region(
+                    || "F(5), F(6)",
+                    |mut region| {
+                        let mut load = |row| {
+                            region
+                                .assign_advice_from_instance(
+                                    || "boundary",
+                                    config.instance,
+                                    row,
+                                    config.fibo.advice[row],
+                                    0,
+                                )
+                                .map(ACell)
+                        };
+                        Ok((load(0)?, load(1)?))
+                    },
+                )?;
+                for _ in 7..11 {
+                    let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+                    prev_b = prev_c;
+                    prev_c = c;
+                }
+                layouter.constrain_instance(prev_c.0.cell(), config.instance, 2)
+            }
+            _ => Err(Error::Synthesis),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, recorder::record};
+
+    fn layered(a: u64, b: u64) -> LayeredFiboCircuit<Fp> {
+        LayeredFiboCircuit {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        }
+    }
+
+    // the last c cell FiboCircuit assigns, its F(10)
+    fn monolithic(a: u64, b: u64) -> Fp {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        };
+        let recorder = record(&circuit, 5, vec![]).unwrap();
+        let (_, last) = recorder
+            .advice
+            .iter()
+            .filter(|((column, _), _)| *column == 2)
+            .max_by_key(|((_, row), _)| *row)
+            .unwrap();
+        last.unwrap()
+    }
+
+    fn instance(boundary: [u64; 2], out: Fp) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::from(boundary[0]), Fp::from(boundary[1]), out]]
+    }
+
+    #[test]
+    fn combined_output_matches_monolithic() {
+        assert_eq!(monolithic(1, 1), Fp::from(55));
+        let public = instance([5, 8], monolithic(1, 1));
+        assert!(LayerCoordinator::run(layered(1, 1), 5, public.clone()).is_ok());
+
+        let all = LayeredCircuit::new(Rc::new(layered(1, 1)), None);
+        MockProver::run(5, &all, public).unwrap().assert_satisfied();
+
+        // F(5) = 2a + 3b, F(6) = 3a + 5b
+        let public = instance([2 * 2 + 3 * 3, 3 * 2 + 5 * 3], monolithic(2, 3));
+        assert!(LayerCoordinator::run(layered(2, 3), 5, public).is_ok());
+    }
+
+    #[test]
+    fn each_layer_is_its_own_circuit() {
+        let circuit = Rc::new(layered(1, 1));
+        for layer in 0..2 {
+            let single = LayeredCircuit::new(circuit.clone(), Some(layer));
+            MockProver::run(5, &single, instance([5, 8], Fp::from(55)))
+                .unwrap()
+                .assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn wrong_output_fails_the_second_layer_only() {
+        let failures =
+            LayerCoordinator::run(layered(1, 1), 5, instance([5, 8], Fp::from(56))).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            failures[0],
+            LayerFailure::Unsatisfied { layer: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn wrong_boundary_fails_the_first_layer() {
+        // F(10) from (5, 9) is 60, so only the hand over is wrong
+        let failures =
+            LayerCoordinator::run(layered(1, 1), 5, instance([5, 9], Fp::from(60))).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            failures[0],
+            LayerFailure::Unsatisfied { layer: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn unknown_layer_is_a_synthesis_error() {
+        let circuit = LayeredCircuit::new(Rc::new(layered(1, 1)), Some(2));
+        assert!(matches!(
+            MockProver::run(5, &circuit, instance([5, 8], Fp::from(55))),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+#![allow(clippy::type_complexity)]
+
+pub mod abi;
+pub mod abs;
+pub mod access_control;
+pub mod allocator;
+pub mod autocomplete;
+pub mod batch_verify;
+pub mod bilinear;
+pub mod bivariate;
+pub mod boolean;
+pub mod chain;
+pub mod checked_assign;
+pub mod compact_proof;
+pub mod compare;
+pub mod compress;
+pub mod concurrent;
+pub mod conditional_gate;
+pub mod congruence;
+pub mod copy_manager;
+pub mod cs_clone;
+pub mod cs_validate;
+pub mod dependency;
+pub mod dynamic_lookup;
+pub mod equiv_check;
+pub mod error_reporter;
+pub mod expand;
+pub mod feistel;
+pub mod fiat_shamir;
+pub mod fibo1;
+pub mod fibo_adaptive;
+pub mod fibo_agg;
+pub mod fibo_all_outputs;
+pub mod fibo_batch;
+pub mod fibo_cache;
+pub mod fibo_holes;
+pub mod fibo_lookahead;
+pub mod fibo_modular;
+pub mod fibo_nested;
+pub mod fibo_multiphase;
+pub mod fibo_online;
+pub mod fibo_pir;
+pub mod fibo_recursive;
+pub mod fibo_segment;
+pub mod fibo_single_region;
+pub mod fibo_sum;
+pub mod fibo_table;
+pub mod fibo_table_gen;
+pub mod fibo_v1;
+pub mod fixed_point;
+pub mod fp2;
+pub mod function;
+pub mod function_v2;
+pub mod gate_flatten;
+pub mod gate_parse;
+pub mod gate_profiler;
+pub mod gradient_descent;
+pub mod hamming;
+pub mod hash;
+pub mod homomorphic;
+pub mod inline;
+pub mod inner_product;
+pub mod inverse;
+pub mod ipa;
+pub mod is_zero;
+pub mod layered;
+pub mod linearize;
+pub mod lookup_range;
+pub mod matrix;
+pub mod migration;
+pub mod monitor;
+pub mod multi_prover;
+pub mod mux;
+pub mod noise;
+pub mod ntt;
+pub mod oracle;
+pub mod packing;
+pub mod padded;
+pub mod perm_analyze;
+pub mod poly_eval;
+pub mod poly_verify;
+pub mod proof_cache;
+pub mod proof_size;
+pub mod propagate;
+pub mod random_oracle;
+pub mod range_check;
+pub mod recorder;
+pub mod recurrence;
+pub mod selective_disclosure;
+pub mod self_contained_proof;
+pub mod set_membership;
+pub mod shared_witness;
+pub mod size_predictor;
+pub mod sorting;
+pub mod soundness_test;
+pub mod sparse_cs;
+pub mod state_machine;
+pub mod static_assert;
+pub mod stego;
+pub mod sum;
+pub mod sumcheck;
+pub mod symmetry;
+pub mod test_vectors;
+pub mod threshold;
+pub mod timestamp;
+pub mod transcript_replay;
+pub mod triple_mul;
+pub mod tuple_hash;
+pub mod verkle;
+pub mod vote;
+pub mod weighted_sum;
+pub mod witness_extract;
+pub mod xor;
@@ -0,0 +1,332 @@
+// Rewrites a gate so no product in it has degree above 2, not counting the
+// selector in front. Whenever both sides of a product multiply past degree 2,
+// a degree-2 side is moved into a fresh advice cell on the same row:
+//
+// s * (x * y * z - out)   becomes   s * (t * z - out)
+//                                   s * (t - x * y)
+//
+// The auxiliary cells are plain witnesses, the circuit has to assign each one
+// the value of its defining expression on every row the gate is enabled.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+// peels selector factors off the front of a gate
+pub(crate) fn split_selector<F: FieldExt>(
+    expr: Expression<F>,
+) -> (Option<Expression<F>>, Expression<F>) {
+    match expr {
+        Expression::Product(a, b) => match (*a, *b) {
+            (s @ Expression::Selector(_), body) | (body, s @ Expression::Selector(_)) => {
+                let (inner, body) = split_selector(body);
+                (Some(inner.map_or(s.clone(), |inner| s * inner)), body)
+            }
+            (a, b) => (None, a * b),
+        },
+        expr => (None, expr),
+    }
+}
+
+pub(crate) fn gated<F: FieldExt>(selector: &Option<Expression<F>>, body: Expression<F>) -> Expression<F> {
+    match selector {
+        Some(selector) => selector.clone() * body,
+        None => body,
+    }
+}
+
+// returns `expr` with degree at most 2, pushing (aux, definition) pairs
+fn rewrite<F: FieldExt>(
+    expr: Expression<F>,
+    aux: &mut impl FnMut() -> Expression<F>,
+    definitions: &mut Vec<(Expression<F>, Expression<F>)>,
+) -> Expression<F> {
+    match expr {
+        Expression::Negated(a) => -rewrite(*a, aux, definitions),
+        Expression::Scaled(a, c) => rewrite(*a, aux, definitions) * c,
+        Expression::Sum(a, b) => rewrite(*a, aux, definitions) + rewrite(*b, aux, definitions),
+        Expression::Product(a, b) => {
+            let mut a = rewrite(*a, aux, definitions);
+            let mut b = rewrite(*b, aux, definitions);
+            if a.degree() + b.degree() > 2 && a.degree() == 2 {
+                let cell = aux();
+                definitions.push((cell.clone(), a));
+                a = cell;
+            }
+            if a.degree() + b.degree() > 2 && b.degree() == 2 {
+                let cell = aux();
+                definitions.push((cell.clone(), b));
+                b = cell;
+            }
+            a * b
+        }
+        expr => expr,
+    }
+}
+
+// the rewritten gate followed by one constraint per auxiliary cell, and the
+// expressions the auxiliary cells stand for
+fn linearize<F: FieldExt>(
+    expr: Expression<F>,
+    aux: &mut impl FnMut() -> Expression<F>,
+) -> (Vec<Expression<F>>, Vec<Expression<F>>) {
+    let (selector, body) = split_selector(expr);
+    let mut definitions = vec![];
+    let body = rewrite(body, aux, &mut definitions);
+
+    let mut constraints = vec![gated(&selector, body)];
+    let mut values = vec![];
+    for (cell, definition) in definitions {
+        constraints.push(gated(&selector, cell - definition.clone()));
+        values.push(definition);
+    }
+    (constraints, values)
+}
+
+fn aux_count<F: FieldExt>(expr: &Expression<F>) -> usize {
+    // any degree 1 placeholder counts the same
+    let mut placeholder = || Expression::Advice {
+        query_index: 0,
+        column_index: 0,
+        rotation: Rotation::cur(),
+    };
+    linearize(expr.clone(), &mut placeholder).1.len()
+}
+
+fn query_all<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+) -> std::vec::IntoIter<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| meta.query_advice(*column, Rotation::cur()))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Rewrites `expr` into constraints of degree at most 2 past the selector. The
+/// auxiliary cells are allocated on `cs` and their definitions registered as a
+/// `linearize` gate, the returned constraints take the place of `expr`.
+pub fn linearize_gate<F: FieldExt>(
+    expr: Expression<F>,
+    cs: &mut ConstraintSystem<F>,
+) -> Vec<Expression<F>> {
+    let count = aux_count(&expr);
+    if count == 0 {
+        return vec![expr];
+    }
+    let columns: Vec<Column<Advice>> = (0..count).map(|_| cs.advice_column()).collect();
+
+    let mut rewritten = vec![];
+    cs.create_gate("linearize", |meta| {
+        let mut cells = query_all(meta, &columns);
+        let (mut constraints, _) = linearize(expr, &mut || cells.next().unwrap());
+        rewritten.push(constraints.remove(0));
+        constraints
+    });
+    rewritten
+}
+
+/// `create_gate` with every constraint linearized. Returns the auxiliary columns
+/// in order, each with the expression its cells have to hold.
+///
+/// `constraints` runs twice, first on a copy of `meta` to see how many auxiliary
+/// columns are needed, so it must query the same cells both times.
+pub fn create_linearized_gate<F: FieldExt>(
+    meta: &mut ConstraintSystem<F>,
+    name: &'static str,
+    constraints: impl Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+) -> Vec<(Column<Advice>, Expression<F>)> {
+    let mut probe = vec![];
+    meta.clone().create_gate(name, |meta| {
+        probe = constraints(meta);
+        probe.clone()
+    });
+    let count = probe.iter().map(aux_count).sum();
+    let columns: Vec<Column<Advice>> = (0..count).map(|_| meta.advice_column()).collect();
+
+    // queried in the same order as on the copy, so the probe's query indices hold
+    let mut values = vec![];
+    meta.create_gate(name, |meta| {
+        constraints(meta);
+        let mut cells = query_all(meta, &columns);
+        probe
+            .into_iter()
+            .flat_map(|expr| {
+                let (constraints, aux) = linearize(expr, &mut || cells.next().unwrap());
+                values.extend(aux);
+                constraints
+            })
+            .collect::<Vec<_>>()
+    });
+    columns.into_iter().zip(values).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Error, Selector},
+    };
+
+    use super::*;
+    use crate::recorder::parse_index;
+
+    // s * (x * y * z - out), as written or linearized
+    #[derive(Clone, Copy)]
+    struct CubicCircuit<const LINEARIZED: bool> {
+        inputs: [u64; 3],
+        out: u64,
+        // value of the auxiliary cell, x * y when honest
+        aux: Option<u64>,
+    }
+
+    type CubicConfig = (
+        [Column<Advice>; 4],
+        Selector,
+        Vec<(Column<Advice>, Expression<Fp>)>,
+    );
+
+    impl<const LINEARIZED: bool> Circuit<Fp> for CubicCircuit<LINEARIZED> {
+        type Config = CubicConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            *self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let columns = [(); 4].map(|_| meta.advice_column());
+            let s = meta.selector();
+            let cubic = move |meta: &mut VirtualCells<'_, Fp>| {
+                let [x, y, z, out] =
+                    columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![meta.query_selector(s) * (x * y * z - out)]
+            };
+            let aux = match LINEARIZED {
+                true => create_linearized_gate(meta, "cubic", cubic),
+                false => {
+                    meta.create_gate("cubic", cubic);
+                    vec![]
+                }
+            };
+            (columns, s, aux)
+        }
+
+        fn synthesize(
+            &self,
+            (columns, s, aux): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let [x, y, z] = self.inputs;
+            layouter.assign_region(
+                || "cubic",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    for (column, value) in columns.iter().zip([x, y, z, self.out]) {
+                        region.assign_advice(
+                            || "cell",
+                            *column,
+                            0,
+                            || Value::known(Fp::from(value)),
+                        )?;
+                    }
+                    for (column, _) in &aux {
+                        let value = self.aux.unwrap_or(x * y);
+                        region.assign_advice(
+                            || "aux",
+                            *column,
+                            0,
+                            || Value::known(Fp::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn advice_columns(meta: &ConstraintSystem<Fp>) -> usize {
+        parse_index(&format!("{:?}", meta.pinned()), "num_advice_columns: ")
+    }
+
+    fn prove<const LINEARIZED: bool>(out: u64, aux: Option<u64>) -> MockProver<Fp> {
+        let circuit = CubicCircuit::<LINEARIZED> {
+            inputs: [2, 3, 4],
+            out,
+            aux,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn cubic_gate_gets_one_auxiliary_cell() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let (_, _, aux) = CubicCircuit::<true>::configure(&mut meta);
+        assert_eq!(aux.len(), 1);
+        assert_eq!(aux[0].1.degree(), 2);
+        // selector times a degree 2 body
+        assert_eq!(meta.degree(), 3);
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        CubicCircuit::<false>::configure(&mut meta);
+        assert_eq!(meta.degree(), 4);
+    }
+
+    #[test]
+    fn same_satisfying_assignment() {
+        prove::<false>(24, None).assert_satisfied();
+        prove::<true>(24, None).assert_satisfied();
+        assert!(prove::<false>(25, None).verify().is_err());
+        assert!(prove::<true>(25, None).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_auxiliary_cell_fails() {
+        // t * z = out holds with t = 8, the definition t = x * y doesn't
+        assert!(prove::<true>(32, Some(8)).verify().is_err());
+    }
+
+    #[test]
+    fn linearize_gate_registers_the_definitions() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let columns = [(); 4].map(|_| meta.advice_column());
+        let mut expr = None;
+        meta.create_gate("probe", |meta| {
+            let [x, y, z, out] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+            let cubic = x * y * z - out;
+            expr = Some(cubic.clone());
+            vec![cubic]
+        });
+
+        let rewritten = linearize_gate(expr.unwrap(), &mut meta);
+        assert_eq!(rewritten.len(), 1);
+        assert_eq!(rewritten[0].degree(), 2);
+        assert_eq!(advice_columns(&meta), 5);
+
+        // already degree 2, left alone
+        let quadratic = Expression::Constant(Fp::one()) * rewritten[0].clone();
+        assert_eq!(linearize_gate(quadratic, &mut meta).len(), 1);
+        assert_eq!(advice_columns(&meta), 5);
+    }
+
+    #[test]
+    fn split_selector_and_gated_round_trip() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let s = meta.selector();
+        let body = Expression::Constant(Fp::from(3)) * Expression::Constant(Fp::from(5));
+        let (selector, split) = split_selector(Expression::Selector(s) * body);
+        assert!(matches!(selector, Some(Expression::Selector(_))));
+        assert_eq!(split.degree(), 0);
+        assert_eq!(gated(&selector, split.clone()).degree(), 1);
+
+        // no selector in front, nothing to peel
+        let (selector, body) = split_selector(split);
+        assert!(selector.is_none());
+        assert_eq!(gated(&selector, body).degree(), 0);
+    }
+}
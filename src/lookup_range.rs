@@ -0,0 +1,208 @@
+// running sum over 10-bit windows, least significant window first
+// | z                      | q_lookup | q_short | s_end |
+// | x                      | 1        | 0       | 0     |
+// | (x - w_0) / 2^10       | 1        | 0       | 0     |
+// | ...                    |          |         |       |
+// | top window             | 1        | 1       | 0     |
+// | 0                      | 0        | 0       | 1     |
+// lookup window: q_lookup * (z(cur) - 2^10 * z(next)) in table
+// lookup short: q_short * z(cur) * 2^(10 - BITS % 10) in table, so the top
+//   window is below 2^(BITS % 10) when BITS is not a multiple of 10
+// gate end: s_end * z == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+pub const LOOKUP_WINDOW_BITS: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct LookupRangeCheckConfig {
+    pub z: Column<Advice>,
+    pub table: TableColumn,
+    pub q_lookup: Selector,
+    pub q_short: Selector,
+    pub s_end: Selector,
+}
+
+pub struct LookupRangeCheckChip<F: FieldExt, const BITS: usize> {
+    config: LookupRangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> LookupRangeCheckChip<F, BITS> {
+    const WINDOWS: usize = BITS.div_ceil(LOOKUP_WINDOW_BITS);
+
+    pub fn construct(config: LookupRangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, z: Column<Advice>) -> LookupRangeCheckConfig {
+        meta.enable_equality(z);
+
+        let table = meta.lookup_table_column();
+        let q_lookup = meta.complex_selector();
+        let q_short = meta.complex_selector();
+        let s_end = meta.selector();
+
+        let window = F::from(1 << LOOKUP_WINDOW_BITS);
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            vec![(q * (z_cur - z_next * window), table)]
+        });
+
+        let shift =
+            F::from(1 << ((LOOKUP_WINDOW_BITS - BITS % LOOKUP_WINDOW_BITS) % LOOKUP_WINDOW_BITS));
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_short);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            vec![(q * z_cur * Expression::Constant(shift), table)]
+        });
+
+        meta.create_gate("lookup range end", |meta| {
+            let s = meta.query_selector(s_end);
+            let z = meta.query_advice(z, Rotation::cur());
+            vec![s * z]
+        });
+
+        LookupRangeCheckConfig {
+            z,
+            table,
+            q_lookup,
+            q_short,
+            s_end,
+        }
+    }
+
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "10-bit table",
+            |mut table| {
+                for value in 0..1 << LOOKUP_WINDOW_BITS {
+                    table.assign_cell(
+                        || "value",
+                        self.config.table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // returns the cell holding x
+    pub fn check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let window_inv = F::from(1 << LOOKUP_WINDOW_BITS).invert().unwrap();
+
+        layouter.assign_region(
+            || "lookup range check",
+            |mut region| {
+                let x_cell = region.assign_advice(|| "x", config.z, 0, || x)?;
+                let mut z = x_cell.clone();
+                for row in 0..Self::WINDOWS {
+                    config.q_lookup.enable(&mut region, row)?;
+
+                    let next = z.value().map(|z| {
+                        let repr = z.to_repr();
+                        let bytes = repr.as_ref();
+                        let low = bytes[0] as u64 | (bytes[1] as u64) << 8;
+                        let window = F::from(low & ((1 << LOOKUP_WINDOW_BITS) - 1));
+                        (*z - window) * window_inv
+                    });
+                    z = region.assign_advice(|| "z", config.z, row + 1, || next)?;
+                }
+                if !BITS.is_multiple_of(LOOKUP_WINDOW_BITS) {
+                    config.q_short.enable(&mut region, Self::WINDOWS - 1)?;
+                }
+                config.s_end.enable(&mut region, Self::WINDOWS)?;
+                Ok(x_cell)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    use super::*;
+
+    struct RangeCircuit<const BITS: usize> {
+        x: Value<Fp>,
+    }
+
+    impl<const BITS: usize> Circuit<Fp> for RangeCircuit<BITS> {
+        type Config = LookupRangeCheckConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                x: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let z = meta.advice_column();
+            LookupRangeCheckChip::<Fp, BITS>::configure(meta, z)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = LookupRangeCheckChip::<Fp, BITS>::construct(config);
+            chip.load_table(layouter.namespace(|| "table"))?;
+            chip.check(layouter.namespace(|| "x"), self.x).map(|_| ())
+        }
+    }
+
+    fn prove<const BITS: usize>(x: u64) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = RangeCircuit::<BITS> {
+            x: Value::known(Fp::from(x)),
+        };
+        MockProver::run(11, &circuit, vec![]).unwrap().verify()
+    }
+
+    #[test]
+    fn twenty_bits() {
+        for x in [0, 1, 1023, 1024, (1 << 20) - 1] {
+            assert_eq!(prove::<20>(x), Ok(()), "{}", x);
+        }
+        // every window of 2^20 is in the table, what is left over fails the end gate
+        assert!(prove::<20>(1 << 20).is_err());
+    }
+
+    #[test]
+    fn thirty_two_bits() {
+        for x in [0, 1 << 20, (1 << 30) + 5, (1 << 32) - 1] {
+            assert_eq!(prove::<32>(x), Ok(()), "{}", x);
+        }
+        // the top window is 4, which the short lookup rejects
+        let failures = prove::<32>(1 << 32).unwrap_err();
+        assert!(failures
+            .iter()
+            .any(|failure| matches!(failure, VerifyFailure::Lookup { .. })));
+    }
+}
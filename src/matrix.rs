@@ -0,0 +1,102 @@
+// C = A * B for N x N matrices, one inner product per entry:
+// c_ij = <row_i(A), col_j(B)>
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::inner_product::{InnerProductChip, InnerProductConfig};
+
+/// Row major matrix of assigned cells.
+pub type Matrix<F> = Vec<Vec<AssignedCell<F, F>>>;
+
+#[derive(Debug, Clone)]
+pub struct MatrixMultiplyConfig {
+    pub inner: InnerProductConfig,
+    pub constant: Column<Fixed>,
+}
+
+// lays a constant matrix out row by row down `column`, each entry fixed by
+// the constant column
+pub(crate) fn load_constant_matrix<F: FieldExt, const N: usize>(
+    mut layouter: impl Layouter<F>,
+    column: Column<Advice>,
+    matrix: [[F; N]; N],
+) -> Result<Matrix<F>, Error> {
+    layouter.assign_region(
+        || "load matrix",
+        |mut region| {
+            matrix
+                .iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    row.iter()
+                        .enumerate()
+                        .map(|(j, entry)| {
+                            region.assign_advice_from_constant(|| "m", column, i * N + j, *entry)
+                        })
+                        .collect()
+                })
+                .collect()
+        },
+    )
+}
+
+pub struct MatrixMultiplyChip<F: FieldExt, const N: usize> {
+    config: MatrixMultiplyConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> MatrixMultiplyChip<F, N> {
+    pub fn construct(config: MatrixMultiplyConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constant: Column<Fixed>,
+    ) -> MatrixMultiplyConfig {
+        meta.enable_constant(constant);
+
+        MatrixMultiplyConfig {
+            inner: InnerProductChip::configure(meta, advice[0], advice[1], advice[2]),
+            constant,
+        }
+    }
+
+    pub fn load_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        matrix: [[F; N]; N],
+    ) -> Result<Matrix<F>, Error> {
+        load_constant_matrix(layouter, self.config.inner.a, matrix)
+    }
+
+    pub fn multiply(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Matrix<F>,
+        b: &Matrix<F>,
+    ) -> Result<Matrix<F>, Error> {
+        let inner = InnerProductChip::<F>::construct(self.config.inner.clone());
+
+        (0..N)
+            .map(|i| {
+                (0..N)
+                    .map(|j| {
+                        let column: Vec<_> = b.iter().map(|row| row[j].clone()).collect();
+                        inner.inner_product(layouter.namespace(|| "c_ij"), &a[i], &column)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
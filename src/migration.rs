@@ -0,0 +1,108 @@
+// Witnesses before halo2_proofs 0.2 were `Option<F>`, with None standing for
+// "not known at keygen". 0.2 replaced them with `Value<F>`, which can't be
+// unwrapped outside the circuit. The helpers here convert between the two, and
+// `LegacyCircuit` keeps the old Option based FiboCircuit constructor working
+// while callers move to `FiboCircuit { a: Value::known(..), .. }`.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+    fibo1::{FiboCircuit, FiboConfig},
+    recorder::value_of,
+};
+
+pub fn migrate_option_to_value<F: FieldExt>(old_witness: Vec<Option<F>>) -> Vec<Value<F>> {
+    old_witness
+        .into_iter()
+        .map(|value| value.map_or_else(Value::unknown, Value::known))
+        .collect()
+}
+
+pub fn migrate_value_to_option<F: FieldExt>(new_witness: Vec<Value<F>>) -> Vec<Option<F>> {
+    new_witness.into_iter().map(value_of).collect()
+}
+
+#[deprecated(note = "use `FiboCircuit` with `Value` witnesses")]
+#[derive(Default)]
+pub struct LegacyCircuit<F> {
+    pub a: Option<F>,
+    pub b: Option<F>,
+}
+
+#[allow(deprecated)]
+impl<F: FieldExt> LegacyCircuit<F> {
+    fn migrated(&self) -> FiboCircuit<F> {
+        match migrate_option_to_value(vec![self.a, self.b])[..] {
+            [a, b] => FiboCircuit { a, b },
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl<F: FieldExt> Circuit<F> for LegacyCircuit<F> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        FiboCircuit::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.migrated().synthesize(config, layouter)
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::record;
+
+    #[test]
+    fn option_value_option_round_trip() {
+        let old = vec![Some(Fp::one()), None, Some(Fp::from(55)), None];
+        assert_eq!(
+            migrate_value_to_option(migrate_option_to_value(old.clone())),
+            old
+        );
+        assert!(migrate_option_to_value::<Fp>(vec![]).is_empty());
+    }
+
+    #[test]
+    fn value_option_value_round_trip() {
+        let new = vec![Value::known(Fp::from(3)), Value::unknown()];
+        let back = migrate_option_to_value(migrate_value_to_option(new.clone()));
+        assert_eq!(migrate_value_to_option(back), migrate_value_to_option(new));
+    }
+
+    #[test]
+    fn legacy_circuit_lays_out_like_fibo_circuit() {
+        let legacy = LegacyCircuit {
+            a: Some(Fp::one()),
+            b: Some(Fp::one()),
+        };
+        MockProver::run(4, &legacy, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let current = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        let legacy = record(&legacy, 4, vec![]).unwrap();
+        let current = record(&current, 4, vec![]).unwrap();
+        assert_eq!(legacy.advice, current.advice);
+        assert_eq!(legacy.selectors, current.selectors);
+    }
+}
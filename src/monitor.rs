@@ -0,0 +1,266 @@
+// Calls back while a circuit is being synthesized, once per advice cell that is
+// assigned a known value. Synthesis runs the circuit's own floor planner
+// against a `Recorder` wrapped in an `Assignment` that reports every advice
+// assignment before passing it on, the same way `PaddedFloorPlanner` tracks
+// cells.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+use crate::recorder::{column_index, constant_columns, value_of, Recorder};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellEvent<F> {
+    // empty for cells assigned outside of a region
+    pub region: String,
+    pub column: usize,
+    pub row: usize,
+    pub value: F,
+}
+
+pub struct CircuitInvariantMonitor<'a, F: FieldExt> {
+    // advice column index, `None` watches every column
+    watches: Vec<(Option<usize>, Box<dyn Fn(&CellEvent<F>) + 'a>)>,
+}
+
+impl<'a, F: FieldExt> Default for CircuitInvariantMonitor<'a, F> {
+    fn default() -> Self {
+        Self { watches: vec![] }
+    }
+}
+
+impl<'a, F: FieldExt> CircuitInvariantMonitor<'a, F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `callback` with every value assigned in the advice column at `column`.
+    pub fn watch_cell(&mut self, column: usize, callback: impl Fn(F) + 'a) {
+        self.watches
+            .push((Some(column), Box::new(move |event| callback(event.value))));
+    }
+
+    /// Calls `callback` for every advice assignment.
+    pub fn watch_all(&mut self, callback: impl Fn(&CellEvent<F>) + 'a) {
+        self.watches.push((None, Box::new(callback)));
+    }
+
+    fn notify(&self, event: &CellEvent<F>) {
+        for (column, callback) in &self.watches {
+            if column.is_none_or(|column| column == event.column) {
+                callback(event);
+            }
+        }
+    }
+
+    /// Synthesizes `circuit` on a 2^k row table, calling back as cells are
+    /// assigned, and returns everything that was recorded.
+    pub fn run<C: Circuit<F>>(
+        &self,
+        circuit: &C,
+        k: u32,
+        instance: Vec<Vec<F>>,
+    ) -> Result<Recorder<F>, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+        let constants = constant_columns(&cs);
+
+        let mut recorder = Recorder::new(k, instance);
+        let mut assignment = MonitoredAssignment {
+            cs: &mut recorder,
+            monitor: self,
+            region: String::new(),
+        };
+        C::FloorPlanner::synthesize(&mut assignment, circuit, config, constants)?;
+        Ok(recorder)
+    }
+}
+
+struct MonitoredAssignment<'m, 'a, F: FieldExt, CS> {
+    cs: &'m mut CS,
+    monitor: &'m CircuitInvariantMonitor<'a, F>,
+    region: String,
+}
+
+impl<'m, 'a, F: FieldExt, CS: Assignment<F>> Assignment<F> for MonitoredAssignment<'m, 'a, F, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.region = name.clone();
+        self.cs.enter_region(|| name)
+    }
+
+    fn exit_region(&mut self) {
+        self.region.clear();
+        self.cs.exit_region()
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value: Value<Assigned<F>> = to().map(|v| v.into());
+        self.cs.assign_advice(annotation, column, row, || value)?;
+
+        if let Some(value) = value_of(value.map(|v| v.evaluate())) {
+            self.monitor.notify(&CellEvent {
+                region: self.region.clone(),
+                column: column_index(column),
+                row,
+                value,
+            });
+        }
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.cs.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.cs.fill_from_row(column, row, to)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::function::FunctionCircuit;
+
+    fn z_column() -> usize {
+        let config = FunctionCircuit::<Fp>::configure(&mut ConstraintSystem::default());
+        column_index(config.z)
+    }
+
+    #[test]
+    fn watches_the_z_column_of_the_function_chip() {
+        let values = RefCell::new(vec![]);
+        let mut monitor = CircuitInvariantMonitor::new();
+        monitor.watch_cell(z_column(), |value| values.borrow_mut().push(value));
+
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        monitor.run(&circuit, 4, vec![]).unwrap();
+
+        // x * 1, x^2, x^3, x^3 + x, x^3 + x + 5 and the expected 35
+        let expected = [3, 9, 27, 30, 35, 35].map(Fp::from);
+        assert_eq!(values.take(), expected);
+    }
+
+    #[test]
+    fn watch_all_sees_regions_columns_and_rows() {
+        let events = RefCell::new(vec![]);
+        let mut monitor = CircuitInvariantMonitor::new();
+        monitor.watch_all(|event| events.borrow_mut().push(event.clone()));
+
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        let recorder = monitor.run(&circuit, 4, vec![]).unwrap();
+        let events = events.take();
+
+        // one event per known advice cell, matching the recorded value
+        assert_eq!(events.len(), recorder.advice.len());
+        for event in &events {
+            assert_eq!(
+                recorder.advice[&(event.column, event.row)],
+                Some(event.value)
+            );
+            assert_eq!(
+                recorder.region_at(event.row).map(|region| &region.name),
+                Some(&event.region)
+            );
+        }
+        assert_eq!(events[0].region, "mul");
+    }
+
+    #[test]
+    fn unknown_values_are_not_reported() {
+        let calls = RefCell::new(0);
+        let mut monitor = CircuitInvariantMonitor::new();
+        monitor.watch_all(|_| *calls.borrow_mut() += 1);
+        // unknown witnesses fail the checked assignment, before any callback
+        let circuit = FunctionCircuit::<Fp>::default();
+        assert!(monitor.run(&circuit, 4, vec![]).is_err());
+        assert_eq!(calls.take(), 0);
+    }
+}
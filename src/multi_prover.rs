@@ -0,0 +1,291 @@
+// Proves several Fibonacci claims with different step counts in one proof.
+//
+// halo2 batches circuits into a single proof only when they share a proving key,
+// so every claim is laid out on the same MAX_STEPS transition rows and the step
+// count n becomes a public input. A row either steps the pair or holds it:
+//
+// | x   | y   | active | acc | s_first | s_step |
+// | a   | b   | 1      | 0   | 1       | 1      |
+// | ... |     |        |     | 0       | 1      |
+// | x_T | y_T |        | n   | 0       | 0      |
+// gate first: s_first * acc == 0
+// gate step: s_step * (x + active * (y - x) - x(next)) == 0
+//            s_step * (y + active * x - y(next)) == 0
+//            s_step * active * (1 - active) == 0
+//            s_step * (acc + active - acc(next)) == 0
+//
+// Holding leaves the pair unchanged, so any n active rows end on the same pair.
+//
+// instance: | a | b | n | y_T |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, verify_proof, Advice, Circuit, Column, ConstraintSystem, Error, Expression,
+        Instance, ProvingKey, Selector, SingleVerifier,
+    },
+    poly::{commitment::Params, Rotation},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use crate::ipa::keygen;
+
+pub const MAX_STEPS: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct FiboStepsConfig {
+    pub advice: [Column<Advice>; 4],
+    pub instance: Column<Instance>,
+    pub s_first: Selector,
+    pub s_step: Selector,
+}
+
+#[derive(Default)]
+pub struct FiboStepsCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F: FieldExt> FiboStepsCircuit<F> {
+    pub fn new(a: F, b: F, n: usize) -> Self {
+        assert!(n <= MAX_STEPS, "at most {} steps", MAX_STEPS);
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            n,
+        }
+    }
+
+    // instance column for n steps from (a, b)
+    pub fn public_inputs(a: F, b: F, n: usize) -> Vec<Vec<F>> {
+        let (_, y) = (0..n).fold((a, b), |(x, y), _| (y, x + y));
+        vec![vec![a, b, F::from(n as u64), y]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FiboStepsCircuit<F> {
+    type Config = FiboStepsConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let [x, y, active, acc] = advice;
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let s_first = meta.selector();
+        let s_step = meta.selector();
+
+        meta.create_gate("first", |meta| {
+            let s = meta.query_selector(s_first);
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * acc]
+        });
+
+        meta.create_gate("step", |meta| {
+            let s = meta.query_selector(s_step);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let y_cur = meta.query_advice(y, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let y_next = meta.query_advice(y, Rotation::next());
+            let active = meta.query_advice(active, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let one = Expression::Constant(F::one());
+
+            vec![
+                s.clone()
+                    * (x_cur.clone() + active.clone() * (y_cur.clone() - x_cur.clone()) - x_next),
+                s.clone() * (y_cur + active.clone() * x_cur - y_next),
+                s.clone() * active.clone() * (one - active.clone()),
+                s * (acc_cur + active - acc_next),
+            ]
+        });
+
+        FiboStepsConfig {
+            advice,
+            instance,
+            s_first,
+            s_step,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let [x, y, active, acc] = config.advice;
+
+        let (a, b, n, out) = layouter.assign_region(
+            || "fibo steps",
+            |mut region| {
+                config.s_first.enable(&mut region, 0)?;
+                let a = region.assign_advice(|| "a", x, 0, || self.a)?;
+                let b = region.assign_advice(|| "b", y, 0, || self.b)?;
+                let mut acc_cell =
+                    region.assign_advice(|| "acc", acc, 0, || Value::known(F::zero()))?;
+                let (mut x_cell, mut y_cell) = (a.clone(), b.clone());
+
+                for row in 0..MAX_STEPS {
+                    config.s_step.enable(&mut region, row)?;
+                    let on = row < self.n;
+                    region.assign_advice(|| "active", active, row, || Value::known(F::from(on)))?;
+
+                    let (x_val, y_val) = (x_cell.value().copied(), y_cell.value().copied());
+                    let (x_next, y_next) = if on {
+                        (y_val, x_val + y_val)
+                    } else {
+                        (x_val, y_val)
+                    };
+                    x_cell = region.assign_advice(|| "x", x, row + 1, || x_next)?;
+                    y_cell = region.assign_advice(|| "y", y, row + 1, || y_next)?;
+                    acc_cell = region.assign_advice(
+                        || "acc",
+                        acc,
+                        row + 1,
+                        || acc_cell.value().map(|acc| *acc + F::from(on)),
+                    )?;
+                }
+                Ok((a, b, acc_cell, y_cell))
+            },
+        )?;
+
+        layouter.constrain_instance(a.cell(), config.instance, 0)?;
+        layouter.constrain_instance(b.cell(), config.instance, 1)?;
+        layouter.constrain_instance(n.cell(), config.instance, 2)?;
+        layouter.constrain_instance(out.cell(), config.instance, 3)
+    }
+}
+
+/// One proving key for every `FiboStepsCircuit`, batching any number of them into
+/// a single IPA proof over the Pasta curves.
+pub struct MultiCircuitProver {
+    pub params: Params<EqAffine>,
+    pub pk: ProvingKey<EqAffine>,
+}
+
+impl MultiCircuitProver {
+    pub fn setup(k: u32) -> Result<Self, Error> {
+        let (params, pk) = keygen(&FiboStepsCircuit::<Fp>::default(), k)?;
+        Ok(Self { params, pk })
+    }
+
+    pub fn prove(
+        &self,
+        circuits: Vec<FiboStepsCircuit<Fp>>,
+        public: &[Vec<Vec<Fp>>],
+    ) -> Result<Vec<u8>, Error> {
+        let columns: Vec<Vec<&[Fp]>> = public
+            .iter()
+            .map(|instance| instance.iter().map(|column| column.as_slice()).collect())
+            .collect();
+        let instances: Vec<&[&[Fp]]> = columns.iter().map(|columns| columns.as_slice()).collect();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &self.params,
+            &self.pk,
+            &circuits,
+            &instances,
+            OsRng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    // checks the public inputs of every batched circuit against one proof
+    pub fn verify(&self, proof: &[u8], public: &[Vec<Vec<Fp>>]) -> Result<(), Error> {
+        let columns: Vec<Vec<&[Fp]>> = public
+            .iter()
+            .map(|instance| instance.iter().map(|column| column.as_slice()).collect())
+            .collect();
+        let instances: Vec<&[&[Fp]]> = columns.iter().map(|columns| columns.as_slice()).collect();
+
+        let strategy = SingleVerifier::new(&self.params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_proof(
+            &self.params,
+            self.pk.get_vk(),
+            strategy,
+            &instances,
+            &mut transcript,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::*;
+
+    const K: u32 = 6;
+    const SIZES: [usize; 5] = [5, 10, 15, 20, 25];
+
+    fn batch() -> (Vec<FiboStepsCircuit<Fp>>, Vec<Vec<Vec<Fp>>>) {
+        SIZES
+            .iter()
+            .map(|&n| {
+                (
+                    FiboStepsCircuit::new(Fp::one(), Fp::one(), n),
+                    FiboStepsCircuit::public_inputs(Fp::one(), Fp::one(), n),
+                )
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn each_size_is_satisfied() {
+        let (circuits, public) = batch();
+        for (circuit, public) in circuits.iter().zip(public) {
+            MockProver::run(K, circuit, public)
+                .unwrap()
+                .assert_satisfied();
+        }
+        // n = 10 from F(1) = F(2) = 1 ends on F(12)
+        assert_eq!(
+            FiboStepsCircuit::public_inputs(Fp::one(), Fp::one(), 10)[0][3],
+            Fp::from(144)
+        );
+    }
+
+    #[test]
+    fn aggregate_proof_verifies() {
+        let prover = MultiCircuitProver::setup(K).unwrap();
+        let (circuits, public) = batch();
+        let proof = prover.prove(circuits, &public).unwrap();
+        prover.verify(&proof, &public).unwrap();
+    }
+
+    #[test]
+    fn aggregate_proof_rejects_a_wrong_claim() {
+        let prover = MultiCircuitProver::setup(K).unwrap();
+        let (circuits, mut public) = batch();
+        let proof = prover.prove(circuits, &public).unwrap();
+        // claim n = 16 for the third circuit
+        public[2][0][2] += Fp::one();
+        assert!(prover.verify(&proof, &public).is_err());
+    }
+
+    #[test]
+    fn wrong_step_count_fails() {
+        let circuit = FiboStepsCircuit::new(Fp::one(), Fp::one(), 5);
+        let public = FiboStepsCircuit::public_inputs(Fp::one(), Fp::one(), 6);
+        assert!(MockProver::run(K, &circuit, public)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
@@ -0,0 +1,273 @@
+// Picks values[k] out of N assigned cells for an assigned index k. Each row
+// holds one candidate, a copy of k and a boolean flag that may only be set on
+// the row whose position equals k, with exactly one flag set overall:
+//
+// | value   | k | flag   | acc                        | flags               | position |
+// | v_0     | k | f_0    | f_0 * v_0                  | f_0                 | 0        |
+// | v_1     | k | f_1    | acc(prev) + f_1 * v_1      | flags(prev) + f_1   | 1        |
+// | ...     |
+// gate mux row: s_row * flag * (1 - flag) == 0, s_row * flag * (k - position) == 0
+// gate mux first: s_first * (acc - flag * value) == 0, s_first * (flags - flag) == 0
+// gate mux next: s_next * (acc - acc(prev) - flag * value) == 0
+//                s_next * (flags - flags(prev) - flag) == 0
+// gate mux last: s_last * (flags - 1) == 0
+//
+// acc on the last row is the selected value. An index outside 0..N can't set
+// any flag and fails the last gate.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct MuxConfig {
+    pub advice: [Column<Advice>; 5],
+    pub position: Column<Fixed>,
+    pub s_row: Selector,
+    pub s_first: Selector,
+    pub s_next: Selector,
+    pub s_last: Selector,
+}
+
+pub struct MuxChip<F: FieldExt> {
+    config: MuxConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> MuxChip<F> {
+    pub fn construct(config: MuxConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        position: Column<Fixed>,
+    ) -> MuxConfig {
+        let [col_value, col_k, col_flag, col_acc, col_flags] = advice;
+        meta.enable_equality(col_value);
+        meta.enable_equality(col_k);
+        meta.enable_equality(col_acc);
+        let s_row = meta.selector();
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+        let s_last = meta.selector();
+
+        meta.create_gate("mux row", |meta| {
+            let s = meta.query_selector(s_row);
+            let k = meta.query_advice(col_k, Rotation::cur());
+            let flag = meta.query_advice(col_flag, Rotation::cur());
+            let position = meta.query_fixed(position, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![
+                s.clone() * flag.clone() * (one - flag.clone()),
+                s * flag * (k - position),
+            ]
+        });
+
+        meta.create_gate("mux first", |meta| {
+            let s = meta.query_selector(s_first);
+            let value = meta.query_advice(col_value, Rotation::cur());
+            let flag = meta.query_advice(col_flag, Rotation::cur());
+            let acc = meta.query_advice(col_acc, Rotation::cur());
+            let flags = meta.query_advice(col_flags, Rotation::cur());
+            vec![s.clone() * (acc - flag.clone() * value), s * (flags - flag)]
+        });
+
+        meta.create_gate("mux next", |meta| {
+            let s = meta.query_selector(s_next);
+            let value = meta.query_advice(col_value, Rotation::cur());
+            let flag = meta.query_advice(col_flag, Rotation::cur());
+            let acc = meta.query_advice(col_acc, Rotation::cur());
+            let flags = meta.query_advice(col_flags, Rotation::cur());
+            let prev_acc = meta.query_advice(col_acc, Rotation::prev());
+            let prev_flags = meta.query_advice(col_flags, Rotation::prev());
+            vec![
+                s.clone() * (acc - prev_acc - flag.clone() * value),
+                s * (flags - prev_flags - flag),
+            ]
+        });
+
+        meta.create_gate("mux last", |meta| {
+            let s = meta.query_selector(s_last);
+            let flags = meta.query_advice(col_flags, Rotation::cur());
+            vec![s * (flags - Expression::Constant(F::one()))]
+        });
+
+        MuxConfig {
+            advice,
+            position,
+            s_row,
+            s_first,
+            s_next,
+            s_last,
+        }
+    }
+
+    /// values[k], for k the index held in `index`.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>],
+        index: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if values.is_empty() {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        let [col_value, col_k, col_flag, col_acc, col_flags] = config.advice;
+        layouter.assign_region(
+            || "mux",
+            |mut region| {
+                let mut acc = Value::known(F::zero());
+                let mut flags = Value::known(F::zero());
+                let mut out = None;
+                for (row, value) in values.iter().enumerate() {
+                    config.s_row.enable(&mut region, row)?;
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_next.enable(&mut region, row)?;
+                    }
+                    if row == values.len() - 1 {
+                        config.s_last.enable(&mut region, row)?;
+                    }
+
+                    let position = F::from(row as u64);
+                    region.assign_fixed(
+                        || "position",
+                        config.position,
+                        row,
+                        || Value::known(position),
+                    )?;
+                    let value = value.copy_advice(|| "value", &mut region, col_value, row)?;
+                    let k = index.copy_advice(|| "k", &mut region, col_k, row)?;
+
+                    let flag = k.value().map(|k| F::from(*k == position));
+                    region.assign_advice(|| "flag", col_flag, row, || flag)?;
+                    acc = acc + flag * value.value().copied();
+                    flags = flags + flag;
+                    out = Some(region.assign_advice(|| "acc", col_acc, row, || acc)?);
+                    region.assign_advice(|| "flags", col_flags, row, || flags)?;
+                }
+                Ok(out.unwrap())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // instance: | k | values[k] |
+    struct SelectCircuit {
+        values: Vec<Fp>,
+    }
+
+    impl Circuit<Fp> for SelectCircuit {
+        type Config = (MuxConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: self.values.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let position = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (MuxChip::configure(meta, advice, position), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let [col_value, col_k, ..] = config.advice;
+            let (values, k) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let values = self
+                        .values
+                        .iter()
+                        .enumerate()
+                        .map(|(row, value)| {
+                            region.assign_advice(
+                                || "value",
+                                col_value,
+                                row,
+                                || Value::known(*value),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let k = region.assign_advice_from_instance(|| "k", instance, 0, col_k, 0)?;
+                    Ok((values, k))
+                },
+            )?;
+            let selected =
+                MuxChip::construct(config).select(layouter.namespace(|| "mux"), &values, &k)?;
+            layouter.constrain_instance(selected.cell(), instance, 1)
+        }
+    }
+
+    fn run(values: &[u64], k: u64, selected: u64) -> MockProver<Fp> {
+        let circuit = SelectCircuit {
+            values: values.iter().copied().map(Fp::from).collect(),
+        };
+        MockProver::run(4, &circuit, vec![vec![Fp::from(k), Fp::from(selected)]]).unwrap()
+    }
+
+    #[test]
+    fn selects_every_position() {
+        let values = [10, 20, 30, 40];
+        for (k, value) in values.iter().enumerate() {
+            run(&values, k as u64, *value).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        assert!(run(&[10, 20, 30, 40], 2, 20).verify().is_err());
+    }
+
+    #[test]
+    fn index_out_of_range_fails() {
+        // no flag can be set, whatever value is claimed
+        assert!(run(&[10, 20, 30, 40], 4, 0).verify().is_err());
+        assert!(run(&[10, 20, 30, 40], 4, 40).verify().is_err());
+    }
+
+    #[test]
+    fn single_value() {
+        run(&[7], 0, 7).assert_satisfied();
+    }
+
+    #[test]
+    fn no_values_is_a_synthesis_error() {
+        let circuit = SelectCircuit { values: vec![] };
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![vec![Fp::zero(); 2]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+// Wraps a circuit and perturbs every advice assignment by epsilon * r for a
+// fresh random field element r. Any gate or copy constraint touching a noisy
+// cell stops holding, so MockProver should reject the wrapped circuit.
+//
+// Advice cells loaded from constants are re-assigned as noisy advice and then
+// pinned to the constant; cells copied from instance columns are forwarded as is.
+
+use std::{cell::RefCell, fmt};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{layouter::RegionLayouter, Cell, Layouter, Region, Table, Value},
+    plonk::{Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+};
+use rand_core::RngCore;
+
+pub struct NoiseInjector<F: FieldExt, C: Circuit<F>, R: RngCore + Clone> {
+    pub circuit: C,
+    pub epsilon: F,
+    rng: RefCell<R>,
+}
+
+impl<F: FieldExt, C: Circuit<F>, R: RngCore + Clone> NoiseInjector<F, C, R> {
+    pub fn new(circuit: C, epsilon: F, rng: R) -> Self {
+        Self {
+            circuit,
+            epsilon,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    fn sample(&self) -> F {
+        self.epsilon * F::random(&mut *self.rng.borrow_mut())
+    }
+}
+
+impl<F: FieldExt, C: Circuit<F>, R: RngCore + Clone> Circuit<F> for NoiseInjector<F, C, R> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(
+            self.circuit.without_witnesses(),
+            self.epsilon,
+            self.rng.borrow().clone(),
+        )
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(
+            config,
+            NoisyLayouter {
+                inner: layouter,
+                noise: self,
+            },
+        )
+    }
+}
+
+pub struct NoisyLayouter<'a, F: FieldExt, L, C: Circuit<F>, R: RngCore + Clone> {
+    inner: L,
+    noise: &'a NoiseInjector<F, C, R>,
+}
+
+impl<'a, F: FieldExt, L: Layouter<F>, C: Circuit<F>, R: RngCore + Clone> Layouter<F>
+    for NoisyLayouter<'a, F, L, C, R>
+{
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let noise = self.noise;
+        self.inner.assign_region(name, |mut region| {
+            let mut noisy = NoisyRegion {
+                region: &mut region,
+                noise,
+            };
+            assignment(Region::from(&mut noisy as &mut dyn RegionLayouter<F>))
+        })
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.inner.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.inner.constrain_instance(cell, column, row)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.inner.get_root().pop_namespace(gadget_name)
+    }
+}
+
+struct NoisyRegion<'r, 'a, F: FieldExt, C: Circuit<F>, R: RngCore + Clone> {
+    region: &'r mut Region<'a, F>,
+    noise: &'r NoiseInjector<F, C, R>,
+}
+
+impl<'r, 'a, F: FieldExt, C: Circuit<F>, R: RngCore + Clone> fmt::Debug
+    for NoisyRegion<'r, 'a, F, C, R>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NoisyRegion")
+            .field("epsilon", &self.noise.epsilon)
+            .finish()
+    }
+}
+
+impl<'r, 'a, F: FieldExt, C: Circuit<F>, R: RngCore + Clone> RegionLayouter<F>
+    for NoisyRegion<'r, 'a, F, C, R>
+{
+    fn enable_selector<'v>(
+        &'v mut self,
+        _annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        selector.enable(self.region, offset)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let noise = Assigned::from(self.noise.sample());
+        self.region
+            .assign_advice(annotation, column, offset, || to() + Value::known(noise))
+            .map(|cell| cell.cell())
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let cell = self.assign_advice(annotation, column, offset, &mut || {
+            Value::known(constant)
+        })?;
+        self.region.constrain_constant(cell, constant)?;
+        Ok(cell)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        self.region
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)
+            .map(|cell| (cell.cell(), cell.value().copied()))
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.region
+            .assign_fixed(annotation, column, offset, to)
+            .map(|cell| cell.cell())
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.region.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.region.constrain_equal(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, function::FunctionCircuit};
+
+    fn fibo() -> FiboCircuit<Fp> {
+        FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        }
+    }
+
+    fn function() -> FunctionCircuit<Fp> {
+        FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        }
+    }
+
+    #[test]
+    fn unit_noise_always_fails() {
+        for _ in 0..20 {
+            let noisy = NoiseInjector::new(fibo(), Fp::one(), OsRng);
+            assert!(MockProver::run(4, &noisy, vec![])
+                .unwrap()
+                .verify()
+                .is_err());
+
+            let noisy = NoiseInjector::new(function(), Fp::one(), OsRng);
+            assert!(MockProver::run(4, &noisy, vec![])
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn zero_noise_keeps_the_circuit_valid() {
+        let noisy = NoiseInjector::new(fibo(), Fp::zero(), OsRng);
+        MockProver::run(4, &noisy, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let noisy = NoiseInjector::new(function(), Fp::zero(), OsRng);
+        MockProver::run(4, &noisy, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+}
@@ -0,0 +1,253 @@
+// Radix-2 Cooley-Tukey NTT, X_k = sum_j x_j * w^(jk) for a primitive N-th root w.
+// The input is laid out in bit reversed order and log2(N) stages of butterflies
+// bring the output back to natural order, one butterfly per row.
+//
+// | a | b | a' | b' | twiddle | s_butterfly |
+// gate butterfly: s_butterfly * (a + twiddle * b - a') == 0
+//                 s_butterfly * (a - twiddle * b - b') == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+/// Primitive N-th root of unity, N a power of two.
+pub fn root_of_unity<F: FieldExt>(n: usize) -> F {
+    assert!(n.is_power_of_two() && n.trailing_zeros() <= F::S);
+    F::root_of_unity().pow_vartime([1 << (F::S - n.trailing_zeros())])
+}
+
+// out of circuit evaluation, the quadratic definition
+pub fn ntt<F: FieldExt, const N: usize>(input: &[F; N]) -> [F; N] {
+    let w: F = root_of_unity(N);
+    let mut output = [F::zero(); N];
+    for (k, out) in output.iter_mut().enumerate() {
+        let w_k = w.pow_vartime([k as u64]);
+        *out = input.iter().rev().fold(F::zero(), |acc, x| acc * w_k + x);
+    }
+    output
+}
+
+fn bit_reverse(i: usize, bits: u32) -> usize {
+    if bits == 0 {
+        return i;
+    }
+    i.reverse_bits() >> (usize::BITS - bits)
+}
+
+#[derive(Debug, Clone)]
+pub struct NttConfig {
+    pub advice: [Column<Advice>; 4],
+    pub twiddle: Column<Fixed>,
+    pub s_butterfly: Selector,
+}
+
+pub struct NttChip<F: FieldExt, const N: usize> {
+    config: NttConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> NttChip<F, N> {
+    pub fn construct(config: NttConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        twiddle: Column<Fixed>,
+    ) -> NttConfig {
+        assert!(N.is_power_of_two(), "NTT size must be a power of two");
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let s_butterfly = meta.selector();
+        meta.create_gate("butterfly", |meta| {
+            let s = meta.query_selector(s_butterfly);
+            let [a, b, a_out, b_out] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let w = meta.query_fixed(twiddle, Rotation::cur());
+            vec![
+                s.clone() * (a.clone() + w.clone() * b.clone() - a_out),
+                s * (a - w * b - b_out),
+            ]
+        });
+
+        NttConfig {
+            advice,
+            twiddle,
+            s_butterfly,
+        }
+    }
+
+    pub fn ntt(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &[Value<F>; N],
+    ) -> Result<[AssignedCell<F, F>; N], Error> {
+        let config = &self.config;
+        let bits = N.trailing_zeros();
+
+        let mut values: Vec<AssignedCell<F, F>> = layouter.assign_region(
+            || "load input",
+            |mut region| {
+                (0..N)
+                    .map(|i| {
+                        let column = config.advice[i % 4];
+                        let x = input[bit_reverse(i, bits)];
+                        region.assign_advice(|| "x", column, i / 4, || x)
+                    })
+                    .collect()
+            },
+        )?;
+
+        for stage in 0..bits {
+            let half = 1 << stage;
+            let w: F = root_of_unity(2 * half);
+            values = layouter.assign_region(
+                || format!("stage {}", stage),
+                |mut region| {
+                    let mut next = values.clone();
+                    let mut row = 0;
+                    for start in (0..N).step_by(2 * half) {
+                        for j in 0..half {
+                            let (i, k) = (start + j, start + j + half);
+                            let twiddle = w.pow_vartime([j as u64]);
+                            config.s_butterfly.enable(&mut region, row)?;
+                            region.assign_fixed(
+                                || "twiddle",
+                                config.twiddle,
+                                row,
+                                || Value::known(twiddle),
+                            )?;
+
+                            let a = values[i].copy_advice(
+                                || "a",
+                                &mut region,
+                                config.advice[0],
+                                row,
+                            )?;
+                            let b = values[k].copy_advice(
+                                || "b",
+                                &mut region,
+                                config.advice[1],
+                                row,
+                            )?;
+                            let t = b.value().map(|b| *b * twiddle);
+                            next[i] = region.assign_advice(
+                                || "a + w * b",
+                                config.advice[2],
+                                row,
+                                || a.value().copied() + t,
+                            )?;
+                            next[k] = region.assign_advice(
+                                || "a - w * b",
+                                config.advice[3],
+                                row,
+                                || a.value().copied() - t,
+                            )?;
+                            row += 1;
+                        }
+                    }
+                    Ok(next)
+                },
+            )?;
+        }
+
+        Ok(values.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // instance: | X_0 | ... | X_{N-1} |
+    struct NttCircuit<const N: usize>([Value<Fp>; N]);
+
+    impl<const N: usize> Circuit<Fp> for NttCircuit<N> {
+        type Config = (NttConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self([Value::unknown(); N])
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let twiddle = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (NttChip::<Fp, N>::configure(meta, advice, twiddle), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let output =
+                NttChip::<Fp, N>::construct(config).ntt(layouter.namespace(|| "ntt"), &self.0)?;
+            for (row, cell) in output.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prove<const N: usize>(input: [Fp; N], output: [Fp; N]) -> MockProver<Fp> {
+        MockProver::run(
+            5,
+            &NttCircuit(input.map(Value::known)),
+            vec![output.to_vec()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn impulse_of_four() {
+        let input = [1, 0, 0, 0].map(Fp::from);
+        assert_eq!(ntt(&input), [Fp::one(); 4]);
+        prove(input, [Fp::one(); 4]).assert_satisfied();
+    }
+
+    #[test]
+    fn shifted_impulse_gives_the_powers_of_w() {
+        let w: Fp = root_of_unity(4);
+        let expected = [0, 1, 2, 3].map(|k| w.pow_vartime([k]));
+        assert_eq!(ntt(&[0, 1, 0, 0].map(Fp::from)), expected);
+        assert_eq!(w.pow_vartime([4]), Fp::one());
+        assert_ne!(w.square(), Fp::one());
+        prove([0, 1, 0, 0].map(Fp::from), expected).assert_satisfied();
+    }
+
+    #[test]
+    fn eight_points_match_the_reference() {
+        let input = [3, 1, 4, 1, 5, 9, 2, 6].map(Fp::from);
+        prove(input, ntt(&input)).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_output_fails() {
+        let input = [1, 2, 3, 4].map(Fp::from);
+        let mut output = ntt(&input);
+        output.swap(1, 3);
+        assert!(prove(input, output).verify().is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+/// Provides values that are cheap to check but expensive (or impossible) to
+/// compute inside the circuit, e.g. inverses or square roots.
+pub trait HintOracle<F: FieldExt> {
+    fn hint(&self, input: F) -> F;
+}
+
+/// Oracle that should never be asked for a hint.
+#[derive(Debug, Clone, Default)]
+pub struct PanicOracle;
+
+impl<F: FieldExt> HintOracle<F> for PanicOracle {
+    fn hint(&self, input: F) -> F {
+        panic!("PanicOracle asked for a hint of {:?}", input)
+    }
+}
+
+/// Oracle backed by precomputed `input -> hint` pairs.
+// field elements are not `Hash`, so keep them in an ordered map
+#[derive(Debug, Clone, Default)]
+pub struct LookupOracle<F: FieldExt> {
+    pub table: BTreeMap<F, F>,
+}
+
+impl<F: FieldExt> LookupOracle<F> {
+    pub fn new(entries: impl IntoIterator<Item = (F, F)>) -> Self {
+        Self {
+            table: entries.into_iter().collect(),
+        }
+    }
+
+    pub fn insert(&mut self, input: F, hint: F) {
+        self.table.insert(input, hint);
+    }
+}
+
+impl<F: FieldExt> HintOracle<F> for LookupOracle<F> {
+    fn hint(&self, input: F) -> F {
+        *self
+            .table
+            .get(&input)
+            .unwrap_or_else(|| panic!("LookupOracle has no hint for {:?}", input))
+    }
+}
@@ -0,0 +1,255 @@
+// Packs COUNT words of WORD_BITS bits into one cell, least significant word first
+// | w_0 | w_1 | ... | w_{COUNT-1} | packed | selector |
+// gate pack: selector * (w_0 + w_1 * 2^WORD_BITS + ... - packed) == 0
+// every word is range checked to [0, 2^WORD_BITS), so the words of a packed
+// value are unique as long as COUNT * WORD_BITS stays below the field size.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::range_check::{RangeCheckChip, RangeCheckConfig};
+
+#[derive(Debug, Clone)]
+pub struct WordPackingConfig<const COUNT: usize> {
+    pub words: [Column<Advice>; COUNT],
+    pub packed: Column<Advice>,
+    pub selector: Selector,
+    pub range: RangeCheckConfig,
+}
+
+pub struct WordPackingChip<F: FieldExt, const WORD_BITS: usize, const COUNT: usize> {
+    config: WordPackingConfig<COUNT>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const WORD_BITS: usize, const COUNT: usize> WordPackingChip<F, WORD_BITS, COUNT> {
+    pub fn construct(config: WordPackingConfig<COUNT>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        words: [Column<Advice>; COUNT],
+        packed: Column<Advice>,
+    ) -> WordPackingConfig<COUNT> {
+        assert!(COUNT > 0, "nothing to pack");
+        assert!(
+            COUNT * WORD_BITS < F::NUM_BITS as usize,
+            "packed words overflow the field"
+        );
+
+        for column in words {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(packed);
+        let selector = meta.selector();
+
+        let shift = F::from(2).pow_vartime([WORD_BITS as u64]);
+        meta.create_gate("pack", |meta| {
+            let s = meta.query_selector(selector);
+            let sum = words
+                .iter()
+                .rev()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .reduce(|acc, word| acc * Expression::Constant(shift) + word)
+                .unwrap();
+            let packed = meta.query_advice(packed, Rotation::cur());
+            vec![s * (sum - packed)]
+        });
+
+        WordPackingConfig {
+            words,
+            packed,
+            selector,
+            range: RangeCheckChip::<F, WORD_BITS>::configure(meta, packed, words[0]),
+        }
+    }
+
+    // word `i` of a packed value
+    fn word(packed: &F, i: usize) -> F {
+        let repr = packed.to_repr();
+        let bytes = repr.as_ref();
+        (0..WORD_BITS).rev().fold(F::zero(), |acc, bit| {
+            let bit = i * WORD_BITS + bit;
+            acc.double() + F::from(((bytes[bit / 8] >> (bit % 8)) & 1) as u64)
+        })
+    }
+
+    // one pack row, then every word is range checked
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        words: [Value<F>; COUNT],
+        packed: Result<&AssignedCell<F, F>, Value<F>>,
+    ) -> Result<([AssignedCell<F, F>; COUNT], AssignedCell<F, F>), Error> {
+        let config = &self.config;
+        let (words, packed) = layouter.assign_region(
+            || "pack",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                let words = words
+                    .iter()
+                    .zip(config.words)
+                    .map(|(word, column)| region.assign_advice(|| "word", column, 0, || *word))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let packed = match &packed {
+                    Ok(cell) => cell.copy_advice(|| "packed", &mut region, config.packed, 0)?,
+                    Err(value) => region.assign_advice(|| "packed", config.packed, 0, || *value)?,
+                };
+                Ok((words, packed))
+            },
+        )?;
+
+        let range = RangeCheckChip::<F, WORD_BITS>::construct(config.range.clone());
+        for word in &words {
+            range.check(layouter.namespace(|| "word range"), word)?;
+        }
+        Ok((words.try_into().unwrap(), packed))
+    }
+
+    pub fn pack(
+        &self,
+        layouter: impl Layouter<F>,
+        words: [Value<F>; COUNT],
+    ) -> Result<([AssignedCell<F, F>; COUNT], AssignedCell<F, F>), Error> {
+        let shift = F::from(2).pow_vartime([WORD_BITS as u64]);
+        let packed = words
+            .iter()
+            .rev()
+            .fold(Value::known(F::zero()), |acc, word| {
+                acc * Value::known(shift) + *word
+            });
+        self.assign(layouter, words, Err(packed))
+    }
+
+    pub fn unpack(
+        &self,
+        layouter: impl Layouter<F>,
+        packed: &AssignedCell<F, F>,
+    ) -> Result<[AssignedCell<F, F>; COUNT], Error> {
+        let mut words = [Value::unknown(); COUNT];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = packed.value().map(|packed| Self::word(packed, i));
+        }
+        self.assign(layouter, words, Ok(packed))
+            .map(|(words, _)| words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    type ByteChip = WordPackingChip<Fp, 8, 4>;
+
+    // packs `bytes` and unpacks `word`
+    // instance: | packed | byte_0 | byte_1 | byte_2 | byte_3 |
+    struct BytesCircuit {
+        bytes: [u64; 4],
+        word: u64,
+    }
+
+    impl Circuit<Fp> for BytesCircuit {
+        type Config = (WordPackingConfig<4>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bytes: [0; 4],
+                word: 0,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let words = [(); 4].map(|_| meta.advice_column());
+            let packed = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (ByteChip::configure(meta, words, packed), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = ByteChip::construct(config.clone());
+            let (_, packed) = chip.pack(
+                layouter.namespace(|| "pack"),
+                self.bytes.map(|byte| Value::known(Fp::from(byte))),
+            )?;
+            layouter.constrain_instance(packed.cell(), instance, 0)?;
+
+            let word = layouter.assign_region(
+                || "word",
+                |mut region| {
+                    region.assign_advice(
+                        || "word",
+                        config.packed,
+                        0,
+                        || Value::known(Fp::from(self.word)),
+                    )
+                },
+            )?;
+            let bytes = chip.unpack(layouter.namespace(|| "unpack"), &word)?;
+            for (row, byte) in bytes.iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), instance, row + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prove(bytes: [u64; 4], word: u64, public: [u64; 5]) -> MockProver<Fp> {
+        let public = vec![public.map(Fp::from).to_vec()];
+        MockProver::run(7, &BytesCircuit { bytes, word }, public).unwrap()
+    }
+
+    #[test]
+    fn four_bytes_into_a_word_and_back() {
+        prove(
+            [0x12, 0x34, 0x56, 0x78],
+            0xdeadbeef,
+            [0x78563412, 0xef, 0xbe, 0xad, 0xde],
+        )
+        .assert_satisfied();
+        prove([0; 4], 0xff, [0, 0xff, 0, 0, 0]).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_packed_word_fails() {
+        assert!(prove([0x12, 0x34, 0x56, 0x78], 0, [0x12345678, 0, 0, 0, 0])
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn wrong_unpacked_byte_fails() {
+        assert!(prove([0; 4], 0xdeadbeef, [0, 0xef, 0xbe, 0xde, 0xad])
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn byte_out_of_range_fails() {
+        // 0x100 + 0x00 * 2^8 packs to the same word as [0x00, 0x01, 0, 0]
+        assert!(prove([0x100, 0, 0, 0], 0, [0x100, 0, 0, 0, 0])
+            .verify()
+            .is_err());
+    }
+}
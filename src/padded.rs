@@ -0,0 +1,241 @@
+// Wraps a circuit so every advice cell left unassigned by its synthesis is set
+// to zero, up to the last usable row of a 2^K row table. Only advice cells are
+// written, no selectors, so the padding rows never switch a gate on.
+//
+// The padding runs in the floor planner, after the inner floor planner is done,
+// through an `Assignment` that remembers which advice cells were written.
+
+use std::{collections::BTreeSet, marker::PhantomData};
+
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+use crate::recorder::{column_index, parse_index};
+
+pub struct PaddedFloorPlanner<P, const K: u32>(PhantomData<P>);
+
+struct PaddingTracker<'a, CS> {
+    cs: &'a mut CS,
+    // (column index, row)
+    assigned: BTreeSet<(usize, usize)>,
+}
+
+impl<'a, F: Field, CS: Assignment<F>> Assignment<F> for PaddingTracker<'a, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.enter_region(name_fn)
+    }
+
+    fn exit_region(&mut self) {
+        self.cs.exit_region()
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.assigned.insert((column_index(column), row));
+        self.cs.assign_advice(annotation, column, row, to)
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.cs.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.cs.fill_from_row(column, row, to)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name)
+    }
+}
+
+impl<P: FloorPlanner, const K: u32> FloorPlanner for PaddedFloorPlanner<P, K> {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        let mut tracker = PaddingTracker {
+            cs,
+            assigned: BTreeSet::new(),
+        };
+        P::synthesize(&mut tracker, circuit, config, constants)?;
+
+        let mut meta = ConstraintSystem::<F>::default();
+        C::configure(&mut meta);
+        let usable_rows = (1 << K) - (meta.blinding_factors() + 1);
+
+        // advice columns are numbered in allocation order, rebuild them by index
+        let mut fresh = ConstraintSystem::<F>::default();
+        let num_advice = parse_index(&format!("{:?}", meta.pinned()), "num_advice_columns: ");
+        let advice: Vec<_> = (0..num_advice).map(|_| fresh.advice_column()).collect();
+
+        let PaddingTracker { cs, assigned } = tracker;
+        for column in advice {
+            for row in 0..usable_rows {
+                if !assigned.contains(&(column_index(column), row)) {
+                    cs.assign_advice(|| "padding", column, row, || Value::known(F::zero()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct PaddedCircuit<F: FieldExt, C: Circuit<F>, const K: u32> {
+    pub circuit: C,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, C: Circuit<F>, const K: u32> PaddedCircuit<F, C, K> {
+    pub fn new(circuit: C) -> Self {
+        Self {
+            circuit,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, C: Circuit<F>, const K: u32> Circuit<F> for PaddedCircuit<F, C, K> {
+    type Config = C::Config;
+    type FloorPlanner = PaddedFloorPlanner<C::FloorPlanner, K>;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.circuit.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, fibo_segment::FiboSegmentCircuit, recorder::record};
+
+    const K: u32 = 5;
+
+    fn fibo() -> FiboCircuit<Fp> {
+        FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        }
+    }
+
+    #[test]
+    fn padded_circuit_passes() {
+        MockProver::run(K, &PaddedCircuit::<_, _, K>::new(fibo()), vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let segment = FiboSegmentCircuit::new(0, 10, Fp::zero(), Fp::one());
+        let public = vec![[0, 1, 55].map(Fp::from).to_vec()];
+        MockProver::run(K, &PaddedCircuit::<_, _, K>::new(segment), public)
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn every_usable_advice_cell_is_assigned() {
+        let inner = record(&fibo(), K, vec![]).unwrap();
+        let padded = record(&PaddedCircuit::<_, _, K>::new(fibo()), K, vec![]).unwrap();
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        FiboCircuit::<Fp>::configure(&mut meta);
+        let usable_rows = (1 << K) - (meta.blinding_factors() + 1);
+        assert_eq!(padded.advice.len(), 3 * usable_rows);
+
+        for (cell, value) in &padded.advice {
+            let expected = inner.advice.get(cell).copied().unwrap_or(Some(Fp::zero()));
+            assert_eq!(*value, expected);
+        }
+    }
+
+    #[test]
+    fn padding_enables_no_selectors() {
+        let inner = record(&fibo(), K, vec![]).unwrap();
+        let padded = record(&PaddedCircuit::<_, _, K>::new(fibo()), K, vec![]).unwrap();
+        assert_eq!(padded.selectors, inner.selectors);
+        assert_eq!(padded.fixed, inner.fixed);
+    }
+}
@@ -0,0 +1,142 @@
+// The permutation argument needs one sigma column per column that takes part
+// in a copy constraint. halo2 allocates one for every equality enabled column;
+// the minimum only counts columns holding a cell of a non-trivial connected
+// component of the copy constraint graph.
+
+use std::collections::{HashMap, HashSet};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Circuit, Column},
+};
+
+use crate::recorder::record;
+
+type CopyCell = (Column<Any>, usize);
+
+#[derive(Debug, Default)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+/// Connected components of the copy constraint graph with more than one cell.
+pub fn copy_components<F: FieldExt, C: Circuit<F>>(circuit: &C, k: u32) -> Vec<Vec<CopyCell>> {
+    let recorder = record(circuit, k, vec![]).expect("circuit should synthesize");
+
+    let mut index: HashMap<CopyCell, usize> = HashMap::new();
+    let mut cells = vec![];
+    let mut id = |cell: CopyCell| {
+        *index.entry(cell).or_insert_with(|| {
+            cells.push(cell);
+            cells.len() - 1
+        })
+    };
+    let edges: Vec<_> = recorder
+        .copies
+        .iter()
+        .map(|(left, right)| (id(*left), id(*right)))
+        .collect();
+
+    let mut union_find = UnionFind::new(cells.len());
+    for (left, right) in edges {
+        union_find.union(left, right);
+    }
+
+    let mut components: HashMap<usize, Vec<CopyCell>> = HashMap::new();
+    for (i, cell) in cells.into_iter().enumerate() {
+        components.entry(union_find.find(i)).or_default().push(cell);
+    }
+    components
+        .into_values()
+        .filter(|component| component.len() > 1)
+        .collect()
+}
+
+pub fn count_permutation_columns<F: FieldExt, C: Circuit<F>>(circuit: &C, k: u32) -> usize {
+    copy_components(circuit, k)
+        .into_iter()
+        .flatten()
+        .map(|(column, _)| column)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, fibo_segment::FiboSegmentCircuit, function::FunctionCircuit};
+
+    #[test]
+    fn fibo_copies_span_its_three_columns() {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        assert_eq!(count_permutation_columns(&circuit, 4), 3);
+        // F(2) up to F(9) are copied into the next rows, F(1) and F(10) aren't
+        assert_eq!(copy_components(&circuit, 4).len(), 8);
+    }
+
+    #[test]
+    fn fibo_segment_columns_stay_flat_as_n_grows() {
+        for end in [2, 5, 10, 20] {
+            let circuit = FiboSegmentCircuit::new(0, end, Fp::zero(), Fp::one());
+            // a, b, c and the instance column, however long the run
+            assert_eq!(count_permutation_columns(&circuit, 5), 4);
+            // one component per term F(0) up to F(end)
+            assert_eq!(copy_components(&circuit, 5).len(), end + 1);
+        }
+    }
+
+    #[test]
+    fn function_needs_no_permutation_column() {
+        // every row takes its inputs as fresh values, nothing is copied, even
+        // though halo2 still allocates a sigma column per equality column
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        assert_eq!(count_permutation_columns(&circuit, 4), 0);
+    }
+
+    #[test]
+    fn union_find_merges_components() {
+        let mut union_find = UnionFind::new(5);
+        union_find.union(0, 1);
+        union_find.union(3, 4);
+        union_find.union(1, 4);
+        assert_eq!(union_find.find(0), union_find.find(3));
+        assert_ne!(union_find.find(0), union_find.find(2));
+    }
+}
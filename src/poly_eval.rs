@@ -0,0 +1,204 @@
+// p(x) by Horner's rule, coefficients highest degree first down the rows:
+//
+// | coeff   | acc                    | x | s_first | s_horner |
+// | p_d     | p_d                    | x | 1       | 0        |
+// | p_{d-1} | acc(prev) * x + p_d-1  | x | 0       | 1        |
+// | ...     |                        |   |         |          |
+// gate horner first: s_first * (acc - coeff) == 0
+// gate horner: s_horner * (acc(prev) * x - acc + coeff) == 0
+//
+// x is copied into every row, acc on the last row is p(x).
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct PolynomialEvalConfig {
+    pub coeff: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub x: Column<Advice>,
+    pub s_first: Selector,
+    pub s_horner: Selector,
+}
+
+pub struct PolynomialEvalChip<F: FieldExt> {
+    config: PolynomialEvalConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> PolynomialEvalChip<F> {
+    pub fn construct(config: PolynomialEvalConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        coeff: Column<Advice>,
+        acc: Column<Advice>,
+        x: Column<Advice>,
+    ) -> PolynomialEvalConfig {
+        meta.enable_equality(acc);
+        meta.enable_equality(x);
+        let s_first = meta.selector();
+        let s_horner = meta.selector();
+
+        meta.create_gate("horner first", |meta| {
+            let s = meta.query_selector(s_first);
+            let coeff = meta.query_advice(coeff, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (acc - coeff)]
+        });
+
+        meta.create_gate("horner", |meta| {
+            let s = meta.query_selector(s_horner);
+            let coeff = meta.query_advice(coeff, Rotation::cur());
+            let prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let x = meta.query_advice(x, Rotation::cur());
+            vec![s * (prev * x - acc + coeff)]
+        });
+
+        PolynomialEvalConfig {
+            coeff,
+            acc,
+            x,
+            s_first,
+            s_horner,
+        }
+    }
+
+    /// p(x), `coefficients` lowest degree first.
+    pub fn evaluate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coefficients: &[Value<F>],
+        x: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if coefficients.is_empty() {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        layouter.assign_region(
+            || "horner",
+            |mut region| {
+                let mut acc: Option<AssignedCell<F, F>> = None;
+                for (row, coeff) in coefficients.iter().rev().enumerate() {
+                    let x = x.copy_advice(|| "x", &mut region, config.x, row)?;
+                    region.assign_advice(|| "coeff", config.coeff, row, || *coeff)?;
+                    let value = match &acc {
+                        None => {
+                            config.s_first.enable(&mut region, row)?;
+                            *coeff
+                        }
+                        Some(prev) => {
+                            config.s_horner.enable(&mut region, row)?;
+                            prev.value().copied() * x.value().copied() + *coeff
+                        }
+                    };
+                    acc = Some(region.assign_advice(|| "acc", config.acc, row, || value)?);
+                }
+                Ok(acc.unwrap())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // p(x) with x from instance row 0 and p(x) exposed at row 1
+    struct EvalCircuit {
+        coefficients: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for EvalCircuit {
+        type Config = (PolynomialEvalConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                coefficients: vec![Value::unknown(); self.coefficients.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let coeff = meta.advice_column();
+            let acc = meta.advice_column();
+            let x = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (PolynomialEvalChip::configure(meta, coeff, acc, x), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice_from_instance(|| "x", instance, 0, config.x, 0),
+            )?;
+            let chip = PolynomialEvalChip::construct(config);
+            let p = chip.evaluate(layouter.namespace(|| "p(x)"), &self.coefficients, &x)?;
+            layouter.constrain_instance(p.cell(), instance, 1)
+        }
+    }
+
+    fn run(coefficients: &[u64], x: u64, p: u64) -> MockProver<Fp> {
+        let circuit = EvalCircuit {
+            coefficients: coefficients
+                .iter()
+                .map(|c| Value::known(Fp::from(*c)))
+                .collect(),
+        };
+        MockProver::run(4, &circuit, vec![vec![Fp::from(x), Fp::from(p)]]).unwrap()
+    }
+
+    #[test]
+    fn horner() {
+        // 3 + 2x + x^2
+        run(&[3, 2, 1], 5, 38).assert_satisfied();
+        run(&[3, 2, 1], 0, 3).assert_satisfied();
+        // 1 + x^3
+        run(&[1, 0, 0, 1], 2, 9).assert_satisfied();
+    }
+
+    #[test]
+    fn constant_polynomial() {
+        run(&[7], 100, 7).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        assert!(run(&[3, 2, 1], 5, 39).verify().is_err());
+    }
+
+    #[test]
+    fn no_coefficients() {
+        let circuit = EvalCircuit {
+            coefficients: vec![],
+        };
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![vec![Fp::one(), Fp::zero()]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
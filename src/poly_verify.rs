@@ -0,0 +1,312 @@
+// Verifies an opening p(z) = v of a committed polynomial inside the circuit.
+// The opening holds when p(X) - v = q(X) * (X - z) for the witnessed quotient
+// q, which is checked at a challenge r derived from the commitment. q(r) comes
+// from PolynomialEvalChip on the first three columns, then
+//
+// | p_r | v | q_r | r | z | s_open |
+// gate opening: s_open * (p_r - v - q_r * (r - z)) == 0
+//
+// Everything touching the commitment itself, a curve point over the other
+// Pasta field, is left to the scheme: loading it, hashing it into r, and
+// opening it at r. None of that is implemented yet, those steps are `todo!()`.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::poly_eval::{PolynomialEvalChip, PolynomialEvalConfig};
+
+/// The commitment side of an opening proof.
+pub trait CommitmentSchemeInstructions<F: FieldExt> {
+    type Commitment;
+
+    fn load_commitment(
+        &self,
+        layouter: impl Layouter<F>,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<Self::Commitment, Error>;
+
+    /// Fiat-Shamir challenge bound to the commitment.
+    fn challenge(
+        &self,
+        layouter: impl Layouter<F>,
+        commitment: &Self::Commitment,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// p(r) for the committed p, checked against the commitment.
+    fn evaluate_commitment(
+        &self,
+        layouter: impl Layouter<F>,
+        commitment: &Self::Commitment,
+        r: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error>;
+}
+
+/// KZG over a pairing friendly curve, needs a non-native pairing chip.
+#[derive(Debug, Clone, Default)]
+pub struct KzgScheme;
+
+/// IPA over the Pasta curves, needs non-native Pallas arithmetic.
+#[derive(Debug, Clone, Default)]
+pub struct IpaScheme;
+
+impl<F: FieldExt> CommitmentSchemeInstructions<F> for KzgScheme {
+    // (x, y) limbs of the G1 point
+    type Commitment = Vec<AssignedCell<F, F>>;
+
+    fn load_commitment(
+        &self,
+        _: impl Layouter<F>,
+        _: Column<Instance>,
+        _: usize,
+    ) -> Result<Self::Commitment, Error> {
+        todo!("load a G1 point as non-native limbs")
+    }
+
+    fn challenge(
+        &self,
+        _: impl Layouter<F>,
+        _: &Self::Commitment,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        todo!("hash the commitment limbs into a challenge")
+    }
+
+    fn evaluate_commitment(
+        &self,
+        _: impl Layouter<F>,
+        _: &Self::Commitment,
+        _: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        todo!("pairing check e(C - [v]G, H) == e(pi, [tau - r]H)")
+    }
+}
+
+impl<F: FieldExt> CommitmentSchemeInstructions<F> for IpaScheme {
+    // (x, y) of the Pallas point, coordinates in the base field
+    type Commitment = Vec<AssignedCell<F, F>>;
+
+    fn load_commitment(
+        &self,
+        _: impl Layouter<F>,
+        _: Column<Instance>,
+        _: usize,
+    ) -> Result<Self::Commitment, Error> {
+        todo!("load a Pallas point as non-native coordinates")
+    }
+
+    fn challenge(
+        &self,
+        _: impl Layouter<F>,
+        _: &Self::Commitment,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        todo!("hash the commitment coordinates into a challenge")
+    }
+
+    fn evaluate_commitment(
+        &self,
+        _: impl Layouter<F>,
+        _: &Self::Commitment,
+        _: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        todo!("fold the IPA rounds and check the final MSM")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PolyCommitVerifierConfig {
+    pub advice: [Column<Advice>; 5],
+    pub instance: Column<Instance>,
+    pub eval: PolynomialEvalConfig,
+    pub s_open: Selector,
+}
+
+pub struct PolyCommitVerifierChip<F: FieldExt, S: CommitmentSchemeInstructions<F>> {
+    config: PolyCommitVerifierConfig,
+    scheme: S,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, S: CommitmentSchemeInstructions<F>> PolyCommitVerifierChip<F, S> {
+    pub fn construct(config: PolyCommitVerifierConfig, scheme: S) -> Self {
+        Self {
+            config,
+            scheme,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        instance: Column<Instance>,
+    ) -> PolyCommitVerifierConfig {
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+        let s_open = meta.selector();
+
+        meta.create_gate("opening", |meta| {
+            let s = meta.query_selector(s_open);
+            let [p_r, v, q_r, r, z] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (p_r - v - q_r * (r - z))]
+        });
+
+        PolyCommitVerifierConfig {
+            advice,
+            instance,
+            eval: PolynomialEvalChip::configure(meta, advice[0], advice[1], advice[2]),
+            s_open,
+        }
+    }
+
+    /// Checks the polynomial committed in instance row `commitment_row` opens
+    /// to `v` at `z`, with `quotient` the coefficients of q, lowest first.
+    pub fn verify_opening(
+        &self,
+        mut layouter: impl Layouter<F>,
+        commitment_row: usize,
+        z: &AssignedCell<F, F>,
+        v: &AssignedCell<F, F>,
+        quotient: &[Value<F>],
+    ) -> Result<(), Error> {
+        let commitment = self.scheme.load_commitment(
+            layouter.namespace(|| "commitment"),
+            self.config.instance,
+            commitment_row,
+        )?;
+        let r = self
+            .scheme
+            .challenge(layouter.namespace(|| "challenge"), &commitment)?;
+        let q_r = self.evaluate(layouter.namespace(|| "q(r)"), quotient, &r)?;
+        let p_r =
+            self.scheme
+                .evaluate_commitment(layouter.namespace(|| "p(r)"), &commitment, &r)?;
+        self.check_opening(layouter.namespace(|| "opening"), &p_r, v, &q_r, &r, z)
+    }
+
+    /// q(r) by Horner's rule, `coefficients` lowest degree first.
+    pub fn evaluate(
+        &self,
+        layouter: impl Layouter<F>,
+        coefficients: &[Value<F>],
+        r: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        PolynomialEvalChip::construct(self.config.eval.clone()).evaluate(layouter, coefficients, r)
+    }
+
+    /// p(r) - v == q(r) * (r - z)
+    pub fn check_opening(
+        &self,
+        mut layouter: impl Layouter<F>,
+        p_r: &AssignedCell<F, F>,
+        v: &AssignedCell<F, F>,
+        q_r: &AssignedCell<F, F>,
+        r: &AssignedCell<F, F>,
+        z: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "opening",
+            |mut region| {
+                config.s_open.enable(&mut region, 0)?;
+                for (i, cell) in [p_r, v, q_r, r, z].into_iter().enumerate() {
+                    cell.copy_advice(|| "opening", &mut region, config.advice[i], 0)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+
+    // The opening check with p(r) computed from the plain coefficients of p in
+    // place of the commitment, r, z and v from instance rows 0, 1 and 2.
+    struct OpeningCircuit {
+        p: Vec<Value<Fp>>,
+        q: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for OpeningCircuit {
+        type Config = PolyCommitVerifierConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                p: vec![Value::unknown(); self.p.len()],
+                q: vec![Value::unknown(); self.q.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            PolyCommitVerifierChip::<Fp, KzgScheme>::configure(meta, advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let [r, z, v] = layouter.assign_region(
+                || "public",
+                |mut region| {
+                    let mut load = |row: usize| {
+                        region.assign_advice_from_instance(
+                            || "public",
+                            config.instance,
+                            row,
+                            config.advice[3],
+                            row,
+                        )
+                    };
+                    Ok([load(0)?, load(1)?, load(2)?])
+                },
+            )?;
+            let chip = PolyCommitVerifierChip::construct(config, KzgScheme);
+            let p_r = chip.evaluate(layouter.namespace(|| "p(r)"), &self.p, &r)?;
+            let q_r = chip.evaluate(layouter.namespace(|| "q(r)"), &self.q, &r)?;
+            chip.check_opening(layouter.namespace(|| "opening"), &p_r, &v, &q_r, &r, &z)
+        }
+    }
+
+    fn run(p: &[u64], q: &[u64], r: u64, z: u64, v: u64) -> MockProver<Fp> {
+        let known = |c: &[u64]| c.iter().map(|c| Value::known(Fp::from(*c))).collect();
+        let circuit = OpeningCircuit {
+            p: known(p),
+            q: known(q),
+        };
+        let instance = vec![Fp::from(r), Fp::from(z), Fp::from(v)];
+        MockProver::run(5, &circuit, vec![instance]).unwrap()
+    }
+
+    // p = 3 + 2X + X^2 opens to 6 at 1 with q = (p - 6) / (X - 1) = 3 + X
+    #[test]
+    fn valid_opening() {
+        run(&[3, 2, 1], &[3, 1], 10, 1, 6).assert_satisfied();
+        run(&[3, 2, 1], &[3, 1], 7, 1, 6).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_value_fails() {
+        assert!(run(&[3, 2, 1], &[3, 1], 10, 1, 7).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_quotient_fails() {
+        assert!(run(&[3, 2, 1], &[4, 1], 10, 1, 6).verify().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+use crate::{ipa::create_ipa_proof, recorder::record};
+
+// covers everything that ends up in the proof: the constraint system, the
+// assignment produced by synthesis and the public inputs
+pub fn circuit_checksum<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    public: &[Vec<F>],
+    k: u32,
+) -> [u8; 32] {
+    let mut cs = ConstraintSystem::<F>::default();
+    C::configure(&mut cs);
+    let recorder = record(circuit, k, public.to_vec()).expect("circuit should synthesize");
+
+    let mut state = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"hola2halo2_ckSum")
+        .to_state();
+    state.update(&k.to_le_bytes());
+    state.update(format!("{:?}", cs.pinned()).as_bytes());
+    state.update(format!("{:?}", recorder.selectors).as_bytes());
+    state.update(format!("{:?}", recorder.advice).as_bytes());
+    state.update(format!("{:?}", recorder.fixed).as_bytes());
+    state.update(format!("{:?}", recorder.copies).as_bytes());
+    state.update(format!("{:?}", public).as_bytes());
+
+    let mut checksum = [0u8; 32];
+    checksum.copy_from_slice(state.finalize().as_bytes());
+    checksum
+}
+
+#[derive(Debug, Default)]
+pub struct ProofCache {
+    pub store: HashMap<[u8; 32], Vec<u8>>,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl ProofCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// proofs are IPA proofs over the Pasta curves, so the field is fixed to Fp
+pub fn get_or_prove<C: Circuit<Fp>>(
+    cache: &mut ProofCache,
+    circuit: C,
+    public: Vec<Vec<Fp>>,
+    k: u32,
+) -> Vec<u8> {
+    let checksum = circuit_checksum(&circuit, &public, k);
+    if let Some(proof) = cache.store.get(&checksum) {
+        cache.hits += 1;
+        return proof.clone();
+    }
+
+    cache.misses += 1;
+    let proof = create_ipa_proof(circuit, &public, k).expect("proof generation should not fail");
+    cache.store.insert(checksum, proof.clone());
+    proof
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value;
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, ipa::verify_ipa_proof};
+
+    fn fibo(a: u64, b: u64) -> FiboCircuit<Fp> {
+        FiboCircuit {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        }
+    }
+
+    #[test]
+    fn second_proof_is_a_cache_hit() {
+        let mut cache = ProofCache::new();
+        let first = get_or_prove(&mut cache, fibo(1, 1), vec![], 4);
+        let second = get_or_prove(&mut cache, fibo(1, 1), vec![], 4);
+
+        assert_eq!((cache.misses, cache.hits), (1, 1));
+        // proving is randomized, equal bytes are the stored proof
+        assert_eq!(first, second);
+        verify_ipa_proof(&fibo(1, 1), &second, &[], 4).unwrap();
+    }
+
+    #[test]
+    fn other_witness_is_a_cache_miss() {
+        let mut cache = ProofCache::new();
+        get_or_prove(&mut cache, fibo(1, 1), vec![], 4);
+        get_or_prove(&mut cache, fibo(1, 2), vec![], 4);
+
+        assert_eq!((cache.misses, cache.hits), (2, 0));
+        assert_eq!(cache.store.len(), 2);
+    }
+}
@@ -0,0 +1,97 @@
+// Size of an IPA proof over the Pasta curves, counted element by element from
+// the order `create_proof` writes the transcript in. Points and scalars both
+// take 32 bytes.
+//
+// The estimate assumes every column is an advice column queried at the current
+// row only, the selectors fold into two fixed columns (as they do for `FiboChip`
+// and `SimpleFunctionChip`) and the constraint system has degree 3, so each
+// permutation column gets its own product chunk.
+
+const ELEMENT_BYTES: usize = 32;
+const DEGREE: usize = 3;
+
+pub fn estimate_proof_size(
+    num_columns: usize,
+    num_rows: usize,
+    num_lookups: usize,
+    num_permutations: usize,
+) -> usize {
+    let k = num_rows.next_power_of_two().trailing_zeros() as usize;
+    let chunks = num_permutations.div_ceil(DEGREE - 2);
+
+    // commitments: advice, lookup permuted input and table, permutation and
+    // lookup products, vanishing random poly and quotient pieces, multiopen f,
+    // IPA blinding poly and the L, R pair of every round
+    let points =
+        num_columns + 2 * num_lookups + chunks + num_lookups + 1 + (DEGREE - 1) + 1 + 1 + 2 * k;
+
+    // evaluations: advice, the selector columns, vanishing random poly, sigma
+    // polys, products at x, x_next and (all but the last chunk) x_last, lookups
+    // at their five points, one per multiopen point set, then the final IPA pair
+    let point_sets =
+        1 + usize::from(chunks > 0) + usize::from(chunks > 1) + usize::from(num_lookups > 0);
+    let scalars = num_columns
+        + 2
+        + 1
+        + num_permutations
+        + (3 * chunks).saturating_sub(1)
+        + 5 * num_lookups
+        + point_sets
+        + 2;
+
+    (points + scalars) * ELEMENT_BYTES
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, function::FunctionCircuit, ipa::create_ipa_proof};
+
+    // within 10% of the real proof
+    fn assert_close(estimate: usize, actual: usize) {
+        let diff = estimate.abs_diff(actual);
+        assert!(
+            diff * 10 <= actual,
+            "estimate {} vs actual {} bytes",
+            estimate,
+            actual
+        );
+    }
+
+    #[test]
+    fn fibo_circuit() {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        for k in [4, 6] {
+            let actual = create_ipa_proof(FiboCircuit { ..circuit }, &[], k)
+                .unwrap()
+                .len();
+            // three advice columns, all three in the permutation
+            assert_close(estimate_proof_size(3, 1 << k, 0, 3), actual);
+        }
+    }
+
+    #[test]
+    fn function_circuit() {
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        let actual = create_ipa_proof(circuit, &[], 4).unwrap().len();
+        // three advice columns with equality enabled
+        assert_close(estimate_proof_size(3, 16, 0, 3), actual);
+    }
+
+    #[test]
+    fn more_rows_add_one_round_per_doubling() {
+        let small = estimate_proof_size(3, 16, 0, 3);
+        assert_eq!(estimate_proof_size(3, 32, 0, 3) - small, 2 * ELEMENT_BYTES);
+        assert_eq!(
+            estimate_proof_size(3, 17, 0, 3),
+            estimate_proof_size(3, 32, 0, 3)
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// Substitutes cells known to hold a constant, a fixed column of 0s or 1s say,
+// into a gate expression and folds what that leaves behind:
+//
+// a + b - c   with b = 0   becomes   a - c
+// s * (a * y - c)   with y = 1   becomes   s * (a - c)
+//
+// 0 drops out of sums and zeroes products, 1 drops out of products, and any
+// node whose operands are all constants becomes a constant. The tree is rebuilt
+// node by node rather than through the operators, which refuse sums over
+// simple selectors.
+
+use std::collections::HashMap;
+
+use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
+
+use crate::gate_flatten::ExprAtom;
+
+fn constant<F: FieldExt>(expr: &Expression<F>) -> Option<F> {
+    match expr {
+        Expression::Constant(c) => Some(*c),
+        _ => None,
+    }
+}
+
+pub fn propagate_constants<F: FieldExt>(
+    expr: Expression<F>,
+    known_constants: &HashMap<ExprAtom<F>, F>,
+) -> Expression<F> {
+    let known = |atom: ExprAtom<F>, expr: Expression<F>| match known_constants.get(&atom) {
+        Some(c) => Expression::Constant(*c),
+        None => expr,
+    };
+    match expr {
+        Expression::Advice {
+            column_index,
+            rotation,
+            ..
+        } => known(ExprAtom::Advice(column_index, rotation.0), expr),
+        Expression::Fixed {
+            column_index,
+            rotation,
+            ..
+        } => known(ExprAtom::Fixed(column_index, rotation.0), expr),
+        Expression::Instance {
+            column_index,
+            rotation,
+            ..
+        } => known(ExprAtom::Instance(column_index, rotation.0), expr),
+        Expression::Selector(selector) => known(ExprAtom::Selector(selector), expr),
+        Expression::Constant(_) => expr,
+        Expression::Negated(a) => {
+            let a = propagate_constants(*a, known_constants);
+            match constant(&a) {
+                Some(c) => Expression::Constant(-c),
+                None => Expression::Negated(Box::new(a)),
+            }
+        }
+        Expression::Scaled(a, factor) => {
+            let a = propagate_constants(*a, known_constants);
+            match constant(&a) {
+                Some(c) => Expression::Constant(c * factor),
+                None if factor == F::zero() => Expression::Constant(F::zero()),
+                None if factor == F::one() => a,
+                None => Expression::Scaled(Box::new(a), factor),
+            }
+        }
+        Expression::Sum(a, b) => {
+            let a = propagate_constants(*a, known_constants);
+            let b = propagate_constants(*b, known_constants);
+            match (constant(&a), constant(&b)) {
+                (Some(x), Some(y)) => Expression::Constant(x + y),
+                (Some(x), None) if x == F::zero() => b,
+                (None, Some(y)) if y == F::zero() => a,
+                _ => Expression::Sum(Box::new(a), Box::new(b)),
+            }
+        }
+        Expression::Product(a, b) => {
+            let a = propagate_constants(*a, known_constants);
+            let b = propagate_constants(*b, known_constants);
+            match (constant(&a), constant(&b)) {
+                (Some(x), Some(y)) => Expression::Constant(x * y),
+                (Some(x), _) | (_, Some(x)) if x == F::zero() => Expression::Constant(F::zero()),
+                (Some(x), None) if x == F::one() => b,
+                (None, Some(y)) if y == F::one() => a,
+                (Some(x), None) => Expression::Scaled(Box::new(b), x),
+                (None, Some(y)) => Expression::Scaled(Box::new(a), y),
+                (None, None) => Expression::Product(Box::new(a), Box::new(b)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        pasta::Fp,
+        plonk::{ConstraintSystem, VirtualCells},
+        poly::Rotation,
+    };
+
+    use super::*;
+    use crate::gate_flatten::flatten_expression;
+
+    fn capture(
+        meta: &mut ConstraintSystem<Fp>,
+        gate: impl FnOnce(&mut VirtualCells<'_, Fp>) -> Expression<Fp>,
+    ) -> Expression<Fp> {
+        let mut captured = None;
+        meta.create_gate("captured", |meta| {
+            let expr = gate(meta);
+            captured = Some(expr.clone());
+            vec![expr]
+        });
+        captured.unwrap()
+    }
+
+    // s * (a + b - c) if `add`, s * (a * b - c) otherwise
+    fn gate(add: bool) -> Expression<Fp> {
+        let mut meta = ConstraintSystem::default();
+        let [a, b, c] = [(); 3].map(|_| meta.advice_column());
+        let s = meta.selector();
+        capture(&mut meta, |meta| {
+            let s = meta.query_selector(s);
+            let [a, b, c] = [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()));
+            match add {
+                true => s * (a + b - c),
+                false => s * (a * b - c),
+            }
+        })
+    }
+
+    fn known(cells: &[(ExprAtom<Fp>, u64)]) -> HashMap<ExprAtom<Fp>, Fp> {
+        cells
+            .iter()
+            .map(|(atom, value)| (*atom, Fp::from(*value)))
+            .collect()
+    }
+
+    fn monomials(expr: &Expression<Fp>) -> Vec<(Fp, Vec<usize>)> {
+        flatten_expression(expr)
+            .into_iter()
+            .map(|(coeff, atoms)| {
+                let columns = atoms
+                    .into_iter()
+                    .filter_map(|atom| match atom {
+                        ExprAtom::Advice(index, _) => Some(index),
+                        _ => None,
+                    })
+                    .collect();
+                (coeff, columns)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zero_drops_out_of_the_add_gate() {
+        // a + b - c with b = 0 is a - c
+        let folded = propagate_constants(gate(true), &known(&[(ExprAtom::Advice(1, 0), 0)]));
+        assert_eq!(
+            monomials(&folded),
+            vec![(Fp::one(), vec![0]), (-Fp::one(), vec![2])]
+        );
+        assert_eq!(folded.degree(), 2);
+    }
+
+    #[test]
+    fn one_drops_out_of_the_mul_gate() {
+        let folded = propagate_constants(gate(false), &known(&[(ExprAtom::Advice(1, 0), 1)]));
+        assert_eq!(
+            monomials(&folded),
+            vec![(Fp::one(), vec![0]), (-Fp::one(), vec![2])]
+        );
+    }
+
+    #[test]
+    fn zero_zeroes_products() {
+        // a * 0 - c is -c
+        let folded = propagate_constants(gate(false), &known(&[(ExprAtom::Advice(0, 0), 0)]));
+        assert_eq!(monomials(&folded), vec![(-Fp::one(), vec![2])]);
+
+        // other constants scale
+        let folded = propagate_constants(gate(false), &known(&[(ExprAtom::Advice(0, 0), 3)]));
+        assert_eq!(
+            monomials(&folded),
+            vec![(Fp::from(3), vec![1]), (-Fp::one(), vec![2])]
+        );
+    }
+
+    #[test]
+    fn every_cell_known_folds_to_a_constant() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let s = meta.selector();
+        let mut cells = known(&[
+            (ExprAtom::Advice(0, 0), 2),
+            (ExprAtom::Advice(1, 0), 3),
+            (ExprAtom::Advice(2, 0), 5),
+        ]);
+        cells.insert(ExprAtom::Selector(s), Fp::one());
+
+        // the first selector of a fresh constraint system is the gate's
+        let folded = propagate_constants(gate(true), &cells);
+        assert!(matches!(folded, Expression::Constant(c) if c == Fp::zero()));
+
+        // with c and the selector unknown, s * (5 - c) is left
+        cells.remove(&ExprAtom::Selector(s));
+        cells.remove(&ExprAtom::Advice(2, 0));
+        let folded = propagate_constants(gate(true), &cells);
+        assert_eq!(folded.degree(), 2);
+        assert_eq!(monomials(&folded).len(), 2);
+    }
+
+    #[test]
+    fn nothing_known_leaves_the_gate() {
+        let folded = propagate_constants(gate(true), &HashMap::new());
+        assert_eq!(monomials(&folded), monomials(&gate(true)));
+    }
+}
@@ -0,0 +1,228 @@
+// Stream of pseudo random field elements out of a seed, the i-th output is
+// H(seed || i) with the algebraic hash from `hash.rs` (there is no Poseidon
+// chip in this crate). The counter starts at the constant 0 and every call
+// steps it by one in its own region:
+//
+// | counter | s_increment |
+// | i       | 1           |
+// | i + 1   | 0           |
+// gate increment: s_increment * (counter(next) - counter - 1) == 0
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::hash::{hash, AlgebraicHashChip, AlgebraicHashConfig};
+
+// out of circuit evaluation of the `counter`-th output
+pub fn random_oracle<F: FieldExt>(seed: F, counter: u64) -> F {
+    hash(seed, F::from(counter))
+}
+
+#[derive(Debug, Clone)]
+pub struct RandomOracleConfig {
+    pub hash: AlgebraicHashConfig,
+    pub counter: Column<Advice>,
+    pub constant: Column<Fixed>,
+    pub s_increment: Selector,
+}
+
+pub struct RandomOracleChip<F: FieldExt> {
+    config: RandomOracleConfig,
+    seed: AssignedCell<F, F>,
+    // next counter to hash, assigned on the first call
+    counter: Option<AssignedCell<F, F>>,
+}
+
+impl<F: FieldExt> RandomOracleChip<F> {
+    pub fn construct(config: RandomOracleConfig, seed: AssignedCell<F, F>) -> Self {
+        Self {
+            config,
+            seed,
+            counter: None,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        round_constant: Column<Fixed>,
+        constant: Column<Fixed>,
+    ) -> RandomOracleConfig {
+        let [state, key, counter] = advice;
+        meta.enable_equality(counter);
+        meta.enable_constant(constant);
+
+        let s_increment = meta.selector();
+        meta.create_gate("increment", |meta| {
+            let s = meta.query_selector(s_increment);
+            let cur = meta.query_advice(counter, Rotation::cur());
+            let next = meta.query_advice(counter, Rotation::next());
+            vec![s * (next - cur - Expression::Constant(F::one()))]
+        });
+
+        RandomOracleConfig {
+            hash: AlgebraicHashChip::configure(meta, state, key, round_constant),
+            counter,
+            constant,
+            s_increment,
+        }
+    }
+
+    pub fn next(&mut self, mut layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config.clone();
+        let counter = match self.counter.take() {
+            Some(counter) => counter,
+            None => layouter.assign_region(
+                || "counter start",
+                |mut region| {
+                    region.assign_advice_from_constant(|| "0", config.counter, 0, F::zero())
+                },
+            )?,
+        };
+
+        let hash = AlgebraicHashChip::construct(config.hash.clone());
+        let output = hash.hash(
+            layouter.namespace(|| "H(seed || counter)"),
+            &self.seed,
+            &counter,
+        )?;
+
+        let next = layouter.assign_region(
+            || "increment",
+            |mut region| {
+                config.s_increment.enable(&mut region, 0)?;
+                let cur = counter.copy_advice(|| "counter", &mut region, config.counter, 0)?;
+                region.assign_advice(
+                    || "counter + 1",
+                    config.counter,
+                    1,
+                    || cur.value().map(|c| *c + F::one()),
+                )
+            },
+        )?;
+        self.counter = Some(next);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    const CALLS: usize = 3;
+
+    // every oracle draws CALLS outputs from the same seed cell
+    //
+    // instance: | seed | oracle 0 outputs | oracle 1 outputs | ...
+    struct OracleCircuit {
+        seed: Value<Fp>,
+        oracles: usize,
+    }
+
+    impl Circuit<Fp> for OracleCircuit {
+        type Config = (RandomOracleConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                seed: Value::unknown(),
+                oracles: self.oracles,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let (round_constant, constant) = (meta.fixed_column(), meta.fixed_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                RandomOracleChip::configure(meta, advice, round_constant, constant),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let seed = layouter.assign_region(
+                || "seed",
+                |mut region| region.assign_advice(|| "seed", config.counter, 0, || self.seed),
+            )?;
+            layouter.constrain_instance(seed.cell(), instance, 0)?;
+
+            let mut row = 1;
+            for _ in 0..self.oracles {
+                let mut oracle = RandomOracleChip::construct(config.clone(), seed.clone());
+                for _ in 0..CALLS {
+                    let output = oracle.next(layouter.namespace(|| "next"))?;
+                    layouter.constrain_instance(output.cell(), instance, row)?;
+                    row += 1;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn sequence(seed: Fp) -> Vec<Fp> {
+        (0..CALLS as u64).map(|i| random_oracle(seed, i)).collect()
+    }
+
+    fn run(seed: Fp, oracles: usize, outputs: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = OracleCircuit {
+            seed: Value::known(seed),
+            oracles,
+        };
+        let public = [vec![seed], outputs].concat();
+        MockProver::run(7, &circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn sequential_calls_produce_different_outputs() {
+        let seed = Fp::from(42);
+        let outputs = sequence(seed);
+        for i in 0..CALLS {
+            for j in i + 1..CALLS {
+                assert_ne!(outputs[i], outputs[j]);
+            }
+        }
+        run(seed, 1, outputs).assert_satisfied();
+    }
+
+    #[test]
+    fn same_seed_produces_the_same_sequence() {
+        let seed = Fp::from(42);
+        assert_eq!(sequence(seed), sequence(seed));
+        // two oracles on the same seed cell draw the same outputs
+        run(seed, 2, [sequence(seed), sequence(seed)].concat()).assert_satisfied();
+        // another seed does not
+        assert_ne!(sequence(Fp::from(43)), sequence(seed));
+    }
+
+    #[test]
+    fn out_of_order_outputs_fail() {
+        let seed = Fp::from(42);
+        let mut outputs = sequence(seed);
+        outputs.swap(0, 1);
+        assert!(run(seed, 1, outputs).verify().is_err());
+    }
+
+    #[test]
+    fn other_seed_outputs_fail() {
+        assert!(run(Fp::from(42), 1, sequence(Fp::from(43)))
+            .verify()
+            .is_err());
+    }
+}
@@ -0,0 +1,210 @@
+// running sum decomposition, least significant bit first
+// | z       | bit | selector | s_end |
+// | x       | b_0 | 1        | 0     |
+// | (x-b0)/2| b_1 | 1        | 0     |
+// | ...     |     |          |       |
+// | 0       |     | 0        | 1     |
+// gate decompose: selector * (z(cur) - 2 * z(next) - bit) == 0, selector * bit * (1 - bit) == 0
+// gate end: s_end * z == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct RangeCheckConfig {
+    pub z: Column<Advice>,
+    pub bit: Column<Advice>,
+    pub selector: Selector,
+    pub s_end: Selector,
+}
+
+/// Proves a cell holds a value in `[0, 2^BITS)`.
+pub struct RangeCheckChip<F: FieldExt, const BITS: usize> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> RangeCheckChip<F, BITS> {
+    pub fn construct(config: RangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        z: Column<Advice>,
+        bit: Column<Advice>,
+    ) -> RangeCheckConfig {
+        meta.enable_equality(z);
+
+        let selector = meta.selector();
+        let s_end = meta.selector();
+
+        meta.create_gate("decompose", |meta| {
+            let s = meta.query_selector(selector);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+
+            vec![
+                s.clone() * (z_cur - two * z_next - bit.clone()),
+                s * bit.clone() * (one - bit),
+            ]
+        });
+
+        meta.create_gate("decompose end", |meta| {
+            let s = meta.query_selector(s_end);
+            let z = meta.query_advice(z, Rotation::cur());
+            vec![s * z]
+        });
+
+        RangeCheckConfig {
+            z,
+            bit,
+            selector,
+            s_end,
+        }
+    }
+
+    // returns the bits, least significant first
+    pub fn check(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        self.check_bits(layouter, value, BITS)
+    }
+
+    // `check` against [0, 2^bits) for a width only known at synthesis time
+    pub fn check_bits(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        bits: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                let mut z = value.copy_advice(|| "z", &mut region, config.z, 0)?;
+                let mut decomposed = Vec::with_capacity(bits);
+                for row in 0..bits {
+                    config.selector.enable(&mut region, row)?;
+
+                    let bit = z
+                        .value()
+                        .map(|z| F::from(z.to_repr().as_ref()[0] as u64 & 1));
+                    let next = z.value().zip(bit).map(|(z, bit)| (*z - bit) * F::TWO_INV);
+
+                    decomposed.push(region.assign_advice(|| "bit", config.bit, row, || bit)?);
+                    z = region.assign_advice(|| "z", config.z, row + 1, || next)?;
+                }
+                config.s_end.enable(&mut region, bits)?;
+                Ok(decomposed)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // value checked against [0, 2^bits), or [0, 2^8) by `check` when bits is None
+    //
+    // instance: | value | bit 0 | bit 1 | ... |
+    struct RangeCircuit {
+        bits: Option<usize>,
+    }
+
+    impl Circuit<Fp> for RangeCircuit {
+        type Config = (RangeCheckConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { bits: self.bits }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [z, bit] = [(); 2].map(|_| meta.advice_column());
+            meta.enable_equality(bit);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (RangeCheckChip::<Fp, 8>::configure(meta, z, bit), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let value = layouter.assign_region(
+                || "value",
+                |mut region| {
+                    region.assign_advice_from_instance(|| "value", instance, 0, config.z, 0)
+                },
+            )?;
+            let chip = RangeCheckChip::<Fp, 8>::construct(config);
+            let bits = match self.bits {
+                Some(bits) => chip.check_bits(layouter.namespace(|| "check"), &value, bits)?,
+                None => chip.check(layouter.namespace(|| "check"), &value)?,
+            };
+            for (i, bit) in bits.iter().enumerate() {
+                layouter.constrain_instance(bit.cell(), instance, i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn run(bits: Option<usize>, value: u64) -> MockProver<Fp> {
+        let width = bits.unwrap_or(8);
+        let decomposed = (0..width).map(|i| Fp::from(value >> i & 1));
+        let public = std::iter::once(Fp::from(value)).chain(decomposed).collect();
+        MockProver::run(5, &RangeCircuit { bits }, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn check_accepts_values_below_2_bits() {
+        for value in [0, 1, 0b1011_0110, 255] {
+            run(None, value).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn check_rejects_2_bits() {
+        assert!(run(None, 256).verify().is_err());
+    }
+
+    #[test]
+    fn check_bits_takes_the_width_at_synthesis() {
+        run(Some(4), 15).assert_satisfied();
+        assert!(run(Some(4), 16).verify().is_err());
+        run(Some(12), 4000).assert_satisfied();
+        assert!(run(Some(12), 4096).verify().is_err());
+    }
+
+    #[test]
+    fn negative_values_are_out_of_range() {
+        // -1 is p - 1, its low 8 bits don't add back up to it
+        let public = vec![[vec![-Fp::one()], vec![Fp::one(); 8]].concat()];
+        let prover = MockProver::run(5, &RangeCircuit { bits: None }, public).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,287 @@
+// halo2_proofs keeps the MockProver state, gate list and column indices private,
+// so the dev tooling in this crate replays synthesis through its own
+// `Assignment` implementation and reads indices back from the Debug output.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    dev::CircuitGates,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ColumnType, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+
+pub fn parse_index(debug: &str, prefix: &str) -> usize {
+    let start = debug
+        .find(prefix)
+        .unwrap_or_else(|| panic!("unexpected debug format {}", debug))
+        + prefix.len();
+    debug[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .unwrap()
+}
+
+pub fn column_index<C: ColumnType>(column: Column<C>) -> usize {
+    parse_index(&format!("{:?}", column), "index: ")
+}
+
+pub fn selector_index(selector: &Selector) -> usize {
+    parse_index(&format!("{:?}", selector), "Selector(")
+}
+
+pub fn value_of<V>(value: Value<V>) -> Option<V> {
+    let mut out = None;
+    value.map(|v| out = Some(v));
+    out
+}
+
+// fixed columns registered through `enable_constant`, rebuilt from the pinned
+// constraint system since the field itself is crate private
+pub fn constant_columns<F: FieldExt>(cs: &ConstraintSystem<F>) -> Vec<Column<Fixed>> {
+    let pinned = format!("{:?}", cs.pinned());
+    let constants = &pinned[pinned.rfind("constants: [").unwrap()..];
+    let constants = &constants[..constants.find(']').unwrap()];
+
+    let mut fresh = ConstraintSystem::<F>::default();
+    let fixed: Vec<_> = (0..parse_index(&pinned, "num_fixed_columns: "))
+        .map(|_| fresh.fixed_column())
+        .collect();
+
+    constants
+        .match_indices("index: ")
+        .map(|(i, _)| fixed[parse_index(&constants[i..], "index: ")])
+        .collect()
+}
+
+/// Gate names with the indices of the selectors each gate queries, in
+/// `create_gate` order.
+pub fn gate_selectors<F: FieldExt, C: Circuit<F>>() -> Vec<(String, Vec<usize>)> {
+    let mut gates: Vec<(String, Vec<usize>)> = vec![];
+    for line in CircuitGates::collect::<F, C>().to_string().lines() {
+        if line.starts_with("Total gates") {
+            break;
+        } else if line.starts_with("- ") && line.ends_with(':') {
+            // named constraint, its expression follows on the next line
+            continue;
+        } else if line.starts_with("- ") || line.starts_with("  ") {
+            let (_, selectors) = gates.last_mut().unwrap();
+            for (i, _) in line.match_indices('S') {
+                let digits = &line[i + 1..];
+                if digits.starts_with(|c: char| c.is_ascii_digit()) {
+                    let selector = parse_index(digits, "");
+                    if !selectors.contains(&selector) {
+                        selectors.push(selector);
+                    }
+                }
+            }
+        } else {
+            gates.push((line.trim_end_matches(':').to_string(), vec![]));
+        }
+    }
+    gates
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRegion {
+    pub name: String,
+    pub rows: Option<(usize, usize)>,
+}
+
+/// Everything assigned while synthesizing a circuit.
+#[derive(Debug)]
+pub struct Recorder<F: FieldExt> {
+    pub k: u32,
+    pub regions: Vec<RecordedRegion>,
+    current_region: Option<usize>,
+    // (selector index, row)
+    pub selectors: Vec<(usize, usize)>,
+    // (column index, row) -> value, `None` when the value is unknown
+    pub advice: BTreeMap<(usize, usize), Option<F>>,
+    pub fixed: BTreeMap<(usize, usize), Option<F>>,
+    pub copies: Vec<((Column<Any>, usize), (Column<Any>, usize))>,
+    pub instance: Vec<Vec<F>>,
+    // like keygen, a layout-only run never asks for advice values
+    witnesses: bool,
+}
+
+impl<F: FieldExt> Recorder<F> {
+    pub fn new(k: u32, instance: Vec<Vec<F>>) -> Self {
+        Self {
+            k,
+            regions: vec![],
+            current_region: None,
+            selectors: vec![],
+            advice: BTreeMap::new(),
+            fixed: BTreeMap::new(),
+            copies: vec![],
+            instance,
+            witnesses: true,
+        }
+    }
+
+    fn update_region_rows(&mut self, row: usize) {
+        if let Some(index) = self.current_region {
+            let rows = &mut self.regions[index].rows;
+            *rows = Some(match *rows {
+                Some((start, end)) => (start.min(row), end.max(row)),
+                None => (row, row),
+            });
+        }
+    }
+
+    pub fn region_at(&self, row: usize) -> Option<&RecordedRegion> {
+        self.regions.iter().find(|region| {
+            region
+                .rows
+                .is_some_and(|(start, end)| (start..=end).contains(&row))
+        })
+    }
+}
+
+/// Runs the circuit's floor planner against a [`Recorder`].
+pub fn record<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    k: u32,
+    instance: Vec<Vec<F>>,
+) -> Result<Recorder<F>, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let constants = constant_columns(&cs);
+
+    let mut recorder = Recorder::new(k, instance);
+    C::FloorPlanner::synthesize(&mut recorder, circuit, config, constants)?;
+    Ok(recorder)
+}
+
+/// [`record`] without calling the advice value closures, every advice cell is
+/// recorded as unknown. Circuits that refuse unknown witnesses still lay out.
+pub fn record_layout<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    k: u32,
+) -> Result<Recorder<F>, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let constants = constant_columns(&cs);
+
+    let mut recorder = Recorder::new(k, vec![]);
+    recorder.witnesses = false;
+    C::FloorPlanner::synthesize(&mut recorder, circuit, config, constants)?;
+    Ok(recorder)
+}
+
+impl<F: FieldExt> Assignment<F> for Recorder<F> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.current_region = Some(self.regions.len());
+        self.regions.push(RecordedRegion {
+            name: name_fn().into(),
+            rows: None,
+        });
+    }
+
+    fn exit_region(&mut self) {
+        self.current_region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update_region_rows(row);
+        self.selectors.push((selector_index(selector), row));
+        Ok(())
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        Ok(self
+            .instance
+            .get(column_index(column))
+            .and_then(|values| values.get(row))
+            .map_or(Value::unknown(), |value| Value::known(*value)))
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update_region_rows(row);
+        let value = match self.witnesses {
+            true => value_of(to().map(|v| v.into().evaluate())),
+            false => None,
+        };
+        self.advice.insert((column_index(column), row), value);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update_region_rows(row);
+        let value = value_of(to().map(|v| v.into().evaluate()));
+        self.fixed.insert((column_index(column), row), value);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.copies
+            .push(((left_column, left_row), (right_column, right_row)));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        let value = value_of(to.map(|v| v.evaluate()));
+        for row in row..(1 << self.k) {
+            self.fixed.insert((column_index(column), row), value);
+        }
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
@@ -0,0 +1,212 @@
+// | x    | coeff_0 | ... | coeff_{n-1} | selector |
+// | x_0  | k_0     | ... | k_{n-1}     | 1        |
+// | x_1  | k_0     | ... | k_{n-1}     | 1        |
+// | ...  |
+// gate: selector * (k_0 * x(cur) + ... + k_{n-1} * x(cur + n - 1) - x(cur + n)) == 0
+//
+// coeffs = [1, 1] is Fibonacci, [1, 2] is Pell, [1, 1, 1] is Tribonacci
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct RecurrenceConfig<const COEFFS: usize> {
+    pub advice: Column<Advice>,
+    pub coeffs: [Column<Fixed>; COEFFS],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct RecurrenceChip<F: FieldExt, const COEFFS: usize> {
+    config: RecurrenceConfig<COEFFS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const COEFFS: usize> RecurrenceChip<F, COEFFS> {
+    pub fn construct(config: RecurrenceConfig<COEFFS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: Column<Advice>,
+        instance: Column<Instance>,
+    ) -> RecurrenceConfig<COEFFS> {
+        let coeffs = [(); COEFFS].map(|_| meta.fixed_column());
+        let selector = meta.selector();
+
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        meta.create_gate("recurrence", |meta| {
+            let s = meta.query_selector(selector);
+            let next = meta.query_advice(advice, Rotation(COEFFS as i32));
+            let sum = coeffs
+                .iter()
+                .enumerate()
+                .map(|(i, coeff)| {
+                    meta.query_fixed(*coeff, Rotation::cur())
+                        * meta.query_advice(advice, Rotation(i as i32))
+                })
+                .reduce(|acc, term| acc + term)
+                .expect("recurrence needs at least one coefficient");
+            vec![s * (sum - next)]
+        });
+
+        RecurrenceConfig {
+            advice,
+            coeffs,
+            selector,
+            instance,
+        }
+    }
+
+    // assign the initial terms followed by `steps` new terms, return the last one
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coeffs: [F; COEFFS],
+        initial: [Value<F>; COEFFS],
+        steps: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "recurrence",
+            |mut region| {
+                let mut terms = Vec::with_capacity(COEFFS + steps);
+                for (row, x) in initial.iter().enumerate() {
+                    terms.push(region.assign_advice(|| "initial", config.advice, row, || *x)?);
+                }
+
+                for row in 0..steps {
+                    config.selector.enable(&mut region, row)?;
+                    for (column, coeff) in config.coeffs.iter().zip(coeffs.iter()) {
+                        region.assign_fixed(|| "coeff", *column, row, || Value::known(*coeff))?;
+                    }
+
+                    let next = terms[row..row + COEFFS]
+                        .iter()
+                        .zip(coeffs.iter())
+                        .fold(Value::known(F::zero()), |acc, (term, coeff)| {
+                            acc + term.value().map(|x| *x * *coeff)
+                        });
+                    terms.push(region.assign_advice(
+                        || "next",
+                        config.advice,
+                        row + COEFFS,
+                        || next,
+                    )?);
+                }
+
+                Ok(terms.pop().unwrap())
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+/// Proves that the public output is the term reached after `steps` applications
+/// of `x_{i+n} = coeffs[0] * x_i + ... + coeffs[n-1] * x_{i+n-1}`.
+pub struct RecurrenceCircuit<F: FieldExt, const COEFFS: usize> {
+    pub coeffs: [F; COEFFS],
+    pub initial: [Value<F>; COEFFS],
+    pub steps: usize,
+}
+
+impl<F: FieldExt, const COEFFS: usize> RecurrenceCircuit<F, COEFFS> {
+    pub fn new(coeffs: [F; COEFFS], initial: [Value<F>; COEFFS], steps: usize) -> Self {
+        Self {
+            coeffs,
+            initial,
+            steps,
+        }
+    }
+}
+
+impl<F: FieldExt, const COEFFS: usize> Circuit<F> for RecurrenceCircuit<F, COEFFS> {
+    type Config = RecurrenceConfig<COEFFS>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.coeffs, [Value::unknown(); COEFFS], self.steps)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = meta.advice_column();
+        let instance = meta.instance_column();
+        RecurrenceChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = RecurrenceChip::<F, COEFFS>::construct(config);
+
+        let out = chip.assign(
+            layouter.namespace(|| "recurrence"),
+            self.coeffs,
+            self.initial,
+            self.steps,
+        )?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn prove<const COEFFS: usize>(
+        coeffs: [u64; COEFFS],
+        initial: [u64; COEFFS],
+        steps: usize,
+        out: u64,
+    ) -> MockProver<Fp> {
+        let circuit = RecurrenceCircuit::new(
+            coeffs.map(Fp::from),
+            initial.map(|x| Value::known(Fp::from(x))),
+            steps,
+        );
+        MockProver::run(5, &circuit, vec![vec![Fp::from(out)]]).unwrap()
+    }
+
+    #[test]
+    fn fibonacci() {
+        // 1, 1, 2, 3, 5, 8, 13, 21, 34, 55
+        prove([1, 1], [1, 1], 8, 55).assert_satisfied();
+    }
+
+    #[test]
+    fn pell() {
+        // 0, 1, 2, 5, 12, 29, 70, 169
+        prove([1, 2], [0, 1], 6, 169).assert_satisfied();
+    }
+
+    #[test]
+    fn tribonacci() {
+        // 0, 0, 1, 1, 2, 4, 7, 13, 24, 44
+        prove([1, 1, 1], [0, 0, 1], 7, 44).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_output_fails() {
+        assert!(prove([1, 1], [1, 1], 8, 56).verify().is_err());
+    }
+}
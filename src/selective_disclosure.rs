@@ -0,0 +1,142 @@
+// Commits to N private attributes with TupleHashChip and reveals only the one
+// at a public index k, picked out of the same attribute cells with MuxChip:
+//
+// H = tuple_hash(r, a_0, ..., a_{N-1}), a_k = mux(a_0, ..., a_{N-1}; k)
+//
+// The other attributes stay in the witness, bound to H.
+//
+// instance: | r | H | k | a_k |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+    mux::{MuxChip, MuxConfig},
+    tuple_hash::{tuple_hash, TupleHashChip, TupleHashConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct SelectiveDisclosureConfig {
+    pub tuple_hash: TupleHashConfig,
+    pub mux: MuxConfig,
+}
+
+pub struct SelectiveDisclosureCircuit<F: FieldExt, const N: usize> {
+    pub attributes: [Value<F>; N],
+}
+
+impl<F: FieldExt, const N: usize> SelectiveDisclosureCircuit<F, N> {
+    pub fn new(attributes: [F; N]) -> Self {
+        Self {
+            attributes: attributes.map(Value::known),
+        }
+    }
+
+    pub fn public_inputs(r: F, attributes: [F; N], k: usize) -> Vec<F> {
+        vec![
+            r,
+            tuple_hash(r, &attributes),
+            F::from(k as u64),
+            attributes[k],
+        ]
+    }
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for SelectiveDisclosureCircuit<F, N> {
+    type Config = SelectiveDisclosureConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            attributes: [Value::unknown(); N],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let tuple_hash = TupleHashChip::<F, N>::configure(meta);
+        let function = &tuple_hash.function;
+        let advice = [
+            function.x,
+            function.y,
+            function.z,
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let position = meta.fixed_column();
+
+        SelectiveDisclosureConfig {
+            mux: MuxChip::configure(meta, advice, position),
+            tuple_hash,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let instance = config.tuple_hash.instance;
+        let index = config.mux.advice[1];
+        let tuple_hash = TupleHashChip::<F, N>::construct(config.tuple_hash);
+        let mux = MuxChip::construct(config.mux);
+
+        let r = tuple_hash.load_challenge(layouter.namespace(|| "r"), 0)?;
+        let attributes =
+            tuple_hash.load_tuple(layouter.namespace(|| "attributes"), self.attributes)?;
+        let hash = tuple_hash.hash(layouter.namespace(|| "commitment"), &r, &attributes)?;
+        layouter.constrain_instance(hash.0.cell(), instance, 1)?;
+
+        let k = layouter.assign_region(
+            || "k",
+            |mut region| region.assign_advice_from_instance(|| "k", instance, 2, index, 0),
+        )?;
+        let cells: Vec<_> = attributes.iter().map(|a| a.0.clone()).collect();
+        let revealed = mux.select(layouter.namespace(|| "reveal"), &cells, &k)?;
+        layouter.constrain_instance(revealed.cell(), instance, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn attributes() -> [Fp; 4] {
+        [19, 1990, 42, 7].map(Fp::from)
+    }
+
+    fn run(attributes: [Fp; 4], public: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = SelectiveDisclosureCircuit::new(attributes);
+        MockProver::run(6, &circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn reveals_each_of_4_attributes() {
+        let r = Fp::from(0xc0ffee);
+        for k in 0..4 {
+            let public = SelectiveDisclosureCircuit::public_inputs(r, attributes(), k);
+            run(attributes(), public).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn wrong_revealed_value_fails() {
+        let mut public = SelectiveDisclosureCircuit::public_inputs(Fp::from(5), attributes(), 1);
+        // a_2 claimed at index 1
+        public[3] = attributes()[2];
+        assert!(run(attributes(), public).verify().is_err());
+    }
+
+    #[test]
+    fn attributes_not_matching_the_commitment_fail() {
+        let public = SelectiveDisclosureCircuit::public_inputs(Fp::from(5), attributes(), 0);
+        let mut other = attributes();
+        other[3] = Fp::from(8);
+        // the revealed a_0 is unchanged, the hidden a_3 is not
+        assert!(run(other, public).verify().is_err());
+    }
+}
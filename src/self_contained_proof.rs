@@ -0,0 +1,305 @@
+// A proof bundled with what checks it: the IPA proof bytes, the instance
+// columns and a fingerprint of the verifying key.
+//
+// halo2 0.2 can't write a VerifyingKey out, let alone read one back without
+// the circuit type, so `vk_bytes` is k followed by blake2b over the pinned
+// verifying key:
+//
+// | k (u32 le) | blake2b-256(pinned vk) |
+//
+// Verifying rebuilds the key for k from the circuit, refuses a proof whose
+// fingerprint doesn't match it and then checks the proof. The JSON form goes
+// through `json!` like `CircuitABI`, with bytes and field elements in hex:
+//
+// { "proof": "..", "public_inputs": [["..", ..], ..], "vk": ".." }
+
+use halo2_proofs::{
+    pasta::{group::ff::PrimeField, EqAffine, Fp},
+    plonk::{Circuit, Error, VerifyingKey},
+};
+use serde_json::{json, Value};
+
+use crate::ipa::{keygen, prove, verify as verify_ipa};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedProof<F> {
+    pub proof_bytes: Vec<u8>,
+    pub public_inputs: Vec<Vec<F>>,
+    pub vk_bytes: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum VerificationError {
+    // vk_bytes isn't k and a 32 byte fingerprint
+    MalformedKey,
+    // the circuit's verifying key for k has another fingerprint
+    KeyMismatch,
+    Plonk(Error),
+    Json(serde_json::Error),
+    // path to the missing or mistyped field
+    Field(String),
+}
+
+impl From<Error> for VerificationError {
+    fn from(error: Error) -> Self {
+        VerificationError::Plonk(error)
+    }
+}
+
+impl From<serde_json::Error> for VerificationError {
+    fn from(error: serde_json::Error) -> Self {
+        VerificationError::Json(error)
+    }
+}
+
+fn vk_bytes(vk: &VerifyingKey<EqAffine>, k: u32) -> Vec<u8> {
+    let fingerprint = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"hola2halo2_vkFpt")
+        .hash(format!("{:?}", vk.pinned()).as_bytes());
+    let mut bytes = k.to_le_bytes().to_vec();
+    bytes.extend_from_slice(fingerprint.as_bytes());
+    bytes
+}
+
+// proofs are IPA proofs over the Pasta curves, so the field is fixed to Fp
+pub fn from_circuit<C: Circuit<Fp>>(
+    circuit: C,
+    public: Vec<Vec<Fp>>,
+    k: u32,
+) -> SimulatedProof<Fp> {
+    let (params, pk) = keygen(&circuit, k).expect("keygen should not fail");
+    let proof_bytes =
+        prove(&params, &pk, circuit, &public).expect("proof generation should not fail");
+    SimulatedProof {
+        proof_bytes,
+        vk_bytes: vk_bytes(pk.get_vk(), k),
+        public_inputs: public,
+    }
+}
+
+/// Checks `p` against the key of `circuit`, any witnesses in it are ignored.
+pub fn verify_with<C: Circuit<Fp>>(
+    p: &SimulatedProof<Fp>,
+    circuit: &C,
+) -> Result<(), VerificationError> {
+    let k = match p.vk_bytes.len() {
+        36 => u32::from_le_bytes(p.vk_bytes[..4].try_into().unwrap()),
+        _ => return Err(VerificationError::MalformedKey),
+    };
+    // keygen on a bogus k would allocate 2^k points
+    if !(1..=32).contains(&k) {
+        return Err(VerificationError::MalformedKey);
+    }
+    let (params, pk) = keygen(circuit, k)?;
+    if vk_bytes(pk.get_vk(), k) != p.vk_bytes {
+        return Err(VerificationError::KeyMismatch);
+    }
+    verify_ipa(&params, pk.get_vk(), &p.proof_bytes, &p.public_inputs)?;
+    Ok(())
+}
+
+/// `verify_with` for circuits whose shape doesn't depend on their fields.
+pub fn verify<C: Circuit<Fp> + Default>(p: &SimulatedProof<Fp>) -> Result<(), VerificationError> {
+    verify_with(p, &C::default())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(s: &str, path: &str) -> Result<Vec<u8>, VerificationError> {
+    let invalid = || VerificationError::Field(path.to_string());
+    if !s.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+fn field_from_hex(s: &str, path: &str) -> Result<Fp, VerificationError> {
+    let mut repr = <Fp as PrimeField>::Repr::default();
+    let bytes = from_hex(s, path)?;
+    if bytes.len() != repr.as_ref().len() {
+        return Err(VerificationError::Field(path.to_string()));
+    }
+    repr.as_mut().copy_from_slice(&bytes);
+    Option::from(Fp::from_repr(repr)).ok_or_else(|| VerificationError::Field(path.to_string()))
+}
+
+impl SimulatedProof<Fp> {
+    pub fn to_json(&self) -> String {
+        let public: Vec<Vec<String>> = self
+            .public_inputs
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|v| to_hex(v.to_repr().as_ref()))
+                    .collect()
+            })
+            .collect();
+        json!({
+            "proof": to_hex(&self.proof_bytes),
+            "public_inputs": public,
+            "vk": to_hex(&self.vk_bytes),
+        })
+        .to_string()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, VerificationError> {
+        let proof: Value = serde_json::from_str(s)?;
+        let hex = |key: &str| {
+            proof[key]
+                .as_str()
+                .ok_or_else(|| VerificationError::Field(key.to_string()))
+                .and_then(|s| from_hex(s, key))
+        };
+        let columns = proof["public_inputs"]
+            .as_array()
+            .ok_or_else(|| VerificationError::Field("public_inputs".to_string()))?;
+
+        let mut public_inputs = vec![];
+        for (i, column) in columns.iter().enumerate() {
+            let path = format!("public_inputs[{}]", i);
+            let column = column
+                .as_array()
+                .ok_or_else(|| VerificationError::Field(path.clone()))?;
+            let values = column
+                .iter()
+                .enumerate()
+                .map(|(j, value)| {
+                    let path = format!("{}[{}]", path, j);
+                    value
+                        .as_str()
+                        .ok_or_else(|| VerificationError::Field(path.clone()))
+                        .and_then(|s| field_from_hex(s, &path))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            public_inputs.push(values);
+        }
+
+        Ok(Self {
+            proof_bytes: hex("proof")?,
+            public_inputs,
+            vk_bytes: hex("vk")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value as Witness;
+
+    use super::*;
+    use crate::{fiat_shamir::FiatShamirCircuit, fibo1::FiboCircuit, function::FunctionCircuit};
+
+    fn fibo_proof() -> SimulatedProof<Fp> {
+        let circuit = FiboCircuit {
+            a: Witness::known(Fp::one()),
+            b: Witness::known(Fp::one()),
+        };
+        from_circuit(circuit, vec![], 4)
+    }
+
+    fn round_trip(p: &SimulatedProof<Fp>) -> SimulatedProof<Fp> {
+        SimulatedProof::from_json(&p.to_json()).unwrap()
+    }
+
+    #[test]
+    fn fibo_round_trip() {
+        let p = fibo_proof();
+        assert_eq!(p.vk_bytes.len(), 36);
+        assert_eq!(p.vk_bytes[..4], 4u32.to_le_bytes());
+        let p = round_trip(&p);
+        verify::<FiboCircuit<Fp>>(&p).unwrap();
+    }
+
+    #[test]
+    fn function_round_trip() {
+        let circuit = FunctionCircuit {
+            x: Witness::known(Fp::from(3)),
+        };
+        let p = round_trip(&from_circuit(circuit, vec![], 4));
+        verify::<FunctionCircuit<Fp>>(&p).unwrap();
+    }
+
+    #[test]
+    fn public_inputs_round_trip() {
+        let (g, x) = (Fp::from(7), Fp::from(11));
+        let circuit = FiatShamirCircuit::new(g, x, Fp::from(13));
+        let p = from_circuit(circuit, vec![FiatShamirCircuit::public_inputs(g, x)], 7);
+        let decoded = round_trip(&p);
+        assert_eq!(decoded, p);
+        verify::<FiatShamirCircuit<Fp>>(&decoded).unwrap();
+
+        let mut wrong = decoded;
+        wrong.public_inputs[0][1] += Fp::one();
+        assert!(matches!(
+            verify::<FiatShamirCircuit<Fp>>(&wrong),
+            Err(VerificationError::Plonk(_))
+        ));
+    }
+
+    #[test]
+    fn other_circuit_is_a_key_mismatch() {
+        assert!(matches!(
+            verify::<FunctionCircuit<Fp>>(&fibo_proof()),
+            Err(VerificationError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn tampered_proof_fails() {
+        let mut p = fibo_proof();
+        p.proof_bytes[40] ^= 1;
+        assert!(matches!(
+            verify::<FiboCircuit<Fp>>(&p),
+            Err(VerificationError::Plonk(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_key() {
+        let mut p = fibo_proof();
+        p.vk_bytes.pop();
+        assert!(matches!(
+            verify::<FiboCircuit<Fp>>(&p),
+            Err(VerificationError::MalformedKey)
+        ));
+
+        let mut p = fibo_proof();
+        p.vk_bytes[..4].copy_from_slice(&40u32.to_le_bytes());
+        assert!(matches!(
+            verify::<FiboCircuit<Fp>>(&p),
+            Err(VerificationError::MalformedKey)
+        ));
+    }
+
+    #[test]
+    fn malformed_json() {
+        let field = |s: &str| match SimulatedProof::from_json(s) {
+            Err(VerificationError::Field(path)) => path,
+            other => panic!("expected a field error, got {:?}", other),
+        };
+        assert_eq!(
+            field(r#"{ "proof": "0", "public_inputs": [], "vk": "" }"#),
+            "proof"
+        );
+        assert_eq!(
+            field(r#"{ "proof": "", "public_inputs": [["00"]], "vk": "" }"#),
+            "public_inputs[0][0]"
+        );
+        assert_eq!(field(r#"{ "proof": "", "vk": "" }"#), "public_inputs");
+        assert!(matches!(
+            SimulatedProof::from_json("not json"),
+            Err(VerificationError::Json(_))
+        ));
+    }
+}
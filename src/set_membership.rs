@@ -0,0 +1,171 @@
+// Proves a private x is one of the public s_0..s_{N-1} without saying which one.
+//
+// | d_i = x - s_i | s_i | x | s_add |   d_i + s_i == x on the add gate
+// prod = d_0 * d_1 * ... * d_{N-1} on N - 1 mul gates
+// member = is_zero(prod)
+//
+// instance: | s_0 | ... | s_{N-1} | member |
+// with member set to 1 a non-member cannot satisfy the circuit, with member set
+// to 0 the same circuit proves non-membership.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+    is_zero::{IsZeroChip, IsZeroConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetMembershipConfig {
+    pub function: SimpleFunctionConfig,
+    pub is_zero: IsZeroConfig,
+    pub instance: Column<Instance>,
+}
+
+pub struct SetMembershipCircuit<F, const N: usize> {
+    pub x: Value<F>,
+}
+
+impl<F: FieldExt, const N: usize> Default for SetMembershipCircuit<F, N> {
+    fn default() -> Self {
+        Self {
+            x: Value::unknown(),
+        }
+    }
+}
+
+impl<F: FieldExt, const N: usize> SetMembershipCircuit<F, N> {
+    pub fn new(x: F) -> Self {
+        Self { x: Value::known(x) }
+    }
+
+    // instance column for the public set and the expected membership flag
+    pub fn public_inputs(set: [F; N], member: bool) -> Vec<Vec<F>> {
+        let mut instance = set.to_vec();
+        instance.push(F::from(member));
+        vec![instance]
+    }
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for SetMembershipCircuit<F, N> {
+    type Config = SetMembershipConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        assert!(N > 0, "empty set");
+
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        SetMembershipConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            is_zero: IsZeroChip::configure(meta, x, y, z),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = SimpleFunctionChip::<F>::construct(config.function.clone());
+
+        let x = layouter.assign_region(
+            || "x",
+            |mut region| region.assign_advice(|| "x", config.function.x, 0, || self.x),
+        )?;
+
+        let diffs = (0..N)
+            .map(|i| {
+                layouter.assign_region(
+                    || "x - s_i",
+                    |mut region| {
+                        config.function.s_add.enable(&mut region, 0)?;
+                        let s = region.assign_advice_from_instance(
+                            || "s_i",
+                            config.instance,
+                            i,
+                            config.function.y,
+                            0,
+                        )?;
+                        x.copy_advice(|| "x", &mut region, config.function.z, 0)?;
+                        let d = x.value().copied() - s.value().copied();
+                        region
+                            .assign_advice(|| "d_i", config.function.x, 0, || d)
+                            .map(Number)
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut prod = diffs[0].clone();
+        for d in &diffs[1..] {
+            prod = chip.mul_cells(layouter.namespace(|| "prod"), &prod, d)?;
+        }
+
+        let member = IsZeroChip::construct(config.is_zero)
+            .is_zero(layouter.namespace(|| "prod == 0"), &prod.0)?;
+        layouter.constrain_instance(member.cell(), config.instance, N)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn set() -> [Fp; 4] {
+        [3, 7, 11, 42].map(Fp::from)
+    }
+
+    fn prove(x: u64, member: bool) -> MockProver<Fp> {
+        let circuit = SetMembershipCircuit::<Fp, 4>::new(Fp::from(x));
+        MockProver::run(
+            5,
+            &circuit,
+            SetMembershipCircuit::public_inputs(set(), member),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn member() {
+        for x in [3, 7, 11, 42] {
+            prove(x, true).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn non_member() {
+        prove(5, false).assert_satisfied();
+        prove(0, false).assert_satisfied();
+    }
+
+    #[test]
+    fn non_member_cannot_claim_membership() {
+        assert!(prove(5, true).verify().is_err());
+        assert!(prove(7, false).verify().is_err());
+    }
+
+    #[test]
+    fn single_element_set() {
+        let circuit = SetMembershipCircuit::<Fp, 1>::new(Fp::from(9));
+        let instance = SetMembershipCircuit::public_inputs([Fp::from(9)], true);
+        MockProver::run(4, &circuit, instance)
+            .unwrap()
+            .assert_satisfied();
+    }
+}
@@ -0,0 +1,163 @@
+// Assigns a sub-expression once and hands the same cell to every consumer,
+// which copy constrain it into their own regions.
+//
+// x^2 is shared by x^3 and x^4:
+// | x   | x   | x^2 |   square
+// | x^2 | x   | x^3 |   mul
+// | x^2 | x^2 | x^4 |   mul
+// instance: | x^3 | x^4 |
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::function::{
+    Number, SimpleFunctionChip, SimpleFunctionConfig, SimpleFunctionInstructions,
+};
+
+/// Cells keyed by the sub-expression they hold, assigned on first request.
+#[derive(Debug)]
+pub struct SharedWitnessRegion<F: FieldExt> {
+    cells: BTreeMap<String, AssignedCell<F, F>>,
+}
+
+impl<F: FieldExt> Default for SharedWitnessRegion<F> {
+    fn default() -> Self {
+        Self {
+            cells: BTreeMap::new(),
+        }
+    }
+}
+
+impl<F: FieldExt> SharedWitnessRegion<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_or_assign(
+        &mut self,
+        name: &str,
+        assign: impl FnOnce() -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if let Some(cell) = self.cells.get(name) {
+            return Ok(cell.clone());
+        }
+        let cell = assign()?;
+        self.cells.insert(name.to_string(), cell.clone());
+        Ok(cell)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SharedPowersConfig {
+    pub function: SimpleFunctionConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct SharedPowersCircuit<F: FieldExt> {
+    pub x: Value<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for SharedPowersCircuit<F> {
+    type Config = SharedPowersConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        SharedPowersConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = SimpleFunctionChip::<F>::construct(config.function);
+        let mut shared = SharedWitnessRegion::new();
+
+        let mut x = None;
+        let x_square = shared.get_or_assign("x^2", || {
+            let (x_cell, x_square) = chip.load_square(layouter.namespace(|| "square"), self.x)?;
+            x = Some(x_cell);
+            Ok(x_square.0)
+        })?;
+        let x_cube = chip.mul_cells(
+            layouter.namespace(|| "x^3"),
+            &Number(x_square),
+            x.as_ref().unwrap(),
+        )?;
+
+        // already assigned above, the square region is not laid out again
+        let x_square = Number(shared.get_or_assign("x^2", || {
+            chip.load_square(layouter.namespace(|| "square"), self.x)
+                .map(|(_, x_square)| x_square.0)
+        })?);
+        let x_fourth = chip.mul_cells(layouter.namespace(|| "x^4"), &x_square, &x_square)?;
+
+        layouter.constrain_instance(x_cube.0.cell(), config.instance, 0)?;
+        layouter.constrain_instance(x_fourth.0.cell(), config.instance, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::record;
+
+    fn instance(x: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::from(x * x * x), Fp::from(x * x * x * x)]]
+    }
+
+    #[test]
+    fn shares_the_square() {
+        let circuit = SharedPowersCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        MockProver::run(4, &circuit, instance(3))
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_power_fails() {
+        let circuit = SharedPowersCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(27), Fp::from(80)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn one_square_region() {
+        let circuit = SharedPowersCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        let recorder = record(&circuit, 4, instance(3)).unwrap();
+        let names: Vec<_> = recorder.regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names.iter().filter(|name| **name == "square").count(), 1);
+        assert_eq!(names.iter().filter(|name| **name == "op").count(), 2);
+    }
+}
@@ -0,0 +1,218 @@
+// Guesses the smallest k a circuit fits in from what its synthesis does,
+// without the MockProver runs at growing k that finding it otherwise takes.
+//
+// Synthesis runs once against the `Recorder` backend, and every row a selector
+// is switched on counts as one instruction of the kind its gates are:
+//
+// lookup   the selector is queried by a lookup argument
+// mul      one of its gates multiplies two cells, degree 2 without the selector
+// add      anything else, additions and copies
+//
+// A linear model turns the counts into rows and k is the first power of two
+// holding them. The coefficients are least squares against 2^(k - 1/2) for the
+// measured minimum k of 20 circuits of this crate, 16 of which it predicts
+// exactly and the rest within one. The intercept stands for the blinding rows;
+// NestedFiboCircuit is the only circuit with lookups, so the lookup weight is
+// mostly its 65 row table spread over two lookups. Gates spanning several rows
+// per selector, as in `ExpandedFiboCircuit`, come out one k too small.
+
+use std::collections::HashSet;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+use crate::{
+    gate_parse::{parse_gates, GateAtom},
+    recorder::{parse_index, record},
+};
+
+// instance rows made up while counting
+const INSTANCE_ROWS: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstructionCounts {
+    pub mul: usize,
+    pub add: usize,
+    pub lookup: usize,
+}
+
+// rows = INTERCEPT + MUL_ROWS * mul + ADD_ROWS * add + LOOKUP_ROWS * lookup
+const INTERCEPT: f64 = 3.64;
+const MUL_ROWS: f64 = 1.03;
+const ADD_ROWS: f64 = 1.2;
+const LOOKUP_ROWS: f64 = 38.6;
+
+pub fn predict_k_from_instruction_counts(
+    mul_count: usize,
+    add_count: usize,
+    lookup_count: usize,
+) -> u32 {
+    let rows = INTERCEPT
+        + MUL_ROWS * mul_count as f64
+        + ADD_ROWS * add_count as f64
+        + LOOKUP_ROWS * lookup_count as f64;
+    (rows.max(1.0).ceil() as usize)
+        .next_power_of_two()
+        .trailing_zeros()
+}
+
+// selectors queried by a lookup argument, read off the pinned constraint system
+fn lookup_selectors(pinned: &str) -> HashSet<usize> {
+    let lookups = &pinned[pinned.rfind("lookups: [").unwrap()..];
+    let lookups = &lookups[..lookups.find("constants: [").unwrap()];
+    lookups
+        .match_indices("Selector(Selector(")
+        .map(|(i, _)| parse_index(&lookups[i..], "Selector(Selector("))
+        .collect()
+}
+
+pub fn count_instructions<F: FieldExt, C: Circuit<F>>(circuit: &C) -> InstructionCounts {
+    let mut cs = ConstraintSystem::<F>::default();
+    C::configure(&mut cs);
+    let pinned = format!("{:?}", cs.pinned());
+    let lookups = lookup_selectors(&pinned);
+
+    let mut muls = HashSet::new();
+    for gate in parse_gates::<F, C>() {
+        for poly in &gate.polys {
+            for (_, atoms) in poly {
+                let cells = atoms
+                    .iter()
+                    .filter(|atom| !matches!(atom, GateAtom::Selector(_)))
+                    .count();
+                if cells < 2 {
+                    continue;
+                }
+                for atom in atoms {
+                    if let GateAtom::Selector(selector) = atom {
+                        muls.insert(*selector);
+                    }
+                }
+            }
+        }
+    }
+
+    // zeros stand in for the instance, checked assignments refuse the unknown
+    // values a missing one reads as. k only bounds `fill_from_row`, which
+    // counting doesn't look at.
+    let columns = parse_index(&pinned, "num_instance_columns: ");
+    let instance = vec![vec![F::zero(); INSTANCE_ROWS]; columns];
+    let recorder = record(circuit, 0, instance).expect("circuit should synthesize");
+    let mut counts = InstructionCounts::default();
+    for (selector, _) in &recorder.selectors {
+        if lookups.contains(selector) {
+            counts.lookup += 1;
+        } else if muls.contains(selector) {
+            counts.mul += 1;
+        } else {
+            counts.add += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::{
+        expand::ExpandedFiboCircuit, fibo1::FiboCircuit, fibo_batch::BatchFiboCircuit,
+        fibo_nested::NestedFiboCircuit, function::FunctionCircuit, triple_mul::TripleMulCircuit,
+    };
+
+    // the smallest k the circuit synthesizes and verifies at
+    fn min_k<C: Circuit<Fp>>(circuit: &C, instance: Vec<Vec<Fp>>) -> u32 {
+        (1..=12)
+            .find(|&k| match MockProver::run(k, circuit, instance.clone()) {
+                Ok(prover) => prover.verify().is_ok(),
+                Err(_) => false,
+            })
+            .expect("circuit should fit in 2^12 rows")
+    }
+
+    fn predict<C: Circuit<Fp>>(circuit: &C) -> u32 {
+        let counts = count_instructions(circuit);
+        predict_k_from_instruction_counts(counts.mul, counts.add, counts.lookup)
+    }
+
+    // asserts the prediction is the minimum k, returns it
+    fn assert_predicted<C: Circuit<Fp>>(circuit: &C, instance: Vec<Vec<Fp>>) -> u32 {
+        let k = min_k(circuit, instance);
+        assert_eq!(predict(circuit), k);
+        k
+    }
+
+    #[test]
+    fn fibo_circuit() {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        assert_eq!(
+            count_instructions(&circuit),
+            InstructionCounts {
+                mul: 0,
+                add: 8,
+                lookup: 0
+            }
+        );
+        assert_eq!(assert_predicted(&circuit, vec![]), 4);
+    }
+
+    #[test]
+    fn function_circuit() {
+        let circuit = FunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        assert_eq!(
+            count_instructions(&circuit),
+            InstructionCounts {
+                mul: 3,
+                add: 3,
+                lookup: 0
+            }
+        );
+        assert_eq!(assert_predicted(&circuit, vec![]), 4);
+    }
+
+    #[test]
+    fn other_circuits() {
+        let triple = TripleMulCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4));
+        assert_eq!(assert_predicted(&triple, vec![vec![Fp::from(24)]]), 3);
+
+        let seeds = [(Fp::one(), Fp::one()), (Fp::from(2), Fp::from(3))];
+        for (n, k) in [(10, 4), (30, 6)] {
+            let batch = BatchFiboCircuit::new(seeds, n);
+            let public = BatchFiboCircuit::public_inputs(seeds, n);
+            assert_eq!(assert_predicted(&batch, vec![public]), k);
+        }
+    }
+
+    #[test]
+    fn lookups() {
+        let circuit = NestedFiboCircuit::new(10, 5, 6);
+        assert_eq!(count_instructions(&circuit).lookup, 2);
+        let public = NestedFiboCircuit::public_inputs(10, 5, 6).unwrap();
+        assert_eq!(assert_predicted(&circuit, vec![public]), 7);
+    }
+
+    #[test]
+    fn multi_row_gates_come_out_one_too_small() {
+        let circuit = ExpandedFiboCircuit::new(Fp::one(), Fp::one(), 10);
+        let public = vec![vec![Fp::one(), Fp::one(), Fp::from(55)]];
+        assert_eq!(predict(&circuit) + 1, min_k(&circuit, public));
+    }
+
+    #[test]
+    fn k_grows_with_the_counts() {
+        // the intercept alone
+        assert_eq!(predict_k_from_instruction_counts(0, 0, 0), 2);
+        assert_eq!(predict_k_from_instruction_counts(0, 10, 0), 4);
+        assert_eq!(predict_k_from_instruction_counts(0, 100, 0), 7);
+        assert_eq!(predict_k_from_instruction_counts(100, 0, 0), 7);
+        assert_eq!(predict_k_from_instruction_counts(0, 0, 1), 6);
+    }
+}
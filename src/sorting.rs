@@ -0,0 +1,208 @@
+// Batcher's bitonic sorting network: every compare-and-swap is a MinChip and a
+// MaxChip over the same pair of cells, so the outputs are a permutation of the
+// inputs and each pair ends up ordered.
+//
+// The comparisons only order values below 2^64, so every input is range
+// checked on load. Without that p - 1 passes for the min of p - 1 and 0.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    compare::{CompareConfig, MaxChip, MinChip},
+    range_check::RangeCheckChip,
+};
+
+#[derive(Debug, Clone)]
+pub struct SortingNetworkConfig {
+    pub input: Column<Advice>,
+    pub min: CompareConfig,
+    pub max: CompareConfig,
+}
+
+/// Sorts `N` (a power of two) values below `2^64` in ascending order.
+pub struct SortingNetworkChip<F: FieldExt, const N: usize> {
+    config: SortingNetworkConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> SortingNetworkChip<F, N> {
+    pub fn construct(config: SortingNetworkConfig) -> Self {
+        assert!(
+            N.is_power_of_two(),
+            "bitonic network needs a power of two inputs"
+        );
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    // the first four columns hold the comparisons, the last two the range checks
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+    ) -> SortingNetworkConfig {
+        let compare = [advice[0], advice[1], advice[2], advice[3]];
+        let range = RangeCheckChip::<F, 64>::configure(meta, advice[4], advice[5]);
+
+        SortingNetworkConfig {
+            input: advice[0],
+            min: MinChip::configure(meta, compare, range.clone()),
+            max: MaxChip::configure(meta, compare, range),
+        }
+    }
+
+    // (i, j, ascending) pairs in network order
+    pub fn network() -> Vec<(usize, usize, bool)> {
+        let mut pairs = vec![];
+        let mut k = 2;
+        while k <= N {
+            let mut j = k / 2;
+            while j > 0 {
+                for i in 0..N {
+                    let l = i ^ j;
+                    if l > i {
+                        pairs.push((i, l, i & k == 0));
+                    }
+                }
+                j /= 2;
+            }
+            k *= 2;
+        }
+        pairs
+    }
+
+    pub fn sort(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: [Value<F>; N],
+    ) -> Result<[AssignedCell<F, F>; N], Error> {
+        let mut cells = layouter.assign_region(
+            || "load inputs",
+            |mut region| {
+                inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(row, x)| region.assign_advice(|| "input", self.config.input, row, || *x))
+                    .collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+        let range = RangeCheckChip::<F, 64>::construct(self.config.min.range.clone());
+        for cell in &cells {
+            range.check(layouter.namespace(|| "input range"), cell)?;
+        }
+
+        let min_chip = MinChip::construct(self.config.min.clone());
+        let max_chip = MaxChip::construct(self.config.max.clone());
+        for (i, j, ascending) in Self::network() {
+            let min = min_chip.min(layouter.namespace(|| "min"), &cells[i], &cells[j])?;
+            let max = max_chip.max(layouter.namespace(|| "max"), &cells[i], &cells[j])?;
+            if ascending {
+                cells[i] = min;
+                cells[j] = max;
+            } else {
+                cells[i] = max;
+                cells[j] = min;
+            }
+        }
+
+        Ok(cells.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // sorts the inputs, instance: | out_0 | ... | out_N-1 |
+    struct SortCircuit<const N: usize> {
+        inputs: [Value<Fp>; N],
+    }
+
+    impl<const N: usize> Circuit<Fp> for SortCircuit<N> {
+        type Config = (SortingNetworkConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: [Value::unknown(); N],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                SortingNetworkChip::<Fp, N>::configure(meta, advice),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = SortingNetworkChip::<Fp, N>::construct(config);
+            let sorted = chip.sort(layouter.namespace(|| "sort"), self.inputs)?;
+            for (row, cell) in sorted.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prove<const N: usize>(inputs: [Fp; N], outputs: [Fp; N]) -> MockProver<Fp> {
+        let circuit = SortCircuit {
+            inputs: inputs.map(Value::known),
+        };
+        let public = vec![outputs.to_vec()];
+        MockProver::run(12, &circuit, public).unwrap()
+    }
+
+    #[test]
+    fn sorts_four() {
+        prove([3, 1, 4, 1].map(Fp::from), [1, 1, 3, 4].map(Fp::from)).assert_satisfied();
+        let sorted = [1, 2, 3, 4].map(Fp::from);
+        prove(sorted, sorted).assert_satisfied();
+    }
+
+    #[test]
+    fn sorts_eight() {
+        let unsorted = [9, 2, 7, u64::MAX, 0, 5, 5, 1].map(Fp::from);
+        let sorted = [0, 1, 2, 5, 5, 7, 9, u64::MAX].map(Fp::from);
+        prove(unsorted, sorted).assert_satisfied();
+        prove(sorted, sorted).assert_satisfied();
+    }
+
+    #[test]
+    fn out_of_order_output_fails() {
+        assert!(
+            prove([3, 1, 4, 2].map(Fp::from), [1, 3, 2, 4].map(Fp::from))
+                .verify()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn input_above_range_fails() {
+        // p - 1 is not below 2^64, whichever way the network orders it
+        let inputs = [-Fp::one(), Fp::zero(), Fp::one(), Fp::from(2)];
+        let outputs = [Fp::zero(), Fp::one(), Fp::from(2), -Fp::one()];
+        assert!(prove(inputs, outputs).verify().is_err());
+    }
+}
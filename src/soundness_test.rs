@@ -0,0 +1,174 @@
+// Sanity checks on a single gate expression. A gate that is the zero polynomial
+// constrains nothing, and a gate with no satisfying assignment once its
+// selectors are on makes every circuit using it unsatisfiable.
+//
+// The expression is multiplied out with `flatten_expression` and evaluated with
+// every selector set to 1:
+// - at a few random points, all zero there means identically zero
+// - over small values 0..4 per cell, then random ones, looking for a non-zero
+//   assignment that satisfies the gate
+
+use rand_core::OsRng;
+
+use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
+
+use crate::gate_flatten::{flatten_expression, ExprAtom};
+
+const RANDOM_POINTS: usize = 8;
+const SMALL_VALUES: u64 = 4;
+const MAX_TRIALS: usize = 1 << 12;
+
+#[derive(Debug, Clone)]
+pub struct SoundnessResult<F> {
+    pub gate_name: String,
+    pub identically_zero: bool,
+    // a value per queried advice, fixed or instance cell, in query order
+    pub satisfying_assignment: Option<Vec<(ExprAtom<F>, F)>>,
+}
+
+impl<F> SoundnessResult<F> {
+    pub fn is_sound(&self) -> bool {
+        !self.identically_zero && self.satisfying_assignment.is_some()
+    }
+}
+
+fn evaluate<F: FieldExt>(
+    terms: &[(F, Vec<ExprAtom<F>>)],
+    cells: &[ExprAtom<F>],
+    values: &[F],
+) -> F {
+    terms.iter().fold(F::zero(), |sum, (coeff, atoms)| {
+        let term = atoms.iter().fold(*coeff, |product, atom| match atom {
+            ExprAtom::Constant(c) => product * c,
+            ExprAtom::Selector(_) => product,
+            cell => {
+                let index = cells.iter().position(|c| c == cell).unwrap();
+                product * values[index]
+            }
+        });
+        sum + term
+    })
+}
+
+pub fn test_gate_soundness<F: FieldExt>(
+    gate_name: &str,
+    expr: &Expression<F>,
+) -> SoundnessResult<F> {
+    let terms = flatten_expression(expr);
+    let mut cells: Vec<ExprAtom<F>> = vec![];
+    for (_, atoms) in &terms {
+        for atom in atoms {
+            if !matches!(atom, ExprAtom::Constant(_) | ExprAtom::Selector(_))
+                && !cells.contains(atom)
+            {
+                cells.push(*atom);
+            }
+        }
+    }
+
+    let random_point = || {
+        (0..cells.len())
+            .map(|_| F::random(OsRng))
+            .collect::<Vec<_>>()
+    };
+    let identically_zero =
+        (0..RANDOM_POINTS).all(|_| evaluate(&terms, &cells, &random_point()) == F::zero());
+
+    // small values first, counting in base SMALL_VALUES, then random points
+    let small = (1..(SMALL_VALUES as usize).saturating_pow(cells.len() as u32))
+        .take(MAX_TRIALS)
+        .map(|mut counter| {
+            (0..cells.len())
+                .map(|_| {
+                    let digit = counter as u64 % SMALL_VALUES;
+                    counter /= SMALL_VALUES as usize;
+                    F::from(digit)
+                })
+                .collect::<Vec<_>>()
+        });
+    let random = (0..MAX_TRIALS).map(|_| random_point());
+    let satisfying_assignment = small
+        .chain(random)
+        .find(|values| {
+            values.iter().any(|v| *v != F::zero()) && evaluate(&terms, &cells, values) == F::zero()
+        })
+        .map(|values| cells.iter().copied().zip(values).collect());
+
+    SoundnessResult {
+        gate_name: gate_name.to_string(),
+        identically_zero,
+        satisfying_assignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        pasta::Fp,
+        plonk::{ConstraintSystem, VirtualCells},
+        poly::Rotation,
+    };
+
+    use super::*;
+
+    // `body` over a, b, c under a selector, as create_gate hands it over
+    fn gate(body: impl FnOnce([Expression<Fp>; 3]) -> Expression<Fp>) -> Expression<Fp> {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let columns = [(); 3].map(|_| meta.advice_column());
+        let s = meta.selector();
+        let mut captured = None;
+        meta.create_gate("gate", |meta: &mut VirtualCells<'_, Fp>| {
+            let s = meta.query_selector(s);
+            let cells = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+            let expr = s * body(cells);
+            captured = Some(expr.clone());
+            vec![expr]
+        });
+        captured.unwrap()
+    }
+
+    fn values(result: &SoundnessResult<Fp>) -> Vec<Fp> {
+        let assignment = result.satisfying_assignment.as_ref().unwrap();
+        assignment.iter().map(|(_, value)| *value).collect()
+    }
+
+    #[test]
+    fn fibo_add_gate_is_sound() {
+        let result = test_gate_soundness("add", &gate(|[a, b, c]| a + b - c));
+        assert!(result.is_sound());
+        assert_eq!(result.gate_name, "add");
+        let [a, b, c] = values(&result)[..] else {
+            panic!()
+        };
+        assert_eq!(a + b, c);
+    }
+
+    #[test]
+    fn function_mul_gate_is_sound() {
+        let result = test_gate_soundness("mul", &gate(|[a, b, c]| a * b - c));
+        assert!(result.is_sound());
+        let [a, b, c] = values(&result)[..] else {
+            panic!()
+        };
+        assert_eq!(a * b, c);
+    }
+
+    #[test]
+    fn zero_polynomial_is_unsound() {
+        let result = test_gate_soundness("zero", &gate(|[a, b, _]| a.clone() * b.clone() - b * a));
+        assert!(result.identically_zero);
+        assert!(!result.is_sound());
+    }
+
+    #[test]
+    fn unsatisfiable_gate_is_unsound() {
+        // a * 0 + 1 is never zero
+        let result = test_gate_soundness(
+            "one",
+            &gate(|[a, _, _]| a * Fp::zero() + Expression::Constant(Fp::one())),
+        );
+        assert!(!result.identically_zero);
+        assert!(result.satisfying_assignment.is_none());
+        assert!(!result.is_sound());
+    }
+}
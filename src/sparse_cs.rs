@@ -0,0 +1,473 @@
+// Portable export of a circuit's constraints.
+//
+// `MockProver` keeps its gates and assignments private, so there is no way to
+// implement `From<MockProver<F>>`; the system is built from the circuit instead,
+// with the gates parsed from `CircuitGates` and the assignment replayed through
+// the crate's `Recorder`.
+//
+// Each gate is stored as a sparse matrix in CSR form: one matrix row per
+// constraint polynomial, one matrix column per distinct monomial of the gate
+// (a product of selector and column queries), and the monomial coefficient as
+// the entry.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Circuit, Column, ConstraintSystem, Error},
+};
+use serde_json::{json, Value};
+
+use crate::{
+    gate_parse::{parse_gates, GateAtom},
+    recorder::{column_index, parse_index, record},
+};
+
+#[derive(Debug, Clone)]
+pub struct SparseGate<F> {
+    pub name: String,
+    /// Rows on which one of the gate's selectors is enabled, or every usable
+    /// row for gates without a selector.
+    pub rows: Vec<usize>,
+    /// Monomial basis, the constant monomial is the empty product.
+    pub monomials: Vec<Vec<GateAtom>>,
+    pub row_ptr: Vec<usize>,
+    pub col_idx: Vec<usize>,
+    pub values: Vec<F>,
+}
+
+impl<F: FieldExt> SparseGate<F> {
+    pub fn num_constraints(&self) -> usize {
+        self.row_ptr.len() - 1
+    }
+
+    /// `(coefficient, monomial)` pairs of the i-th constraint polynomial.
+    pub fn constraint(&self, i: usize) -> impl Iterator<Item = (F, &[GateAtom])> {
+        (self.row_ptr[i]..self.row_ptr[i + 1])
+            .map(move |j| (self.values[j], self.monomials[self.col_idx[j]].as_slice()))
+    }
+}
+
+// a cell of the assignment, column kinds ordered advice, fixed, instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Cell {
+    Advice(usize, usize),
+    Fixed(usize, usize),
+    Instance(usize, usize),
+}
+
+impl Cell {
+    fn from_column(column: Column<Any>, row: usize) -> Self {
+        let index = column_index(column);
+        match column.column_type() {
+            Any::Advice => Cell::Advice(index, row),
+            Any::Fixed => Cell::Fixed(index, row),
+            Any::Instance => Cell::Instance(index, row),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SparseConstraintSystem<F> {
+    pub num_rows: usize,
+    pub num_cols: usize,
+    pub gates: Vec<SparseGate<F>>,
+    // assignment needed to lower the gates to R1CS
+    selectors: BTreeSet<(usize, usize)>,
+    fixed: BTreeMap<(usize, usize), F>,
+    advice: BTreeMap<(usize, usize), Option<F>>,
+    instance: Vec<Vec<F>>,
+    copies: Vec<(Cell, Cell)>,
+}
+
+impl<F: FieldExt> SparseConstraintSystem<F> {
+    pub fn from_circuit<C: Circuit<F>>(
+        circuit: &C,
+        k: u32,
+        instance: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        let recorder = record(circuit, k, instance)?;
+
+        let mut cs = ConstraintSystem::<F>::default();
+        C::configure(&mut cs);
+        let pinned = format!("{:?}", cs.pinned());
+        let num_cols = [
+            "num_advice_columns: ",
+            "num_fixed_columns: ",
+            "num_instance_columns: ",
+        ]
+        .iter()
+        .map(|prefix| parse_index(&pinned, prefix))
+        .sum();
+        let num_rows = 1 << k;
+        let usable_rows = num_rows - (cs.blinding_factors() + 1);
+
+        let gates = parse_gates::<F, C>()
+            .into_iter()
+            .map(|gate| {
+                let mut selectors = BTreeSet::new();
+                let mut monomials: Vec<Vec<GateAtom>> = vec![];
+                let (mut row_ptr, mut col_idx, mut values) = (vec![0], vec![], vec![]);
+                for poly in gate.polys {
+                    for (coeff, atoms) in poly {
+                        for atom in &atoms {
+                            if let GateAtom::Selector(selector) = atom {
+                                selectors.insert(*selector);
+                            }
+                        }
+                        let col = monomials
+                            .iter()
+                            .position(|m| *m == atoms)
+                            .unwrap_or_else(|| {
+                                monomials.push(atoms);
+                                monomials.len() - 1
+                            });
+                        col_idx.push(col);
+                        values.push(coeff);
+                    }
+                    row_ptr.push(col_idx.len());
+                }
+
+                let rows = if selectors.is_empty() {
+                    (0..usable_rows).collect()
+                } else {
+                    recorder
+                        .selectors
+                        .iter()
+                        .filter(|(selector, _)| selectors.contains(selector))
+                        .map(|(_, row)| *row)
+                        .collect::<BTreeSet<_>>()
+                        .into_iter()
+                        .collect()
+                };
+
+                SparseGate {
+                    name: gate.name,
+                    rows,
+                    monomials,
+                    row_ptr,
+                    col_idx,
+                    values,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            num_rows,
+            num_cols,
+            gates,
+            selectors: recorder.selectors.iter().copied().collect(),
+            fixed: recorder
+                .fixed
+                .iter()
+                .filter_map(|(cell, value)| value.map(|value| (*cell, value)))
+                .collect(),
+            advice: recorder.advice.clone(),
+            instance: recorder.instance.clone(),
+            copies: recorder
+                .copies
+                .iter()
+                .map(|((lc, lr), (rc, rr))| {
+                    (Cell::from_column(*lc, *lr), Cell::from_column(*rc, *rr))
+                })
+                .collect(),
+        })
+    }
+
+    fn rotate(&self, row: usize, rotation: i32) -> usize {
+        (row as i64 + rotation as i64).rem_euclid(self.num_rows as i64) as usize
+    }
+
+    /// Lowers every active gate row to an `a * b = c` constraint over the
+    /// witness vector `z`, where `z[0] = 1` and copy constrained cells share a
+    /// variable. Selectors and fixed cells are folded into the coefficients.
+    ///
+    /// Panics if a gate row has more than one product of two cells or any
+    /// product of three, since it would not fit in a single constraint.
+    pub fn to_r1cs_json(&self) -> Value {
+        let mut variables = Variables::new(&self.copies);
+
+        let mut constraints = vec![];
+        for gate in &self.gates {
+            for &row in &gate.rows {
+                for i in 0..gate.num_constraints() {
+                    let mut linear: BTreeMap<usize, F> = BTreeMap::new();
+                    let mut quadratic: BTreeMap<(usize, usize), F> = BTreeMap::new();
+                    for (coeff, atoms) in gate.constraint(i) {
+                        let mut coeff = coeff;
+                        let mut vars = vec![];
+                        for atom in atoms {
+                            match *atom {
+                                GateAtom::Selector(selector) => {
+                                    if !self.selectors.contains(&(selector, row)) {
+                                        coeff = F::zero();
+                                    }
+                                }
+                                GateAtom::Fixed(column, rotation) => {
+                                    let row = self.rotate(row, rotation);
+                                    coeff *= self
+                                        .fixed
+                                        .get(&(column, row))
+                                        .copied()
+                                        .unwrap_or_else(F::zero);
+                                }
+                                GateAtom::Advice(column, rotation) => {
+                                    vars.push(
+                                        variables
+                                            .get(Cell::Advice(column, self.rotate(row, rotation))),
+                                    );
+                                }
+                                GateAtom::Instance(column, rotation) => {
+                                    vars.push(
+                                        variables.get(Cell::Instance(
+                                            column,
+                                            self.rotate(row, rotation),
+                                        )),
+                                    );
+                                }
+                            }
+                        }
+                        vars.sort_unstable();
+                        match vars[..] {
+                            [] => *linear.entry(0).or_insert_with(F::zero) += coeff,
+                            [x] => *linear.entry(x).or_insert_with(F::zero) += coeff,
+                            [x, y] => *quadratic.entry((x, y)).or_insert_with(F::zero) += coeff,
+                            _ => assert!(
+                                bool::from(coeff.is_zero()),
+                                "gate '{}' has degree > 2 at row {}",
+                                gate.name,
+                                row
+                            ),
+                        }
+                    }
+                    linear.retain(|_, coeff| !bool::from(coeff.is_zero()));
+                    quadratic.retain(|_, coeff| !bool::from(coeff.is_zero()));
+
+                    let (a, b, c) = match quadratic.len() {
+                        0 if linear.is_empty() => continue,
+                        0 => (BTreeMap::from([(0, F::one())]), linear, BTreeMap::new()),
+                        1 => {
+                            let (&(x, y), &coeff) = quadratic.iter().next().unwrap();
+                            let c = linear
+                                .into_iter()
+                                .map(|(var, coeff)| (var, -coeff))
+                                .collect();
+                            (
+                                BTreeMap::from([(x, coeff)]),
+                                BTreeMap::from([(y, F::one())]),
+                                c,
+                            )
+                        }
+                        _ => panic!(
+                            "gate '{}' has more than one product term at row {}",
+                            gate.name, row
+                        ),
+                    };
+                    constraints.push(json!({
+                        "gate": gate.name,
+                        "row": row,
+                        "a": lc_json(&a),
+                        "b": lc_json(&b),
+                        "c": lc_json(&c),
+                    }));
+                }
+            }
+        }
+
+        // copies into fixed columns pin the shared variable to a constant
+        for (cell, var) in variables.assigned.clone() {
+            if let Cell::Fixed(column, row) = cell {
+                let value = self
+                    .fixed
+                    .get(&(column, row))
+                    .copied()
+                    .unwrap_or_else(F::zero);
+                constraints.push(json!({
+                    "gate": "constant",
+                    "row": row,
+                    "a": lc_json(&BTreeMap::from([(var, F::one())])),
+                    "b": lc_json(&BTreeMap::from([(0, F::one())])),
+                    "c": lc_json(&BTreeMap::from([(0, value)])),
+                }));
+            }
+        }
+
+        let mut witness = vec![None; variables.count];
+        witness[0] = Some(F::one());
+        let mut public = BTreeSet::new();
+        for (cell, var) in &variables.assigned {
+            let value = match *cell {
+                Cell::Advice(column, row) => self.advice.get(&(column, row)).copied().flatten(),
+                Cell::Fixed(column, row) => self.fixed.get(&(column, row)).copied(),
+                Cell::Instance(column, row) => {
+                    public.insert(*var);
+                    self.instance
+                        .get(column)
+                        .and_then(|values| values.get(row))
+                        .copied()
+                }
+            };
+            if witness[*var].is_none() {
+                witness[*var] = value;
+            }
+        }
+
+        json!({
+            "num_variables": variables.count,
+            "public": public.into_iter().collect::<Vec<_>>(),
+            "constraints": constraints,
+            "witness": witness
+                .iter()
+                .map(|value| value.map(|value| format!("{:?}", value)))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn lc_json<F: FieldExt>(lc: &BTreeMap<usize, F>) -> Value {
+    lc.iter()
+        .map(|(var, coeff)| (var.to_string(), Value::String(format!("{:?}", coeff))))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+// witness variables, cells linked by copy constraints share one
+struct Variables {
+    parent: BTreeMap<Cell, Cell>,
+    assigned: BTreeMap<Cell, usize>,
+    ids: BTreeMap<Cell, usize>,
+    count: usize,
+}
+
+impl Variables {
+    fn new(copies: &[(Cell, Cell)]) -> Self {
+        let mut variables = Self {
+            parent: BTreeMap::new(),
+            assigned: BTreeMap::new(),
+            ids: BTreeMap::new(),
+            count: 1,
+        };
+        for &(left, right) in copies {
+            let (left, right) = (variables.root(left), variables.root(right));
+            if left != right {
+                variables.parent.insert(left, right);
+            }
+        }
+        for &(left, right) in copies {
+            variables.get(left);
+            variables.get(right);
+        }
+        variables
+    }
+
+    fn root(&self, mut cell: Cell) -> Cell {
+        while let Some(&parent) = self.parent.get(&cell) {
+            cell = parent;
+        }
+        cell
+    }
+
+    fn get(&mut self, cell: Cell) -> usize {
+        let root = self.root(cell);
+        let count = &mut self.count;
+        let id = *self.ids.entry(root).or_insert_with(|| {
+            *count += 1;
+            *count - 1
+        });
+        self.assigned.insert(cell, id);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value as Witness, pasta::Fp};
+
+    use super::*;
+    use crate::{fibo1::FiboCircuit, gate_parse::parse_hex};
+
+    fn fibo() -> SparseConstraintSystem<Fp> {
+        let circuit = FiboCircuit {
+            a: Witness::known(Fp::one()),
+            b: Witness::known(Fp::one()),
+        };
+        SparseConstraintSystem::from_circuit(&circuit, 4, vec![]).unwrap()
+    }
+
+    fn field(value: &Value) -> Fp {
+        parse_hex(value.as_str().unwrap().trim_start_matches("0x"))
+    }
+
+    // sum of coeff * z[var] over a linear combination
+    fn eval(lc: &Value, z: &[Fp]) -> Fp {
+        lc.as_object()
+            .unwrap()
+            .iter()
+            .map(|(var, coeff)| field(coeff) * z[var.parse::<usize>().unwrap()])
+            .fold(Fp::zero(), |acc, term| acc + term)
+    }
+
+    fn satisfied(r1cs: &Value) -> bool {
+        let z: Vec<Fp> = r1cs["witness"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| {
+                if value.is_null() {
+                    Fp::zero()
+                } else {
+                    field(value)
+                }
+            })
+            .collect();
+        r1cs["constraints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|constraint| {
+                eval(&constraint["a"], &z) * eval(&constraint["b"], &z)
+                    == eval(&constraint["c"], &z)
+            })
+    }
+
+    #[test]
+    fn fibo_add_gate_in_csr() {
+        let cs = fibo();
+        assert_eq!(cs.num_rows, 16);
+        assert_eq!(cs.num_cols, 3);
+        assert_eq!(cs.gates.len(), 1);
+
+        let add = &cs.gates[0];
+        assert_eq!(add.name, "add");
+        // the first row and one per term from F(4) to F(10)
+        assert_eq!(add.rows.len(), 8);
+        // s * a + s * b - s * c
+        assert_eq!(add.num_constraints(), 1);
+        assert_eq!(add.row_ptr, vec![0, 3]);
+        let mut coeffs: Vec<Fp> = add.constraint(0).map(|(coeff, _)| coeff).collect();
+        coeffs.sort();
+        assert_eq!(coeffs, vec![Fp::one(), Fp::one(), -Fp::one()]);
+        assert!(add
+            .constraint(0)
+            .all(|(_, atoms)| atoms.len() == 2 && atoms.contains(&GateAtom::Selector(0))));
+    }
+
+    #[test]
+    fn fibo_r1cs_is_satisfied() {
+        let r1cs = fibo().to_r1cs_json();
+        assert_eq!(r1cs["constraints"].as_array().unwrap().len(), 8);
+        assert!(r1cs["public"].as_array().unwrap().is_empty());
+        // F(1), F(2) and one variable per term up to F(10), the rest is copies
+        assert_eq!(r1cs["num_variables"], 11);
+        assert!(satisfied(&r1cs));
+    }
+
+    #[test]
+    fn tampered_r1cs_witness_fails() {
+        let mut r1cs = fibo().to_r1cs_json();
+        let witness = r1cs["witness"].as_array_mut().unwrap();
+        let last = witness.len() - 1;
+        witness[last] = Value::String(format!("{:?}", Fp::from(56)));
+        assert!(!satisfied(&r1cs));
+    }
+}
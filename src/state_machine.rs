@@ -0,0 +1,234 @@
+// Synthesis split into named states. Each state registers a handler and the
+// states it may be entered from, `transition` checks that guard, runs the
+// handler on the layouter and moves on:
+//
+// start --> multiply --> add --> constrain
+//
+// Entering a state from anywhere not listed, or one that was never registered,
+// is `Error::Synthesis` and leaves the machine where it was. Handlers share
+// intermediate cells through whatever they capture.
+
+use std::{collections::HashMap, marker::PhantomData};
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
+
+pub const START: &str = "start";
+
+pub type StateHandler<'h, L> = Box<dyn FnMut(&mut L) -> Result<(), Error> + 'h>;
+
+pub struct CircuitStateMachine<'l, 'h, F: FieldExt, L: Layouter<F>> {
+    state: String,
+    layouter: &'l mut L,
+    // state -> (states it may be entered from, handler)
+    handlers: HashMap<String, (Vec<String>, StateHandler<'h, L>)>,
+    // every state entered, in order
+    history: Vec<String>,
+    _marker: PhantomData<F>,
+}
+
+impl<'l, 'h, F: FieldExt, L: Layouter<F>> CircuitStateMachine<'l, 'h, F, L> {
+    pub fn new(layouter: &'l mut L) -> Self {
+        Self {
+            state: START.to_string(),
+            layouter,
+            handlers: HashMap::new(),
+            history: vec![START.to_string()],
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn register(
+        &mut self,
+        state: &str,
+        from: &[&str],
+        handler: impl FnMut(&mut L) -> Result<(), Error> + 'h,
+    ) {
+        let from = from.iter().map(|state| state.to_string()).collect();
+        self.handlers
+            .insert(state.to_string(), (from, Box::new(handler)));
+    }
+
+    pub fn transition(&mut self, next_state: &str) -> Result<(), Error> {
+        let (from, handler) = self.handlers.get_mut(next_state).ok_or(Error::Synthesis)?;
+        if !from.contains(&self.state) {
+            return Err(Error::Synthesis);
+        }
+        handler(self.layouter)?;
+        self.state = next_state.to_string();
+        self.history.push(self.state.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+
+    use super::*;
+
+    // start --> multiply --> add --> constrain, plus a "fail" state entered
+    // from anywhere whose handler errors. Runs `script` and logs, for every
+    // step, whether the transition went through and the state after it.
+    struct ScriptCircuit {
+        script: Vec<&'static str>,
+        log: RefCell<Vec<(bool, String)>>,
+        handled: RefCell<Vec<String>>,
+        history: RefCell<Vec<String>>,
+    }
+
+    impl ScriptCircuit {
+        fn run(script: &[&'static str]) -> Self {
+            let circuit = Self {
+                script: script.to_vec(),
+                log: RefCell::new(vec![]),
+                handled: RefCell::new(vec![]),
+                history: RefCell::new(vec![]),
+            };
+            MockProver::run(4, &circuit, vec![])
+                .unwrap()
+                .assert_satisfied();
+            circuit
+        }
+    }
+
+    impl Circuit<Fp> for ScriptCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                script: self.script.clone(),
+                log: RefCell::new(vec![]),
+                handled: RefCell::new(vec![]),
+                history: RefCell::new(vec![]),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            column: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let mut machine = CircuitStateMachine::new(&mut layouter);
+            let states = [
+                ("multiply", vec![START]),
+                ("add", vec!["multiply"]),
+                ("constrain", vec!["add"]),
+            ];
+            for (state, from) in states {
+                let handled = &self.handled;
+                machine.register(state, &from, move |layouter| {
+                    handled.borrow_mut().push(state.to_string());
+                    layouter.assign_region(
+                        || state,
+                        |mut region| {
+                            region.assign_advice(|| state, column, 0, || Value::known(Fp::one()))
+                        },
+                    )?;
+                    Ok(())
+                });
+            }
+            machine.register("fail", &[START, "multiply", "add", "constrain"], |_| {
+                Err(Error::Synthesis)
+            });
+
+            for step in &self.script {
+                let ok = machine.transition(step).is_ok();
+                self.log
+                    .borrow_mut()
+                    .push((ok, machine.state().to_string()));
+            }
+            *self.history.borrow_mut() = machine.history().to_vec();
+            Ok(())
+        }
+    }
+
+    fn log(steps: &[(bool, &str)]) -> Vec<(bool, String)> {
+        steps
+            .iter()
+            .map(|(ok, state)| (*ok, state.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn registered_order() {
+        let circuit = ScriptCircuit::run(&["multiply", "add", "constrain"]);
+        assert_eq!(
+            circuit.log.into_inner(),
+            log(&[(true, "multiply"), (true, "add"), (true, "constrain")])
+        );
+        assert_eq!(
+            circuit.handled.into_inner(),
+            ["multiply", "add", "constrain"]
+        );
+        assert_eq!(
+            circuit.history.into_inner(),
+            [START, "multiply", "add", "constrain"]
+        );
+    }
+
+    #[test]
+    fn skipped_state_is_refused() {
+        // add isn't entered from start, the machine stays put and carries on
+        let circuit = ScriptCircuit::run(&["add", "multiply", "constrain", "add"]);
+        assert_eq!(
+            circuit.log.into_inner(),
+            log(&[
+                (false, START),
+                (true, "multiply"),
+                (false, "multiply"),
+                (true, "add"),
+            ])
+        );
+        assert_eq!(circuit.handled.into_inner(), ["multiply", "add"]);
+        assert_eq!(circuit.history.into_inner(), [START, "multiply", "add"]);
+    }
+
+    #[test]
+    fn state_is_not_reentered() {
+        let circuit = ScriptCircuit::run(&["multiply", "multiply"]);
+        assert_eq!(
+            circuit.log.into_inner(),
+            log(&[(true, "multiply"), (false, "multiply")])
+        );
+        assert_eq!(circuit.handled.into_inner(), ["multiply"]);
+    }
+
+    #[test]
+    fn unregistered_state_is_refused() {
+        let circuit = ScriptCircuit::run(&["divide", "multiply"]);
+        assert_eq!(
+            circuit.log.into_inner(),
+            log(&[(false, START), (true, "multiply")])
+        );
+    }
+
+    #[test]
+    fn failing_handler_leaves_the_state() {
+        let circuit = ScriptCircuit::run(&["multiply", "fail", "add"]);
+        assert_eq!(
+            circuit.log.into_inner(),
+            log(&[(true, "multiply"), (false, "multiply"), (true, "add")])
+        );
+        assert_eq!(circuit.history.into_inner(), [START, "multiply", "add"]);
+    }
+}
@@ -0,0 +1,47 @@
+// Architectural invariants checked while a chip configures, so a broken layout
+// fails at keygen (or in MockProver::run) instead of surfacing later as an
+// unsatisfied constraint.
+
+use halo2_proofs::{arithmetic::FieldExt, plonk::ConstraintSystem};
+
+use crate::recorder::parse_index;
+
+/// Panics with `message` unless `condition` holds, along with the shape of the
+/// constraint system configured so far.
+pub fn static_assert<F: FieldExt>(meta: &ConstraintSystem<F>, condition: bool, message: &str) {
+    if condition {
+        return;
+    }
+    let pinned = format!("{:?}", meta.pinned());
+    panic!(
+        "static assertion failed: {} ({} advice, {} fixed, {} instance columns configured)",
+        message,
+        parse_index(&pinned, "num_advice_columns: "),
+        parse_index(&pinned, "num_fixed_columns: "),
+        parse_index(&pinned, "num_instance_columns: "),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+
+    #[test]
+    fn holding_condition_passes() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        meta.advice_column();
+        static_assert(&meta, true, "unreachable");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "static assertion failed: even advice columns (1 advice, 0 fixed, 0 instance columns configured)"
+    )]
+    fn failing_condition_panics_with_the_shape() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        meta.advice_column();
+        static_assert(&meta, false, "even advice columns");
+    }
+}
@@ -0,0 +1,385 @@
+// Fibonacci cells that carry a hidden message. Every cell holds F(i) + delta_i
+// with delta_i < 2^8 one byte of the message, and the deltas sit next to the
+// cells so the gate can take them back out:
+//
+// | a'       | b'       | c'       | da | db | dc | selector |
+// | F(1)+m_0 | F(2)+m_1 | F(3)+m_2 | m_0| m_1| m_2| 1        |
+// | F(2)+m_1 | F(3)+m_2 | F(4)+m_3 | m_1| m_2| m_3| 1        |
+// | ...      |
+// gate stego: selector * ((a' - da) + (b' - db) - (c' - dc)) == 0
+//
+// Each new delta is range checked to 8 bits. The Fibonacci relation only holds
+// on a' - da and friends, the low bits of the cells themselves are free, which
+// is the point: nothing else in the circuit pins them down.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    fibo1::ACell,
+    range_check::{RangeCheckChip, RangeCheckConfig},
+};
+
+const DELTA_BITS: usize = 8;
+
+// F(1) + m_0, F(2) + m_1, ... with F(1) = a, F(2) = b
+pub fn embed_message<F: FieldExt>(a: F, b: F, message: &[u8]) -> Vec<F> {
+    let (mut prev, mut last) = (a, b);
+    message
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            let term = match i {
+                0 => a,
+                1 => b,
+                _ => {
+                    (prev, last) = (last, prev + last);
+                    last
+                }
+            };
+            term + F::from(*byte as u64)
+        })
+        .collect()
+}
+
+// the deltas back from the cells, None if one isn't a byte
+pub fn extract_message<F: FieldExt>(a: F, b: F, cells: &[F]) -> Option<Vec<u8>> {
+    let plain = embed_message(a, b, &vec![0; cells.len()]);
+    cells
+        .iter()
+        .zip(plain)
+        .map(|(cell, term)| {
+            let delta = (*cell - term).to_repr();
+            let bytes = delta.as_ref();
+            bytes[1..].iter().all(|b| *b == 0).then_some(bytes[0])
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct SteganographicFiboConfig {
+    pub advice: [Column<Advice>; 6],
+    pub selector: Selector,
+    pub range: RangeCheckConfig,
+}
+
+pub struct SteganographicFiboChip<F: FieldExt> {
+    config: SteganographicFiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SteganographicFiboChip<F> {
+    pub fn construct(config: SteganographicFiboConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+    ) -> SteganographicFiboConfig {
+        let [col_a, col_b, col_c, col_da, col_db, col_dc] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let selector = meta.selector();
+
+        meta.create_gate("stego", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(col_a, Rotation::cur());
+            let b = meta.query_advice(col_b, Rotation::cur());
+            let c = meta.query_advice(col_c, Rotation::cur());
+            let da = meta.query_advice(col_da, Rotation::cur());
+            let db = meta.query_advice(col_db, Rotation::cur());
+            let dc = meta.query_advice(col_dc, Rotation::cur());
+            vec![s * ((a - da) + (b - db) - (c - dc))]
+        });
+
+        SteganographicFiboConfig {
+            advice,
+            selector,
+            range: RangeCheckChip::<F, DELTA_BITS>::configure(meta, col_da, col_db),
+        }
+    }
+
+    /// Lays out one cell per message byte, at least three, starting from
+    /// F(1) = a and F(2) = b. Returns the cells.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        message: &[Value<u8>],
+    ) -> Result<Vec<ACell<F>>, Error> {
+        if message.len() < 3 {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        let [col_a, col_b, col_c, col_da, col_db, col_dc] = config.advice;
+        let delta = |byte: &Value<u8>| byte.map(|byte| F::from(byte as u64));
+
+        let mut cells = vec![];
+        let mut deltas = vec![];
+        // (b', c', db, dc) of the row before
+        let mut carried: Option<[AssignedCell<F, F>; 4]> = None;
+        let (mut prev, mut last) = (a, b);
+        for byte in &message[2..] {
+            let next = prev + last;
+            let [a, b, c, da, db, dc] = layouter.assign_region(
+                || "stego row",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    let [a, b, da, db] = match &carried {
+                        None => [
+                            region.assign_advice(|| "a'", col_a, 0, || a + delta(&message[0]))?,
+                            region.assign_advice(|| "b'", col_b, 0, || b + delta(&message[1]))?,
+                            region.assign_advice(|| "da", col_da, 0, || delta(&message[0]))?,
+                            region.assign_advice(|| "db", col_db, 0, || delta(&message[1]))?,
+                        ],
+                        Some([b, c, db, dc]) => [
+                            b.copy_advice(|| "a'", &mut region, col_a, 0)?,
+                            c.copy_advice(|| "b'", &mut region, col_b, 0)?,
+                            db.copy_advice(|| "da", &mut region, col_da, 0)?,
+                            dc.copy_advice(|| "db", &mut region, col_db, 0)?,
+                        ],
+                    };
+                    let c = region.assign_advice(|| "c'", col_c, 0, || next + delta(byte))?;
+                    let dc = region.assign_advice(|| "dc", col_dc, 0, || delta(byte))?;
+                    Ok([a, b, c, da, db, dc])
+                },
+            )?;
+            if carried.is_none() {
+                cells.extend([a, b.clone()]);
+                deltas.extend([da, db.clone()]);
+            }
+            cells.push(c.clone());
+            deltas.push(dc.clone());
+            carried = Some([b, c, db, dc]);
+            (prev, last) = (last, next);
+        }
+
+        let range = RangeCheckChip::<F, DELTA_BITS>::construct(config.range.clone());
+        for (i, delta) in deltas.iter().enumerate() {
+            range.check_bits(
+                layouter.namespace(|| format!("delta {}", i)),
+                delta,
+                DELTA_BITS,
+            )?;
+        }
+        Ok(cells.into_iter().map(ACell).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct SteganographicFiboCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub message: Vec<Value<u8>>,
+}
+
+impl<F: FieldExt> SteganographicFiboCircuit<F> {
+    pub fn new(a: F, b: F, message: &[u8]) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            message: message.iter().copied().map(Value::known).collect(),
+        }
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for SteganographicFiboCircuit<F> {
+    type Config = SteganographicFiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            message: vec![Value::unknown(); self.message.len()],
+            ..Default::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        SteganographicFiboChip::configure(meta, advice)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = SteganographicFiboChip::construct(config);
+        chip.assign(
+            layouter.namespace(|| "stego"),
+            self.a,
+            self.b,
+            &self.message,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::record;
+
+    // One stego row with the six cells given directly and every delta range
+    // checked, to break the layout the chip would never produce.
+    struct RowCircuit {
+        row: [u64; 6],
+    }
+
+    impl Circuit<Fp> for RowCircuit {
+        type Config = SteganographicFiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { row: [0; 6] }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            SteganographicFiboCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let deltas = layouter.assign_region(
+                || "stego row",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    let mut deltas = vec![];
+                    for (i, value) in self.row.iter().enumerate() {
+                        let cell = region.assign_advice(
+                            || "cell",
+                            config.advice[i],
+                            0,
+                            || Value::known(Fp::from(*value)),
+                        )?;
+                        if i >= 3 {
+                            deltas.push(cell);
+                        }
+                    }
+                    Ok(deltas)
+                },
+            )?;
+            let range = RangeCheckChip::<Fp, DELTA_BITS>::construct(config.range);
+            for delta in &deltas {
+                range.check_bits(layouter.namespace(|| "delta"), delta, DELTA_BITS)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn embed_and_extract() {
+        let (a, b) = (Fp::one(), Fp::one());
+        let cells = embed_message(a, b, b"hidden");
+        assert_eq!(cells[0], Fp::from(1 + b'h' as u64));
+        assert_eq!(cells[2], Fp::from(2 + b'd' as u64));
+        assert_eq!(extract_message(a, b, &cells), Some(b"hidden".to_vec()));
+    }
+
+    #[test]
+    fn extract_rejects_non_byte_deltas() {
+        let (a, b) = (Fp::one(), Fp::one());
+        let mut cells = embed_message(a, b, b"hidden");
+        cells[3] += Fp::from(256);
+        assert_eq!(extract_message(a, b, &cells), None);
+        cells[3] = -Fp::one();
+        assert_eq!(extract_message(a, b, &cells), None);
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let (a, b) = (Fp::one(), Fp::one());
+        let message = b"hello world";
+        let circuit = SteganographicFiboCircuit::new(a, b, message);
+        MockProver::run(8, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        // the first row holds a', b', c', every later row one more c'
+        let recorder = record(&circuit, 8, vec![]).unwrap();
+        let mut cells = vec![];
+        for region in recorder.regions.iter().filter(|r| r.name == "stego row") {
+            let (row, _) = region.rows.unwrap();
+            if cells.is_empty() {
+                cells.push(recorder.advice[&(0, row)].unwrap());
+                cells.push(recorder.advice[&(1, row)].unwrap());
+            }
+            cells.push(recorder.advice[&(2, row)].unwrap());
+        }
+        assert_eq!(cells, embed_message(a, b, message));
+        assert_eq!(extract_message(a, b, &cells), Some(message.to_vec()));
+    }
+
+    #[test]
+    fn low_bits_are_free() {
+        // the same message on a different start, and the extreme bytes
+        let circuit = SteganographicFiboCircuit::new(Fp::from(2), Fp::from(3), &[0, 255, 128, 1]);
+        MockProver::run(8, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn stego_gate() {
+        // F(1) + 3, F(2) + 4, F(3) + 5
+        let valid = RowCircuit {
+            row: [4, 5, 7, 3, 4, 5],
+        };
+        MockProver::run(6, &valid, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let wrong = RowCircuit {
+            row: [4, 5, 8, 3, 4, 5],
+        };
+        assert!(MockProver::run(6, &wrong, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn delta_past_a_byte_fails() {
+        // satisfies the stego gate, dc = 256 fails its range check
+        let circuit = RowCircuit {
+            row: [4, 5, 258, 3, 4, 256],
+        };
+        assert!(MockProver::run(6, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn short_message() {
+        let circuit = SteganographicFiboCircuit::new(Fp::one(), Fp::one(), b"hi");
+        assert!(matches!(
+            MockProver::run(8, &circuit, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
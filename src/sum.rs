@@ -0,0 +1,102 @@
+// | x   | acc            | s_first | s_next |
+// | x_0 | x_0            | 1       | 0      |
+// | x_1 | x_0 + x_1      | 0       | 1      |
+// | ... |
+// gate sum first: s_first * (acc - x) == 0
+// gate sum next: s_next * (acc(prev) + x - acc) == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct SumConfig {
+    pub x: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub s_first: Selector,
+    pub s_next: Selector,
+}
+
+pub struct SumChip<F: FieldExt> {
+    config: SumConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SumChip<F> {
+    pub fn construct(config: SumConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        x: Column<Advice>,
+        acc: Column<Advice>,
+    ) -> SumConfig {
+        meta.enable_equality(x);
+        meta.enable_equality(acc);
+
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+
+        meta.create_gate("sum first", |meta| {
+            let s = meta.query_selector(s_first);
+            let x = meta.query_advice(x, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (acc - x)]
+        });
+
+        meta.create_gate("sum next", |meta| {
+            let s = meta.query_selector(s_next);
+            let x = meta.query_advice(x, Rotation::cur());
+            let prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (prev + x - acc)]
+        });
+
+        SumConfig {
+            x,
+            acc,
+            s_first,
+            s_next,
+        }
+    }
+
+    pub fn sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!values.is_empty(), "nothing to sum");
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "sum",
+            |mut region| {
+                let mut acc: Option<AssignedCell<F, F>> = None;
+                for (row, value) in values.iter().enumerate() {
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_next.enable(&mut region, row)?;
+                    }
+                    value.copy_advice(|| "x", &mut region, config.x, row)?;
+
+                    let acc_val = match &acc {
+                        None => value.value().copied(),
+                        Some(prev) => prev.value().copied() + value.value().copied(),
+                    };
+                    acc = Some(region.assign_advice(|| "acc", config.acc, row, || acc_val)?);
+                }
+                Ok(acc.unwrap())
+            },
+        )
+    }
+}
@@ -0,0 +1,247 @@
+// The sumcheck claim for a multilinear polynomial in NUM_VARS variables:
+//
+// sum over x in {0, 1}^NUM_VARS of f(x) == H
+//
+// f is given by its coefficients c_S, one per monomial prod_{i in S} x_i with S
+// read as a bitmask over the variables (c_0 is the constant term, c_1 the x_0
+// term, c_3 the x_0 * x_1 term, ...). Each coefficient sits in its own fixed
+// column, the hypercube points are fixed too, one per row:
+//
+// | x_0 | ... | x_{n-1} | c_0 | ... | c_{2^n-1} | eval | acc                  |
+// | 0   | ... | 0       | c_0 | ... |           | f(0) | f(0)                 |
+// | 1   | ... | 0       | c_0 | ... |           | f(1) | acc(prev) + f(1)     |
+// | ... |
+// gate eval: s_eval * (sum_S c_S * prod_{i in S} x_i - eval) == 0
+// gate sum first: s_first * (acc - eval) == 0
+// gate sum next: s_next * (acc(prev) + eval - acc) == 0
+//
+// The sum is accumulated directly rather than through the round by round
+// protocol, so the gate degree grows with NUM_VARS.
+//
+// instance: | H |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
+};
+
+// f(point), with `point` the bitmask of the variables set to 1
+pub fn evaluate_multilinear<F: FieldExt>(coefficients: &[F], point: usize) -> F {
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(monomial, _)| monomial & point == *monomial)
+        .fold(F::zero(), |acc, (_, c)| acc + *c)
+}
+
+pub fn hypercube_sum<F: FieldExt>(coefficients: &[F]) -> F {
+    (0..coefficients.len()).fold(F::zero(), |acc, point| {
+        acc + evaluate_multilinear(coefficients, point)
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct SumcheckConfig {
+    pub point: Vec<Column<Fixed>>,
+    pub coefficients: Vec<Column<Fixed>>,
+    pub eval: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub instance: Column<Instance>,
+    pub s_eval: Selector,
+    pub s_first: Selector,
+    pub s_next: Selector,
+}
+
+pub struct SumcheckCircuit<F: FieldExt, const NUM_VARS: usize> {
+    // 2^NUM_VARS coefficients, indexed by monomial bitmask
+    pub coefficients: Vec<F>,
+}
+
+impl<F: FieldExt, const NUM_VARS: usize> SumcheckCircuit<F, NUM_VARS> {
+    pub fn new(coefficients: Vec<F>) -> Self {
+        Self { coefficients }
+    }
+
+    pub fn public_inputs(&self) -> Vec<F> {
+        vec![hypercube_sum(&self.coefficients)]
+    }
+}
+
+impl<F: FieldExt, const NUM_VARS: usize> Circuit<F> for SumcheckCircuit<F, NUM_VARS> {
+    type Config = SumcheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the coefficients are part of the circuit, not the witness
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.coefficients.clone())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let point: Vec<_> = (0..NUM_VARS).map(|_| meta.fixed_column()).collect();
+        let coefficients: Vec<_> = (0..1 << NUM_VARS).map(|_| meta.fixed_column()).collect();
+        let eval = meta.advice_column();
+        let acc = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(acc);
+        meta.enable_equality(instance);
+        let s_eval = meta.selector();
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+
+        meta.create_gate("eval", |meta| {
+            let s = meta.query_selector(s_eval);
+            let x: Vec<_> = point
+                .iter()
+                .map(|column| meta.query_fixed(*column, Rotation::cur()))
+                .collect();
+            let f = coefficients
+                .iter()
+                .enumerate()
+                .map(|(monomial, column)| {
+                    (0..NUM_VARS)
+                        .filter(|i| monomial >> i & 1 == 1)
+                        .fold(meta.query_fixed(*column, Rotation::cur()), |term, i| {
+                            term * x[i].clone()
+                        })
+                })
+                .fold(Expression::Constant(F::zero()), |sum, term| sum + term);
+            let eval = meta.query_advice(eval, Rotation::cur());
+            vec![s * (f - eval)]
+        });
+
+        meta.create_gate("sum first", |meta| {
+            let s = meta.query_selector(s_first);
+            let eval = meta.query_advice(eval, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (acc - eval)]
+        });
+
+        meta.create_gate("sum next", |meta| {
+            let s = meta.query_selector(s_next);
+            let eval = meta.query_advice(eval, Rotation::cur());
+            let prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (prev + eval - acc)]
+        });
+
+        SumcheckConfig {
+            point,
+            coefficients,
+            eval,
+            acc,
+            instance,
+            s_eval,
+            s_first,
+            s_next,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.coefficients.len() != 1 << NUM_VARS {
+            return Err(Error::Synthesis);
+        }
+
+        let sum = layouter.assign_region(
+            || "hypercube",
+            |mut region| {
+                let mut acc = None;
+                let mut running = F::zero();
+                for point in 0..1 << NUM_VARS {
+                    config.s_eval.enable(&mut region, point)?;
+                    if point == 0 {
+                        config.s_first.enable(&mut region, point)?;
+                    } else {
+                        config.s_next.enable(&mut region, point)?;
+                    }
+                    for (i, column) in config.point.iter().enumerate() {
+                        let bit = F::from((point >> i & 1) as u64);
+                        region.assign_fixed(|| "x", *column, point, || Value::known(bit))?;
+                    }
+                    for (c, column) in self.coefficients.iter().zip(&config.coefficients) {
+                        region.assign_fixed(|| "c", *column, point, || Value::known(*c))?;
+                    }
+
+                    let eval = evaluate_multilinear(&self.coefficients, point);
+                    running += eval;
+                    region.assign_advice(|| "f(x)", config.eval, point, || Value::known(eval))?;
+                    acc = Some(region.assign_advice(
+                        || "acc",
+                        config.acc,
+                        point,
+                        || Value::known(running),
+                    )?);
+                }
+                Ok(acc.unwrap())
+            },
+        )?;
+
+        layouter.constrain_instance(sum.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    // f(x, y) = 3x + y + 2xy, indexed by monomial: 1, x, y, xy
+    fn f() -> Vec<Fp> {
+        [0, 3, 1, 2].map(Fp::from).to_vec()
+    }
+
+    #[test]
+    fn evaluations_on_the_square() {
+        // (0, 0), (1, 0), (0, 1), (1, 1)
+        let evaluations: Vec<_> = (0..4)
+            .map(|point| evaluate_multilinear(&f(), point))
+            .collect();
+        assert_eq!(evaluations, [0, 3, 1, 6].map(Fp::from));
+        assert_eq!(hypercube_sum(&f()), Fp::from(10));
+    }
+
+    #[test]
+    fn two_variable_sum() {
+        let circuit = SumcheckCircuit::<Fp, 2>::new(f());
+        assert_eq!(circuit.public_inputs(), vec![Fp::from(10)]);
+        MockProver::run(4, &circuit, vec![vec![Fp::from(10)]])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_sum_fails() {
+        let circuit = SumcheckCircuit::<Fp, 2>::new(f());
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(11)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn three_variable_sum() {
+        // 1 + x_0 x_1 x_2: 8 ones and one extra at (1, 1, 1)
+        let mut coefficients = vec![Fp::zero(); 8];
+        coefficients[0] = Fp::one();
+        coefficients[7] = Fp::one();
+        let circuit = SumcheckCircuit::<Fp, 3>::new(coefficients);
+        MockProver::run(5, &circuit, vec![vec![Fp::from(9)]])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_number_of_coefficients_is_a_synthesis_error() {
+        let circuit = SumcheckCircuit::<Fp, 2>::new(f()[..3].to_vec());
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![vec![Fp::zero()]]),
+            Err(Error::Synthesis)
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+// Whether a gate is unchanged by swapping two advice columns. A symmetric gate,
+// s * (a * b - c) or s * (a + b - c), can't tell its two inputs apart, so any
+// constraint that depends on their order has to come from somewhere else.
+//
+// Both sides are multiplied out with `flatten_expression`, constants folded
+// into the coefficients and like terms combined, so the comparison holds up to
+// commutativity and the order the gate was written in.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, Expression},
+};
+
+use crate::{
+    gate_flatten::{flatten_expression, ExprAtom},
+    recorder::{column_index, selector_index},
+};
+
+// (kind, index, rotation), ordered so monomials sort the same way every time
+type AtomKey = (u8, usize, i32);
+
+fn normal_form<F: FieldExt>(
+    expr: &Expression<F>,
+    swap: Option<(usize, usize)>,
+) -> BTreeMap<Vec<AtomKey>, F> {
+    let swapped = |index: usize| match swap {
+        Some((a, b)) if index == a => b,
+        Some((a, b)) if index == b => a,
+        _ => index,
+    };
+
+    let mut terms = BTreeMap::new();
+    for (mut coeff, atoms) in flatten_expression(expr) {
+        let mut key = Vec::with_capacity(atoms.len());
+        for atom in atoms {
+            match atom {
+                ExprAtom::Constant(c) => coeff *= c,
+                ExprAtom::Advice(index, rotation) => key.push((0, swapped(index), rotation)),
+                ExprAtom::Fixed(index, rotation) => key.push((1, index, rotation)),
+                ExprAtom::Instance(index, rotation) => key.push((2, index, rotation)),
+                ExprAtom::Selector(selector) => key.push((3, selector_index(&selector), 0)),
+            }
+        }
+        key.sort_unstable();
+        *terms.entry(key).or_insert_with(F::zero) += coeff;
+    }
+    terms.retain(|_, coeff| *coeff != F::zero());
+    terms
+}
+
+/// True when `expr` is the same polynomial with `col1` and `col2` swapped.
+pub fn check_gate_symmetry<F: FieldExt>(
+    expr: &Expression<F>,
+    col1: Column<Advice>,
+    col2: Column<Advice>,
+) -> bool {
+    normal_form(expr, None) == normal_form(expr, Some((column_index(col1), column_index(col2))))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        pasta::Fp,
+        plonk::{ConstraintSystem, VirtualCells},
+        poly::Rotation,
+    };
+
+    use super::*;
+
+    // `gate` over a, b, c under a selector, as create_gate hands it over
+    fn gate(
+        build: impl FnOnce(&mut VirtualCells<'_, Fp>, [Column<Advice>; 3]) -> Expression<Fp>,
+    ) -> (Expression<Fp>, [Column<Advice>; 3]) {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let columns = [(); 3].map(|_| meta.advice_column());
+        let s = meta.selector();
+        let mut captured = None;
+        meta.create_gate("gate", |meta| {
+            let s = meta.query_selector(s);
+            let expr = s * build(meta, columns);
+            captured = Some(expr.clone());
+            vec![expr]
+        });
+        (captured.unwrap(), columns)
+    }
+
+    fn cur(meta: &mut VirtualCells<'_, Fp>, columns: [Column<Advice>; 3]) -> [Expression<Fp>; 3] {
+        columns.map(|column| meta.query_advice(column, Rotation::cur()))
+    }
+
+    #[test]
+    fn mul_gate_is_symmetric() {
+        let (expr, [a, b, c]) = gate(|meta, columns| {
+            let [a, b, c] = cur(meta, columns);
+            a * b - c
+        });
+        assert!(check_gate_symmetry(&expr, a, b));
+        assert!(!check_gate_symmetry(&expr, a, c));
+    }
+
+    #[test]
+    fn add_gate_is_symmetric() {
+        let (expr, [a, b, c]) = gate(|meta, columns| {
+            let [a, b, c] = cur(meta, columns);
+            a + b - c
+        });
+        assert!(check_gate_symmetry(&expr, a, b));
+        assert!(check_gate_symmetry(&expr, b, a));
+        assert!(!check_gate_symmetry(&expr, b, c));
+    }
+
+    #[test]
+    fn sub_gate_is_not_symmetric() {
+        let (expr, [a, b, _]) = gate(|meta, columns| {
+            let [a, b, c] = cur(meta, columns);
+            a - b - c
+        });
+        assert!(!check_gate_symmetry(&expr, a, b));
+    }
+
+    #[test]
+    fn written_order_does_not_matter() {
+        // b * a + a * b * 2 - 3 * (b * a) cancels out, leaving s * c
+        let (expr, [a, b, c]) = gate(|meta, columns| {
+            let [a, b, c] = cur(meta, columns);
+            b.clone() * a.clone() + a.clone() * b.clone() * Fp::from(2)
+                - Expression::Constant(Fp::from(3)) * (b * a)
+                + c
+        });
+        assert!(check_gate_symmetry(&expr, a, b));
+        assert!(check_gate_symmetry(&expr, a, a));
+        assert!(!check_gate_symmetry(&expr, a, c));
+    }
+
+    #[test]
+    fn rotations_are_kept_apart() {
+        let (expr, [a, b, _]) = gate(|meta, [a, b, _]| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::next());
+            a + b
+        });
+        assert!(!check_gate_symmetry(&expr, a, b));
+    }
+}
@@ -0,0 +1,137 @@
+// Known answers for the Fibonacci and function chips, each run through
+// MockProver with the answer as a public input.
+//
+// Fibonacci vectors go through `OnlineFiboCircuit` with F(1) = a, F(2) = b and
+// 8 steps, which exposes F(10). Function vectors go through
+// `ParametricFunctionCircuit` with the coefficients of x^3 + x + 5.
+
+use halo2_proofs::{
+    circuit::Value,
+    dev::{MockProver, VerifyFailure},
+    pasta::Fp,
+    plonk::{Circuit, Error},
+};
+
+use crate::{fibo_online::OnlineFiboCircuit, function::ParametricFunctionCircuit};
+
+/// (a, b, F(10)) for F(1) = a, F(2) = b.
+pub const FIBO_VECTORS: &[(u64, u64, u64)] = &[
+    (1, 1, 55),
+    (0, 1, 34),
+    (1, 2, 89),
+    (2, 3, 144),
+    (5, 8, 377),
+    (0, 0, 0),
+];
+
+/// (x, x^3 + x + 5)
+pub const FUNCTION_VECTORS: &[(u64, u64)] = &[
+    (0, 5),
+    (1, 7),
+    (2, 15),
+    (3, 35),
+    (10, 1015),
+    (1000, 1_000_001_005),
+];
+
+const K: u32 = 5;
+
+#[derive(Debug)]
+pub enum TestVectorFailure {
+    Synthesis {
+        circuit: &'static str,
+        vector: usize,
+        error: Error,
+    },
+    Unsatisfied {
+        circuit: &'static str,
+        vector: usize,
+        failures: Vec<VerifyFailure>,
+    },
+}
+
+fn run<C: Circuit<Fp>>(
+    name: &'static str,
+    vector: usize,
+    circuit: C,
+    public: Vec<Fp>,
+) -> Option<TestVectorFailure> {
+    let prover = match MockProver::run(K, &circuit, vec![public]) {
+        Ok(prover) => prover,
+        Err(error) => {
+            return Some(TestVectorFailure::Synthesis {
+                circuit: name,
+                vector,
+                error,
+            })
+        }
+    };
+    prover
+        .verify()
+        .err()
+        .map(|failures| TestVectorFailure::Unsatisfied {
+            circuit: name,
+            vector,
+            failures,
+        })
+}
+
+pub fn run_test_vectors() -> Result<(), Vec<TestVectorFailure>> {
+    let fibo = FIBO_VECTORS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &(a, b, f10))| {
+            let circuit = OnlineFiboCircuit {
+                a: Value::known(Fp::from(a)),
+                b: Value::known(Fp::from(b)),
+                steps: 8,
+            };
+            let public = vec![Fp::from(a), Fp::from(b), Fp::from(f10)];
+            run("OnlineFiboCircuit", i, circuit, public)
+        });
+
+    let function = FUNCTION_VECTORS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &(x, y))| {
+            let circuit = ParametricFunctionCircuit {
+                x: Value::known(Fp::from(x)),
+            };
+            let public = [1, 0, 1, 5, y].map(Fp::from).to_vec();
+            run("ParametricFunctionCircuit", i, circuit, public)
+        });
+
+    let failures: Vec<_> = fibo.chain(function).collect();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_vectors_pass() {
+        let result = run_test_vectors();
+        assert!(matches!(result, Ok(())), "{:?}", result);
+    }
+
+    #[test]
+    fn wrong_vector_is_reported() {
+        let circuit = ParametricFunctionCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        let public = [1, 0, 1, 5, 36].map(Fp::from).to_vec();
+        match run("ParametricFunctionCircuit", 7, circuit, public) {
+            Some(TestVectorFailure::Unsatisfied {
+                circuit, vector, ..
+            }) => {
+                assert_eq!((circuit, vector), ("ParametricFunctionCircuit", 7));
+            }
+            other => panic!("expected an unsatisfied vector, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// Proves that at least `k` of `N` private booleans are set.
+//
+// | sum | k   | diff | s_threshold |
+// gate threshold: s_threshold * (sum - k - diff) == 0
+// diff is range checked to [0, 2^8), which fails when sum < k since diff wraps
+// around the field.
+//
+// instance: | k | N |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    boolean::{BooleanChip, BooleanConfig},
+    range_check::{RangeCheckChip, RangeCheckConfig},
+    sum::{SumChip, SumConfig},
+};
+
+pub const THRESHOLD_BITS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct ThresholdConfig {
+    pub advice: [Column<Advice>; 4],
+    pub constant: Column<Fixed>,
+    pub instance: Column<Instance>,
+    pub s_threshold: Selector,
+    pub boolean: BooleanConfig,
+    pub sum: SumConfig,
+    pub range: RangeCheckConfig,
+}
+
+pub struct ThresholdCircuit<F: FieldExt, const N: usize> {
+    pub inputs: [Value<F>; N],
+}
+
+impl<F: FieldExt, const N: usize> ThresholdCircuit<F, N> {
+    pub fn new(inputs: [bool; N]) -> Self {
+        Self {
+            inputs: inputs.map(|b| Value::known(F::from(b))),
+        }
+    }
+
+    // instance column for a threshold `k`
+    pub fn public_inputs(k: u64) -> Vec<Vec<F>> {
+        vec![vec![F::from(k), F::from(N as u64)]]
+    }
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for ThresholdCircuit<F, N> {
+    type Config = ThresholdConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            inputs: [Value::unknown(); N],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        assert!(N < 1 << THRESHOLD_BITS);
+
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_constant(constant);
+        meta.enable_equality(instance);
+
+        let boolean = BooleanChip::configure(meta, advice[0]);
+        let sum = SumChip::configure(meta, advice[0], advice[1]);
+        let range = RangeCheckChip::<F, THRESHOLD_BITS>::configure(meta, advice[2], advice[3]);
+
+        let s_threshold = meta.selector();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.create_gate("threshold", |meta| {
+            let s = meta.query_selector(s_threshold);
+            let sum = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_advice(advice[1], Rotation::cur());
+            let diff = meta.query_advice(advice[2], Rotation::cur());
+            vec![s * (sum - k - diff)]
+        });
+
+        ThresholdConfig {
+            advice,
+            constant,
+            instance,
+            s_threshold,
+            boolean,
+            sum,
+            range,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let boolean = BooleanChip::construct(config.boolean.clone());
+        let bits = self
+            .inputs
+            .iter()
+            .map(|b| boolean.assign(layouter.namespace(|| "input"), *b))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let sum =
+            SumChip::construct(config.sum.clone()).sum(layouter.namespace(|| "sum"), &bits)?;
+
+        let (diff, n) = layouter.assign_region(
+            || "threshold",
+            |mut region| {
+                config.s_threshold.enable(&mut region, 0)?;
+                sum.copy_advice(|| "sum", &mut region, config.advice[0], 0)?;
+                let k = region.assign_advice_from_instance(
+                    || "k",
+                    config.instance,
+                    0,
+                    config.advice[1],
+                    0,
+                )?;
+                let diff = region.assign_advice(
+                    || "diff",
+                    config.advice[2],
+                    0,
+                    || sum.value().copied() - k.value().copied(),
+                )?;
+                let n = region.assign_advice_from_constant(
+                    || "n",
+                    config.advice[3],
+                    0,
+                    F::from(N as u64),
+                )?;
+                Ok((diff, n))
+            },
+        )?;
+
+        RangeCheckChip::<F, THRESHOLD_BITS>::construct(config.range)
+            .check(layouter.namespace(|| "sum >= k"), &diff)?;
+        layouter.constrain_instance(n.cell(), config.instance, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn prove(circuit: ThresholdCircuit<Fp, 5>, public: Vec<Vec<Fp>>) -> MockProver<Fp> {
+        MockProver::run(6, &circuit, public).unwrap()
+    }
+
+    fn two_of_five(inputs: [bool; 5]) -> MockProver<Fp> {
+        prove(
+            ThresholdCircuit::new(inputs),
+            ThresholdCircuit::<Fp, 5>::public_inputs(2),
+        )
+    }
+
+    #[test]
+    fn at_least_two_of_five() {
+        two_of_five([true, true, false, false, false]).assert_satisfied();
+        two_of_five([false, true, false, false, true]).assert_satisfied();
+        two_of_five([true, false, true, true, false]).assert_satisfied();
+        two_of_five([true; 5]).assert_satisfied();
+    }
+
+    #[test]
+    fn fewer_than_two_fails() {
+        assert!(two_of_five([false; 5]).verify().is_err());
+        assert!(two_of_five([false, false, true, false, false])
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn non_boolean_input_fails() {
+        // 2 + 0 + ... reaches k = 2 with a single input
+        let mut circuit = ThresholdCircuit::new([false; 5]);
+        circuit.inputs[0] = Value::known(Fp::from(2));
+        let public = ThresholdCircuit::<Fp, 5>::public_inputs(2);
+        assert!(prove(circuit, public).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_n_fails() {
+        let circuit = ThresholdCircuit::new([true; 5]);
+        assert!(prove(circuit, vec![vec![Fp::from(2), Fp::from(6)]])
+            .verify()
+            .is_err());
+    }
+}
@@ -0,0 +1,216 @@
+// Opens a commitment C = H(H(value || nonce) || height) and proves the block
+// height bound into it is below a public maximum.
+//
+// | max_height | height | diff | s_before |
+// gate before: s_before * (max_height - height - 1 - diff) == 0
+// height and diff are both range checked to [0, 2^16): diff wraps around the
+// field when height >= max_height, and a height below 0 wraps to a value far
+// outside the range.
+//
+// instance: | C | max_height |
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    hash::{hash, AlgebraicHashChip, AlgebraicHashConfig},
+    range_check::{RangeCheckChip, RangeCheckConfig},
+};
+
+pub const HEIGHT_BITS: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct TimestampConfig {
+    pub advice: [Column<Advice>; 4],
+    pub instance: Column<Instance>,
+    pub s_before: Selector,
+    pub hash: AlgebraicHashConfig,
+    pub range: RangeCheckConfig,
+}
+
+#[derive(Default)]
+pub struct TimestampCircuit<F> {
+    pub value: Value<F>,
+    pub nonce: Value<F>,
+    pub height: Value<F>,
+}
+
+impl<F: FieldExt> TimestampCircuit<F> {
+    pub fn new(value: F, nonce: F, height: u64) -> Self {
+        Self {
+            value: Value::known(value),
+            nonce: Value::known(nonce),
+            height: Value::known(F::from(height)),
+        }
+    }
+
+    // instance column for a commitment to `value` under `nonce` at `height`
+    pub fn public_inputs(value: F, nonce: F, height: u64, max_height: u64) -> Vec<Vec<F>> {
+        vec![vec![
+            hash(hash(value, nonce), F::from(height)),
+            F::from(max_height),
+        ]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for TimestampCircuit<F> {
+    type Config = TimestampConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let round_constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let hash = AlgebraicHashChip::configure(meta, advice[0], advice[1], round_constant);
+        let range = RangeCheckChip::<F, HEIGHT_BITS>::configure(meta, advice[2], advice[3]);
+
+        let s_before = meta.selector();
+        meta.create_gate("before", |meta| {
+            let s = meta.query_selector(s_before);
+            let max_height = meta.query_advice(advice[0], Rotation::cur());
+            let height = meta.query_advice(advice[1], Rotation::cur());
+            let diff = meta.query_advice(advice[2], Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![s * (max_height - height - one - diff)]
+        });
+
+        TimestampConfig {
+            advice,
+            instance,
+            s_before,
+            hash,
+            range,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let (value, nonce, height) = layouter.assign_region(
+            || "open",
+            |mut region| {
+                let value = region.assign_advice(|| "value", config.advice[0], 0, || self.value)?;
+                let nonce = region.assign_advice(|| "nonce", config.advice[1], 0, || self.nonce)?;
+                let height =
+                    region.assign_advice(|| "height", config.advice[2], 0, || self.height)?;
+                Ok((value, nonce, height))
+            },
+        )?;
+        let hash_chip = AlgebraicHashChip::construct(config.hash.clone());
+        let inner = hash_chip.hash(layouter.namespace(|| "H(value || nonce)"), &value, &nonce)?;
+        let commitment =
+            hash_chip.hash(layouter.namespace(|| "H(.. || height)"), &inner, &height)?;
+        layouter.constrain_instance(commitment.cell(), config.instance, 0)?;
+
+        let diff = layouter.assign_region(
+            || "before",
+            |mut region| {
+                config.s_before.enable(&mut region, 0)?;
+                let max_height = region.assign_advice_from_instance(
+                    || "max_height",
+                    config.instance,
+                    1,
+                    config.advice[0],
+                    0,
+                )?;
+                height.copy_advice(|| "height", &mut region, config.advice[1], 0)?;
+                region.assign_advice(
+                    || "diff",
+                    config.advice[2],
+                    0,
+                    || max_height.value().copied() - self.height - Value::known(F::one()),
+                )
+            },
+        )?;
+
+        let range = RangeCheckChip::<F, HEIGHT_BITS>::construct(config.range);
+        range.check(layouter.namespace(|| "height >= 0"), &height)?;
+        range.check(layouter.namespace(|| "height < max_height"), &diff)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    const K: u32 = 7;
+
+    fn run(circuit: TimestampCircuit<Fp>, instance: Vec<Vec<Fp>>) -> MockProver<Fp> {
+        MockProver::run(K, &circuit, instance).unwrap()
+    }
+
+    #[test]
+    fn valid_timestamp() {
+        let (value, nonce) = (Fp::from(42), Fp::from(7));
+        let prover = run(
+            TimestampCircuit::new(value, nonce, 100),
+            TimestampCircuit::public_inputs(value, nonce, 100, 101),
+        );
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn expired_timestamp_fails() {
+        let (value, nonce) = (Fp::from(42), Fp::from(7));
+        for max_height in [100, 50] {
+            let prover = run(
+                TimestampCircuit::new(value, nonce, 100),
+                TimestampCircuit::public_inputs(value, nonce, 100, max_height),
+            );
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn height_must_match_the_commitment() {
+        // committed at 100, claims 10 to get under the maximum
+        let (value, nonce) = (Fp::from(42), Fp::from(7));
+        let prover = run(
+            TimestampCircuit::new(value, nonce, 10),
+            TimestampCircuit::public_inputs(value, nonce, 100, 50),
+        );
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn negative_height_fails() {
+        // -1 would pass the diff check alone: max_height - (-1) - 1 = max_height
+        let (value, nonce) = (Fp::from(42), Fp::from(7));
+        let height = -Fp::one();
+        let circuit = TimestampCircuit {
+            value: Value::known(value),
+            nonce: Value::known(nonce),
+            height: Value::known(height),
+        };
+        let instance = vec![vec![hash(hash(value, nonce), height), Fp::from(50)]];
+        assert!(run(circuit, instance).verify().is_err());
+    }
+
+    #[test]
+    fn wrong_opening_fails() {
+        let (value, nonce) = (Fp::from(42), Fp::from(7));
+        let prover = run(
+            TimestampCircuit::new(value + Fp::one(), nonce, 10),
+            TimestampCircuit::public_inputs(value, nonce, 10, 50),
+        );
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,247 @@
+// Replays the verifier on a proof and records what it takes out of the
+// transcript, in order: the commitments it absorbs, the challenges it squeezes
+// and the evaluations it reads, ending with the outcome of the final check on
+// the polynomial commitment opening.
+//
+// halo2's own `verify_proof` does the work, reading through a transcript that
+// records as it goes, so the steps are exactly the verifier's. The challenges
+// come out in a fixed order, named as in the halo2 book:
+//
+// theta, beta, gamma, y, x               plonk
+// x1, x2, x3, x4                         multiopen
+// xi, z, u_0 .. u_k-1                    IPA opening, one u per round
+//
+// Commitments are numbered in the order they enter the transcript, instance
+// commitments first, then advice, lookup, permutation and vanishing ones.
+
+use std::{fmt, io};
+
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{verify_proof, Error, SingleVerifier, VerifyingKey},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge, Transcript, TranscriptRead},
+};
+
+const CHALLENGES: [&str; 11] = [
+    "theta", "beta", "gamma", "y", "x", "x1", "x2", "x3", "x4", "xi", "z",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifierStep {
+    Challenge { challenge_name: String, value: Fp },
+    Commitment { column: usize, point: EqAffine },
+    Evaluation { index: usize, value: Fp },
+    // the opening argument's multiscalar multiplication came out to zero
+    FinalCheck { passed: bool },
+}
+
+impl fmt::Display for VerifierStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierStep::Challenge {
+                challenge_name,
+                value,
+            } => write!(f, "challenge {} = {:?}", challenge_name, value),
+            VerifierStep::Commitment { column, point } => {
+                write!(f, "commitment {} = {:?}", column, point)
+            }
+            VerifierStep::Evaluation { index, value } => {
+                write!(f, "evaluation {} = {:?}", index, value)
+            }
+            VerifierStep::FinalCheck { passed } => write!(f, "final check passed: {}", passed),
+        }
+    }
+}
+
+struct RecordingTranscript<'a> {
+    inner: Blake2bRead<&'a [u8], EqAffine, Challenge255<EqAffine>>,
+    steps: Vec<VerifierStep>,
+    commitments: usize,
+    evaluations: usize,
+    challenges: usize,
+}
+
+impl RecordingTranscript<'_> {
+    fn commitment(&mut self, point: EqAffine) {
+        self.steps.push(VerifierStep::Commitment {
+            column: self.commitments,
+            point,
+        });
+        self.commitments += 1;
+    }
+}
+
+impl Transcript<EqAffine, Challenge255<EqAffine>> for RecordingTranscript<'_> {
+    fn squeeze_challenge(&mut self) -> Challenge255<EqAffine> {
+        let challenge = self.inner.squeeze_challenge();
+        let challenge_name = match CHALLENGES.get(self.challenges) {
+            Some(name) => name.to_string(),
+            None => format!("u_{}", self.challenges - CHALLENGES.len()),
+        };
+        self.steps.push(VerifierStep::Challenge {
+            challenge_name,
+            value: challenge.get_scalar(),
+        });
+        self.challenges += 1;
+        challenge
+    }
+
+    // the instance commitments, computed by the verifier
+    fn common_point(&mut self, point: EqAffine) -> io::Result<()> {
+        self.inner.common_point(point)?;
+        self.commitment(point);
+        Ok(())
+    }
+
+    // only the verifying key goes in this way
+    fn common_scalar(&mut self, scalar: Fp) -> io::Result<()> {
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl TranscriptRead<EqAffine, Challenge255<EqAffine>> for RecordingTranscript<'_> {
+    fn read_point(&mut self) -> io::Result<EqAffine> {
+        let point = self.inner.read_point()?;
+        self.commitment(point);
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fp> {
+        let value = self.inner.read_scalar()?;
+        self.steps.push(VerifierStep::Evaluation {
+            index: self.evaluations,
+            value,
+        });
+        self.evaluations += 1;
+        Ok(value)
+    }
+}
+
+/// Prints every step once replayed. A proof that can't be read to the end
+/// stops at the last step that could, without a final check.
+pub fn replay_transcript(
+    proof: &[u8],
+    vk: &VerifyingKey<EqAffine>,
+    public: &[Vec<Fp>],
+) -> Vec<VerifierStep> {
+    // the key only keeps its domain, k is the log of its size
+    let k = vk.get_domain().empty_lagrange().len().trailing_zeros();
+    let params = Params::<EqAffine>::new(k);
+
+    let instance: Vec<&[Fp]> = public.iter().map(|column| column.as_slice()).collect();
+    let mut transcript = RecordingTranscript {
+        inner: Blake2bRead::init(proof),
+        steps: vec![],
+        commitments: 0,
+        evaluations: 0,
+        challenges: 0,
+    };
+    let strategy = SingleVerifier::new(&params);
+    let result = verify_proof(&params, vk, strategy, &[&instance], &mut transcript);
+
+    let mut steps = transcript.steps;
+    // any other error stopped the verifier before the check
+    match result {
+        Ok(()) => steps.push(VerifierStep::FinalCheck { passed: true }),
+        Err(Error::ConstraintSystemFailure) => {
+            steps.push(VerifierStep::FinalCheck { passed: false })
+        }
+        Err(_) => {}
+    }
+    for step in &steps {
+        println!("{}", step);
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value;
+
+    use super::*;
+    use crate::{
+        fibo1::FiboCircuit,
+        ipa::{keygen, prove},
+    };
+
+    const K: u32 = 4;
+
+    // a FiboCircuit proof and the key that checks it
+    fn fibo_proof() -> (Vec<u8>, VerifyingKey<EqAffine>) {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        let (params, pk) = keygen(&circuit, K).unwrap();
+        let proof = prove(&params, &pk, circuit, &[]).unwrap();
+        (proof, pk.get_vk().clone())
+    }
+
+    fn challenges(steps: &[VerifierStep]) -> Vec<&str> {
+        steps
+            .iter()
+            .filter_map(|step| match step {
+                VerifierStep::Challenge { challenge_name, .. } => Some(challenge_name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fibo_proof_ends_in_a_passing_final_check() {
+        let (proof, vk) = fibo_proof();
+        let steps = replay_transcript(&proof, &vk, &[]);
+        assert_eq!(
+            steps.last(),
+            Some(&VerifierStep::FinalCheck { passed: true })
+        );
+
+        // one u per IPA round, one round per bit of k
+        let mut names = CHALLENGES.to_vec();
+        names.extend(["u_0", "u_1", "u_2", "u_3"]);
+        assert_eq!(challenges(&steps), names);
+        assert!(matches!(
+            steps[0],
+            VerifierStep::Commitment { column: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let (proof, vk) = fibo_proof();
+        assert_eq!(
+            replay_transcript(&proof, &vk, &[]),
+            replay_transcript(&proof, &vk, &[])
+        );
+    }
+
+    #[test]
+    fn tampered_evaluation_fails_the_final_check() {
+        let (mut proof, vk) = fibo_proof();
+        let steps = replay_transcript(&proof, &vk, &[]);
+        let points = steps
+            .iter()
+            .take_while(|step| !matches!(step, VerifierStep::Evaluation { .. }))
+            .filter(|step| matches!(step, VerifierStep::Commitment { .. }))
+            .count();
+        // points and scalars are 32 bytes each, flip the first evaluation
+        proof[32 * points] ^= 1;
+
+        let steps = replay_transcript(&proof, &vk, &[]);
+        assert_eq!(
+            steps.last(),
+            Some(&VerifierStep::FinalCheck { passed: false })
+        );
+    }
+
+    #[test]
+    fn truncated_proof_has_no_final_check() {
+        let (proof, vk) = fibo_proof();
+        let steps = replay_transcript(&proof[..64], &vk, &[]);
+        assert!(!steps
+            .iter()
+            .any(|step| matches!(step, VerifierStep::FinalCheck { .. })));
+        assert!(steps.len() <= 3);
+    }
+}
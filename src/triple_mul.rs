@@ -0,0 +1,227 @@
+// a * b * c in one region. The gate spans two rows under one selector and the
+// product t of the first row is read again from the second, so it is neither
+// copied nor laid out twice:
+//
+// | l | r | o   | selector |
+// | a | b | t   | 1        |
+// | c |   | out | 0        |
+// gate triple mul: selector * (l * r - o) == 0
+//                  selector * (o * l(next) - o(next)) == 0
+//
+// Two `load_mul` calls take two regions and a copy of t for the same product.
+//
+// instance: | a * b * c |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct TripleMulConfig {
+    pub advice: [Column<Advice>; 3],
+    pub selector: Selector,
+}
+
+pub struct TripleMulChip<F: FieldExt> {
+    config: TripleMulConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> TripleMulChip<F> {
+    pub fn construct(config: TripleMulConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+    ) -> TripleMulConfig {
+        let [l, r, o] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let selector = meta.selector();
+
+        meta.create_gate("triple mul", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(l, Rotation::cur());
+            let b = meta.query_advice(r, Rotation::cur());
+            let t = meta.query_advice(o, Rotation::cur());
+            let c = meta.query_advice(l, Rotation::next());
+            let out = meta.query_advice(o, Rotation::next());
+            vec![s.clone() * (a * b - t.clone()), s * (t * c - out)]
+        });
+
+        TripleMulConfig { advice, selector }
+    }
+
+    pub fn triple_mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        c: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [l, r, o] = self.config.advice;
+        layouter.assign_region(
+            || "triple mul",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                region.assign_advice(|| "a", l, 0, || a)?;
+                region.assign_advice(|| "b", r, 0, || b)?;
+                region.assign_advice(|| "t", o, 0, || a * b)?;
+                region.assign_advice(|| "c", l, 1, || c)?;
+                region.assign_advice(|| "out", o, 1, || a * b * c)
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TripleMulCircuitConfig {
+    pub mul: TripleMulConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct TripleMulCircuit<F> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub c: Value<F>,
+}
+
+impl<F: FieldExt> TripleMulCircuit<F> {
+    pub fn new(a: F, b: F, c: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            c: Value::known(c),
+        }
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for TripleMulCircuit<F> {
+    type Config = TripleMulCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        TripleMulCircuitConfig {
+            mul: TripleMulChip::configure(meta, advice),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = TripleMulChip::construct(config.mul);
+        let out = chip.triple_mul(layouter.namespace(|| "abc"), self.a, self.b, self.c)?;
+        layouter.constrain_instance(out.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::record;
+
+    // the two rows of the gate laid out by hand, `t` and `out` as given
+    struct RowsCircuit {
+        cells: [u64; 5],
+    }
+
+    impl Circuit<Fp> for RowsCircuit {
+        type Config = TripleMulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { cells: self.cells }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            TripleMulChip::configure(meta, advice)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let [l, r, o] = config.advice;
+            let [a, b, t, c, out] = self.cells.map(|cell| Value::known(Fp::from(cell)));
+            layouter.assign_region(
+                || "rows",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", l, 0, || a)?;
+                    region.assign_advice(|| "b", r, 0, || b)?;
+                    region.assign_advice(|| "t", o, 0, || t)?;
+                    region.assign_advice(|| "c", l, 1, || c)?;
+                    region.assign_advice(|| "out", o, 1, || out)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn prove(a: u64, b: u64, c: u64, out: u64) -> MockProver<Fp> {
+        let circuit = TripleMulCircuit::new(Fp::from(a), Fp::from(b), Fp::from(c));
+        MockProver::run(4, &circuit, vec![vec![Fp::from(out)]]).unwrap()
+    }
+
+    #[test]
+    fn two_three_four() {
+        prove(2, 3, 4, 24).assert_satisfied();
+        prove(0, 3, 4, 0).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_product_fails() {
+        assert!(prove(2, 3, 4, 25).verify().is_err());
+        // a * b alone
+        assert!(prove(2, 3, 4, 6).verify().is_err());
+    }
+
+    #[test]
+    fn one_region() {
+        let circuit = TripleMulCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4));
+        let recorder = record(&circuit, 4, vec![vec![Fp::from(24)]]).unwrap();
+        assert_eq!(recorder.regions.len(), 1);
+        assert_eq!(recorder.regions[0].name, "triple mul");
+        assert_eq!(recorder.regions[0].rows, Some((0, 1)));
+        // t is laid out once, in row 0
+        assert_eq!(recorder.advice[&(2, 0)], Some(Fp::from(6)));
+        assert_eq!(recorder.advice[&(2, 1)], Some(Fp::from(24)));
+    }
+
+    #[test]
+    fn both_rows_are_checked() {
+        let prove = |cells| MockProver::run(4, &RowsCircuit { cells }, vec![]).unwrap();
+        prove([2, 3, 6, 4, 24]).assert_satisfied();
+        // t isn't a * b, out = t * c still holds
+        assert!(prove([2, 3, 7, 4, 28]).verify().is_err());
+        // t = a * b, out isn't t * c
+        assert!(prove([2, 3, 6, 4, 25]).verify().is_err());
+    }
+}
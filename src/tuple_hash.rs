@@ -0,0 +1,233 @@
+// Hash of an ordered tuple (v_1, ..., v_K) as a polynomial evaluated at a
+// challenge r, on the add and mul gates of SimpleFunctionChip:
+//
+// acc_0 = r, acc_i = acc_{i-1} * r + v_i, hash = acc_K
+//
+// which takes K mul and K add rows. Reordering the tuple changes the hash
+// unless r is a root of the difference polynomial.
+//
+// instance: | r | hash |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::function::{Number, SimpleFunctionChip, SimpleFunctionConfig};
+
+// out of circuit hash
+pub fn tuple_hash<F: FieldExt>(r: F, values: &[F]) -> F {
+    values.iter().fold(r, |acc, value| acc * r + *value)
+}
+
+#[derive(Debug, Clone)]
+pub struct TupleHashConfig {
+    pub function: SimpleFunctionConfig,
+    pub instance: Column<Instance>,
+}
+
+pub struct TupleHashChip<F: FieldExt, const K: usize> {
+    config: TupleHashConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const K: usize> TupleHashChip<F, K> {
+    pub fn construct(config: TupleHashConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> TupleHashConfig {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        TupleHashConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            instance,
+        }
+    }
+
+    /// Copies the challenge r in from instance row `row`.
+    pub fn load_challenge(
+        &self,
+        mut layouter: impl Layouter<F>,
+        row: usize,
+    ) -> Result<Number<F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "challenge",
+            |mut region| {
+                region
+                    .assign_advice_from_instance(|| "r", config.instance, row, config.function.x, 0)
+                    .map(Number)
+            },
+        )
+    }
+
+    pub fn load_tuple(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: [Value<F>; K],
+    ) -> Result<[Number<F>; K], Error> {
+        let config = &self.config;
+        let cells = layouter.assign_region(
+            || "tuple",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        region
+                            .assign_advice(|| "v", config.function.y, row, || *value)
+                            .map(Number)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+        cells.try_into().map_err(|_| Error::Synthesis)
+    }
+
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        r: &Number<F>,
+        values: &[Number<F>; K],
+    ) -> Result<Number<F>, Error> {
+        let chip = SimpleFunctionChip::<F>::construct(self.config.function.clone());
+        values
+            .iter()
+            .enumerate()
+            .try_fold(r.clone(), |acc, (i, value)| {
+                let scaled =
+                    chip.mul_cells(layouter.namespace(|| format!("acc_{} * r", i)), &acc, r)?;
+                chip.add_cells(
+                    layouter.namespace(|| format!("+ v_{}", i + 1)),
+                    &scaled,
+                    value,
+                )
+            })
+    }
+
+    /// Hashes the tuple under the challenge in instance row 0 and exposes
+    /// the hash at row 1.
+    pub fn commit(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: [Value<F>; K],
+    ) -> Result<Number<F>, Error> {
+        let r = self.load_challenge(layouter.namespace(|| "r"), 0)?;
+        let values = self.load_tuple(layouter.namespace(|| "tuple"), values)?;
+        let hash = self.hash(layouter.namespace(|| "hash"), &r, &values)?;
+        layouter.constrain_instance(hash.0.cell(), self.config.instance, 1)?;
+        Ok(hash)
+    }
+}
+
+pub struct TupleHashCircuit<F: FieldExt, const K: usize> {
+    pub values: [Value<F>; K],
+}
+
+impl<F: FieldExt, const K: usize> TupleHashCircuit<F, K> {
+    pub fn new(values: [F; K]) -> Self {
+        Self {
+            values: values.map(Value::known),
+        }
+    }
+
+    pub fn public_inputs(r: F, values: [F; K]) -> Vec<F> {
+        vec![r, tuple_hash(r, &values)]
+    }
+}
+
+impl<F: FieldExt, const K: usize> Circuit<F> for TupleHashCircuit<F, K> {
+    type Config = TupleHashConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: [Value::unknown(); K],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        TupleHashChip::<F, K>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = TupleHashChip::<F, K>::construct(config);
+        chip.commit(layouter.namespace(|| "tuple hash"), self.values)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+    use crate::recorder::record;
+
+    fn run<const K: usize>(values: [u64; K], public: Vec<Fp>) -> MockProver<Fp> {
+        let circuit = TupleHashCircuit::new(values.map(Fp::from));
+        MockProver::run(5, &circuit, vec![public]).unwrap()
+    }
+
+    #[test]
+    fn k_3() {
+        let (r, values) = (Fp::from(10), [1, 2, 3].map(Fp::from));
+        // 10^4 + 1 * 10^2 + 2 * 10 + 3
+        assert_eq!(tuple_hash(r, &values), Fp::from(10123));
+        run([1, 2, 3], TupleHashCircuit::public_inputs(r, values)).assert_satisfied();
+    }
+
+    #[test]
+    fn k_5() {
+        let r = Fp::from(0x1234_5678);
+        let values = [5, 4, 3, 2, 1].map(Fp::from);
+        run([5, 4, 3, 2, 1], TupleHashCircuit::public_inputs(r, values)).assert_satisfied();
+    }
+
+    #[test]
+    fn k_mul_and_k_add_rows() {
+        let circuit = TupleHashCircuit::new([5, 4, 3, 2, 1].map(Fp::from));
+        let public = TupleHashCircuit::public_inputs(Fp::from(3), [5, 4, 3, 2, 1].map(Fp::from));
+        let recorder = record(&circuit, 5, vec![public]).unwrap();
+        let count = |name: &str| {
+            recorder
+                .regions
+                .iter()
+                .filter(|region| region.name == name)
+                .count()
+        };
+        // `mul_cells` and `add_cells` both lay out an "op" region
+        assert_eq!(count("op"), 2 * 5);
+    }
+
+    #[test]
+    fn reordered_tuple_fails() {
+        let r = Fp::from(10);
+        let public = TupleHashCircuit::public_inputs(r, [1, 2, 3].map(Fp::from));
+        assert!(run([3, 2, 1], public.clone()).verify().is_err());
+        assert!(run([1, 2, 4], public).verify().is_err());
+    }
+
+    #[test]
+    fn other_challenge_fails() {
+        let values = [5, 4, 3, 2, 1].map(Fp::from);
+        let mut public = TupleHashCircuit::public_inputs(Fp::from(3), values);
+        public[0] = Fp::from(4);
+        assert!(run([5, 4, 3, 2, 1], public).verify().is_err());
+    }
+}
@@ -0,0 +1,295 @@
+// Inclusion in a tree where every node commits to its children as an inner
+// product with a fixed basis, c = <children, basis> with basis_i = 7^i. Walking
+// up from the leaf, each level loads the node's children, pins the child on the
+// path (the leaf, then the commitment of the level below) to its slot, and
+// commits with `InnerProductChip`. The last commitment is the root.
+//
+// node region, one per level:
+// | node        |
+// | child_0     |
+// | ...         |
+// | child_{w-1} |
+// | 7^0         |
+// | ...         |
+// | 7^{w-1}     |
+//
+// The linear commitment stands in for the Pedersen/IPA commitments of a real
+// Verkle tree, it is neither hiding nor binding.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::inner_product::{InnerProductChip, InnerProductConfig};
+
+fn basis<F: FieldExt>(width: usize) -> Vec<F> {
+    let seed = F::from(7);
+    (0..width)
+        .scan(F::one(), |power, _| {
+            let current = *power;
+            *power *= seed;
+            Some(current)
+        })
+        .collect()
+}
+
+// out of circuit commitment of one node
+pub fn verkle_commit<F: FieldExt>(children: &[F]) -> F {
+    children
+        .iter()
+        .zip(basis::<F>(children.len()))
+        .fold(F::zero(), |acc, (child, basis)| acc + *child * basis)
+}
+
+#[derive(Debug, Clone)]
+pub struct VerkleTreeConfig {
+    pub inner_product: InnerProductConfig,
+    pub node: Column<Advice>,
+    pub constant: Column<Fixed>,
+}
+
+pub struct VerkleTreeChip<F: FieldExt> {
+    config: VerkleTreeConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> VerkleTreeChip<F> {
+    pub fn construct(config: VerkleTreeConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        constant: Column<Fixed>,
+    ) -> VerkleTreeConfig {
+        let [node, a, b, acc] = advice;
+        meta.enable_equality(node);
+        meta.enable_constant(constant);
+
+        VerkleTreeConfig {
+            inner_product: InnerProductChip::configure(meta, a, b, acc),
+            node,
+            constant,
+        }
+    }
+
+    /// `path[level]` holds the children of the node at that level, bottom up,
+    /// and `indices[level]` the slot the path passes through. Returns the root.
+    pub fn prove_inclusion(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaf: Value<F>,
+        path: &[Vec<Value<F>>],
+        indices: &[usize],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        if path.is_empty()
+            || path.len() != indices.len()
+            || path
+                .iter()
+                .zip(indices)
+                .any(|(node, index)| *index >= node.len())
+        {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        let inner_product = InnerProductChip::construct(config.inner_product.clone());
+
+        let mut child = layouter.assign_region(
+            || "leaf",
+            |mut region| region.assign_advice(|| "leaf", config.node, 0, || leaf),
+        )?;
+
+        for (level, (node, index)) in path.iter().zip(indices).enumerate() {
+            let (children, basis) = layouter.assign_region(
+                || format!("node {}", level),
+                |mut region| {
+                    let children = node
+                        .iter()
+                        .enumerate()
+                        .map(|(row, value)| {
+                            region.assign_advice(|| "child", config.node, row, || *value)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    region.constrain_equal(child.cell(), children[*index].cell())?;
+
+                    let basis = basis::<F>(node.len())
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, basis)| {
+                            region.assign_advice_from_constant(
+                                || "basis",
+                                config.node,
+                                node.len() + i,
+                                basis,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((children, basis))
+                },
+            )?;
+
+            child = inner_product.inner_product(
+                layouter.namespace(|| format!("commit {}", level)),
+                &children,
+                &basis,
+            )?;
+        }
+        Ok(child)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // instance: | root |
+    struct InclusionCircuit {
+        leaf: Fp,
+        path: Vec<Vec<Fp>>,
+        indices: Vec<usize>,
+    }
+
+    impl Circuit<Fp> for InclusionCircuit {
+        type Config = (VerkleTreeConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                leaf: self.leaf,
+                path: self.path.clone(),
+                indices: self.indices.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (VerkleTreeChip::configure(meta, advice, constant), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let path: Vec<Vec<_>> = self
+                .path
+                .iter()
+                .map(|node| node.iter().copied().map(Value::known).collect())
+                .collect();
+            let root = VerkleTreeChip::construct(config).prove_inclusion(
+                layouter.namespace(|| "inclusion"),
+                Value::known(self.leaf),
+                &path,
+                &self.indices,
+            )?;
+            layouter.constrain_instance(root.cell(), instance, 0)
+        }
+    }
+
+    fn values(values: &[u64]) -> Vec<Fp> {
+        values.iter().copied().map(Fp::from).collect()
+    }
+
+    // depth 2, width 3: leaf 6 in the middle of [5, 6, 9], whose commitment is
+    // the middle child of the root
+    fn depth_2() -> (InclusionCircuit, Fp) {
+        let bottom = values(&[5, 6, 9]);
+        let top = vec![Fp::from(100), verkle_commit(&bottom), Fp::from(200)];
+        let root = verkle_commit(&top);
+        let circuit = InclusionCircuit {
+            leaf: Fp::from(6),
+            path: vec![bottom, top],
+            indices: vec![1, 1],
+        };
+        (circuit, root)
+    }
+
+    fn run(circuit: &InclusionCircuit, root: Fp) -> MockProver<Fp> {
+        MockProver::run(6, circuit, vec![vec![root]]).unwrap()
+    }
+
+    #[test]
+    fn commitment_is_the_inner_product_with_powers_of_7() {
+        assert_eq!(
+            verkle_commit(&values(&[5, 6, 9])),
+            Fp::from(5 + 6 * 7 + 9 * 49)
+        );
+    }
+
+    #[test]
+    fn depth_2_inclusion() {
+        let (circuit, root) = depth_2();
+        run(&circuit, root).assert_satisfied();
+    }
+
+    #[test]
+    fn depth_2_with_different_widths() {
+        let bottom = values(&[1, 2]);
+        let top = vec![
+            Fp::from(3),
+            Fp::from(4),
+            Fp::from(5),
+            verkle_commit(&bottom),
+        ];
+        let circuit = InclusionCircuit {
+            leaf: Fp::one(),
+            path: vec![bottom, top.clone()],
+            indices: vec![0, 3],
+        };
+        run(&circuit, verkle_commit(&top)).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_root_fails() {
+        let (circuit, root) = depth_2();
+        assert!(run(&circuit, root + Fp::one()).verify().is_err());
+    }
+
+    #[test]
+    fn leaf_not_in_its_slot_fails() {
+        let (mut circuit, root) = depth_2();
+        circuit.leaf = Fp::from(7);
+        assert!(run(&circuit, root).verify().is_err());
+
+        let (mut circuit, root) = depth_2();
+        circuit.indices[0] = 2;
+        assert!(run(&circuit, root).verify().is_err());
+    }
+
+    #[test]
+    fn commitment_not_in_its_parent_fails() {
+        let (mut circuit, root) = depth_2();
+        circuit.indices[1] = 0;
+        assert!(run(&circuit, root).verify().is_err());
+    }
+
+    #[test]
+    fn malformed_path_is_a_synthesis_error() {
+        for indices in [vec![1], vec![1, 3]] {
+            let (mut circuit, root) = depth_2();
+            circuit.indices = indices;
+            assert!(matches!(
+                MockProver::run(6, &circuit, vec![vec![root]]),
+                Err(Error::Synthesis)
+            ));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// One voter's step of a public running tally: the vote stays private, the proof
+// shows it is 0 or 1 and that the tally moved by exactly that much.
+//
+// | tally | vote | tally + vote | s_add |
+// vote is assigned on the boolean gate and copied into the add gate.
+//
+// instance: | tally | tally + vote |
+// A vote over n voters is n proofs, each starting from the previous tally.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::{
+    boolean::{BooleanChip, BooleanConfig},
+    function::{Number, SimpleFunctionChip, SimpleFunctionConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct VoteConfig {
+    pub function: SimpleFunctionConfig,
+    pub boolean: BooleanConfig,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Default)]
+pub struct VoteCircuit<F> {
+    pub vote: Value<F>,
+}
+
+impl<F: FieldExt> VoteCircuit<F> {
+    pub fn new(vote: bool) -> Self {
+        Self {
+            vote: Value::known(F::from(vote)),
+        }
+    }
+
+    // instance column for a vote cast on top of `tally`
+    pub fn public_inputs(tally: u64, vote: bool) -> Vec<Vec<F>> {
+        vec![vec![F::from(tally), F::from(tally + vote as u64)]]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for VoteCircuit<F> {
+    type Config = VoteConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        VoteConfig {
+            function: SimpleFunctionChip::configure(meta, x, y, z),
+            boolean: BooleanChip::configure(meta, y),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let function = SimpleFunctionChip::construct(config.function.clone());
+        let boolean = BooleanChip::construct(config.boolean);
+
+        let tally = layouter.assign_region(
+            || "tally",
+            |mut region| {
+                region
+                    .assign_advice_from_instance(
+                        || "tally",
+                        config.instance,
+                        0,
+                        config.function.x,
+                        0,
+                    )
+                    .map(Number)
+            },
+        )?;
+        let vote = boolean
+            .assign(layouter.namespace(|| "vote"), self.vote)
+            .map(Number)?;
+
+        let next = function.add_cells(layouter.namespace(|| "tally + vote"), &tally, &vote)?;
+        layouter.constrain_instance(next.0.cell(), config.instance, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    use super::*;
+
+    fn prove(circuit: VoteCircuit<Fp>, public: Vec<Vec<Fp>>) -> MockProver<Fp> {
+        MockProver::run(4, &circuit, public).unwrap()
+    }
+
+    #[test]
+    fn five_voters() {
+        let votes = [true, false, true, true, false];
+        let mut tally = 0;
+        for (voter, vote) in votes.into_iter().enumerate() {
+            let public = VoteCircuit::public_inputs(tally, vote);
+            prove(VoteCircuit::new(vote), public).assert_satisfied();
+            tally += vote as u64;
+            assert_eq!(tally, [1, 1, 2, 3, 3][voter]);
+        }
+    }
+
+    #[test]
+    fn tally_moved_by_the_wrong_amount_fails() {
+        // voted 0, claims the tally went up
+        let public = vec![vec![Fp::from(2), Fp::from(3)]];
+        assert!(prove(VoteCircuit::new(false), public).verify().is_err());
+        let public = vec![vec![Fp::from(2), Fp::from(2)]];
+        assert!(prove(VoteCircuit::new(true), public).verify().is_err());
+    }
+
+    #[test]
+    fn non_boolean_vote_fails() {
+        let circuit = VoteCircuit {
+            vote: Value::known(Fp::from(2)),
+        };
+        let public = vec![vec![Fp::from(2), Fp::from(4)]];
+        assert!(prove(circuit, public).verify().is_err());
+    }
+}
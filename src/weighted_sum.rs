@@ -0,0 +1,191 @@
+// out = w_0 * a_0 + ... + w_N-1 * a_N-1 for weights fixed by the circuit
+//
+// | a   | w   | p       | s_mul |
+// | a_0 | w_0 | w_0 a_0 | 1     |
+// | ... |     |         |       |
+// gate weight: s_mul * (w * a - p) == 0
+//
+// w is a fixed column, so the weights are part of the verifying key. The N
+// products are then added up pairwise on SimpleFunctionChip, N - 1 add gates.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::function::{Number, SimpleFunctionChip, SimpleFunctionConfig};
+
+#[derive(Debug, Clone)]
+pub struct WeightedSumConfig {
+    pub advice: [Column<Advice>; 3],
+    pub weight: Column<Fixed>,
+    pub s_mul: Selector,
+    pub function: SimpleFunctionConfig,
+}
+
+pub struct WeightedSumChip<F: FieldExt, const N: usize> {
+    config: WeightedSumConfig,
+    weights: [F; N],
+}
+
+impl<F: FieldExt, const N: usize> WeightedSumChip<F, N> {
+    pub fn construct(config: WeightedSumConfig, weights: [F; N]) -> Self {
+        Self { config, weights }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        weight: Column<Fixed>,
+    ) -> WeightedSumConfig {
+        let [a, _, p] = advice;
+        let s_mul = meta.selector();
+
+        meta.create_gate("weight", |meta| {
+            let s = meta.query_selector(s_mul);
+            let a = meta.query_advice(a, Rotation::cur());
+            let w = meta.query_fixed(weight, Rotation::cur());
+            let p = meta.query_advice(p, Rotation::cur());
+            vec![s * (w * a - p)]
+        });
+
+        WeightedSumConfig {
+            advice,
+            weight,
+            s_mul,
+            function: SimpleFunctionChip::configure(meta, advice[0], advice[1], advice[2]),
+        }
+    }
+
+    pub fn weighted_sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[Value<F>; N],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(N > 0, "nothing to sum");
+        let config = &self.config;
+        let [a, _, p] = config.advice;
+
+        let products = layouter.assign_region(
+            || "weights",
+            |mut region| {
+                let mut products = Vec::with_capacity(N);
+                for (row, (input, weight)) in inputs.iter().zip(self.weights).enumerate() {
+                    config.s_mul.enable(&mut region, row)?;
+                    region.assign_advice(|| "a", a, row, || *input)?;
+                    region.assign_fixed(|| "w", config.weight, row, || Value::known(weight))?;
+                    let product = region
+                        .assign_advice(|| "p", p, row, || *input * Value::known(weight))
+                        .map(Number)?;
+                    products.push(product);
+                }
+                Ok(products)
+            },
+        )?;
+
+        let function = SimpleFunctionChip::construct(config.function.clone());
+        let mut products = products.into_iter();
+        let mut sum = products.next().unwrap();
+        for product in products {
+            sum = function.add_cells(layouter.namespace(|| "add"), &sum, &product)?;
+        }
+        Ok(sum.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+    use crate::recorder::record;
+
+    // the weighted sum of `inputs` exposed at instance row 0
+    struct WeightedCircuit<const N: usize> {
+        weights: [u64; N],
+        inputs: [Value<Fp>; N],
+    }
+
+    impl<const N: usize> WeightedCircuit<N> {
+        fn new(weights: [u64; N], inputs: [u64; N]) -> Self {
+            Self {
+                weights,
+                inputs: inputs.map(|input| Value::known(Fp::from(input))),
+            }
+        }
+    }
+
+    impl<const N: usize> Circuit<Fp> for WeightedCircuit<N> {
+        type Config = (WeightedSumConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                weights: self.weights,
+                inputs: [Value::unknown(); N],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let weight = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                WeightedSumChip::<Fp, N>::configure(meta, advice, weight),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = WeightedSumChip::construct(config, self.weights.map(Fp::from));
+            let sum = chip.weighted_sum(layouter.namespace(|| "weighted sum"), &self.inputs)?;
+            layouter.constrain_instance(sum.cell(), instance, 0)
+        }
+    }
+
+    fn prove<const N: usize>(weights: [u64; N], inputs: [u64; N], sum: u64) -> MockProver<Fp> {
+        let circuit = WeightedCircuit::new(weights, inputs);
+        MockProver::run(4, &circuit, vec![vec![Fp::from(sum)]]).unwrap()
+    }
+
+    #[test]
+    fn ones_weighted_1_to_4() {
+        prove([1, 2, 3, 4], [1, 1, 1, 1], 10).assert_satisfied();
+    }
+
+    #[test]
+    fn other_inputs() {
+        prove([1, 2, 3, 4], [5, 0, 2, 1], 15).assert_satisfied();
+        prove([7], [6], 42).assert_satisfied();
+    }
+
+    #[test]
+    fn wrong_sum_fails() {
+        assert!(prove([1, 2, 3, 4], [1, 1, 1, 1], 4).verify().is_err());
+        // the weights reversed
+        assert!(prove([4, 3, 2, 1], [1, 2, 3, 4], 30).verify().is_err());
+    }
+
+    #[test]
+    fn n_products_and_n_minus_1_adds() {
+        let circuit = WeightedCircuit::new([1, 2, 3, 4], [1, 1, 1, 1]);
+        let recorder = record(&circuit, 4, vec![vec![Fp::from(10)]]).unwrap();
+        let names: Vec<_> = recorder.regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["weights", "op", "op", "op"]);
+        // the weights sit in the fixed column
+        let weights: Vec<_> = (0..4).map(|row| recorder.fixed[&(0, row)]).collect();
+        assert_eq!(weights, [1, 2, 3, 4].map(|w| Some(Fp::from(w))));
+    }
+}
@@ -0,0 +1,157 @@
+// The advice values of a MockProver run as a [column][row] matrix. MockProver
+// only exposes them through its Debug output, which prints every advice cell as
+// `Unassigned`, `Assigned(0x..)` or `Poison(n)`, so the matrix is parsed back
+// from there. Unassigned and poisoned (blinding) cells come out as None.
+
+use halo2_proofs::{arithmetic::FieldExt, dev::MockProver};
+
+// big endian hex from the field's Debug output, back into the field
+fn parse_field<F: FieldExt>(hex: &str) -> Option<F> {
+    let hex = hex.trim_start_matches("0x");
+    let mut repr = F::Repr::default();
+    let bytes = repr.as_mut();
+    if hex.len() != bytes.len() * 2 {
+        return None;
+    }
+    for (i, byte) in bytes.iter_mut().rev().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Option::from(F::from_repr(repr))
+}
+
+fn parse_cell<F: FieldExt>(cell: &str) -> Option<F> {
+    cell.strip_prefix("Assigned(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .and_then(parse_field)
+}
+
+pub fn extract_witness<F: FieldExt>(prover: &MockProver<F>) -> Vec<Vec<Option<F>>> {
+    let debug = format!("{:?}", prover);
+    let start = debug
+        .find("advice: [")
+        .expect("MockProver prints its advice")
+        + "advice: [".len();
+    let end = start
+        + debug[start..]
+            .find("], instance: ")
+            .expect("advice is followed by instance");
+    let matrix = &debug[start..end];
+    if matrix.is_empty() {
+        return vec![];
+    }
+
+    matrix
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split("], [")
+        .map(|column| column.split(", ").map(parse_cell).collect())
+        .collect()
+}
+
+/// Prints the witness with a row per line, up to the last row holding a value.
+pub fn print_witness<F: FieldExt>(witness: &[Vec<Option<F>>]) {
+    let rows = witness
+        .iter()
+        .filter_map(|column| column.iter().rposition(Option::is_some))
+        .max()
+        .map_or(0, |last| last + 1);
+
+    let cells: Vec<Vec<String>> = (0..rows)
+        .map(|row| {
+            witness
+                .iter()
+                .map(|column| match column[row] {
+                    Some(value) => {
+                        let hex = format!("{:?}", value);
+                        let digits = hex.trim_start_matches("0x").trim_start_matches('0');
+                        format!("0x{}", if digits.is_empty() { "0" } else { digits })
+                    }
+                    None => "-".to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = (0..witness.len())
+        .map(|column| {
+            cells
+                .iter()
+                .map(|row| row[column].len())
+                .chain([format!("a{}", column).len()])
+                .max()
+                .unwrap_or(1)
+        })
+        .collect();
+
+    let line = |row: Vec<String>| {
+        let row: Vec<_> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!(" {:>width$} ", cell, width = width))
+            .collect();
+        println!("|{}|", row.join("|"));
+    };
+    line(
+        (0..witness.len())
+            .map(|column| format!("a{}", column))
+            .collect(),
+    );
+    for row in cells {
+        line(row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    use super::*;
+    use crate::fibo1::FiboCircuit;
+
+    fn fibo_witness() -> Vec<Vec<Option<Fp>>> {
+        let circuit = FiboCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        extract_witness(&MockProver::run(4, &circuit, vec![]).unwrap())
+    }
+
+    #[test]
+    fn column_2_row_0_is_f1_plus_f1() {
+        let witness = fibo_witness();
+        assert_eq!(witness[0][0], Some(Fp::one()));
+        assert_eq!(witness[1][0], Some(Fp::one()));
+        assert_eq!(witness[2][0], Some(Fp::one() + Fp::one()));
+    }
+
+    #[test]
+    fn columns_by_rows() {
+        let witness = fibo_witness();
+        assert_eq!(witness.len(), 3);
+        assert!(witness.iter().all(|column| column.len() == 16));
+
+        let c: Vec<_> = [2, 3, 5, 8, 13, 21, 34, 55]
+            .map(|value| Some(Fp::from(value)))
+            .to_vec();
+        assert_eq!(witness[2][..8], c[..]);
+        // unassigned and blinding rows
+        assert!(witness
+            .iter()
+            .all(|column| column[8..].iter().all(Option::is_none)));
+    }
+
+    #[test]
+    fn field_round_trips_through_debug() {
+        for value in [Fp::zero(), Fp::from(55), -Fp::one()] {
+            assert_eq!(parse_field::<Fp>(&format!("{:?}", value)), Some(value));
+        }
+        assert_eq!(parse_field::<Fp>("0x12"), None);
+        assert_eq!(parse_cell::<Fp>("Unassigned"), None);
+        assert_eq!(parse_cell::<Fp>("Poison(3)"), None);
+    }
+
+    #[test]
+    fn prints_the_witness() {
+        print_witness(&fibo_witness());
+        print_witness::<Fp>(&[]);
+    }
+}
@@ -0,0 +1,225 @@
+// Bitwise xor of two bytes, all three running sums decompose together, least
+// significant bit first.
+// | a        | b        | out        | a_bit | b_bit | selector | s_end |
+// | a        | b        | a ^ b      | a_0   | b_0   | 1        | 0     |
+// | (a-a0)/2 | (b-b0)/2 | ...        | a_1   | b_1   | 1        | 0     |
+// | ...      |          |            |       |       |          |       |
+// | 0        | 0        | 0          |       |       | 0        | 1     |
+// gate xor: selector * (a(cur) - 2 * a(next) - a_bit) == 0, same for b,
+//           selector * (out(cur) - 2 * out(next) - (a_bit + b_bit - 2 * a_bit * b_bit)) == 0,
+//           both bits boolean
+// gate xor end: s_end * a == 0, s_end * b == 0, s_end * out == 0
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+pub const XOR_BITS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct XorConfig {
+    pub advice: [Column<Advice>; 3],
+    pub bits: [Column<Advice>; 2],
+    pub selector: Selector,
+    pub s_end: Selector,
+}
+
+/// Xors two cells holding bytes, failing unless both are in `[0, 256)`.
+pub struct XorChip<F: FieldExt> {
+    config: XorConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> XorChip<F> {
+    pub fn construct(config: XorConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        bits: [Column<Advice>; 2],
+    ) -> XorConfig {
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let selector = meta.selector();
+        let s_end = meta.selector();
+
+        meta.create_gate("xor", |meta| {
+            let s = meta.query_selector(selector);
+            let [a, b, out] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let [a_next, b_next, out_next] =
+                advice.map(|column| meta.query_advice(column, Rotation::next()));
+            let [a_bit, b_bit] = bits.map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+
+            let out_bit =
+                a_bit.clone() + b_bit.clone() - two.clone() * a_bit.clone() * b_bit.clone();
+            vec![
+                s.clone() * (a - two.clone() * a_next - a_bit.clone()),
+                s.clone() * (b - two.clone() * b_next - b_bit.clone()),
+                s.clone() * (out - two * out_next - out_bit),
+                s.clone() * a_bit.clone() * (one.clone() - a_bit),
+                s * b_bit.clone() * (one - b_bit),
+            ]
+        });
+
+        meta.create_gate("xor end", |meta| {
+            let s = meta.query_selector(s_end);
+            advice
+                .map(|column| s.clone() * meta.query_advice(column, Rotation::cur()))
+                .to_vec()
+        });
+
+        XorConfig {
+            advice,
+            bits,
+            selector,
+            s_end,
+        }
+    }
+
+    pub fn xor(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let low_bit = |z: &F| F::from(z.to_repr().as_ref()[0] as u64 & 1);
+        let shift = |z: &F, bit: F| (*z - bit) * F::TWO_INV;
+
+        layouter.assign_region(
+            || "xor",
+            |mut region| {
+                let mut a = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let mut b = b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let value = a.value().zip(b.value()).map(|(a, b)| {
+                    F::from((a.to_repr().as_ref()[0] ^ b.to_repr().as_ref()[0]) as u64)
+                });
+                let result = region.assign_advice(|| "a ^ b", config.advice[2], 0, || value)?;
+
+                let mut out = result.clone();
+                for row in 0..XOR_BITS {
+                    config.selector.enable(&mut region, row)?;
+
+                    let a_bit = a.value().map(low_bit);
+                    let b_bit = b.value().map(low_bit);
+                    let out_bit = out.value().map(low_bit);
+                    region.assign_advice(|| "a_bit", config.bits[0], row, || a_bit)?;
+                    region.assign_advice(|| "b_bit", config.bits[1], row, || b_bit)?;
+
+                    let next = |z: &AssignedCell<F, F>, bit: Value<F>| {
+                        z.value().zip(bit).map(|(z, bit)| shift(z, bit))
+                    };
+                    let (a_next, b_next, out_next) =
+                        (next(&a, a_bit), next(&b, b_bit), next(&out, out_bit));
+                    a = region.assign_advice(|| "a", config.advice[0], row + 1, || a_next)?;
+                    b = region.assign_advice(|| "b", config.advice[1], row + 1, || b_next)?;
+                    out = region.assign_advice(|| "out", config.advice[2], row + 1, || out_next)?;
+                }
+                config.s_end.enable(&mut region, XOR_BITS)?;
+                Ok(result)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    // instance: | a ^ b |
+    struct XorCircuit(u64, u64);
+
+    impl Circuit<Fp> for XorCircuit {
+        type Config = (XorConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(0, 0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let bits = [(); 2].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (XorChip::configure(meta, advice, bits), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (a, b) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let a = region.assign_advice(
+                        || "a",
+                        config.advice[0],
+                        0,
+                        || Value::known(Fp::from(self.0)),
+                    )?;
+                    let b = region.assign_advice(
+                        || "b",
+                        config.advice[1],
+                        0,
+                        || Value::known(Fp::from(self.1)),
+                    )?;
+                    Ok((a, b))
+                },
+            )?;
+            let out = XorChip::construct(config).xor(layouter.namespace(|| "xor"), &a, &b)?;
+            layouter.constrain_instance(out.cell(), instance, 0)
+        }
+    }
+
+    fn prove(a: u64, b: u64, out: u64) -> MockProver<Fp> {
+        MockProver::run(5, &XorCircuit(a, b), vec![vec![Fp::from(out)]]).unwrap()
+    }
+
+    #[test]
+    fn xor_bytes() {
+        for (a, b) in [
+            (0xaa, 0x55),
+            (0xff, 0xff),
+            (0, 0),
+            (0x12, 0x34),
+            (0x80, 0x01),
+        ] {
+            prove(a, b, a ^ b).assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn wrong_xor_fails() {
+        assert!(prove(0xaa, 0x55, 0xfe).verify().is_err());
+        // a + b
+        assert!(prove(0x03, 0x01, 0x04).verify().is_err());
+    }
+
+    #[test]
+    fn input_above_a_byte_fails() {
+        assert!(prove(0x100, 0x01, 0x01).verify().is_err());
+    }
+}